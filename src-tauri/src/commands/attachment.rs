@@ -54,6 +54,7 @@ pub async fn delete_attachment_by_path(
 
 /// 查询附件列表
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn query_attachments(
     service: State<'_, AttachmentService>,
     page: i64,
@@ -0,0 +1,15 @@
+use crate::services::cash_flow::dto::{CashFlowForecast, CashFlowForecastDto};
+use crate::services::cash_flow::CashFlowService;
+use tauri::State;
+
+/// 获取现金流预测
+#[tauri::command]
+pub async fn get_cash_flow_forecast(
+    service: State<'_, CashFlowService>,
+    input: CashFlowForecastDto,
+) -> Result<CashFlowForecast, String> {
+    service
+        .get_cash_flow_forecast(input)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,26 @@
+use crate::services::costing::dto::{
+    CogsReport, InventoryValuation, InventoryValuationDto, QueryCogsDto,
+};
+use crate::services::costing::CostingService;
+use tauri::State;
+
+/// 查询销售成本
+#[tauri::command]
+pub async fn get_cogs(
+    service: State<'_, CostingService>,
+    input: QueryCogsDto,
+) -> Result<CogsReport, String> {
+    service.get_cogs(input).await.map_err(|e| e.to_string())
+}
+
+/// 获取库存估值
+#[tauri::command]
+pub async fn get_inventory_valuation(
+    service: State<'_, CostingService>,
+    input: InventoryValuationDto,
+) -> Result<InventoryValuation, String> {
+    service
+        .get_inventory_valuation(input)
+        .await
+        .map_err(|e| e.to_string())
+}
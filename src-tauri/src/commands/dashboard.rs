@@ -0,0 +1,15 @@
+use crate::services::dashboard::dto::{DashboardSeries, DashboardSeriesDto};
+use crate::services::dashboard::DashboardService;
+use tauri::State;
+
+/// 获取仪表盘时间序列
+#[tauri::command]
+pub async fn get_dashboard_series(
+    service: State<'_, DashboardService>,
+    input: DashboardSeriesDto,
+) -> Result<DashboardSeries, String> {
+    service
+        .get_dashboard_series(input)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,54 @@
+use crate::entity::order::Model as OrderModel;
+use crate::services::goods_receipt::dto::{
+    CreateGoodsReceiptDto, GoodsReceiptDetail, OrderReceivingSummary,
+};
+use crate::services::goods_receipt::GoodsReceiptService;
+use tauri::State;
+
+/// 登记采购收货单
+#[tauri::command]
+pub async fn create_goods_receipt(
+    service: State<'_, GoodsReceiptService>,
+    input: CreateGoodsReceiptDto,
+) -> Result<GoodsReceiptDetail, String> {
+    service
+        .create_goods_receipt(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除采购收货单
+#[tauri::command]
+pub async fn delete_goods_receipt(
+    service: State<'_, GoodsReceiptService>,
+    receipt_id: i64,
+) -> Result<OrderModel, String> {
+    service
+        .delete_goods_receipt(receipt_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询订单的收货单
+#[tauri::command]
+pub async fn get_goods_receipts(
+    service: State<'_, GoodsReceiptService>,
+    order_id: i64,
+) -> Result<Vec<GoodsReceiptDetail>, String> {
+    service
+        .get_goods_receipts(order_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询采购订单收货汇总
+#[tauri::command]
+pub async fn get_order_receiving(
+    service: State<'_, GoodsReceiptService>,
+    order_id: i64,
+) -> Result<OrderReceivingSummary, String> {
+    service
+        .get_order_receiving(order_id)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,39 @@
+use crate::services::ledger::dto::{CustomerBalance, CustomerLedger, CustomerLedgerDto};
+use crate::services::ledger::LedgerService;
+use tauri::State;
+
+/// 获取客户往来明细
+#[tauri::command]
+pub async fn get_customer_ledger(
+    service: State<'_, LedgerService>,
+    input: CustomerLedgerDto,
+) -> Result<CustomerLedger, String> {
+    service
+        .get_customer_ledger(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取客户往来余额（可指定截止日期）
+#[tauri::command]
+pub async fn get_customer_balance(
+    service: State<'_, LedgerService>,
+    customer_id: i64,
+    as_of: Option<String>,
+) -> Result<CustomerBalance, String> {
+    service
+        .get_customer_balance(customer_id, as_of)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取全部客户当前往来余额
+#[tauri::command]
+pub async fn get_all_customer_balances(
+    service: State<'_, LedgerService>,
+) -> Result<Vec<CustomerBalance>, String> {
+    service
+        .get_all_customer_balances()
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,39 @@
+use crate::entity::stock_lot;
+use crate::services::lot::dto::{ExpiringLot, LotMismatch, QueryExpiringLotsDto};
+use crate::services::lot::LotService;
+use tauri::State;
+
+/// 查询商品有剩余的批次
+#[tauri::command]
+pub async fn get_product_lots(
+    service: State<'_, LotService>,
+    product_id: i64,
+) -> Result<Vec<stock_lot::Model>, String> {
+    service
+        .get_product_lots(product_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询临期批次
+#[tauri::command]
+pub async fn get_expiring_lots(
+    service: State<'_, LotService>,
+    input: QueryExpiringLotsDto,
+) -> Result<Vec<ExpiringLot>, String> {
+    service
+        .get_expiring_lots(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 批次核对（库存数量与批次数量不一致的商品）
+#[tauri::command]
+pub async fn get_lot_mismatches(
+    service: State<'_, LotService>,
+) -> Result<Vec<LotMismatch>, String> {
+    service
+        .get_lot_mismatches()
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,11 +1,29 @@
 mod accounting;
 mod accounting_book;
 mod attachment;
+mod cash_flow;
 mod category;
 mod chat;
+mod costing;
 mod customer;
+mod dashboard;
+mod goods_receipt;
+mod ledger;
+mod lot;
+mod numbering;
 mod order;
+mod order_revision;
+mod order_template;
+mod printer;
 mod product;
+mod profit;
+mod quotation;
+mod receipt;
+mod reorder;
+mod setting;
+mod statement;
+mod stock;
+mod stocktake;
 
 pub fn with_install_tauri_commands(
     builder: tauri::Builder<tauri::Wry>,
@@ -60,14 +78,86 @@ pub fn with_install_tauri_commands(
         product::get_product_by_id,
         product::search_products,
         order::create_order,
+        order::clone_order,
         order::settle_order,
+        order::batch_settle_orders,
+        order::add_order_payment,
+        order::get_order_payments,
+        order::get_order_balance,
+        order::create_order_return,
+        order::get_order_returns,
+        order::reopen_order,
         order::get_settle_preview,
         order::cancel_order,
         order::update_order,
+        order::get_order_margin,
         order::get_all_orders,
         order::get_order_by_id,
         order::get_orders_by_customer_id,
         order::get_orders_by_status,
-        order::query_orders
+        order::query_orders,
+        order_revision::get_order_revisions,
+        order_revision::get_order_revision,
+        order_revision::diff_order_revisions,
+        order_revision::restore_order_revision,
+        goods_receipt::create_goods_receipt,
+        goods_receipt::delete_goods_receipt,
+        goods_receipt::get_goods_receipts,
+        goods_receipt::get_order_receiving,
+        order_template::create_order_template,
+        order_template::save_order_as_template,
+        order_template::update_order_template,
+        order_template::delete_order_template,
+        order_template::get_order_template_by_id,
+        order_template::get_order_templates,
+        order_template::create_order_from_template,
+        quotation::create_quotation,
+        quotation::update_quotation,
+        quotation::send_quotation,
+        quotation::convert_quotation_to_order,
+        quotation::get_quotation_by_id,
+        quotation::get_all_quotations,
+        quotation::get_quotations_by_customer_id,
+        cash_flow::get_cash_flow_forecast,
+        dashboard::get_dashboard_series,
+        costing::get_cogs,
+        costing::get_inventory_valuation,
+        profit::get_profit_report,
+        ledger::get_customer_ledger,
+        ledger::get_customer_balance,
+        ledger::get_all_customer_balances,
+        numbering::get_numbering_rules,
+        numbering::update_numbering_rule,
+        numbering::reset_numbering_rule,
+        printer::print_order_receipt,
+        receipt::get_order_receipt,
+        receipt::export_order_receipt,
+        setting::get_shop_profile,
+        setting::update_shop_profile,
+        setting::get_printer_config,
+        setting::update_printer_config,
+        setting::get_purchase_config,
+        setting::update_purchase_config,
+        setting::get_margin_config,
+        setting::update_margin_config,
+        setting::get_costing_config,
+        setting::update_costing_config,
+        statement::get_customer_statement,
+        statement::export_customer_statement,
+        stock::get_stock_list,
+        stock::get_product_stock,
+        stock::get_stock_movements,
+        stocktake::create_stocktake,
+        stocktake::update_stocktake,
+        stocktake::delete_stocktake,
+        stocktake::get_stocktake,
+        stocktake::get_stocktakes,
+        stocktake::approve_stocktake,
+        reorder::get_low_stock_products,
+        reorder::get_reorder_suggestions,
+        reorder::create_reorder_orders,
+        lot::get_product_lots,
+        lot::get_expiring_lots,
+        lot::get_lot_mismatches
     ])
 }
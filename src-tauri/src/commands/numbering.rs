@@ -0,0 +1,38 @@
+use crate::services::numbering::dto::{NumberingRuleInfo, UpdateNumberingRuleDto};
+use crate::services::numbering::NumberingService;
+use tauri::State;
+
+/// 获取全部单据编号规则
+#[tauri::command]
+pub async fn get_numbering_rules(
+    service: State<'_, NumberingService>,
+) -> Result<Vec<NumberingRuleInfo>, String> {
+    service
+        .get_numbering_rules()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 更新单据编号规则
+#[tauri::command]
+pub async fn update_numbering_rule(
+    service: State<'_, NumberingService>,
+    input: UpdateNumberingRuleDto,
+) -> Result<NumberingRuleInfo, String> {
+    service
+        .update_numbering_rule(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 恢复默认单据编号规则
+#[tauri::command]
+pub async fn reset_numbering_rule(
+    service: State<'_, NumberingService>,
+    document_type: String,
+) -> Result<NumberingRuleInfo, String> {
+    service
+        .reset_numbering_rule(document_type)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,7 +1,10 @@
 use crate::entity::order::Model as OrderModel;
 use crate::entity::order_item::Model as OrderItemModel;
+use crate::entity::order_payment::Model as OrderPaymentModel;
 use crate::services::order::dto::{
-    CreateOrderDto, QueryOrdersDto, SettleOrderDto, SettlePreview, UpdateOrderDto,
+    AddOrderPaymentDto, BatchSettleOrdersDto, BatchSettleResult, CloneOrderDto, CreateOrderDto,
+    CreateOrderReturnDto, OrderBalance, OrderMargin, OrderReturnDetail, OrderWithMargin,
+    QueryOrdersDto, ReopenOrderDto, SettleOrderDto, SettlePreview, UpdateOrderDto,
 };
 use crate::services::order::OrderService;
 use rust_decimal::Decimal;
@@ -29,8 +32,25 @@ pub struct QueryOrdersResult {
 pub async fn create_order(
     service: State<'_, OrderService>,
     input: CreateOrderDto,
+) -> Result<OrderWithMargin, String> {
+    let order = service
+        .create_order(input)
+        .await
+        .map_err(|e| e.to_string())?;
+    let margin = service
+        .get_order_margin(order.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(OrderWithMargin { order, margin })
+}
+
+/// 复制订单（再来一单）
+#[tauri::command]
+pub async fn clone_order(
+    service: State<'_, OrderService>,
+    input: CloneOrderDto,
 ) -> Result<OrderModel, String> {
-    service.create_order(input).await.map_err(|e| e.to_string())
+    service.clone_order(input).await.map_err(|e| e.to_string())
 }
 
 /// 结账订单
@@ -42,6 +62,84 @@ pub async fn settle_order(
     service.settle_order(input).await.map_err(|e| e.to_string())
 }
 
+/// 批量结账订单
+#[tauri::command]
+pub async fn batch_settle_orders(
+    service: State<'_, OrderService>,
+    input: BatchSettleOrdersDto,
+) -> Result<BatchSettleResult, String> {
+    service
+        .batch_settle_orders(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 登记订单收付款
+#[tauri::command]
+pub async fn add_order_payment(
+    service: State<'_, OrderService>,
+    input: AddOrderPaymentDto,
+) -> Result<OrderModel, String> {
+    service.add_payment(input).await.map_err(|e| e.to_string())
+}
+
+/// 获取订单收付款记录
+#[tauri::command]
+pub async fn get_order_payments(
+    service: State<'_, OrderService>,
+    order_id: i64,
+) -> Result<Vec<OrderPaymentModel>, String> {
+    service
+        .get_order_payments(order_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取订单未结余额
+#[tauri::command]
+pub async fn get_order_balance(
+    service: State<'_, OrderService>,
+    order_id: i64,
+) -> Result<OrderBalance, String> {
+    service
+        .get_order_balance(order_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 创建退货单
+#[tauri::command]
+pub async fn create_order_return(
+    service: State<'_, OrderService>,
+    input: CreateOrderReturnDto,
+) -> Result<OrderReturnDetail, String> {
+    service
+        .create_return(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取订单退货单列表
+#[tauri::command]
+pub async fn get_order_returns(
+    service: State<'_, OrderService>,
+    order_id: i64,
+) -> Result<Vec<OrderReturnDetail>, String> {
+    service
+        .get_order_returns(order_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 撤销订单结账
+#[tauri::command]
+pub async fn reopen_order(
+    service: State<'_, OrderService>,
+    input: ReopenOrderDto,
+) -> Result<OrderModel, String> {
+    service.reopen_order(input).await.map_err(|e| e.to_string())
+}
+
 /// 获取结算预览
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -72,8 +170,28 @@ pub async fn cancel_order(service: State<'_, OrderService>, id: i64) -> Result<O
 pub async fn update_order(
     service: State<'_, OrderService>,
     input: UpdateOrderDto,
-) -> Result<OrderModel, String> {
-    service.update_order(input).await.map_err(|e| e.to_string())
+) -> Result<OrderWithMargin, String> {
+    let order = service
+        .update_order(input)
+        .await
+        .map_err(|e| e.to_string())?;
+    let margin = service
+        .get_order_margin(order.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(OrderWithMargin { order, margin })
+}
+
+/// 测算订单毛利（仅销售订单）
+#[tauri::command]
+pub async fn get_order_margin(
+    service: State<'_, OrderService>,
+    order_id: i64,
+) -> Result<Option<OrderMargin>, String> {
+    service
+        .get_order_margin(order_id)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// 获取所有订单
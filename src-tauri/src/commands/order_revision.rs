@@ -0,0 +1,56 @@
+use crate::entity::order::Model as OrderModel;
+use crate::entity::order_revision::Model as OrderRevisionModel;
+use crate::services::order_revision::dto::{
+    DiffOrderRevisionsDto, OrderRevisionDetail, OrderRevisionDiff, RestoreOrderRevisionDto,
+};
+use crate::services::order_revision::OrderRevisionService;
+use tauri::State;
+
+/// 查询订单的所有版本
+#[tauri::command]
+pub async fn get_order_revisions(
+    service: State<'_, OrderRevisionService>,
+    order_id: i64,
+) -> Result<Vec<OrderRevisionModel>, String> {
+    service
+        .get_revisions(order_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询订单指定版本（含明细快照）
+#[tauri::command]
+pub async fn get_order_revision(
+    service: State<'_, OrderRevisionService>,
+    order_id: i64,
+    version: i32,
+) -> Result<OrderRevisionDetail, String> {
+    service
+        .get_revision(order_id, version)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 比较订单的两个版本
+#[tauri::command]
+pub async fn diff_order_revisions(
+    service: State<'_, OrderRevisionService>,
+    input: DiffOrderRevisionsDto,
+) -> Result<OrderRevisionDiff, String> {
+    service
+        .diff_revisions(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 将待结账订单恢复为指定版本
+#[tauri::command]
+pub async fn restore_order_revision(
+    service: State<'_, OrderRevisionService>,
+    input: RestoreOrderRevisionDto,
+) -> Result<OrderModel, String> {
+    service
+        .restore_revision(input)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,89 @@
+use crate::entity::order::Model as OrderModel;
+use crate::entity::order_template::Model as OrderTemplateModel;
+use crate::services::order_template::dto::{
+    CreateOrderFromTemplateDto, CreateOrderTemplateDto, OrderTemplateDetail,
+    SaveOrderAsTemplateDto, UpdateOrderTemplateDto,
+};
+use crate::services::order_template::OrderTemplateService;
+use tauri::State;
+
+/// 创建订单模板
+#[tauri::command]
+pub async fn create_order_template(
+    service: State<'_, OrderTemplateService>,
+    input: CreateOrderTemplateDto,
+) -> Result<OrderTemplateDetail, String> {
+    service
+        .create_template(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 将订单保存为模板
+#[tauri::command]
+pub async fn save_order_as_template(
+    service: State<'_, OrderTemplateService>,
+    input: SaveOrderAsTemplateDto,
+) -> Result<OrderTemplateDetail, String> {
+    service
+        .save_order_as_template(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 编辑订单模板
+#[tauri::command]
+pub async fn update_order_template(
+    service: State<'_, OrderTemplateService>,
+    input: UpdateOrderTemplateDto,
+) -> Result<OrderTemplateDetail, String> {
+    service
+        .update_template(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除订单模板
+#[tauri::command]
+pub async fn delete_order_template(
+    service: State<'_, OrderTemplateService>,
+    id: i64,
+) -> Result<(), String> {
+    service.delete_template(id).await.map_err(|e| e.to_string())
+}
+
+/// 根据 ID 获取订单模板详情
+#[tauri::command]
+pub async fn get_order_template_by_id(
+    service: State<'_, OrderTemplateService>,
+    id: i64,
+) -> Result<Option<OrderTemplateDetail>, String> {
+    service
+        .get_template_by_id(id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取订单模板列表（可按客户筛选）
+#[tauri::command]
+pub async fn get_order_templates(
+    service: State<'_, OrderTemplateService>,
+    customer_id: Option<i64>,
+) -> Result<Vec<OrderTemplateModel>, String> {
+    service
+        .get_templates(customer_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按模板创建订单
+#[tauri::command]
+pub async fn create_order_from_template(
+    service: State<'_, OrderTemplateService>,
+    input: CreateOrderFromTemplateDto,
+) -> Result<OrderModel, String> {
+    service
+        .create_order_from_template(input)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,15 @@
+use crate::services::printer::dto::{PrintReceiptDto, PrintResult};
+use crate::services::printer::PrinterService;
+use tauri::State;
+
+/// 打印订单小票（ESC/POS）
+#[tauri::command]
+pub async fn print_order_receipt(
+    service: State<'_, PrinterService>,
+    input: PrintReceiptDto,
+) -> Result<PrintResult, String> {
+    service
+        .print_receipt(input)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,15 @@
+use crate::services::profit::dto::{ProfitReport, ProfitReportDto};
+use crate::services::profit::ProfitService;
+use tauri::State;
+
+/// 获取利润报表
+#[tauri::command]
+pub async fn get_profit_report(
+    service: State<'_, ProfitService>,
+    input: ProfitReportDto,
+) -> Result<ProfitReport, String> {
+    service
+        .get_profit_report(input)
+        .await
+        .map_err(|e| e.to_string())
+}
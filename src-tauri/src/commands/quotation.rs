@@ -0,0 +1,87 @@
+use crate::entity::order::Model as OrderModel;
+use crate::entity::quotation::Model as QuotationModel;
+use crate::services::quotation::dto::{
+    ConvertQuotationDto, CreateQuotationDto, QuotationDetail, UpdateQuotationDto,
+};
+use crate::services::quotation::QuotationService;
+use tauri::State;
+
+/// 创建报价单
+#[tauri::command]
+pub async fn create_quotation(
+    service: State<'_, QuotationService>,
+    input: CreateQuotationDto,
+) -> Result<QuotationDetail, String> {
+    service
+        .create_quotation(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 编辑报价单
+#[tauri::command]
+pub async fn update_quotation(
+    service: State<'_, QuotationService>,
+    input: UpdateQuotationDto,
+) -> Result<QuotationModel, String> {
+    service
+        .update_quotation(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 标记报价单已发送
+#[tauri::command]
+pub async fn send_quotation(
+    service: State<'_, QuotationService>,
+    id: i64,
+) -> Result<QuotationModel, String> {
+    service.send_quotation(id).await.map_err(|e| e.to_string())
+}
+
+/// 报价单转为订单
+#[tauri::command]
+pub async fn convert_quotation_to_order(
+    service: State<'_, QuotationService>,
+    input: ConvertQuotationDto,
+) -> Result<OrderModel, String> {
+    service
+        .convert_to_order(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 根据 ID 获取报价单详情
+#[tauri::command]
+pub async fn get_quotation_by_id(
+    service: State<'_, QuotationService>,
+    id: i64,
+) -> Result<Option<QuotationDetail>, String> {
+    service
+        .get_quotation_by_id(id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取所有报价单
+#[tauri::command]
+pub async fn get_all_quotations(
+    service: State<'_, QuotationService>,
+) -> Result<Vec<QuotationModel>, String> {
+    service
+        .get_all_quotations()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 根据客户 ID 获取报价单列表
+#[tauri::command]
+pub async fn get_quotations_by_customer_id(
+    service: State<'_, QuotationService>,
+    customer_id: i64,
+) -> Result<Vec<QuotationModel>, String> {
+    service
+        .get_quotations_by_customer_id(customer_id)
+        .await
+        .map_err(|e| e.to_string())
+}
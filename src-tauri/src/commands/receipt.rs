@@ -0,0 +1,45 @@
+use crate::services::attachment::storage::AttachmentStorage;
+use crate::services::receipt::dto::{ExportReceiptDto, OrderReceipt, ReceiptFile, ReceiptKind};
+use crate::services::receipt::ReceiptService;
+use tauri::{AppHandle, Manager, State};
+
+/// 获取订单票据（结构化数据）
+#[tauri::command]
+pub async fn get_order_receipt(
+    service: State<'_, ReceiptService>,
+    order_id: i64,
+    kind: Option<String>,
+) -> Result<OrderReceipt, String> {
+    let kind = match kind {
+        Some(s) => s
+            .parse::<ReceiptKind>()
+            .map_err(|_| format!("无效的票据类型: {}", s))?,
+        None => ReceiptKind::Receipt,
+    };
+    service
+        .generate_receipt(order_id, kind)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 导出订单票据文件（HTML / PDF；保存为附件时写入附件目录，否则写入票据目录）
+#[tauri::command]
+pub async fn export_order_receipt(
+    app: AppHandle,
+    service: State<'_, ReceiptService>,
+    input: ExportReceiptDto,
+) -> Result<ReceiptFile, String> {
+    let output_dir = if input.save_as_attachment.unwrap_or(false) {
+        AttachmentStorage::get_monthly_dir(&app).map_err(|e| e.to_string())?
+    } else {
+        app.path()
+            .app_data_dir()
+            .map_err(|e| format!("无法获取应用数据目录: {}", e))?
+            .join("fileStorage")
+            .join("receipt")
+    };
+    service
+        .export_receipt(input, &output_dir)
+        .await
+        .map_err(|e| e.to_string())
+}
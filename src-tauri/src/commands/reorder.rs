@@ -0,0 +1,38 @@
+use crate::entity::order::Model as OrderModel;
+use crate::services::reorder::dto::{CreateReorderOrdersDto, LowStockProduct, ReorderSuggestion};
+use crate::services::reorder::ReorderService;
+use tauri::State;
+
+/// 查询低于最低库存的商品
+#[tauri::command]
+pub async fn get_low_stock_products(
+    service: State<'_, ReorderService>,
+) -> Result<Vec<LowStockProduct>, String> {
+    service
+        .get_low_stock_products()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询按供应商分组的补货建议
+#[tauri::command]
+pub async fn get_reorder_suggestions(
+    service: State<'_, ReorderService>,
+) -> Result<Vec<ReorderSuggestion>, String> {
+    service
+        .get_reorder_suggestions()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按补货建议生成采购订单
+#[tauri::command]
+pub async fn create_reorder_orders(
+    service: State<'_, ReorderService>,
+    input: CreateReorderOrdersDto,
+) -> Result<Vec<OrderModel>, String> {
+    service
+        .create_reorder_orders(input)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,110 @@
+use crate::services::setting::dto::{
+    CostingConfig, MarginConfig, PrinterConfig, PurchaseConfig, ShopProfile,
+};
+use crate::services::setting::SettingService;
+use tauri::State;
+
+/// 获取店铺信息
+#[tauri::command]
+pub async fn get_shop_profile(service: State<'_, SettingService>) -> Result<ShopProfile, String> {
+    service.get_shop_profile().await.map_err(|e| e.to_string())
+}
+
+/// 更新店铺信息
+#[tauri::command]
+pub async fn update_shop_profile(
+    service: State<'_, SettingService>,
+    input: ShopProfile,
+) -> Result<ShopProfile, String> {
+    service
+        .update_shop_profile(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取小票打印机配置
+#[tauri::command]
+pub async fn get_printer_config(
+    service: State<'_, SettingService>,
+) -> Result<PrinterConfig, String> {
+    service
+        .get_printer_config()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 更新小票打印机配置
+#[tauri::command]
+pub async fn update_printer_config(
+    service: State<'_, SettingService>,
+    input: PrinterConfig,
+) -> Result<PrinterConfig, String> {
+    service
+        .update_printer_config(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取采购设置
+#[tauri::command]
+pub async fn get_purchase_config(
+    service: State<'_, SettingService>,
+) -> Result<PurchaseConfig, String> {
+    service
+        .get_purchase_config()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 更新采购设置
+#[tauri::command]
+pub async fn update_purchase_config(
+    service: State<'_, SettingService>,
+    input: PurchaseConfig,
+) -> Result<PurchaseConfig, String> {
+    service
+        .update_purchase_config(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取毛利预警设置
+#[tauri::command]
+pub async fn get_margin_config(service: State<'_, SettingService>) -> Result<MarginConfig, String> {
+    service.get_margin_config().await.map_err(|e| e.to_string())
+}
+
+/// 更新毛利预警设置
+#[tauri::command]
+pub async fn update_margin_config(
+    service: State<'_, SettingService>,
+    input: MarginConfig,
+) -> Result<MarginConfig, String> {
+    service
+        .update_margin_config(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取成本核算设置
+#[tauri::command]
+pub async fn get_costing_config(
+    service: State<'_, SettingService>,
+) -> Result<CostingConfig, String> {
+    service
+        .get_costing_config()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 更新成本核算设置
+#[tauri::command]
+pub async fn update_costing_config(
+    service: State<'_, SettingService>,
+    input: CostingConfig,
+) -> Result<CostingConfig, String> {
+    service
+        .update_costing_config(input)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,34 @@
+use crate::services::statement::dto::{CustomerStatement, CustomerStatementDto, StatementFile};
+use crate::services::statement::StatementService;
+use tauri::{AppHandle, Manager, State};
+
+/// 生成客户对账单（结构化数据）
+#[tauri::command]
+pub async fn get_customer_statement(
+    service: State<'_, StatementService>,
+    input: CustomerStatementDto,
+) -> Result<CustomerStatement, String> {
+    service
+        .generate_statement(&input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 导出客户对账单文件（HTML / PDF，保存至应用数据目录）
+#[tauri::command]
+pub async fn export_customer_statement(
+    app: AppHandle,
+    service: State<'_, StatementService>,
+    input: CustomerStatementDto,
+) -> Result<StatementFile, String> {
+    let output_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("无法获取应用数据目录: {}", e))?
+        .join("fileStorage")
+        .join("statement");
+    service
+        .export_statement(input, &output_dir)
+        .await
+        .map_err(|e| e.to_string())
+}
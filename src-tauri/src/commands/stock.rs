@@ -0,0 +1,36 @@
+use crate::entity::{product_stock, stock_movement};
+use crate::services::stock::dto::QueryStockMovementsDto;
+use crate::services::stock::StockService;
+use tauri::State;
+
+/// 查询所有商品的当前库存
+#[tauri::command]
+pub async fn get_stock_list(
+    service: State<'_, StockService>,
+) -> Result<Vec<product_stock::Model>, String> {
+    service.get_stock_list().await.map_err(|e| e.to_string())
+}
+
+/// 查询指定商品的当前库存
+#[tauri::command]
+pub async fn get_product_stock(
+    service: State<'_, StockService>,
+    product_id: i64,
+) -> Result<Vec<product_stock::Model>, String> {
+    service
+        .get_product_stock(product_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询商品的库存变动流水
+#[tauri::command]
+pub async fn get_stock_movements(
+    service: State<'_, StockService>,
+    input: QueryStockMovementsDto,
+) -> Result<Vec<stock_movement::Model>, String> {
+    service
+        .get_stock_movements(input)
+        .await
+        .map_err(|e| e.to_string())
+}
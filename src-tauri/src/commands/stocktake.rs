@@ -0,0 +1,78 @@
+use crate::entity::stocktake;
+use crate::services::stocktake::dto::{
+    ApproveStocktakeDto, CreateStocktakeDto, StocktakeDetail, UpdateStocktakeDto,
+};
+use crate::services::stocktake::StocktakeService;
+use tauri::State;
+
+/// 创建盘点单
+#[tauri::command]
+pub async fn create_stocktake(
+    service: State<'_, StocktakeService>,
+    input: CreateStocktakeDto,
+) -> Result<StocktakeDetail, String> {
+    service
+        .create_stocktake(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 修改盘点单（仅草稿）
+#[tauri::command]
+pub async fn update_stocktake(
+    service: State<'_, StocktakeService>,
+    input: UpdateStocktakeDto,
+) -> Result<StocktakeDetail, String> {
+    service
+        .update_stocktake(input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除盘点单（仅草稿）
+#[tauri::command]
+pub async fn delete_stocktake(
+    service: State<'_, StocktakeService>,
+    stocktake_id: i64,
+) -> Result<(), String> {
+    service
+        .delete_stocktake(stocktake_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询盘点单详情
+#[tauri::command]
+pub async fn get_stocktake(
+    service: State<'_, StocktakeService>,
+    stocktake_id: i64,
+) -> Result<StocktakeDetail, String> {
+    service
+        .get_stocktake(stocktake_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询盘点单列表
+#[tauri::command]
+pub async fn get_stocktakes(
+    service: State<'_, StocktakeService>,
+    status: Option<String>,
+) -> Result<Vec<stocktake::Model>, String> {
+    service
+        .get_stocktakes(status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 审核盘点单
+#[tauri::command]
+pub async fn approve_stocktake(
+    service: State<'_, StocktakeService>,
+    input: ApproveStocktakeDto,
+) -> Result<StocktakeDetail, String> {
+    service
+        .approve_stocktake(input)
+        .await
+        .map_err(|e| e.to_string())
+}
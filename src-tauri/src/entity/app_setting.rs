@@ -0,0 +1,22 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 应用设置实体（键值对存储）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "app_setting")]
+pub struct Model {
+    /// 设置键（如 shop.name）
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub key: String,
+    /// 设置值
+    pub value: String,
+    /// 更新时间
+    pub update_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,54 @@
+use crate::enums::DocumentType;
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveValue, ExprTrait};
+
+/// 单据流水号实体（按单据类型 + 重置周期分别计数）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "document_sequence")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub document_type: DocumentType,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub period: String, // 周期标识：YYYYMMDD / YYYYMM / YYYY / ALL
+    pub seq: i32, // 周期内流水号
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 获取下一个流水号
+    ///
+    /// 以 `seq = seq + 1` 原子递增，并发写入时不会取到相同的流水号；
+    /// 周期内首次取号时插入新记录（主键冲突时报错而非重复）
+    pub async fn get_next_sequence<C: sea_orm::ConnectionTrait>(
+        db: &C,
+        document_type: DocumentType,
+        period: &str,
+    ) -> Result<i32, Box<dyn std::error::Error>> {
+        let result = Entity::update_many()
+            .col_expr(Column::Seq, Expr::col(Column::Seq).add(1))
+            .filter(Column::DocumentType.eq(document_type))
+            .filter(Column::Period.eq(period))
+            .exec(db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            let new_seq = ActiveModel {
+                document_type: ActiveValue::Set(document_type),
+                period: ActiveValue::Set(period.to_string()),
+                seq: ActiveValue::Set(1),
+            };
+            new_seq.insert(db).await?;
+            return Ok(1);
+        }
+
+        let model = Entity::find_by_id((document_type, period.to_string()))
+            .one(db)
+            .await?
+            .ok_or("单据流水号不存在")?;
+        Ok(model.seq)
+    }
+}
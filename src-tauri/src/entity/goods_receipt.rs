@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 采购收货单实体（关联采购订单，一张订单可分多次收货）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "goods_receipt")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 收货单编号（按收货单编号规则生成）
+    #[sea_orm(unique)]
+    pub receipt_no: String,
+    /// 采购订单 ID
+    pub order_id: i64,
+    /// 备注（如到货情况、破损说明）
+    pub remark: Option<String>,
+    /// 收货时间
+    pub create_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
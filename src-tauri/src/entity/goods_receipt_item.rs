@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 采购收货单明细实体
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "goods_receipt_item")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联收货单 ID
+    pub receipt_id: i64,
+    /// 采购订单明细 ID
+    pub order_item_id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称快照
+    pub product_name: String,
+    /// 本次实收数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub quantity: Decimal,
+    /// 计量单位快照
+    pub unit: String,
+    /// 批次号（为空时结账入库按收货单编号登记批次）
+    pub lot_no: Option<String>,
+    /// 生产日期
+    pub production_date: Option<NaiveDateTime>,
+    /// 到期日期
+    pub expiry_date: Option<NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
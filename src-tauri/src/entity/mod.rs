@@ -2,6 +2,7 @@ pub mod accounting_book;
 pub mod accounting_book_seq;
 pub mod accounting_record;
 pub mod accounting_record_seq;
+pub mod app_setting;
 pub mod attachment;
 pub mod category;
 pub mod category_seq;
@@ -9,22 +10,46 @@ pub mod chat_message_seq;
 pub mod chat_session;
 pub mod customer;
 pub mod customer_seq;
+pub mod document_sequence;
+pub mod goods_receipt;
+pub mod goods_receipt_item;
+pub mod numbering_rule;
 pub mod order;
 pub mod order_item;
+pub mod order_payment;
+pub mod order_return;
+pub mod order_return_item;
+pub mod order_revision;
+pub mod order_revision_item;
 pub mod order_seq;
+pub mod order_template;
+pub mod order_template_item;
 mod prelude;
 pub mod product;
 pub mod product_seq;
+pub mod product_stock;
+pub mod quotation;
+pub mod quotation_item;
 pub mod section_summary;
+pub mod stock_lot;
+pub mod stock_lot_consumption;
+pub mod stock_movement;
+pub mod stocktake;
+pub mod stocktake_item;
+
+use sea_orm::{ConnectionTrait, Statement};
 
 pub async fn with_install_entities(
     db: &sea_orm::DatabaseConnection,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    index_legacy_document_nos(db).await?;
+
     db.get_schema_builder()
         .register(accounting_record::Entity)
         .register(accounting_record_seq::Entity)
         .register(accounting_book::Entity)
         .register(accounting_book_seq::Entity)
+        .register(app_setting::Entity)
         .register(attachment::Entity)
         .register(category::Entity)
         .register(category_seq::Entity)
@@ -32,14 +57,79 @@ pub async fn with_install_entities(
         .register(chat_message_seq::Entity)
         .register(customer::Entity)
         .register(customer_seq::Entity)
+        .register(document_sequence::Entity)
+        .register(goods_receipt::Entity)
+        .register(goods_receipt_item::Entity)
+        .register(numbering_rule::Entity)
         .register(product::Entity)
         .register(product_seq::Entity)
+        .register(product_stock::Entity)
         .register(order::Entity)
         .register(order_item::Entity)
+        .register(order_payment::Entity)
+        .register(order_return::Entity)
+        .register(order_return_item::Entity)
+        .register(order_revision::Entity)
+        .register(order_revision_item::Entity)
         .register(order_seq::Entity)
+        .register(order_template::Entity)
+        .register(order_template_item::Entity)
+        .register(quotation::Entity)
+        .register(quotation_item::Entity)
         .register(section_summary::Entity)
+        .register(stock_lot::Entity)
+        .register(stock_lot_consumption::Entity)
+        .register(stock_movement::Entity)
+        .register(stocktake::Entity)
+        .register(stocktake_item::Entity)
         .sync(db)
         .await?;
 
     Ok(())
 }
+
+/// 为存在旧版本重复编号的单据表预先建立部分唯一索引（一次性迁移，索引已存在时跳过）
+///
+/// 旧版订单编号为当日序号（#1, #2...），退货单编号由订单编号派生，跨日会重复。
+/// 已打印或导出的单据编号不能改动，因此不重新编号：唯一索引只约束最后一条重复编号之后的记录。
+/// 索引名与实体 `unique` 生成的相同，schema 同步时不再重复创建；没有重复编号时交由同步建立完整唯一索引
+async fn index_legacy_document_nos(
+    db: &sea_orm::DatabaseConnection,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let backend = db.get_database_backend();
+    for (table, column) in [("order", "order_no"), ("order_return", "return_no")] {
+        let index = format!("idx-{table}-{column}");
+        let existing = db
+            .query_all_raw(Statement::from_sql_and_values(
+                backend,
+                "SELECT type FROM sqlite_master \
+                 WHERE (type = 'table' AND name = ?) OR (type = 'index' AND name = ?)",
+                [table.into(), index.as_str().into()],
+            ))
+            .await?;
+        // 表尚未创建或索引已建立
+        if existing.len() != 1 {
+            continue;
+        }
+
+        let cutoff: Option<i64> = db
+            .query_one_raw(Statement::from_string(
+                backend,
+                format!(
+                    r#"SELECT MAX(id) AS cutoff FROM "{table}" WHERE "{column}" IN
+                    (SELECT "{column}" FROM "{table}" GROUP BY "{column}" HAVING COUNT(*) > 1)"#
+                ),
+            ))
+            .await?
+            .map(|row| row.try_get("", "cutoff"))
+            .transpose()?
+            .flatten();
+        if let Some(cutoff) = cutoff {
+            db.execute_unprepared(&format!(
+                r#"CREATE UNIQUE INDEX "{index}" ON "{table}" ("{column}") WHERE id > {cutoff}"#
+            ))
+            .await?;
+        }
+    }
+    Ok(())
+}
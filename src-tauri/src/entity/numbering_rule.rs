@@ -0,0 +1,25 @@
+use crate::enums::{DocumentType, ResetPolicy};
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 单据编号规则实体（每种单据一条，未配置时使用默认规则）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "numbering_rule")]
+pub struct Model {
+    /// 单据类型
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub document_type: DocumentType,
+    /// 编号模板（如 XS-{yyyyMMdd}-{seq:4}）
+    pub pattern: String,
+    /// 流水号重置周期
+    pub reset_policy: ResetPolicy,
+    /// 更新时间
+    pub update_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,4 +1,4 @@
-use crate::enums::{AccountingChannel, OrderStatus, OrderSubType, OrderType};
+use crate::enums::{AccountingChannel, OrderStatus, OrderSubType, OrderType, ReceivingStatus};
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use sea_orm::entity::prelude::*;
@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: i64,
-    /// 可读订单编号（格式 #N，如 #1, #2，当日序号）
+    /// 可读订单编号（按订单编号规则生成，如 XS-20240601-0001，全局唯一）
+    #[sea_orm(unique)]
     pub order_no: String,
     /// 订单类型（Sales / Purchase）
     pub order_type: OrderType,
@@ -29,7 +30,8 @@ pub struct Model {
     pub sub_type: OrderSubType,
     /// 订单状态
     pub status: OrderStatus,
-    /// 支付/收款渠道（创建时默认 Unknown，结账时更新）
+    /// 支付/收款渠道（创建时默认 Unknown，收付款、结账时按收付款记录更新：
+    /// 单一渠道为该渠道，分多个渠道收付款时为 Unknown，各渠道金额以收付款记录为准）
     pub channel: AccountingChannel,
     /// 备注
     pub remark: Option<String>,
@@ -37,6 +39,14 @@ pub struct Model {
     pub create_at: NaiveDateTime,
     /// 结账时间
     pub settled_at: Option<NaiveDateTime>,
+    /// 预计收付款日期（可选，用于现金流预测）
+    pub due_date: Option<NaiveDateTime>,
+    /// 最近一次撤销结账时间（None 表示从未撤销）
+    pub reopened_at: Option<NaiveDateTime>,
+    /// 来源报价单 ID（由报价单转换生成时写入）
+    pub quotation_id: Option<i64>,
+    /// 收货状态（仅采购订单，None 表示销售订单或收货功能上线前创建的订单）
+    pub receiving_status: Option<ReceivingStatus>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -61,6 +71,10 @@ impl ActiveModelBehavior for ActiveModel {
             remark: sea_orm::ActiveValue::NotSet,
             create_at: sea_orm::ActiveValue::Set(now),
             settled_at: sea_orm::ActiveValue::NotSet,
+            due_date: sea_orm::ActiveValue::NotSet,
+            reopened_at: sea_orm::ActiveValue::NotSet,
+            quotation_id: sea_orm::ActiveValue::NotSet,
+            receiving_status: sea_orm::ActiveValue::NotSet,
         }
     }
 }
@@ -23,7 +23,13 @@ pub struct Model {
     /// 成交单价
     #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
     pub unit_price: Decimal,
-    /// 小计（= quantity × unit_price）
+    /// 明细折扣金额
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", default_value = 0)]
+    pub discount_amount: Decimal,
+    /// 明细折扣率（百分比，如 10 表示优惠 10%；按金额折扣时为 None）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub discount_rate: Option<Decimal>,
+    /// 小计（= quantity × unit_price - discount_amount）
     #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
     pub subtotal: Decimal,
     /// 备注
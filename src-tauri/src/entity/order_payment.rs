@@ -0,0 +1,46 @@
+use crate::enums::AccountingChannel;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 订单收付款实体（一笔订单可分多次收付款）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "order_payment")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联订单 ID
+    pub order_id: i64,
+    /// 收付款金额
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub amount: Decimal,
+    /// 收付款渠道
+    pub channel: AccountingChannel,
+    /// 收付款时间
+    pub paid_at: NaiveDateTime,
+    /// 备注
+    pub remark: Option<String>,
+    /// 创建时间
+    pub create_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        use chrono::Local;
+        let now = Local::now().naive_local();
+        Self {
+            id: sea_orm::ActiveValue::NotSet,
+            order_id: sea_orm::ActiveValue::NotSet,
+            amount: sea_orm::ActiveValue::NotSet,
+            channel: sea_orm::ActiveValue::NotSet,
+            paid_at: sea_orm::ActiveValue::NotSet,
+            remark: sea_orm::ActiveValue::NotSet,
+            create_at: sea_orm::ActiveValue::Set(now),
+        }
+    }
+}
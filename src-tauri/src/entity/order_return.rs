@@ -0,0 +1,57 @@
+use crate::enums::{AccountingChannel, OrderType};
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 退货单实体（关联已结账的原订单）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "order_return")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 退货单编号（按退货单编号规则生成，全局唯一）
+    #[sea_orm(unique)]
+    pub return_no: String,
+    /// 原订单 ID
+    pub order_id: i64,
+    /// 原订单类型（销售退货 / 采购退货）
+    pub order_type: OrderType,
+    /// 客户 ID（冗余存储，便于往来账查询）
+    pub customer_id: Option<i64>,
+    /// 退货商品金额（按原成交单价）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub total_amount: Decimal,
+    /// 退款金额
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub refund_amount: Decimal,
+    /// 退款渠道
+    pub channel: AccountingChannel,
+    /// 退货原因
+    pub reason: Option<String>,
+    /// 退货时间
+    pub create_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        use chrono::Local;
+        let now = Local::now().naive_local();
+        Self {
+            id: sea_orm::ActiveValue::NotSet,
+            return_no: sea_orm::ActiveValue::NotSet,
+            order_id: sea_orm::ActiveValue::NotSet,
+            order_type: sea_orm::ActiveValue::NotSet,
+            customer_id: sea_orm::ActiveValue::NotSet,
+            total_amount: sea_orm::ActiveValue::NotSet,
+            refund_amount: sea_orm::ActiveValue::NotSet,
+            channel: sea_orm::ActiveValue::NotSet,
+            reason: sea_orm::ActiveValue::NotSet,
+            create_at: sea_orm::ActiveValue::Set(now),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 退货单明细实体
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "order_return_item")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联退货单 ID
+    pub return_id: i64,
+    /// 原订单明细 ID
+    pub order_item_id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称快照
+    pub product_name: String,
+    /// 退货数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub quantity: Decimal,
+    /// 计量单位快照
+    pub unit: String,
+    /// 原成交单价
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub unit_price: Decimal,
+    /// 小计（= quantity × unit_price）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub subtotal: Decimal,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,39 @@
+use crate::enums::OrderRevisionOrigin;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 订单版本快照实体（待结账订单每次编辑后保存一份）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "order_revision")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联订单 ID
+    pub order_id: i64,
+    /// 版本号（同一订单内从 1 递增）
+    pub version: i32,
+    /// 版本来源
+    pub origin: OrderRevisionOrigin,
+    /// 应收/应付总额快照
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub total_amount: Decimal,
+    /// 实收/实付总额快照
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub actual_amount: Decimal,
+    /// 备注快照
+    pub remark: Option<String>,
+    /// 预计收付款日期快照
+    pub due_date: Option<NaiveDateTime>,
+    /// 恢复来源版本号（仅恢复历史版本时有值）
+    pub restored_from: Option<i32>,
+    /// 创建时间
+    pub create_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
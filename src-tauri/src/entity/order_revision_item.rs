@@ -0,0 +1,42 @@
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 订单版本明细快照实体
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "order_revision_item")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联订单版本 ID
+    pub revision_id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称快照
+    pub product_name: String,
+    /// 数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub quantity: Decimal,
+    /// 计量单位快照
+    pub unit: String,
+    /// 单价
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub unit_price: Decimal,
+    /// 明细折扣金额
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", default_value = 0)]
+    pub discount_amount: Decimal,
+    /// 明细折扣率（百分比；按金额折扣时为 None）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub discount_rate: Option<Decimal>,
+    /// 小计（= quantity × unit_price - discount_amount）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub subtotal: Decimal,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,36 @@
+use crate::enums::{OrderSubType, OrderType};
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 订单模板实体（按客户保存的常用订单，用于快速下单）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "order_template")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 模板名称（同一客户下唯一）
+    pub name: String,
+    /// 订单类型（Sales / Purchase）
+    pub order_type: OrderType,
+    /// 订单业务类型
+    pub sub_type: OrderSubType,
+    /// 关联客户 ID（散客为 None）
+    pub customer_id: Option<i64>,
+    /// 客户名称（冗余快照）
+    pub customer_name: Option<String>,
+    /// 备注（下单时作为订单备注）
+    pub remark: Option<String>,
+    /// 创建时间
+    pub create_at: NaiveDateTime,
+    /// 更新时间
+    pub update_at: NaiveDateTime,
+    /// 最近一次下单时间
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
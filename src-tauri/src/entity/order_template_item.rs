@@ -0,0 +1,39 @@
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 订单模板明细实体
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "order_template_item")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联模板 ID
+    pub template_id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称快照
+    pub product_name: String,
+    /// 数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub quantity: Decimal,
+    /// 计量单位快照
+    pub unit: String,
+    /// 保存时的单价
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub unit_price: Decimal,
+    /// 明细折扣金额（按折扣率折扣时为 None）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub discount_amount: Option<Decimal>,
+    /// 明细折扣率（百分比；按金额折扣时为 None）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub discount_rate: Option<Decimal>,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
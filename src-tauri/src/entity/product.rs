@@ -24,6 +24,12 @@ pub struct Model {
     /// 参考采购价
     #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
     pub default_purchase_price: Option<Decimal>,
+    /// 最低库存（按商品单位，低于该数量时提示补货）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub min_stock: Option<Decimal>,
+    /// 补货数量（生成补货建议时的建议采购数量）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub reorder_quantity: Option<Decimal>,
     /// 商品编码
     pub sku: Option<String>,
     /// 检索关键词，多个关键词以分号分隔
@@ -63,6 +69,8 @@ impl ActiveModelBehavior for ActiveModel {
             unit: sea_orm::ActiveValue::NotSet,
             default_sell_price: sea_orm::ActiveValue::NotSet,
             default_purchase_price: sea_orm::ActiveValue::NotSet,
+            min_stock: sea_orm::ActiveValue::NotSet,
+            reorder_quantity: sea_orm::ActiveValue::NotSet,
             sku: sea_orm::ActiveValue::NotSet,
             keywords: sea_orm::ActiveValue::NotSet,
             remark: sea_orm::ActiveValue::NotSet,
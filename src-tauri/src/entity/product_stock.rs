@@ -0,0 +1,29 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 商品当前库存实体（按商品 + 单位汇总，随库存变动流水同步更新）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "product_stock")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称（取最近一次变动的名称快照）
+    pub product_name: String,
+    /// 计量单位
+    pub unit: String,
+    /// 当前库存数量（允许为负，表示先销后进）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub quantity: Decimal,
+    /// 最近变动时间
+    pub update_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
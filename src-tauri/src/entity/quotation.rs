@@ -0,0 +1,62 @@
+use crate::enums::QuotationStatus;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 报价单实体（接受后可转为销售订单）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "quotation")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 报价单编号（按报价单编号规则生成）
+    #[sea_orm(unique)]
+    pub quotation_no: String,
+    /// 关联客户 ID（散客为 None）
+    pub customer_id: Option<i64>,
+    /// 客户名称（冗余快照）
+    pub customer_name: Option<String>,
+    /// 报价总额（明细折扣后小计之和）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub total_amount: Decimal,
+    /// 报价单状态
+    pub status: QuotationStatus,
+    /// 有效期截止时间（当日 23:59:59）
+    pub valid_until: NaiveDateTime,
+    /// 转换生成的订单 ID（未转换为 None）
+    pub order_id: Option<i64>,
+    /// 备注
+    pub remark: Option<String>,
+    /// 创建时间
+    pub create_at: NaiveDateTime,
+    /// 发送时间
+    pub sent_at: Option<NaiveDateTime>,
+    /// 转为订单时间
+    pub accepted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        use chrono::Local;
+        let now = Local::now().naive_local();
+        Self {
+            id: sea_orm::ActiveValue::NotSet,
+            quotation_no: sea_orm::ActiveValue::NotSet,
+            customer_id: sea_orm::ActiveValue::NotSet,
+            customer_name: sea_orm::ActiveValue::NotSet,
+            total_amount: sea_orm::ActiveValue::NotSet,
+            status: sea_orm::ActiveValue::NotSet,
+            valid_until: sea_orm::ActiveValue::NotSet,
+            order_id: sea_orm::ActiveValue::NotSet,
+            remark: sea_orm::ActiveValue::NotSet,
+            create_at: sea_orm::ActiveValue::Set(now),
+            sent_at: sea_orm::ActiveValue::NotSet,
+            accepted_at: sea_orm::ActiveValue::NotSet,
+        }
+    }
+}
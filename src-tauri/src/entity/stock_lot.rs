@@ -0,0 +1,49 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 库存批次实体（采购订单结账入库时按收货明细登记，盘盈按盘点单登记调整批次，
+/// 销售出库、盘亏按到期日期先到先出扣减）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "stock_lot")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称快照
+    pub product_name: String,
+    /// 计量单位（批次按商品 + 单位分别记录）
+    pub unit: String,
+    /// 批次号（未填写时取收货单编号，无收货记录的取订单编号，盘盈取盘点单编号）
+    pub lot_no: String,
+    /// 生产日期
+    pub production_date: Option<NaiveDateTime>,
+    /// 到期日期（为空表示不跟踪保质期，出库时排在最后）
+    pub expiry_date: Option<NaiveDateTime>,
+    /// 入库数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub received_quantity: Decimal,
+    /// 剩余数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub remaining_quantity: Decimal,
+    /// 采购订单 ID（盘盈调整批次为空）
+    pub order_id: Option<i64>,
+    /// 采购订单明细 ID
+    pub order_item_id: Option<i64>,
+    /// 盘点单 ID（盘盈调整批次才有）
+    pub stocktake_id: Option<i64>,
+    /// 收货单 ID（按收货明细登记的批次才有）
+    pub receipt_id: Option<i64>,
+    /// 收货单明细 ID
+    pub receipt_item_id: Option<i64>,
+    /// 入库时间
+    pub create_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
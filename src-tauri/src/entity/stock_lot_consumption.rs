@@ -0,0 +1,38 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 批次出库记录实体（销售出库、采购退货、盘亏扣减批次为正，销售退货、撤销结账恢复批次为负；
+/// 批次剩余不足时未覆盖的数量登记为无批次记录）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "stock_lot_consumption")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联批次 ID（为空表示批次未覆盖的数量）
+    pub lot_id: Option<i64>,
+    /// 商品 ID
+    pub product_id: i64,
+    /// 单位
+    pub unit: String,
+    /// 关联订单 ID（盘亏扣减为空）
+    pub order_id: Option<i64>,
+    /// 关联订单明细 ID
+    pub order_item_id: Option<i64>,
+    /// 关联退货单 ID（退货时写入）
+    pub return_id: Option<i64>,
+    /// 关联盘点单 ID（盘亏时写入）
+    pub stocktake_id: Option<i64>,
+    /// 扣减数量（扣减为正，恢复为负）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub quantity: Decimal,
+    /// 记录时间
+    pub create_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
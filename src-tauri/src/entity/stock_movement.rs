@@ -0,0 +1,46 @@
+use crate::enums::StockMovementType;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 库存变动流水实体（每次入库、出库、冲回各记一条）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "stock_movement")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称快照
+    pub product_name: String,
+    /// 计量单位（库存按商品 + 单位分别记录）
+    pub unit: String,
+    /// 变动类型
+    pub movement_type: StockMovementType,
+    /// 变动数量（入库为正，出库为负）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub quantity: Decimal,
+    /// 变动后结存数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub balance: Decimal,
+    /// 入库单位成本（采购入库为采购单价，采购冲回沿用原单价；其余变动由成本核算推算）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub unit_cost: Option<Decimal>,
+    /// 关联订单 ID
+    pub order_id: Option<i64>,
+    /// 关联订单明细 ID（订单结账、退货时写入）
+    pub order_item_id: Option<i64>,
+    /// 来源单据 ID（订单结账、撤销结账为订单 ID，退货为退货单 ID，盘点调整为盘点单 ID）
+    pub source_id: Option<i64>,
+    /// 来源单据编号
+    pub source_no: Option<String>,
+    /// 变动时间
+    pub create_at: NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,35 @@
+use crate::enums::StocktakeStatus;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 盘点单实体（草稿可反复修改盘点数量，审核后写入库存调整）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "stocktake")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 盘点单编号（按盘点单编号规则生成）
+    #[sea_orm(unique)]
+    pub stocktake_no: String,
+    /// 盘点单状态
+    pub status: StocktakeStatus,
+    /// 备注
+    pub remark: Option<String>,
+    /// 盘点差异金额合计（盘盈为正，盘亏为负；审核时按成本核算单位成本计算）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub variance_amount: Option<Decimal>,
+    /// 盘亏损耗记账记录 ID（审核时选择登记损耗才有）
+    pub loss_record_id: Option<i64>,
+    /// 创建时间
+    pub create_at: NaiveDateTime,
+    /// 审核时间
+    pub approved_at: Option<NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
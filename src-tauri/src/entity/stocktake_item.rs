@@ -0,0 +1,42 @@
+use rust_decimal::Decimal;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 盘点单明细实体（每个商品 + 单位一条）
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[sea_orm(table_name = "stocktake_item")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 关联盘点单 ID
+    pub stocktake_id: i64,
+    /// 关联商品 ID
+    pub product_id: i64,
+    /// 商品名称快照
+    pub product_name: String,
+    /// 计量单位
+    pub unit: String,
+    /// 账面数量（草稿时为录入时的库存，审核时按当前库存重新取值）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub system_quantity: Decimal,
+    /// 实盘数量
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub counted_quantity: Decimal,
+    /// 差异数量（实盘 - 账面，盘盈为正，盘亏为负）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))")]
+    pub variance: Decimal,
+    /// 单位成本（审核时写入，无成本记录时为空）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub unit_cost: Option<Decimal>,
+    /// 差异金额（差异数量 × 单位成本）
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub variance_amount: Option<Decimal>,
+    /// 备注（如破损、过期说明）
+    pub remark: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -47,7 +47,7 @@ impl AccountingType {
 }
 
 /// 记账渠道枚举
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumIter)]
 pub enum AccountingChannel {
     /// 现金
     Cash,
@@ -1,11 +1,19 @@
 pub mod accounting;
 pub mod chat;
 pub mod customer;
+pub mod numbering;
 pub mod order;
+pub mod order_revision;
 pub mod order_sub_type;
+pub mod quotation;
+pub mod stock;
 
 pub use accounting::*;
 pub use chat::*;
 pub use customer::*;
+pub use numbering::*;
 pub use order::*;
+pub use order_revision::*;
 pub use order_sub_type::*;
+pub use quotation::*;
+pub use stock::*;
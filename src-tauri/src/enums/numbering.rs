@@ -0,0 +1,214 @@
+use sea_orm::sea_query::{ColumnType as SeaQueryColumnType, StringLen};
+use sea_orm::{DbErr, TryGetable, Value};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+/// 单据类型（每种单据独立编号）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumIter)]
+pub enum DocumentType {
+    /// 销售订单
+    SalesOrder,
+    /// 采购订单
+    PurchaseOrder,
+    /// 销售退货单
+    SalesReturn,
+    /// 采购退货单
+    PurchaseReturn,
+    /// 客户对账单
+    Statement,
+    /// 报价单
+    Quotation,
+    /// 采购收货单
+    GoodsReceipt,
+    /// 盘点单
+    Stocktake,
+}
+
+impl std::str::FromStr for DocumentType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SalesOrder" => Ok(DocumentType::SalesOrder),
+            "PurchaseOrder" => Ok(DocumentType::PurchaseOrder),
+            "SalesReturn" => Ok(DocumentType::SalesReturn),
+            "PurchaseReturn" => Ok(DocumentType::PurchaseReturn),
+            "Statement" => Ok(DocumentType::Statement),
+            "Quotation" => Ok(DocumentType::Quotation),
+            "GoodsReceipt" => Ok(DocumentType::GoodsReceipt),
+            "Stocktake" => Ok(DocumentType::Stocktake),
+            _ => Err(()),
+        }
+    }
+}
+
+impl DocumentType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::SalesOrder => "SalesOrder",
+            DocumentType::PurchaseOrder => "PurchaseOrder",
+            DocumentType::SalesReturn => "SalesReturn",
+            DocumentType::PurchaseReturn => "PurchaseReturn",
+            DocumentType::Statement => "Statement",
+            DocumentType::Quotation => "Quotation",
+            DocumentType::GoodsReceipt => "GoodsReceipt",
+            DocumentType::Stocktake => "Stocktake",
+        }
+    }
+}
+
+// SeaORM 转换 trait 实现
+impl TryGetable for DocumentType {
+    fn try_get_by<I: sea_orm::ColIdx>(
+        res: &sea_orm::QueryResult,
+        idx: I,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get_by(idx).map_err(sea_orm::TryGetError::DbErr)?;
+        value
+            .parse::<DocumentType>()
+            .map_err(|_| sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的单据类型"))))
+    }
+
+    fn try_get(
+        res: &sea_orm::QueryResult,
+        pre: &str,
+        col: &str,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get(pre, col).map_err(sea_orm::TryGetError::DbErr)?;
+        value
+            .parse::<DocumentType>()
+            .map_err(|_| sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的单据类型"))))
+    }
+}
+
+impl sea_orm::sea_query::ValueType for DocumentType {
+    fn try_from(v: Value) -> Result<Self, sea_orm::sea_query::ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => s
+                .parse::<DocumentType>()
+                .map_err(|_| sea_orm::sea_query::ValueTypeErr),
+            _ => Err(sea_orm::sea_query::ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(DocumentType).to_owned()
+    }
+
+    fn array_type() -> sea_orm::sea_query::ArrayType {
+        sea_orm::sea_query::ArrayType::String
+    }
+
+    fn column_type() -> SeaQueryColumnType {
+        SeaQueryColumnType::String(StringLen::None)
+    }
+}
+
+impl From<DocumentType> for Value {
+    fn from(e: DocumentType) -> Value {
+        Value::String(Some(e.as_str().to_string()))
+    }
+}
+
+impl sea_orm::TryFromU64 for DocumentType {
+    fn try_from_u64(_n: u64) -> Result<Self, DbErr> {
+        Err(DbErr::Type(String::from("无法将 u64 转换为 DocumentType")))
+    }
+}
+
+/// 流水号重置周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumIter)]
+pub enum ResetPolicy {
+    /// 每日重置
+    Daily,
+    /// 每月重置
+    Monthly,
+    /// 每年重置
+    Yearly,
+    /// 从不重置
+    Never,
+}
+
+impl std::str::FromStr for ResetPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Daily" => Ok(ResetPolicy::Daily),
+            "Monthly" => Ok(ResetPolicy::Monthly),
+            "Yearly" => Ok(ResetPolicy::Yearly),
+            "Never" => Ok(ResetPolicy::Never),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ResetPolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ResetPolicy::Daily => "Daily",
+            ResetPolicy::Monthly => "Monthly",
+            ResetPolicy::Yearly => "Yearly",
+            ResetPolicy::Never => "Never",
+        }
+    }
+}
+
+// SeaORM 转换 trait 实现
+impl TryGetable for ResetPolicy {
+    fn try_get_by<I: sea_orm::ColIdx>(
+        res: &sea_orm::QueryResult,
+        idx: I,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get_by(idx).map_err(sea_orm::TryGetError::DbErr)?;
+        value.parse::<ResetPolicy>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的流水号重置周期")))
+        })
+    }
+
+    fn try_get(
+        res: &sea_orm::QueryResult,
+        pre: &str,
+        col: &str,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get(pre, col).map_err(sea_orm::TryGetError::DbErr)?;
+        value.parse::<ResetPolicy>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的流水号重置周期")))
+        })
+    }
+}
+
+impl sea_orm::sea_query::ValueType for ResetPolicy {
+    fn try_from(v: Value) -> Result<Self, sea_orm::sea_query::ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => s
+                .parse::<ResetPolicy>()
+                .map_err(|_| sea_orm::sea_query::ValueTypeErr),
+            _ => Err(sea_orm::sea_query::ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(ResetPolicy).to_owned()
+    }
+
+    fn array_type() -> sea_orm::sea_query::ArrayType {
+        sea_orm::sea_query::ArrayType::String
+    }
+
+    fn column_type() -> SeaQueryColumnType {
+        SeaQueryColumnType::String(StringLen::None)
+    }
+}
+
+impl From<ResetPolicy> for Value {
+    fn from(e: ResetPolicy) -> Value {
+        Value::String(Some(e.as_str().to_string()))
+    }
+}
+
+impl sea_orm::TryFromU64 for ResetPolicy {
+    fn try_from_u64(_n: u64) -> Result<Self, DbErr> {
+        Err(DbErr::Type(String::from("无法将 u64 转换为 ResetPolicy")))
+    }
+}
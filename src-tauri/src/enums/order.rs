@@ -1,4 +1,4 @@
-use sea_orm::sea_query::{ColumnType as SeaQueryColumnType, StringLen};
+use sea_orm::sea_query::{ColumnType as SeaQueryColumnType, Nullable, StringLen};
 use sea_orm::{DbErr, TryGetable, Value};
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumIter};
@@ -97,6 +97,8 @@ impl sea_orm::TryFromU64 for OrderType {
 pub enum OrderStatus {
     /// 待结账
     Pending,
+    /// 部分收付款
+    PartiallyPaid,
     /// 已结账
     Settled,
     /// 已取消
@@ -109,6 +111,7 @@ impl std::str::FromStr for OrderStatus {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "Pending" => Ok(OrderStatus::Pending),
+            "PartiallyPaid" => Ok(OrderStatus::PartiallyPaid),
             "Settled" => Ok(OrderStatus::Settled),
             "Cancelled" => Ok(OrderStatus::Cancelled),
             _ => Err(()),
@@ -120,6 +123,7 @@ impl OrderStatus {
     fn as_str(&self) -> &'static str {
         match self {
             OrderStatus::Pending => "Pending",
+            OrderStatus::PartiallyPaid => "PartiallyPaid",
             OrderStatus::Settled => "Settled",
             OrderStatus::Cancelled => "Cancelled",
         }
@@ -186,3 +190,105 @@ impl sea_orm::TryFromU64 for OrderStatus {
         )))
     }
 }
+
+/// 采购订单收货状态枚举
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter)]
+pub enum ReceivingStatus {
+    /// 未收货
+    NotReceived,
+    /// 部分收货
+    PartiallyReceived,
+    /// 已全部收货
+    Received,
+}
+
+impl std::str::FromStr for ReceivingStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NotReceived" => Ok(ReceivingStatus::NotReceived),
+            "PartiallyReceived" => Ok(ReceivingStatus::PartiallyReceived),
+            "Received" => Ok(ReceivingStatus::Received),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ReceivingStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReceivingStatus::NotReceived => "NotReceived",
+            ReceivingStatus::PartiallyReceived => "PartiallyReceived",
+            ReceivingStatus::Received => "Received",
+        }
+    }
+}
+
+// SeaORM 转换 trait 实现
+impl TryGetable for ReceivingStatus {
+    fn try_get_by<I: sea_orm::ColIdx>(
+        res: &sea_orm::QueryResult,
+        idx: I,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        // 经由 String 的 TryGetable 读取，保留 Null 以便 Option<ReceivingStatus> 解码为 None
+        let value = <String as TryGetable>::try_get_by(res, idx)?;
+        value.parse::<ReceivingStatus>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的收货状态")))
+        })
+    }
+
+    fn try_get(
+        res: &sea_orm::QueryResult,
+        pre: &str,
+        col: &str,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value = <String as TryGetable>::try_get(res, pre, col)?;
+        value.parse::<ReceivingStatus>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的收货状态")))
+        })
+    }
+}
+
+impl sea_orm::sea_query::ValueType for ReceivingStatus {
+    fn try_from(v: Value) -> Result<Self, sea_orm::sea_query::ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => s
+                .parse::<ReceivingStatus>()
+                .map_err(|_| sea_orm::sea_query::ValueTypeErr),
+            _ => Err(sea_orm::sea_query::ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(ReceivingStatus).to_owned()
+    }
+
+    fn array_type() -> sea_orm::sea_query::ArrayType {
+        sea_orm::sea_query::ArrayType::String
+    }
+
+    fn column_type() -> SeaQueryColumnType {
+        SeaQueryColumnType::String(StringLen::None)
+    }
+}
+
+impl From<ReceivingStatus> for Value {
+    fn from(e: ReceivingStatus) -> Value {
+        Value::String(Some(e.as_str().to_string()))
+    }
+}
+
+impl sea_orm::TryFromU64 for ReceivingStatus {
+    fn try_from_u64(_n: u64) -> Result<Self, DbErr> {
+        Err(DbErr::Type(String::from(
+            "无法将 u64 转换为 ReceivingStatus",
+        )))
+    }
+}
+
+impl Nullable for ReceivingStatus {
+    fn null() -> Value {
+        Value::String(None)
+    }
+}
@@ -0,0 +1,103 @@
+use sea_orm::sea_query::{ColumnType as SeaQueryColumnType, StringLen};
+use sea_orm::{DbErr, TryGetable, Value};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+/// 订单版本来源枚举
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter)]
+pub enum OrderRevisionOrigin {
+    /// 首次编辑前的原始内容
+    Original,
+    /// 编辑订单
+    Edit,
+    /// 恢复历史版本
+    Restore,
+    /// 结账时按实收数量调整
+    Receiving,
+}
+
+impl std::str::FromStr for OrderRevisionOrigin {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Original" => Ok(OrderRevisionOrigin::Original),
+            "Edit" => Ok(OrderRevisionOrigin::Edit),
+            "Restore" => Ok(OrderRevisionOrigin::Restore),
+            "Receiving" => Ok(OrderRevisionOrigin::Receiving),
+            _ => Err(()),
+        }
+    }
+}
+
+impl OrderRevisionOrigin {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OrderRevisionOrigin::Original => "Original",
+            OrderRevisionOrigin::Edit => "Edit",
+            OrderRevisionOrigin::Restore => "Restore",
+            OrderRevisionOrigin::Receiving => "Receiving",
+        }
+    }
+}
+
+// SeaORM 转换 trait 实现
+impl TryGetable for OrderRevisionOrigin {
+    fn try_get_by<I: sea_orm::ColIdx>(
+        res: &sea_orm::QueryResult,
+        idx: I,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get_by(idx).map_err(sea_orm::TryGetError::DbErr)?;
+        value.parse::<OrderRevisionOrigin>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的订单版本来源")))
+        })
+    }
+
+    fn try_get(
+        res: &sea_orm::QueryResult,
+        pre: &str,
+        col: &str,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get(pre, col).map_err(sea_orm::TryGetError::DbErr)?;
+        value.parse::<OrderRevisionOrigin>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的订单版本来源")))
+        })
+    }
+}
+
+impl sea_orm::sea_query::ValueType for OrderRevisionOrigin {
+    fn try_from(v: Value) -> Result<Self, sea_orm::sea_query::ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => s
+                .parse::<OrderRevisionOrigin>()
+                .map_err(|_| sea_orm::sea_query::ValueTypeErr),
+            _ => Err(sea_orm::sea_query::ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(OrderRevisionOrigin).to_owned()
+    }
+
+    fn array_type() -> sea_orm::sea_query::ArrayType {
+        sea_orm::sea_query::ArrayType::String
+    }
+
+    fn column_type() -> SeaQueryColumnType {
+        SeaQueryColumnType::String(StringLen::None)
+    }
+}
+
+impl From<OrderRevisionOrigin> for Value {
+    fn from(e: OrderRevisionOrigin) -> Value {
+        Value::String(Some(e.as_str().to_string()))
+    }
+}
+
+impl sea_orm::TryFromU64 for OrderRevisionOrigin {
+    fn try_from_u64(_n: u64) -> Result<Self, DbErr> {
+        Err(DbErr::Type(String::from(
+            "无法将 u64 转换为 OrderRevisionOrigin",
+        )))
+    }
+}
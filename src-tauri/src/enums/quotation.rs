@@ -0,0 +1,103 @@
+use sea_orm::sea_query::{ColumnType as SeaQueryColumnType, StringLen};
+use sea_orm::{DbErr, TryGetable, Value};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+/// 报价单状态枚举
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter)]
+pub enum QuotationStatus {
+    /// 草稿
+    Draft,
+    /// 已发送
+    Sent,
+    /// 已接受（已转为订单）
+    Accepted,
+    /// 已过期
+    Expired,
+}
+
+impl std::str::FromStr for QuotationStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Draft" => Ok(QuotationStatus::Draft),
+            "Sent" => Ok(QuotationStatus::Sent),
+            "Accepted" => Ok(QuotationStatus::Accepted),
+            "Expired" => Ok(QuotationStatus::Expired),
+            _ => Err(()),
+        }
+    }
+}
+
+impl QuotationStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            QuotationStatus::Draft => "Draft",
+            QuotationStatus::Sent => "Sent",
+            QuotationStatus::Accepted => "Accepted",
+            QuotationStatus::Expired => "Expired",
+        }
+    }
+}
+
+// SeaORM 转换 trait 实现
+impl TryGetable for QuotationStatus {
+    fn try_get_by<I: sea_orm::ColIdx>(
+        res: &sea_orm::QueryResult,
+        idx: I,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get_by(idx).map_err(sea_orm::TryGetError::DbErr)?;
+        value
+            .parse::<QuotationStatus>()
+            .map_err(|_| sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的报价单状态"))))
+    }
+
+    fn try_get(
+        res: &sea_orm::QueryResult,
+        pre: &str,
+        col: &str,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get(pre, col).map_err(sea_orm::TryGetError::DbErr)?;
+        value
+            .parse::<QuotationStatus>()
+            .map_err(|_| sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的报价单状态"))))
+    }
+}
+
+impl sea_orm::sea_query::ValueType for QuotationStatus {
+    fn try_from(v: Value) -> Result<Self, sea_orm::sea_query::ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => s
+                .parse::<QuotationStatus>()
+                .map_err(|_| sea_orm::sea_query::ValueTypeErr),
+            _ => Err(sea_orm::sea_query::ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(QuotationStatus).to_owned()
+    }
+
+    fn array_type() -> sea_orm::sea_query::ArrayType {
+        sea_orm::sea_query::ArrayType::String
+    }
+
+    fn column_type() -> SeaQueryColumnType {
+        SeaQueryColumnType::String(StringLen::None)
+    }
+}
+
+impl From<QuotationStatus> for Value {
+    fn from(e: QuotationStatus) -> Value {
+        Value::String(Some(e.as_str().to_string()))
+    }
+}
+
+impl sea_orm::TryFromU64 for QuotationStatus {
+    fn try_from_u64(_n: u64) -> Result<Self, DbErr> {
+        Err(DbErr::Type(String::from(
+            "无法将 u64 转换为 QuotationStatus",
+        )))
+    }
+}
@@ -0,0 +1,202 @@
+use sea_orm::sea_query::{ColumnType as SeaQueryColumnType, StringLen};
+use sea_orm::{DbErr, TryGetable, Value};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+/// 库存变动类型枚举
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter)]
+pub enum StockMovementType {
+    /// 采购入库（采购订单结账）
+    PurchaseIn,
+    /// 销售出库（销售订单结账）
+    SalesOut,
+    /// 采购退货出库
+    PurchaseReturn,
+    /// 销售退货入库
+    SalesReturn,
+    /// 撤销结账冲回
+    Reopen,
+    /// 盘点调整（盘盈为正，盘亏为负）
+    Stocktake,
+}
+
+impl std::str::FromStr for StockMovementType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PurchaseIn" => Ok(StockMovementType::PurchaseIn),
+            "SalesOut" => Ok(StockMovementType::SalesOut),
+            "PurchaseReturn" => Ok(StockMovementType::PurchaseReturn),
+            "SalesReturn" => Ok(StockMovementType::SalesReturn),
+            "Reopen" => Ok(StockMovementType::Reopen),
+            "Stocktake" => Ok(StockMovementType::Stocktake),
+            _ => Err(()),
+        }
+    }
+}
+
+impl StockMovementType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            StockMovementType::PurchaseIn => "PurchaseIn",
+            StockMovementType::SalesOut => "SalesOut",
+            StockMovementType::PurchaseReturn => "PurchaseReturn",
+            StockMovementType::SalesReturn => "SalesReturn",
+            StockMovementType::Reopen => "Reopen",
+            StockMovementType::Stocktake => "Stocktake",
+        }
+    }
+}
+
+// SeaORM 转换 trait 实现
+impl TryGetable for StockMovementType {
+    fn try_get_by<I: sea_orm::ColIdx>(
+        res: &sea_orm::QueryResult,
+        idx: I,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get_by(idx).map_err(sea_orm::TryGetError::DbErr)?;
+        value.parse::<StockMovementType>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的库存变动类型")))
+        })
+    }
+
+    fn try_get(
+        res: &sea_orm::QueryResult,
+        pre: &str,
+        col: &str,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get(pre, col).map_err(sea_orm::TryGetError::DbErr)?;
+        value.parse::<StockMovementType>().map_err(|_| {
+            sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的库存变动类型")))
+        })
+    }
+}
+
+impl sea_orm::sea_query::ValueType for StockMovementType {
+    fn try_from(v: Value) -> Result<Self, sea_orm::sea_query::ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => s
+                .parse::<StockMovementType>()
+                .map_err(|_| sea_orm::sea_query::ValueTypeErr),
+            _ => Err(sea_orm::sea_query::ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(StockMovementType).to_owned()
+    }
+
+    fn array_type() -> sea_orm::sea_query::ArrayType {
+        sea_orm::sea_query::ArrayType::String
+    }
+
+    fn column_type() -> SeaQueryColumnType {
+        SeaQueryColumnType::String(StringLen::None)
+    }
+}
+
+impl From<StockMovementType> for Value {
+    fn from(e: StockMovementType) -> Value {
+        Value::String(Some(e.as_str().to_string()))
+    }
+}
+
+impl sea_orm::TryFromU64 for StockMovementType {
+    fn try_from_u64(_n: u64) -> Result<Self, DbErr> {
+        Err(DbErr::Type(String::from(
+            "无法将 u64 转换为 StockMovementType",
+        )))
+    }
+}
+
+/// 盘点单状态枚举
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter)]
+pub enum StocktakeStatus {
+    /// 草稿（可修改盘点数量）
+    Draft,
+    /// 已审核（已写入库存调整）
+    Approved,
+}
+
+impl std::str::FromStr for StocktakeStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Draft" => Ok(StocktakeStatus::Draft),
+            "Approved" => Ok(StocktakeStatus::Approved),
+            _ => Err(()),
+        }
+    }
+}
+
+impl StocktakeStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            StocktakeStatus::Draft => "Draft",
+            StocktakeStatus::Approved => "Approved",
+        }
+    }
+}
+
+// SeaORM 转换 trait 实现
+impl TryGetable for StocktakeStatus {
+    fn try_get_by<I: sea_orm::ColIdx>(
+        res: &sea_orm::QueryResult,
+        idx: I,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get_by(idx).map_err(sea_orm::TryGetError::DbErr)?;
+        value
+            .parse::<StocktakeStatus>()
+            .map_err(|_| sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的盘点单状态"))))
+    }
+
+    fn try_get(
+        res: &sea_orm::QueryResult,
+        pre: &str,
+        col: &str,
+    ) -> Result<Self, sea_orm::TryGetError> {
+        let value: String = res.try_get(pre, col).map_err(sea_orm::TryGetError::DbErr)?;
+        value
+            .parse::<StocktakeStatus>()
+            .map_err(|_| sea_orm::TryGetError::DbErr(DbErr::Type(String::from("无效的盘点单状态"))))
+    }
+}
+
+impl sea_orm::sea_query::ValueType for StocktakeStatus {
+    fn try_from(v: Value) -> Result<Self, sea_orm::sea_query::ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => s
+                .parse::<StocktakeStatus>()
+                .map_err(|_| sea_orm::sea_query::ValueTypeErr),
+            _ => Err(sea_orm::sea_query::ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        stringify!(StocktakeStatus).to_owned()
+    }
+
+    fn array_type() -> sea_orm::sea_query::ArrayType {
+        sea_orm::sea_query::ArrayType::String
+    }
+
+    fn column_type() -> SeaQueryColumnType {
+        SeaQueryColumnType::String(StringLen::None)
+    }
+}
+
+impl From<StocktakeStatus> for Value {
+    fn from(e: StocktakeStatus) -> Value {
+        Value::String(Some(e.as_str().to_string()))
+    }
+}
+
+impl sea_orm::TryFromU64 for StocktakeStatus {
+    fn try_from_u64(_n: u64) -> Result<Self, DbErr> {
+        Err(DbErr::Type(String::from(
+            "无法将 u64 转换为 StocktakeStatus",
+        )))
+    }
+}
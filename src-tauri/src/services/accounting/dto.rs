@@ -67,6 +67,7 @@ impl AddAccountingRecordDto {
 
 impl ModifyAccountingRecordDto {
     /// 将可选字段转换为内部精确类型
+    #[allow(clippy::type_complexity)]
     pub fn to_internal_types(
         &self,
     ) -> Result<
@@ -1,8 +1,8 @@
 use chrono::{Local, NaiveDateTime};
 use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
-    TransactionTrait,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
 };
 
 use crate::entity::accounting_book;
@@ -12,6 +12,19 @@ use crate::services::accounting_book::DEFAULT_BOOK_ID;
 
 use super::dto::{AddAccountingRecordDto, CreateWriteOffRecordDto, ModifyAccountingRecordDto};
 
+/// 查询订单关联的全部记账记录（含冲账，按 ID 升序），可在事务中使用
+pub(crate) async fn find_records_by_order_id<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
+    let records = accounting_record::Entity::find()
+        .filter(accounting_record::Column::OrderId.eq(order_id))
+        .order_by_asc(accounting_record::Column::Id)
+        .all(conn)
+        .await?;
+    Ok(records)
+}
+
 /// 记账服务
 #[derive(Debug)]
 pub struct AccountingService {
@@ -332,10 +345,6 @@ impl AccountingService {
         &self,
         order_id: i64,
     ) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
-        let records = accounting_record::Entity::find()
-            .filter(accounting_record::Column::OrderId.eq(order_id))
-            .all(&self.db)
-            .await?;
-        Ok(records)
+        find_records_by_order_id(&self.db, order_id).await
     }
 }
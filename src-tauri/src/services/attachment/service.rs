@@ -112,6 +112,7 @@ impl AttachmentService {
     }
 
     /// 查询附件列表(支持分页和筛选)
+    #[allow(clippy::too_many_arguments)]
     pub async fn query_attachments(
         &self,
         page: i64,
@@ -0,0 +1,119 @@
+use crate::enums::{AccountingChannel, AccountingType};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 现金流预测请求 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CashFlowForecastDto {
+    /// 预测天数（可选，默认 30，常用 30/60/90）
+    pub horizon_days: Option<i64>,
+    /// 预测起始日期（可选，格式 YYYY-MM-DD，默认今天）
+    pub start_date: Option<String>,
+}
+
+/// 预测条目来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ForecastSource {
+    /// 已入账但记账日期在预测区间内的记录
+    Posted,
+    /// 待结账订单
+    PendingOrder,
+    /// 待入账记录
+    PendingPosting,
+    /// 历史记录识别出的周期性收支
+    Recurring,
+}
+
+/// 渠道余额
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelBalance {
+    /// 渠道
+    pub channel: AccountingChannel,
+    /// 余额
+    pub balance: Decimal,
+}
+
+/// 预测条目（单笔预计收支）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastItem {
+    /// 预计发生日期
+    pub date: NaiveDate,
+    /// 来源
+    pub source: ForecastSource,
+    /// 关联单据 ID（订单 ID 或记账记录 ID，周期性条目为 None）
+    pub reference_id: Option<i64>,
+    /// 标题
+    pub title: String,
+    /// 渠道（为空表示尚未确定收付款渠道的待结账订单）
+    pub channel: Option<AccountingChannel>,
+    /// 金额（正数为流入，负数为流出）
+    pub amount: Decimal,
+}
+
+/// 预测日汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastDay {
+    /// 日期
+    pub date: NaiveDate,
+    /// 当日预计流入
+    pub inflow: Decimal,
+    /// 当日预计流出（正数）
+    pub outflow: Decimal,
+    /// 当日净额
+    pub net: Decimal,
+    /// 当日结束时各渠道余额
+    pub balances: Vec<ChannelBalance>,
+    /// 截至当日未指定渠道的预计收支累计（不计入渠道余额和负余额判断）
+    pub unassigned: Decimal,
+    /// 余额为负的渠道
+    pub negative_channels: Vec<AccountingChannel>,
+}
+
+/// 识别出的周期性收支模式
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringPattern {
+    /// 标题
+    pub title: String,
+    /// 记账类型
+    pub accounting_type: AccountingType,
+    /// 渠道
+    pub channel: AccountingChannel,
+    /// 周期（天）
+    pub interval_days: i64,
+    /// 平均金额（正数为流入，负数为流出）
+    pub average_amount: Decimal,
+    /// 历史出现次数
+    pub occurrences: usize,
+    /// 最近一次发生日期
+    pub last_date: NaiveDate,
+}
+
+/// 现金流预测结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CashFlowForecast {
+    /// 起始日期
+    pub start_date: NaiveDate,
+    /// 结束日期（含）
+    pub end_date: NaiveDate,
+    /// 期初各渠道余额
+    pub opening_balances: Vec<ChannelBalance>,
+    /// 预测条目明细
+    pub items: Vec<ForecastItem>,
+    /// 逐日汇总
+    pub days: Vec<ForecastDay>,
+    /// 识别出的周期性收支
+    pub recurring_patterns: Vec<RecurringPattern>,
+    /// 存在渠道余额为负的日期
+    pub negative_days: Vec<NaiveDate>,
+    /// 预测期内总流入
+    pub total_inflow: Decimal,
+    /// 预测期内总流出（正数）
+    pub total_outflow: Decimal,
+}
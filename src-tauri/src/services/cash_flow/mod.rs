@@ -0,0 +1,5 @@
+pub mod dto;
+pub mod service;
+
+pub use dto::*;
+pub use service::CashFlowService;
@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Local, NaiveDate};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    QueryFilter, Statement,
+};
+use strum::IntoEnumIterator;
+
+use super::dto::{
+    CashFlowForecast, CashFlowForecastDto, ChannelBalance, ForecastDay, ForecastItem,
+    ForecastSource, RecurringPattern,
+};
+use crate::entity::{accounting_record, order, order_payment};
+use crate::enums::{
+    AccountingChannel, AccountingRecordState, AccountingType, OrderStatus, OrderType,
+};
+use crate::services::dashboard::service::{sum_expr, to_decimal};
+
+/// 默认预测天数
+pub const DEFAULT_HORIZON_DAYS: i64 = 30;
+
+/// 最大预测天数
+const MAX_HORIZON_DAYS: i64 = 366;
+
+/// 识别周期性收支所需的最少出现次数
+const MIN_RECURRING_OCCURRENCES: usize = 3;
+
+/// 识别周期性收支时回看的天数（起始日之前）
+const RECURRING_LOOKBACK_DAYS: i64 = 366;
+
+/// 期初余额汇总行（按渠道 + 生效记账类型，金额为按金额精度换算的整数）
+#[derive(Debug, FromQueryResult)]
+struct OpeningBalanceRow {
+    channel: AccountingChannel,
+    /// 记录类型（冲账记录取被冲账记录的类型）
+    accounting_type: AccountingType,
+    amount: i64,
+}
+
+/// 记账类型对现金的影响方向（收入为正，支出为负）
+fn direction_of(accounting_type: &AccountingType) -> Decimal {
+    match accounting_type {
+        AccountingType::Income | AccountingType::InvestmentIncome => Decimal::ONE,
+        AccountingType::Expenditure | AccountingType::InvestmentLoss => Decimal::NEGATIVE_ONE,
+        AccountingType::WriteOff => Decimal::ONE,
+    }
+}
+
+/// 计算记录的带符号现金影响（冲账记录按被冲账记录的类型确定方向）
+fn signed_amount(
+    record: &accounting_record::Model,
+    type_map: &HashMap<i64, AccountingType>,
+) -> Decimal {
+    let effective_type = match (&record.accounting_type, record.write_off_id) {
+        (AccountingType::WriteOff, Some(parent_id)) => type_map
+            .get(&parent_id)
+            .cloned()
+            .unwrap_or(AccountingType::WriteOff),
+        (t, _) => t.clone(),
+    };
+    record.amount * direction_of(&effective_type)
+}
+
+/// 从同一标题/类型/渠道的历史日期中识别固定周期
+///
+/// 要求至少出现 3 次，且每个间隔与中位间隔的偏差不超过 max(2 天, 中位间隔的 25%)
+pub fn detect_interval(dates: &[NaiveDate]) -> Option<i64> {
+    if dates.len() < MIN_RECURRING_OCCURRENCES {
+        return None;
+    }
+
+    let mut sorted = dates.to_vec();
+    sorted.sort();
+
+    let mut intervals: Vec<i64> = sorted
+        .windows(2)
+        .map(|w| (w[1] - w[0]).num_days())
+        .collect();
+    intervals.sort();
+
+    let median = intervals[intervals.len() / 2];
+    if median < 1 {
+        return None;
+    }
+
+    let tolerance = std::cmp::max(2, median / 4);
+    if intervals.iter().all(|i| (i - median).abs() <= tolerance) {
+        Some(median)
+    } else {
+        None
+    }
+}
+
+/// 现金流预测服务
+#[derive(Debug)]
+pub struct CashFlowService {
+    db: DatabaseConnection,
+}
+
+impl CashFlowService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 生成现金流预测（待结账订单 + 待入账记录 + 周期性收支，逐日推算各渠道余额，
+    /// 未确定渠道的待结账订单单独累计）
+    pub async fn get_cash_flow_forecast(
+        &self,
+        input: CashFlowForecastDto,
+    ) -> Result<CashFlowForecast, Box<dyn std::error::Error>> {
+        let horizon_days = input.horizon_days.unwrap_or(DEFAULT_HORIZON_DAYS);
+        if !(1..=MAX_HORIZON_DAYS).contains(&horizon_days) {
+            return Err(format!("预测天数必须在 1 到 {} 之间", MAX_HORIZON_DAYS).into());
+        }
+
+        let start_date = match &input.start_date {
+            Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map_err(|_| "无效的起始日期格式，应为 YYYY-MM-DD".to_string())?,
+            None => Local::now().date_naive(),
+        };
+        let end_date = start_date + Duration::days(horizon_days - 1);
+
+        let start_time = start_date.and_hms_opt(0, 0, 0).unwrap();
+        let end_time = (end_date + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap();
+
+        // 期初余额：起始日期之前的已入账记录按渠道汇总
+        let opening = self.opening_balances(start_time).await?;
+
+        // 预测区间内的记录，以及逾期未入账的记录
+        let records = accounting_record::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(accounting_record::Column::RecordTime.gte(start_time))
+                            .add(accounting_record::Column::RecordTime.lt(end_time)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(
+                                accounting_record::Column::State
+                                    .eq(AccountingRecordState::PendingPosting),
+                            )
+                            .add(accounting_record::Column::RecordTime.lt(start_time)),
+                    ),
+            )
+            .all(&self.db)
+            .await?;
+
+        // 冲账记录按被冲账记录的类型判定方向
+        let parent_ids: Vec<i64> = records.iter().filter_map(|r| r.write_off_id).collect();
+        let type_map: HashMap<i64, AccountingType> = accounting_record::Entity::find()
+            .filter(accounting_record::Column::Id.is_in(parent_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|r| (r.id, r.accounting_type))
+            .collect();
+
+        let mut items: Vec<ForecastItem> = Vec::new();
+
+        for record in &records {
+            let date = record.record_time.date();
+            let amount = signed_amount(record, &type_map);
+
+            match record.state {
+                AccountingRecordState::Posted => {
+                    if date <= end_date {
+                        items.push(ForecastItem {
+                            date,
+                            source: ForecastSource::Posted,
+                            reference_id: Some(record.id),
+                            title: record.title.clone(),
+                            channel: Some(record.channel.clone()),
+                            amount,
+                        });
+                    }
+                }
+                AccountingRecordState::PendingPosting => {
+                    // 逾期未入账的记录视为起始日发生
+                    let expected = std::cmp::max(date, start_date);
+                    if expected <= end_date {
+                        items.push(ForecastItem {
+                            date: expected,
+                            source: ForecastSource::PendingPosting,
+                            reference_id: Some(record.id),
+                            title: record.title.clone(),
+                            channel: Some(record.channel.clone()),
+                            amount,
+                        });
+                    }
+                }
+            }
+        }
+
+        // 待结账 / 部分收付款订单：未结金额按预计收付款日期计入（无日期或已逾期视为起始日）
+        let pending_orders = order::Entity::find()
+            .filter(order::Column::Status.is_in([OrderStatus::Pending, OrderStatus::PartiallyPaid]))
+            .all(&self.db)
+            .await?;
+
+        let pending_ids: Vec<i64> = pending_orders.iter().map(|o| o.id).collect();
+        let mut paid_map: HashMap<i64, Decimal> = HashMap::new();
+        for payment in order_payment::Entity::find()
+            .filter(order_payment::Column::OrderId.is_in(pending_ids))
+            .all(&self.db)
+            .await?
+        {
+            *paid_map.entry(payment.order_id).or_insert(Decimal::ZERO) += payment.amount;
+        }
+
+        for o in &pending_orders {
+            let expected = o
+                .due_date
+                .map(|d| std::cmp::max(d.date(), start_date))
+                .unwrap_or(start_date);
+            let outstanding =
+                o.actual_amount - paid_map.get(&o.id).copied().unwrap_or(Decimal::ZERO);
+            if expected > end_date || outstanding <= Decimal::ZERO {
+                continue;
+            }
+
+            let (amount, title) = match o.order_type {
+                OrderType::Sales => (outstanding, format!("销售订单-{}", o.order_no)),
+                OrderType::Purchase => (-outstanding, format!("采购订单-{}", o.order_no)),
+            };
+
+            // 订单渠道按已收付款汇总：单一渠道沿用该渠道，待结账或分多个渠道收付款的未指定渠道
+            let channel = match o.channel {
+                AccountingChannel::Unknown => None,
+                ref channel => Some(channel.clone()),
+            };
+
+            items.push(ForecastItem {
+                date: expected,
+                source: ForecastSource::PendingOrder,
+                reference_id: Some(o.id),
+                title,
+                channel,
+                amount,
+            });
+        }
+
+        // 周期性收支：基于起始日之前、未关联订单的已入账主记录
+        let recurring_patterns = self.detect_recurring_patterns(start_date).await?;
+        for pattern in &recurring_patterns {
+            let mut next = pattern.last_date + Duration::days(pattern.interval_days);
+            // 跳过起始日之前的周期
+            while next < start_date {
+                next += Duration::days(pattern.interval_days);
+            }
+            while next <= end_date {
+                items.push(ForecastItem {
+                    date: next,
+                    source: ForecastSource::Recurring,
+                    reference_id: None,
+                    title: pattern.title.clone(),
+                    channel: Some(pattern.channel.clone()),
+                    amount: pattern.average_amount,
+                });
+                next += Duration::days(pattern.interval_days);
+            }
+        }
+
+        items.sort_by_key(|i| i.date);
+
+        // 逐日推算渠道余额（未指定渠道的单独累计）
+        let mut balances = opening.clone();
+        let mut unassigned = Decimal::ZERO;
+        let mut days: Vec<ForecastDay> = Vec::new();
+        let mut negative_days: Vec<NaiveDate> = Vec::new();
+        let mut total_inflow = Decimal::ZERO;
+        let mut total_outflow = Decimal::ZERO;
+
+        let mut date = start_date;
+        while date <= end_date {
+            let mut inflow = Decimal::ZERO;
+            let mut outflow = Decimal::ZERO;
+
+            for item in items.iter().filter(|i| i.date == date) {
+                if item.amount >= Decimal::ZERO {
+                    inflow += item.amount;
+                } else {
+                    outflow += -item.amount;
+                }
+                match &item.channel {
+                    Some(channel) => {
+                        *balances.entry(channel.clone()).or_insert(Decimal::ZERO) += item.amount
+                    }
+                    None => unassigned += item.amount,
+                }
+            }
+
+            let day_balances = Self::ordered_balances(&balances);
+            let negative_channels: Vec<AccountingChannel> = day_balances
+                .iter()
+                .filter(|b| b.balance < Decimal::ZERO)
+                .map(|b| b.channel.clone())
+                .collect();
+
+            if !negative_channels.is_empty() {
+                negative_days.push(date);
+            }
+
+            total_inflow += inflow;
+            total_outflow += outflow;
+
+            days.push(ForecastDay {
+                date,
+                inflow,
+                outflow,
+                net: inflow - outflow,
+                balances: day_balances,
+                unassigned,
+                negative_channels,
+            });
+
+            date += Duration::days(1);
+        }
+
+        Ok(CashFlowForecast {
+            start_date,
+            end_date,
+            opening_balances: Self::ordered_balances(&opening),
+            items,
+            days,
+            recurring_patterns,
+            negative_days,
+            total_inflow,
+            total_outflow,
+        })
+    }
+
+    /// 期初余额：指定时间之前的已入账记录按渠道汇总（冲账记录按被冲账记录的类型确定方向）
+    async fn opening_balances(
+        &self,
+        before: chrono::NaiveDateTime,
+    ) -> Result<HashMap<AccountingChannel, Decimal>, Box<dyn std::error::Error>> {
+        let sql = format!(
+            "SELECT r.channel, \
+             COALESCE(p.accounting_type, r.accounting_type) AS accounting_type, {} AS amount \
+             FROM accounting_record r \
+             LEFT JOIN accounting_record p ON p.id = r.write_off_id \
+             WHERE r.state = ? AND r.record_time < ? \
+             GROUP BY r.channel, COALESCE(p.accounting_type, r.accounting_type)",
+            sum_expr("r.amount")
+        );
+        let rows = OpeningBalanceRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            sql,
+            [AccountingRecordState::Posted.into(), before.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        let mut opening: HashMap<AccountingChannel, Decimal> = HashMap::new();
+        for row in rows {
+            *opening.entry(row.channel).or_insert(Decimal::ZERO) +=
+                to_decimal(row.amount) * direction_of(&row.accounting_type);
+        }
+        Ok(opening)
+    }
+
+    /// 识别周期性收支（按标题 + 记账类型 + 渠道分组，仅回看起始日之前一年内的主记录）
+    async fn detect_recurring_patterns(
+        &self,
+        before: NaiveDate,
+    ) -> Result<Vec<RecurringPattern>, Box<dyn std::error::Error>> {
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::State.eq(AccountingRecordState::Posted))
+            .filter(accounting_record::Column::OrderId.is_null())
+            .filter(accounting_record::Column::WriteOffId.is_null())
+            .filter(accounting_record::Column::AccountingType.ne(AccountingType::WriteOff))
+            .filter(
+                accounting_record::Column::RecordTime
+                    .gte((before - Duration::days(RECURRING_LOOKBACK_DAYS)).and_hms_opt(0, 0, 0)),
+            )
+            .filter(accounting_record::Column::RecordTime.lt(before.and_hms_opt(0, 0, 0)))
+            .all(&self.db)
+            .await?;
+
+        let mut groups: HashMap<(String, String, String), Vec<&accounting_record::Model>> =
+            HashMap::new();
+
+        for record in &records {
+            groups
+                .entry((
+                    record.title.clone(),
+                    record.accounting_type.to_string(),
+                    record.channel.to_string(),
+                ))
+                .or_default()
+                .push(record);
+        }
+
+        let mut patterns: Vec<RecurringPattern> = groups
+            .into_values()
+            .filter_map(|group| {
+                let dates: Vec<NaiveDate> = group.iter().map(|r| r.record_time.date()).collect();
+                let interval_days = detect_interval(&dates)?;
+                let last_date = *dates.iter().max()?;
+
+                // 已中断的模式（超过两个周期未再出现）不再预测
+                if (before - last_date).num_days() > interval_days * 2 {
+                    return None;
+                }
+
+                let total: Decimal = group
+                    .iter()
+                    .map(|r| r.amount * direction_of(&r.accounting_type))
+                    .sum();
+                let average_amount = (total / Decimal::from(group.len())).round_dp(2);
+                let first = group[0];
+
+                Some(RecurringPattern {
+                    title: first.title.clone(),
+                    accounting_type: first.accounting_type.clone(),
+                    channel: first.channel.clone(),
+                    interval_days,
+                    average_amount,
+                    occurrences: group.len(),
+                    last_date,
+                })
+            })
+            .collect();
+
+        patterns.sort_by(|a, b| a.title.cmp(&b.title));
+        Ok(patterns)
+    }
+
+    /// 按渠道枚举顺序输出余额（仅包含出现过的渠道）
+    fn ordered_balances(balances: &HashMap<AccountingChannel, Decimal>) -> Vec<ChannelBalance> {
+        AccountingChannel::iter()
+            .filter_map(|channel| {
+                balances.get(&channel).map(|balance| ChannelBalance {
+                    channel: channel.clone(),
+                    balance: *balance,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,99 @@
+use crate::enums::StockMovementType;
+use crate::services::setting::dto::CostingMethod;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 查询销售成本 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCogsDto {
+    /// 开始日期（格式 YYYY-MM-DD，含，可选）
+    pub start_date: Option<String>,
+    /// 结束日期（格式 YYYY-MM-DD，含，可选）
+    pub end_date: Option<String>,
+    /// 订单筛选（可选）
+    pub order_id: Option<i64>,
+}
+
+/// 销售明细成本（销售出库为正，退货入库、撤销结账冲回为负）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SalesLineCost {
+    /// 库存变动 ID
+    pub movement_id: i64,
+    /// 变动类型
+    pub movement_type: StockMovementType,
+    /// 关联订单 ID
+    pub order_id: Option<i64>,
+    /// 关联订单明细 ID
+    pub order_item_id: Option<i64>,
+    /// 来源单据编号
+    pub source_no: Option<String>,
+    /// 商品 ID
+    pub product_id: i64,
+    /// 商品名称
+    pub product_name: String,
+    /// 单位
+    pub unit: String,
+    /// 销售数量
+    pub quantity: Decimal,
+    /// 单位成本
+    pub unit_cost: Decimal,
+    /// 销售成本
+    pub cost: Decimal,
+    /// 变动时间
+    pub date: NaiveDateTime,
+}
+
+/// 销售成本报表
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CogsReport {
+    /// 成本核算方法
+    pub method: CostingMethod,
+    /// 销售明细成本（按变动时间升序）
+    pub lines: Vec<SalesLineCost>,
+    /// 销售成本合计
+    pub total_cost: Decimal,
+}
+
+/// 库存估值查询 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryValuationDto {
+    /// 估值日期（格式 YYYY-MM-DD，按当日结束时的库存估值，可选，默认今天）
+    pub as_of_date: Option<String>,
+}
+
+/// 商品库存估值
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValuationLine {
+    /// 商品 ID
+    pub product_id: i64,
+    /// 商品名称
+    pub product_name: String,
+    /// 单位
+    pub unit: String,
+    /// 结存数量
+    pub quantity: Decimal,
+    /// 单位成本（结存数量不大于 0 时为 None）
+    pub unit_cost: Option<Decimal>,
+    /// 库存金额
+    pub value: Decimal,
+}
+
+/// 库存估值报表
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryValuation {
+    /// 估值日期
+    pub as_of_date: NaiveDate,
+    /// 成本核算方法
+    pub method: CostingMethod,
+    /// 各商品估值（按商品 ID、单位排序，不含结存为 0 的商品）
+    pub lines: Vec<ValuationLine>,
+    /// 库存金额合计
+    pub total_value: Decimal,
+}
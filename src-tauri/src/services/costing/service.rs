@@ -0,0 +1,483 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use chrono::{Duration, Local, NaiveDate};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+
+use super::dto::{
+    CogsReport, InventoryValuation, InventoryValuationDto, QueryCogsDto, SalesLineCost,
+    ValuationLine,
+};
+use crate::entity::stock_movement;
+use crate::enums::StockMovementType;
+use crate::services::setting::dto::CostingMethod;
+use crate::services::setting::service::load_costing_config;
+
+/// 库存键（商品 ID + 单位）
+type StockKey = (i64, String);
+
+/// 解析日期（YYYY-MM-DD）
+fn parse_date(s: &str, err: &str) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| err.to_string().into())
+}
+
+/// 成本层（先进先出按入库顺序出库）
+struct CostLayer {
+    quantity: Decimal,
+    unit_cost: Decimal,
+    order_id: Option<i64>,
+}
+
+/// 单个商品 + 单位的成本状态
+#[derive(Default)]
+struct CostState {
+    /// 结存数量（可能为负，表示先销后进）
+    quantity: Decimal,
+    /// 结存金额（移动加权平均）
+    value: Decimal,
+    /// 成本层（先进先出）
+    layers: VecDeque<CostLayer>,
+    /// 最近一次入库单位成本（无结存时出库按此计价）
+    last_cost: Option<Decimal>,
+}
+
+impl CostState {
+    /// 当前出库单位成本
+    fn unit_cost(&self, method: CostingMethod) -> Option<Decimal> {
+        match method {
+            CostingMethod::MovingAverage if self.quantity > Decimal::ZERO => {
+                Some(self.value / self.quantity)
+            }
+            CostingMethod::Fifo => self.layers.front().map(|l| l.unit_cost).or(self.last_cost),
+            _ => self.last_cost,
+        }
+    }
+
+    /// 结存金额
+    fn value(&self, method: CostingMethod) -> Decimal {
+        match method {
+            CostingMethod::MovingAverage if self.quantity > Decimal::ZERO => self.value,
+            CostingMethod::MovingAverage => Decimal::ZERO,
+            CostingMethod::Fifo => self.layers.iter().map(|l| l.quantity * l.unit_cost).sum(),
+        }
+    }
+
+    /// 按指定单位成本入库（负库存先冲抵，不形成成本层）
+    fn receive(
+        &mut self,
+        method: CostingMethod,
+        quantity: Decimal,
+        unit_cost: Decimal,
+        order_id: Option<i64>,
+    ) {
+        let prior = self.quantity;
+        self.quantity += quantity;
+        self.last_cost = Some(unit_cost);
+        match method {
+            CostingMethod::MovingAverage => {
+                if prior <= Decimal::ZERO {
+                    self.value = self.quantity.max(Decimal::ZERO) * unit_cost;
+                } else {
+                    self.value += quantity * unit_cost;
+                }
+            }
+            CostingMethod::Fifo => {
+                let filled = if prior < Decimal::ZERO {
+                    quantity.min(-prior)
+                } else {
+                    Decimal::ZERO
+                };
+                if quantity > filled {
+                    self.layers.push_back(CostLayer {
+                        quantity: quantity - filled,
+                        unit_cost,
+                        order_id,
+                    });
+                }
+            }
+        }
+    }
+
+    /// 按核算方法出库，返回出库成本（结存不足部分按最近入库成本计价）
+    fn issue(&mut self, method: CostingMethod, quantity: Decimal) -> Decimal {
+        let cost = match method {
+            CostingMethod::MovingAverage => quantity * self.unit_cost(method).unwrap_or_default(),
+            CostingMethod::Fifo => {
+                let mut remaining = quantity;
+                let mut cost = Decimal::ZERO;
+                while remaining > Decimal::ZERO {
+                    let Some(layer) = self.layers.front_mut() else {
+                        cost += remaining * self.last_cost.unwrap_or_default();
+                        break;
+                    };
+                    let taken = remaining.min(layer.quantity);
+                    cost += taken * layer.unit_cost;
+                    layer.quantity -= taken;
+                    remaining -= taken;
+                    if layer.quantity.is_zero() {
+                        self.layers.pop_front();
+                    }
+                }
+                cost
+            }
+        };
+        self.quantity -= quantity;
+        self.reduce_value(cost);
+        cost
+    }
+
+    /// 按指定单位成本出库（采购退货、撤销采购结账），先进先出时优先扣减该订单的成本层
+    fn issue_at(
+        &mut self,
+        method: CostingMethod,
+        quantity: Decimal,
+        unit_cost: Decimal,
+        order_id: Option<i64>,
+    ) -> Decimal {
+        let cost = quantity * unit_cost;
+        if method == CostingMethod::Fifo {
+            let mut remaining = quantity;
+            for layer in self.layers.iter_mut().filter(|l| l.order_id == order_id) {
+                let taken = remaining.min(layer.quantity);
+                layer.quantity -= taken;
+                remaining -= taken;
+            }
+            for layer in self.layers.iter_mut() {
+                let taken = remaining.min(layer.quantity);
+                layer.quantity -= taken;
+                remaining -= taken;
+            }
+            self.layers.retain(|l| !l.quantity.is_zero());
+        }
+        self.quantity -= quantity;
+        self.reduce_value(cost);
+        cost
+    }
+
+    /// 移动加权平均出库后扣减结存金额（无结存时清零）
+    fn reduce_value(&mut self, cost: Decimal) {
+        if self.quantity <= Decimal::ZERO {
+            self.value = Decimal::ZERO;
+        } else {
+            self.value = (self.value - cost).max(Decimal::ZERO);
+        }
+    }
+}
+
+/// 按时间顺序重放库存变动的成本核算结果
+#[derive(Default)]
+struct CostReplay {
+    states: HashMap<StockKey, CostState>,
+    names: HashMap<StockKey, String>,
+    /// 每条库存变动的成本金额（绝对值）
+    movement_costs: HashMap<i64, Decimal>,
+    /// 出库时尚无计价入库记录、成本未知的库存变动
+    unpriced: HashSet<i64>,
+}
+
+/// 重放库存变动
+///
+/// 采购入库按采购单价入库；销售出库按核算方法计价；采购退货、撤销采购结账按原采购单价出库；
+/// 销售退货、撤销销售结账按该订单原出库成本入库
+fn replay(method: CostingMethod, movements: &[stock_movement::Model]) -> CostReplay {
+    let mut result = CostReplay::default();
+    // 按订单 + 商品汇总的采购入库、销售出库（数量，金额），用于退货和撤销结账按原成本冲回
+    let mut purchased: HashMap<(Option<i64>, StockKey), (Decimal, Decimal)> = HashMap::new();
+    let mut sold: HashMap<(Option<i64>, StockKey), (Decimal, Decimal)> = HashMap::new();
+    let average = |totals: Option<&(Decimal, Decimal)>| {
+        totals
+            .filter(|(quantity, _)| *quantity > Decimal::ZERO)
+            .map(|(quantity, amount)| amount / quantity)
+    };
+
+    for m in movements {
+        let key: StockKey = (m.product_id, m.unit.clone());
+        let order_key = (m.order_id, key.clone());
+        result.names.insert(key.clone(), m.product_name.clone());
+        let state = result.states.entry(key).or_default();
+        let quantity = m.quantity.abs();
+
+        let cost = if m.quantity > Decimal::ZERO {
+            let unit_cost = match m.unit_cost {
+                Some(unit_cost) => unit_cost,
+                None => average(sold.get(&order_key))
+                    .or_else(|| state.unit_cost(method))
+                    .unwrap_or_default(),
+            };
+            // 从未有过计价入库时（如无采购记录的销售退货）按零成本入库，但不视为已知成本
+            let priced = m.unit_cost.is_some() || state.last_cost.is_some();
+            state.receive(method, quantity, unit_cost, m.order_id);
+            if !priced {
+                state.last_cost = None;
+            }
+            let cost = quantity * unit_cost;
+            match m.movement_type {
+                StockMovementType::PurchaseIn => {
+                    let entry = purchased.entry(order_key).or_default();
+                    entry.0 += quantity;
+                    entry.1 += cost;
+                }
+                StockMovementType::SalesReturn | StockMovementType::Reopen => {
+                    let entry = sold.entry(order_key).or_default();
+                    entry.0 -= quantity;
+                    entry.1 -= cost;
+                }
+                _ => {}
+            }
+            cost
+        } else if m.quantity < Decimal::ZERO {
+            let origin_cost = m.unit_cost.or_else(|| match m.movement_type {
+                StockMovementType::PurchaseReturn => average(purchased.get(&order_key)),
+                _ => None,
+            });
+            match origin_cost {
+                Some(unit_cost) => {
+                    let cost = state.issue_at(method, quantity, unit_cost, m.order_id);
+                    let entry = purchased.entry(order_key).or_default();
+                    entry.0 -= quantity;
+                    entry.1 -= cost;
+                    cost
+                }
+                None => {
+                    if state.last_cost.is_none() {
+                        result.unpriced.insert(m.id);
+                    }
+                    let cost = state.issue(method, quantity);
+                    if m.movement_type == StockMovementType::SalesOut {
+                        let entry = sold.entry(order_key).or_default();
+                        entry.0 += quantity;
+                        entry.1 += cost;
+                    }
+                    cost
+                }
+            }
+        } else {
+            Decimal::ZERO
+        };
+        result.movement_costs.insert(m.id, cost);
+    }
+
+    result
+}
+
+/// 查询截至指定日期（含）的全部库存变动（按变动时间升序）
+pub(crate) async fn load_movements<C: ConnectionTrait>(
+    conn: &C,
+    until: Option<NaiveDate>,
+) -> Result<Vec<stock_movement::Model>, Box<dyn std::error::Error>> {
+    let mut query = stock_movement::Entity::find();
+    if let Some(date) = until {
+        query = query.filter(
+            stock_movement::Column::CreateAt.lt((date + Duration::days(1)).and_hms_opt(0, 0, 0)),
+        );
+    }
+    let movements = query
+        .order_by_asc(stock_movement::Column::CreateAt)
+        .order_by_asc(stock_movement::Column::Id)
+        .all(conn)
+        .await?;
+    Ok(movements)
+}
+
+/// 按当前核算方法查询各商品 + 单位的当前出库单位成本（无计价入库记录的不返回）
+pub(crate) async fn current_unit_costs<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<HashMap<StockKey, Decimal>, Box<dyn std::error::Error>> {
+    let method = load_costing_config(conn).await?.method;
+    let movements = load_movements(conn, None).await?;
+    let replay = replay(method, &movements);
+    Ok(replay
+        .states
+        .into_iter()
+        .filter(|(_, state)| state.last_cost.is_some())
+        .filter_map(|(key, state)| state.unit_cost(method).map(|c| (key, c.round_dp(4))))
+        .collect())
+}
+
+/// 销售订单明细的净销售成本（最近一次结账的销售出库扣除销售退货），按订单明细 ID 索引
+///
+/// 与销售成本报表按同一次重放计价；出库时尚无计价入库记录的明细成本未知，不返回
+pub(crate) async fn sales_item_costs<C: ConnectionTrait>(
+    conn: &C,
+    order_ids: &HashSet<i64>,
+) -> Result<HashMap<i64, Decimal>, Box<dyn std::error::Error>> {
+    let method = load_costing_config(conn).await?.method;
+    let movements = load_movements(conn, None).await?;
+    let replay = replay(method, &movements);
+
+    // 撤销结账前的出库已整单冲回，只取每张订单最后一次撤销结账之后的变动
+    let mut last_reopen: HashMap<i64, i64> = HashMap::new();
+    for m in movements
+        .iter()
+        .filter(|m| m.movement_type == StockMovementType::Reopen)
+    {
+        if let Some(order_id) = m.order_id {
+            last_reopen.insert(order_id, m.id);
+        }
+    }
+
+    let mut costs: HashMap<i64, Decimal> = HashMap::new();
+    let mut unpriced: HashSet<i64> = HashSet::new();
+    for m in movements.iter().filter(|m| {
+        matches!(
+            m.movement_type,
+            StockMovementType::SalesOut | StockMovementType::SalesReturn
+        )
+    }) {
+        let (Some(order_id), Some(item_id)) = (m.order_id, m.order_item_id) else {
+            continue;
+        };
+        if !order_ids.contains(&order_id) || last_reopen.get(&order_id).is_some_and(|id| m.id < *id)
+        {
+            continue;
+        }
+        if replay.unpriced.contains(&m.id) {
+            unpriced.insert(item_id);
+        }
+        let cost = replay
+            .movement_costs
+            .get(&m.id)
+            .copied()
+            .unwrap_or_default();
+        *costs.entry(item_id).or_default() += if m.quantity < Decimal::ZERO {
+            cost
+        } else {
+            -cost
+        };
+    }
+
+    Ok(costs
+        .into_iter()
+        .filter(|(item_id, _)| !unpriced.contains(item_id))
+        .map(|(item_id, cost)| (item_id, cost.round_dp(2)))
+        .collect())
+}
+
+/// 成本核算服务
+#[derive(Debug)]
+pub struct CostingService {
+    db: DatabaseConnection,
+}
+
+impl CostingService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查询销售明细成本（销售出库、销售退货、撤销销售结账）
+    pub async fn get_cogs(
+        &self,
+        input: QueryCogsDto,
+    ) -> Result<CogsReport, Box<dyn std::error::Error>> {
+        let start_date = match &input.start_date {
+            Some(s) => Some(parse_date(s, "无效的开始日期格式，应为 YYYY-MM-DD")?),
+            None => None,
+        };
+        let end_date = match &input.end_date {
+            Some(s) => Some(parse_date(s, "无效的结束日期格式，应为 YYYY-MM-DD")?),
+            None => None,
+        };
+        if let (Some(start), Some(end)) = (start_date, end_date) {
+            if end < start {
+                return Err("结束日期不能早于开始日期".into());
+            }
+        }
+
+        let method = load_costing_config(&self.db).await?.method;
+        // 成本依赖此前全部库存变动，从头重放到结束日期
+        let movements = load_movements(&self.db, end_date).await?;
+        let replay = replay(method, &movements);
+
+        let lines: Vec<SalesLineCost> = movements
+            .into_iter()
+            .filter(|m| match m.movement_type {
+                StockMovementType::SalesOut | StockMovementType::SalesReturn => true,
+                // 撤销销售结账为入库冲回，撤销采购结账为出库冲回
+                StockMovementType::Reopen => m.quantity > Decimal::ZERO,
+                _ => false,
+            })
+            .filter(|m| start_date.is_none_or(|d| m.create_at.date() >= d))
+            .filter(|m| input.order_id.is_none_or(|id| m.order_id == Some(id)))
+            .map(|m| {
+                let quantity = -m.quantity;
+                let cost = replay
+                    .movement_costs
+                    .get(&m.id)
+                    .copied()
+                    .unwrap_or_default();
+                let cost = if quantity < Decimal::ZERO {
+                    -cost
+                } else {
+                    cost
+                };
+                SalesLineCost {
+                    movement_id: m.id,
+                    movement_type: m.movement_type,
+                    order_id: m.order_id,
+                    order_item_id: m.order_item_id,
+                    source_no: m.source_no,
+                    product_id: m.product_id,
+                    product_name: m.product_name,
+                    unit: m.unit,
+                    quantity,
+                    unit_cost: if quantity.is_zero() {
+                        Decimal::ZERO
+                    } else {
+                        (cost / quantity).round_dp(4)
+                    },
+                    cost: cost.round_dp(2),
+                    date: m.create_at,
+                }
+            })
+            .collect();
+
+        Ok(CogsReport {
+            method,
+            total_cost: lines.iter().map(|l| l.cost).sum(),
+            lines,
+        })
+    }
+
+    /// 库存估值（按指定日期结束时的结存数量和核算成本）
+    pub async fn get_inventory_valuation(
+        &self,
+        input: InventoryValuationDto,
+    ) -> Result<InventoryValuation, Box<dyn std::error::Error>> {
+        let as_of_date = match &input.as_of_date {
+            Some(s) => parse_date(s, "无效的估值日期格式，应为 YYYY-MM-DD")?,
+            None => Local::now().date_naive(),
+        };
+
+        let method = load_costing_config(&self.db).await?.method;
+        let movements = load_movements(&self.db, Some(as_of_date)).await?;
+        let replay = replay(method, &movements);
+
+        let mut lines: Vec<ValuationLine> = replay
+            .states
+            .iter()
+            .filter(|(_, state)| !state.quantity.is_zero())
+            .map(|(key, state)| {
+                let value = state.value(method).round_dp(2);
+                ValuationLine {
+                    product_id: key.0,
+                    product_name: replay.names.get(key).cloned().unwrap_or_default(),
+                    unit: key.1.clone(),
+                    quantity: state.quantity,
+                    unit_cost: (state.quantity > Decimal::ZERO)
+                        .then(|| (state.value(method) / state.quantity).round_dp(4)),
+                    value,
+                }
+            })
+            .collect();
+        lines.sort_by(|a, b| a.product_id.cmp(&b.product_id).then(a.unit.cmp(&b.unit)));
+
+        Ok(InventoryValuation {
+            as_of_date,
+            method,
+            total_value: lines.iter().map(|l| l.value).sum(),
+            lines,
+        })
+    }
+}
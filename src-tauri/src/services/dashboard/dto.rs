@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 时间粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeGranularity {
+    /// 按日
+    Day,
+    /// 按周（周一为一周起始）
+    Week,
+    /// 按月
+    Month,
+}
+
+impl std::str::FromStr for TimeGranularity {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Day" => Ok(TimeGranularity::Day),
+            "Week" => Ok(TimeGranularity::Week),
+            "Month" => Ok(TimeGranularity::Month),
+            _ => Err(()),
+        }
+    }
+}
+
+/// 仪表盘时间序列查询 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardSeriesDto {
+    /// 开始日期（格式 YYYY-MM-DD，含）
+    pub start_date: String,
+    /// 结束日期（格式 YYYY-MM-DD，含）
+    pub end_date: String,
+    /// 时间粒度（Day / Week / Month）
+    pub granularity: String,
+    /// 账本筛选（可选）
+    pub book_id: Option<i64>,
+    /// 渠道筛选（可选）
+    pub channel: Option<String>,
+    /// 品类筛选（可选，记账记录按品类的销售/进货账本筛选，订单按明细商品所属品类筛选）
+    pub category_id: Option<i64>,
+}
+
+/// 指标汇总
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardMetrics {
+    /// 收入（含冲账抵减）
+    pub income: Decimal,
+    /// 支出（含冲账抵减）
+    pub expenditure: Decimal,
+    /// 净额（收入 - 支出）
+    pub net: Decimal,
+    /// 销售订单数（未取消，按创建时间）
+    pub order_count: i64,
+    /// 平均客单价
+    pub average_order_value: Decimal,
+    /// 已结账销售额（按结账时间）
+    pub settled_sales: Decimal,
+}
+
+/// 时间桶
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardBucket {
+    /// 时间桶起始日期（日 / 周一 / 月初）
+    pub bucket_start: NaiveDate,
+    /// 时间桶指标
+    #[serde(flatten)]
+    pub metrics: DashboardMetrics,
+}
+
+/// 单项指标环比
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricDelta {
+    /// 本期值
+    pub current: Decimal,
+    /// 上期值
+    pub previous: Decimal,
+    /// 变化量（本期 - 上期）
+    pub change: Decimal,
+    /// 变化率（百分比，上期为 0 时为 None）
+    pub change_rate: Option<Decimal>,
+}
+
+/// 各指标环比
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardDeltas {
+    pub income: MetricDelta,
+    pub expenditure: MetricDelta,
+    pub net: MetricDelta,
+    pub order_count: MetricDelta,
+    pub average_order_value: MetricDelta,
+    pub settled_sales: MetricDelta,
+}
+
+/// 仪表盘时间序列结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardSeries {
+    /// 时间粒度
+    pub granularity: TimeGranularity,
+    /// 开始日期
+    pub start_date: NaiveDate,
+    /// 结束日期（含）
+    pub end_date: NaiveDate,
+    /// 时间桶序列（无数据的时间桶补零）
+    pub buckets: Vec<DashboardBucket>,
+    /// 本期汇总
+    pub totals: DashboardMetrics,
+    /// 上期（紧邻本期之前的等长区间）汇总
+    pub previous_totals: DashboardMetrics,
+    /// 上期开始日期
+    pub previous_start_date: NaiveDate,
+    /// 上期结束日期（含）
+    pub previous_end_date: NaiveDate,
+    /// 环比变化
+    pub deltas: DashboardDeltas,
+}
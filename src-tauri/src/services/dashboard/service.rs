@@ -0,0 +1,502 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, QueryFilter,
+    Statement, Value,
+};
+
+use super::dto::{
+    DashboardBucket, DashboardDeltas, DashboardMetrics, DashboardSeries, DashboardSeriesDto,
+    MetricDelta, TimeGranularity,
+};
+use crate::entity::category;
+use crate::enums::{
+    AccountingChannel, AccountingRecordState, AccountingType, OrderStatus, OrderType,
+};
+use crate::services::accounting_book::DEFAULT_BOOK_ID;
+use crate::services::category::DEFAULT_CATEGORY_NAME;
+
+/// 单次查询允许的最大时间桶数量
+const MAX_BUCKETS: usize = 1000;
+
+/// 金额列的小数位数（Decimal(19, 4)），SQL 汇总时先按此精度换算为整数再求和
+const AMOUNT_SCALE: u32 = 4;
+
+/// 记账记录分桶汇总行（金额为按 AMOUNT_SCALE 换算的整数）
+#[derive(Debug, FromQueryResult)]
+struct RecordBucketRow {
+    bucket: Option<String>,
+    /// 记录类型（冲账记录取被冲账记录的类型）
+    accounting_type: AccountingType,
+    amount: i64,
+}
+
+/// 订单分桶汇总行（按创建时间，金额为按 AMOUNT_SCALE 换算的整数）
+#[derive(Debug, FromQueryResult)]
+struct OrderBucketRow {
+    bucket: Option<String>,
+    order_count: i64,
+    order_value: i64,
+}
+
+/// 已结账销售额分桶汇总行（按结账时间，金额为按 AMOUNT_SCALE 换算的整数）
+#[derive(Debug, FromQueryResult)]
+struct SettledBucketRow {
+    bucket: Option<String>,
+    settled_sales: i64,
+}
+
+/// 时间桶累加值
+#[derive(Debug, Clone, Default)]
+struct BucketAccumulator {
+    income: Decimal,
+    expenditure: Decimal,
+    order_count: i64,
+    order_value: Decimal,
+    settled_sales: Decimal,
+}
+
+impl BucketAccumulator {
+    fn add(&mut self, other: &BucketAccumulator) {
+        self.income += other.income;
+        self.expenditure += other.expenditure;
+        self.order_count += other.order_count;
+        self.order_value += other.order_value;
+        self.settled_sales += other.settled_sales;
+    }
+
+    fn to_metrics(&self) -> DashboardMetrics {
+        let average_order_value = if self.order_count > 0 {
+            (self.order_value / Decimal::from(self.order_count)).round_dp(2)
+        } else {
+            Decimal::ZERO
+        };
+        DashboardMetrics {
+            income: self.income,
+            expenditure: self.expenditure,
+            net: self.income - self.expenditure,
+            order_count: self.order_count,
+            average_order_value,
+            settled_sales: self.settled_sales,
+        }
+    }
+}
+
+/// 已解析的筛选条件
+struct SeriesFilter {
+    book_id: Option<i64>,
+    channel: Option<AccountingChannel>,
+    category: Option<category::Model>,
+    /// "未分类"品类 ID（商品未设置品类时归入该品类）
+    default_category_id: i64,
+}
+
+/// 生成精确求和表达式（按金额精度换算为整数后求和，避免 SQLite 浮点累加误差）
+pub(crate) fn sum_expr(column: &str) -> String {
+    format!(
+        "SUM(CAST(ROUND({} * {}) AS INTEGER))",
+        column,
+        10i64.pow(AMOUNT_SCALE)
+    )
+}
+
+/// 整数汇总值换算回 Decimal 金额
+pub(crate) fn to_decimal(value: i64) -> Decimal {
+    Decimal::new(value, AMOUNT_SCALE)
+}
+
+/// 生成 SQLite 分桶表达式（结果为时间桶起始日期 YYYY-MM-DD）
+fn bucket_expr(granularity: TimeGranularity, column: &str) -> String {
+    match granularity {
+        TimeGranularity::Day => format!("date({})", column),
+        TimeGranularity::Week => format!("date({}, 'weekday 0', '-6 days')", column),
+        TimeGranularity::Month => format!("strftime('%Y-%m-01', {})", column),
+    }
+}
+
+/// 计算日期所在时间桶的起始日期
+pub fn bucket_start(granularity: TimeGranularity, date: NaiveDate) -> NaiveDate {
+    match granularity {
+        TimeGranularity::Day => date,
+        TimeGranularity::Week => {
+            date - Duration::days(date.weekday().num_days_from_monday() as i64)
+        }
+        TimeGranularity::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+/// 下一个时间桶的起始日期
+fn next_bucket(granularity: TimeGranularity, start: NaiveDate) -> NaiveDate {
+    match granularity {
+        TimeGranularity::Day => start + Duration::days(1),
+        TimeGranularity::Week => start + Duration::days(7),
+        TimeGranularity::Month => start
+            .checked_add_months(Months::new(1))
+            .unwrap_or(NaiveDate::MAX),
+    }
+}
+
+/// 环比计算
+fn delta(current: Decimal, previous: Decimal) -> MetricDelta {
+    let change = current - previous;
+    let change_rate = if previous.is_zero() {
+        None
+    } else {
+        Some((change / previous.abs() * Decimal::ONE_HUNDRED).round_dp(2))
+    };
+    MetricDelta {
+        current,
+        previous,
+        change,
+        change_rate,
+    }
+}
+
+/// 仪表盘统计服务
+#[derive(Debug)]
+pub struct DashboardService {
+    db: DatabaseConnection,
+}
+
+impl DashboardService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取仪表盘时间序列（收入、支出、净额、订单数、客单价、已结账销售额，含环比）
+    pub async fn get_dashboard_series(
+        &self,
+        input: DashboardSeriesDto,
+    ) -> Result<DashboardSeries, Box<dyn std::error::Error>> {
+        let granularity = input
+            .granularity
+            .parse::<TimeGranularity>()
+            .map_err(|_| "无效的时间粒度".to_string())?;
+        let start_date = NaiveDate::parse_from_str(&input.start_date, "%Y-%m-%d")
+            .map_err(|_| "无效的开始日期格式，应为 YYYY-MM-DD".to_string())?;
+        let end_date = NaiveDate::parse_from_str(&input.end_date, "%Y-%m-%d")
+            .map_err(|_| "无效的结束日期格式，应为 YYYY-MM-DD".to_string())?;
+        if end_date < start_date {
+            return Err("结束日期不能早于开始日期".into());
+        }
+
+        let channel = match &input.channel {
+            Some(s) => Some(
+                s.parse::<AccountingChannel>()
+                    .map_err(|_| "无效的渠道".to_string())?,
+            ),
+            None => None,
+        };
+        let category = match input.category_id {
+            Some(id) => Some(
+                category::Entity::find_by_id(id)
+                    .one(&self.db)
+                    .await?
+                    .ok_or("品类不存在")?,
+            ),
+            None => None,
+        };
+        let default_category_id = category::Entity::find()
+            .filter(category::Column::Name.eq(DEFAULT_CATEGORY_NAME))
+            .one(&self.db)
+            .await?
+            .map(|c| c.id)
+            .unwrap_or(0);
+
+        let filter = SeriesFilter {
+            book_id: input.book_id,
+            channel,
+            category,
+            default_category_id,
+        };
+
+        // 生成完整时间桶序列（无数据的时间桶补零）
+        let mut bucket_starts: Vec<NaiveDate> = Vec::new();
+        let mut cursor = bucket_start(granularity, start_date);
+        while cursor <= end_date {
+            bucket_starts.push(cursor);
+            if bucket_starts.len() > MAX_BUCKETS {
+                return Err("时间桶数量过多，请缩小日期范围或增大时间粒度".into());
+            }
+            cursor = next_bucket(granularity, cursor);
+        }
+
+        let current = self
+            .collect_buckets(granularity, start_date, end_date, &filter)
+            .await?;
+
+        // 上期：紧邻本期之前的等长区间
+        let period_days = (end_date - start_date).num_days() + 1;
+        let previous_end_date = start_date - Duration::days(1);
+        let previous_start_date = previous_end_date - Duration::days(period_days - 1);
+        let previous = self
+            .collect_buckets(granularity, previous_start_date, previous_end_date, &filter)
+            .await?;
+
+        let mut totals = BucketAccumulator::default();
+        let buckets: Vec<DashboardBucket> = bucket_starts
+            .into_iter()
+            .map(|date| {
+                let acc = current.get(&date).cloned().unwrap_or_default();
+                totals.add(&acc);
+                DashboardBucket {
+                    bucket_start: date,
+                    metrics: acc.to_metrics(),
+                }
+            })
+            .collect();
+
+        let mut previous_acc = BucketAccumulator::default();
+        for acc in previous.values() {
+            previous_acc.add(acc);
+        }
+
+        let totals = totals.to_metrics();
+        let previous_totals = previous_acc.to_metrics();
+        let deltas = DashboardDeltas {
+            income: delta(totals.income, previous_totals.income),
+            expenditure: delta(totals.expenditure, previous_totals.expenditure),
+            net: delta(totals.net, previous_totals.net),
+            order_count: delta(
+                Decimal::from(totals.order_count),
+                Decimal::from(previous_totals.order_count),
+            ),
+            average_order_value: delta(
+                totals.average_order_value,
+                previous_totals.average_order_value,
+            ),
+            settled_sales: delta(totals.settled_sales, previous_totals.settled_sales),
+        };
+
+        Ok(DashboardSeries {
+            granularity,
+            start_date,
+            end_date,
+            buckets,
+            totals,
+            previous_totals,
+            previous_start_date,
+            previous_end_date,
+            deltas,
+        })
+    }
+
+    /// 按时间桶汇总区间内的记账记录与订单
+    async fn collect_buckets(
+        &self,
+        granularity: TimeGranularity,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &SeriesFilter,
+    ) -> Result<BTreeMap<NaiveDate, BucketAccumulator>, Box<dyn std::error::Error>> {
+        let start_time = start_date.and_hms_opt(0, 0, 0).unwrap();
+        let end_time = end_date.and_hms_opt(23, 59, 59).unwrap();
+
+        let mut result: BTreeMap<NaiveDate, BucketAccumulator> = BTreeMap::new();
+        let parse_bucket = |bucket: &Option<String>| {
+            bucket
+                .as_deref()
+                .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+        };
+
+        for row in self
+            .query_record_buckets(granularity, start_time, end_time, filter)
+            .await?
+        {
+            if let Some(date) = parse_bucket(&row.bucket) {
+                let acc = result.entry(date).or_default();
+                match row.accounting_type {
+                    AccountingType::Income | AccountingType::InvestmentIncome => {
+                        acc.income += to_decimal(row.amount)
+                    }
+                    AccountingType::Expenditure | AccountingType::InvestmentLoss => {
+                        acc.expenditure += to_decimal(row.amount)
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for row in self
+            .query_order_buckets(granularity, start_time, end_time, filter)
+            .await?
+        {
+            if let Some(date) = parse_bucket(&row.bucket) {
+                let acc = result.entry(date).or_default();
+                acc.order_count += row.order_count;
+                acc.order_value += to_decimal(row.order_value);
+            }
+        }
+
+        for row in self
+            .query_settled_buckets(granularity, start_time, end_time, filter)
+            .await?
+        {
+            if let Some(date) = parse_bucket(&row.bucket) {
+                result.entry(date).or_default().settled_sales += to_decimal(row.settled_sales);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 已入账记录按时间桶、记录类型汇总收入/支出（冲账记录按被冲账记录的类型归类）
+    async fn query_record_buckets(
+        &self,
+        granularity: TimeGranularity,
+        start_time: NaiveDateTime,
+        end_time: NaiveDateTime,
+        filter: &SeriesFilter,
+    ) -> Result<Vec<RecordBucketRow>, Box<dyn std::error::Error>> {
+        let mut sql = format!(
+            "SELECT {} AS bucket, \
+             COALESCE(p.accounting_type, r.accounting_type) AS accounting_type, {} AS amount \
+             FROM accounting_record r \
+             LEFT JOIN accounting_record p ON p.id = r.write_off_id \
+             WHERE r.state = ? AND r.record_time >= ? AND r.record_time <= ? \
+             AND COALESCE(p.accounting_type, r.accounting_type) IN (?, ?, ?, ?)",
+            bucket_expr(granularity, "r.record_time"),
+            sum_expr("r.amount")
+        );
+        let mut values: Vec<Value> = vec![
+            AccountingRecordState::Posted.into(),
+            start_time.into(),
+            end_time.into(),
+            AccountingType::Income.into(),
+            AccountingType::InvestmentIncome.into(),
+            AccountingType::Expenditure.into(),
+            AccountingType::InvestmentLoss.into(),
+        ];
+
+        if let Some(book_id) = filter.book_id {
+            // 未归类账本同时包含 book_id 为空的记录
+            if book_id == DEFAULT_BOOK_ID {
+                sql.push_str(" AND (r.book_id = ? OR r.book_id IS NULL)");
+            } else {
+                sql.push_str(" AND r.book_id = ?");
+            }
+            values.push(book_id.into());
+        }
+        if let Some(channel) = &filter.channel {
+            sql.push_str(" AND r.channel = ?");
+            values.push(channel.clone().into());
+        }
+        if let Some(category) = &filter.category {
+            sql.push_str(" AND r.book_id IN (?, ?)");
+            values.push(category.sell_book_id.into());
+            values.push(category.purchase_book_id.into());
+        }
+        sql.push_str(" GROUP BY bucket, COALESCE(p.accounting_type, r.accounting_type)");
+
+        let rows = RecordBucketRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            sql,
+            values,
+        ))
+        .all(&self.db)
+        .await?;
+        Ok(rows)
+    }
+
+    /// 未取消的销售订单按创建时间分桶汇总订单数与订单金额
+    async fn query_order_buckets(
+        &self,
+        granularity: TimeGranularity,
+        start_time: NaiveDateTime,
+        end_time: NaiveDateTime,
+        filter: &SeriesFilter,
+    ) -> Result<Vec<OrderBucketRow>, Box<dyn std::error::Error>> {
+        let mut sql = format!(
+            "SELECT {} AS bucket, COUNT(*) AS order_count, {} AS order_value \
+             FROM \"order\" o \
+             WHERE o.order_type = ? AND o.status <> ? AND o.create_at >= ? AND o.create_at <= ?",
+            bucket_expr(granularity, "o.create_at"),
+            sum_expr("o.actual_amount")
+        );
+        let mut values: Vec<Value> = vec![
+            OrderType::Sales.into(),
+            OrderStatus::Cancelled.into(),
+            start_time.into(),
+            end_time.into(),
+        ];
+        Self::push_order_filters(&mut sql, &mut values, filter);
+        sql.push_str(" GROUP BY bucket");
+
+        let rows = OrderBucketRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            sql,
+            values,
+        ))
+        .all(&self.db)
+        .await?;
+        Ok(rows)
+    }
+
+    /// 已结账销售订单按结账时间分桶汇总销售额
+    async fn query_settled_buckets(
+        &self,
+        granularity: TimeGranularity,
+        start_time: NaiveDateTime,
+        end_time: NaiveDateTime,
+        filter: &SeriesFilter,
+    ) -> Result<Vec<SettledBucketRow>, Box<dyn std::error::Error>> {
+        let mut sql = format!(
+            "SELECT {} AS bucket, {} AS settled_sales \
+             FROM \"order\" o \
+             WHERE o.order_type = ? AND o.status = ? AND o.settled_at >= ? AND o.settled_at <= ?",
+            bucket_expr(granularity, "o.settled_at"),
+            sum_expr("o.actual_amount")
+        );
+        let mut values: Vec<Value> = vec![
+            OrderType::Sales.into(),
+            OrderStatus::Settled.into(),
+            start_time.into(),
+            end_time.into(),
+        ];
+        Self::push_order_filters(&mut sql, &mut values, filter);
+        sql.push_str(" GROUP BY bucket");
+
+        let rows = SettledBucketRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            sql,
+            values,
+        ))
+        .all(&self.db)
+        .await?;
+        Ok(rows)
+    }
+
+    /// 追加订单筛选条件（账本/品类按明细商品所属品类匹配，命中任一明细即计入整单；
+    /// 渠道按订单渠道或任一笔收付款渠道匹配，混合渠道订单计入其包含的各渠道）
+    fn push_order_filters(sql: &mut String, values: &mut Vec<Value>, filter: &SeriesFilter) {
+        if let Some(book_id) = filter.book_id {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM order_item oi \
+                 LEFT JOIN product p ON p.id = oi.product_id \
+                 JOIN category c ON c.id = COALESCE(p.category_id, ?) \
+                 WHERE oi.order_id = o.id AND c.sell_book_id = ?)",
+            );
+            values.push(filter.default_category_id.into());
+            values.push(book_id.into());
+        }
+        if let Some(channel) = &filter.channel {
+            sql.push_str(
+                " AND (o.channel = ? OR EXISTS (SELECT 1 FROM order_payment op \
+                 WHERE op.order_id = o.id AND op.channel = ?))",
+            );
+            values.push(channel.clone().into());
+            values.push(channel.clone().into());
+        }
+        if let Some(category) = &filter.category {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM order_item oi \
+                 LEFT JOIN product p ON p.id = oi.product_id \
+                 WHERE oi.order_id = o.id AND COALESCE(p.category_id, ?) = ?)",
+            );
+            values.push(filter.default_category_id.into());
+            values.push(category.id.into());
+        }
+    }
+}
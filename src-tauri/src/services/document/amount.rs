@@ -0,0 +1,98 @@
+//! 人民币金额大写（用于收据、送货单等票据）
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+const DIGITS: [char; 10] = ['零', '壹', '贰', '叁', '肆', '伍', '陆', '柒', '捌', '玖'];
+/// 组内单位（个、拾、佰、仟）
+const UNITS: [&str; 4] = ["", "拾", "佰", "仟"];
+/// 每四位一组的组单位
+const GROUP_UNITS: [&str; 5] = ["", "万", "亿", "万亿", "亿亿"];
+
+/// 整数部分转大写（不含"元"）
+fn integer_to_uppercase(value: u128) -> String {
+    let digits: Vec<u32> = value
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    let len = digits.len();
+
+    let mut result = String::new();
+    let mut zero_pending = false;
+    let mut group_has_digit = false;
+    for (idx, digit) in digits.into_iter().enumerate() {
+        let pos = len - 1 - idx;
+        let unit = pos % 4;
+        let group = pos / 4;
+
+        if digit == 0 {
+            zero_pending = true;
+        } else {
+            if zero_pending && !result.is_empty() {
+                result.push('零');
+            }
+            zero_pending = false;
+            group_has_digit = true;
+            result.push(DIGITS[digit as usize]);
+            result.push_str(UNITS[unit]);
+        }
+
+        // 一组结束：组内有非零数字时追加组单位，组单位吸收其后的零
+        if unit == 0 {
+            if group_has_digit && group > 0 {
+                result.push_str(GROUP_UNITS[group.min(GROUP_UNITS.len() - 1)]);
+                zero_pending = false;
+            }
+            group_has_digit = false;
+        }
+    }
+
+    result
+}
+
+/// 金额转人民币大写，如 1234.50 → 壹仟贰佰叁拾肆元伍角整（按分四舍五入）
+pub fn uppercase_amount(amount: Decimal) -> String {
+    let rounded = amount.round_dp(2);
+    let prefix = if rounded.is_sign_negative() && !rounded.is_zero() {
+        "负"
+    } else {
+        ""
+    };
+
+    let cents = (rounded.abs() * Decimal::ONE_HUNDRED)
+        .to_u128()
+        .unwrap_or(0);
+    let integer = cents / 100;
+    let jiao = (cents / 10 % 10) as usize;
+    let fen = (cents % 10) as usize;
+
+    let mut result = String::from(prefix);
+    if integer > 0 {
+        result.push_str(&integer_to_uppercase(integer));
+        result.push('元');
+    }
+
+    if jiao == 0 && fen == 0 {
+        if integer == 0 {
+            result.push_str("零元");
+        }
+        result.push('整');
+        return result;
+    }
+
+    if jiao > 0 {
+        result.push(DIGITS[jiao]);
+        result.push('角');
+    } else if integer > 0 {
+        result.push('零');
+    }
+    if fen > 0 {
+        result.push(DIGITS[fen]);
+        result.push('分');
+    } else {
+        result.push('整');
+    }
+
+    result
+}
@@ -0,0 +1,46 @@
+use rust_decimal::Decimal;
+
+/// HTML 文本转义
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 金额格式化（保留两位小数）
+pub fn money(amount: Decimal) -> String {
+    format!("{:.2}", amount.round_dp(2))
+}
+
+/// 数量格式化（去除多余的小数位 0）
+pub fn quantity(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+/// 包装完整 HTML 文档（内联基础样式，便于直接打印）
+pub fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n\
+         body {{ font-family: \"PingFang SC\", \"Microsoft YaHei\", sans-serif; font-size: 13px; margin: 24px; color: #222; }}\n\
+         h1 {{ font-size: 20px; text-align: center; margin-bottom: 4px; }}\n\
+         h2 {{ font-size: 15px; margin: 18px 0 6px; }}\n\
+         table {{ width: 100%; border-collapse: collapse; margin-bottom: 8px; }}\n\
+         th, td {{ border: 1px solid #999; padding: 4px 6px; }}\n\
+         th {{ background: #f2f2f2; }}\n\
+         td.num {{ text-align: right; }}\n\
+         .meta {{ text-align: center; color: #555; }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        body
+    )
+}
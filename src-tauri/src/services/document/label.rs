@@ -0,0 +1,32 @@
+//! 单据显示名称（渠道、订单类型、订单状态）
+
+use crate::enums::{AccountingChannel, OrderStatus, OrderType};
+
+/// 订单类型显示名称
+pub fn order_type_label(order_type: &OrderType) -> &'static str {
+    match order_type {
+        OrderType::Sales => "销售",
+        OrderType::Purchase => "采购",
+    }
+}
+
+/// 订单状态显示名称
+pub fn order_status_label(status: &OrderStatus) -> &'static str {
+    match status {
+        OrderStatus::Pending => "待结账",
+        OrderStatus::PartiallyPaid => "部分收付款",
+        OrderStatus::Settled => "已结账",
+        OrderStatus::Cancelled => "已取消",
+    }
+}
+
+/// 渠道显示名称
+pub fn channel_label(channel: &AccountingChannel) -> &'static str {
+    match channel {
+        AccountingChannel::Cash => "现金",
+        AccountingChannel::AliPay => "支付宝",
+        AccountingChannel::Wechat => "微信",
+        AccountingChannel::BankCard => "银行卡",
+        AccountingChannel::Unknown => "未知",
+    }
+}
@@ -0,0 +1,74 @@
+//! 单据渲染公共模块（HTML 转义 / 最小 PDF 生成 / 大写金额 / 文件输出）
+
+pub mod amount;
+pub mod html;
+pub mod label;
+pub mod pdf;
+
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+pub use amount::uppercase_amount;
+pub use pdf::PdfWriter;
+
+/// 单据输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DocumentFormat {
+    Html,
+    Pdf,
+}
+
+impl std::str::FromStr for DocumentFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Html" => Ok(DocumentFormat::Html),
+            "Pdf" => Ok(DocumentFormat::Pdf),
+            _ => Err(()),
+        }
+    }
+}
+
+impl DocumentFormat {
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocumentFormat::Html => "html",
+            DocumentFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// 将单据文件写入指定目录，返回文件路径
+///
+/// 编号中的路径分隔符等字符替换为下划线；同名文件已存在时追加生成时间
+pub async fn write_document(
+    output_dir: &Path,
+    document_no: &str,
+    format: DocumentFormat,
+    generated_at: NaiveDateTime,
+    bytes: Vec<u8>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    tokio::fs::create_dir_all(output_dir).await?;
+    let base_name: String = document_no
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            _ => c,
+        })
+        .collect();
+    let mut file_path = output_dir.join(format!("{}.{}", base_name, format.extension()));
+    if tokio::fs::try_exists(&file_path).await? {
+        file_path = output_dir.join(format!(
+            "{}-{}.{}",
+            base_name,
+            generated_at.format("%Y%m%d%H%M%S"),
+            format.extension()
+        ));
+    }
+    tokio::fs::write(&file_path, bytes).await?;
+    Ok(file_path)
+}
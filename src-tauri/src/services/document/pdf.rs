@@ -0,0 +1,167 @@
+//! 最小 PDF 生成器
+//!
+//! 使用 PDF 阅读器内置的 STSong-Light 中文字体（Adobe-GB1，UniGB-UCS2-H 编码），
+//! 无需嵌入字体文件即可输出中文。仅支持单一字体的文本与直线绘制。
+
+/// A4 页面宽度（pt）
+pub const PAGE_WIDTH: f32 = 595.0;
+/// A4 页面高度（pt）
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+/// PDF 文档构建器
+#[derive(Debug, Default)]
+pub struct PdfWriter {
+    /// 每页的内容流
+    pages: Vec<String>,
+}
+
+/// 将文本编码为 UCS-2 大端十六进制字符串（BMP 以外的字符替换为 ?）
+fn encode_text(text: &str) -> String {
+    let mut hex = String::with_capacity(text.len() * 4);
+    for c in text.chars() {
+        let code = c as u32;
+        let code = if code > 0xFFFF { '?' as u32 } else { code };
+        hex.push_str(&format!("{:04X}", code));
+    }
+    hex
+}
+
+/// 估算文本显示宽度（半角字符按 0.5 个字号，全角字符按 1 个字号）
+pub fn text_width(text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| if c.is_ascii() { 0.5 } else { 1.0 })
+        .sum::<f32>()
+        * size
+}
+
+impl PdfWriter {
+    pub fn new() -> Self {
+        Self {
+            pages: vec![String::new()],
+        }
+    }
+
+    /// 新增一页，后续绘制内容写入新页
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+    }
+
+    /// 页数
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn current(&mut self) -> &mut String {
+        if self.pages.is_empty() {
+            self.pages.push(String::new());
+        }
+        self.pages.last_mut().unwrap()
+    }
+
+    /// 在 (x, y) 处绘制文本（坐标原点为页面左下角）
+    pub fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let op = format!(
+            "BT /F1 {:.1} Tf {:.2} {:.2} Td <{}> Tj ET\n",
+            size,
+            x,
+            y,
+            encode_text(text)
+        );
+        self.current().push_str(&op);
+    }
+
+    /// 右对齐绘制文本（right 为文本右边界）
+    pub fn text_right(&mut self, right: f32, y: f32, size: f32, text: &str) {
+        let x = right - text_width(text, size);
+        self.text(x, y, size, text);
+    }
+
+    /// 居中绘制文本
+    pub fn text_center(&mut self, center: f32, y: f32, size: f32, text: &str) {
+        let x = center - text_width(text, size) / 2.0;
+        self.text(x, y, size, text);
+    }
+
+    /// 绘制直线
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let op = format!("0.5 w {:.2} {:.2} m {:.2} {:.2} l S\n", x1, y1, x2, y2);
+        self.current().push_str(&op);
+    }
+
+    /// 输出 PDF 字节
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // 固定对象：1 目录，2 页面树，3 Type0 字体，4 CIDFont，5 字体描述；之后每页占两个对象（页面 + 内容流）
+        let page_count = self.pages.len().max(1);
+        let mut objects: Vec<String> = Vec::new();
+
+        let kids: Vec<String> = (0..page_count)
+            .map(|i| format!("{} 0 R", 6 + i * 2))
+            .collect();
+
+        objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+        objects.push(format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            page_count
+        ));
+        objects.push(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light /Encoding /UniGB-UCS2-H \
+             /DescendantFonts [4 0 R] >>"
+                .to_string(),
+        );
+        objects.push(
+            "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light \
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 2 >> \
+             /FontDescriptor 5 0 R /DW 1000 /W [1 95 500] >>"
+                .to_string(),
+        );
+        objects.push(
+            "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 \
+             /FontBBox [-25 -254 1000 880] /ItalicAngle 0 /Ascent 880 /Descent -120 \
+             /CapHeight 880 /StemV 93 >>"
+                .to_string(),
+        );
+
+        for i in 0..page_count {
+            let content = self.pages.get(i).cloned().unwrap_or_default();
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                7 + i * 2
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ));
+        }
+
+        let mut out: Vec<u8> = Vec::new();
+        out.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+
+        let mut offsets: Vec<usize> = Vec::with_capacity(objects.len());
+        for (idx, obj) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", idx + 1, obj).as_bytes());
+        }
+
+        let xref_offset = out.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in &offsets {
+            xref.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        ));
+        out.extend_from_slice(xref.as_bytes());
+
+        out
+    }
+}
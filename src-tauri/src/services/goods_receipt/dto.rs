@@ -0,0 +1,74 @@
+use crate::entity::{goods_receipt, goods_receipt_item};
+use crate::enums::ReceivingStatus;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 收货明细 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGoodsReceiptItemDto {
+    /// 采购订单明细 ID
+    pub order_item_id: i64,
+    /// 本次实收数量（必须大于 0，允许超过订单数量）
+    pub quantity: Decimal,
+    /// 批次号（可选，不传则取收货单编号；订单结账入库时登记为库存批次）
+    pub lot_no: Option<String>,
+    /// 生产日期（格式 YYYY-MM-DD，可选）
+    pub production_date: Option<String>,
+    /// 到期日期（格式 YYYY-MM-DD，可选，不早于生产日期）
+    pub expiry_date: Option<String>,
+}
+
+/// 创建收货单 DTO（仅未取消的采购订单）
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGoodsReceiptDto {
+    /// 采购订单 ID
+    pub order_id: i64,
+    /// 收货明细列表
+    pub items: Vec<CreateGoodsReceiptItemDto>,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+/// 收货单详情（收货单 + 明细列表）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoodsReceiptDetail {
+    pub receipt: goods_receipt::Model,
+    pub items: Vec<goods_receipt_item::Model>,
+}
+
+/// 订单明细收货情况
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivingLine {
+    /// 采购订单明细 ID
+    pub order_item_id: i64,
+    /// 商品 ID
+    pub product_id: i64,
+    /// 商品名称
+    pub product_name: String,
+    /// 计量单位
+    pub unit: String,
+    /// 订单数量
+    pub ordered_quantity: Decimal,
+    /// 累计实收数量
+    pub received_quantity: Decimal,
+    /// 短缺数量（实收少于订单数量的部分）
+    pub short_quantity: Decimal,
+    /// 超收数量（实收多于订单数量的部分）
+    pub over_quantity: Decimal,
+}
+
+/// 采购订单收货汇总
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderReceivingSummary {
+    /// 采购订单 ID
+    pub order_id: i64,
+    /// 收货状态
+    pub status: ReceivingStatus,
+    /// 各明细收货情况
+    pub lines: Vec<ReceivingLine>,
+}
@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+use super::dto::{CreateGoodsReceiptDto, GoodsReceiptDetail, OrderReceivingSummary, ReceivingLine};
+use crate::entity::goods_receipt::{self, ActiveModel as GoodsReceiptActiveModel};
+use crate::entity::goods_receipt_item::{self, ActiveModel as GoodsReceiptItemActiveModel};
+use crate::entity::order::{self, ActiveModel as OrderActiveModel, Model as OrderModel};
+use crate::entity::order_item;
+use crate::enums::{DocumentType, OrderRevisionOrigin, OrderStatus, OrderType, ReceivingStatus};
+use crate::services::numbering::service::next_document_no;
+use crate::services::order_revision::service::{ensure_original_revision, record_revision};
+use crate::services::setting::service::load_purchase_config;
+
+/// 解析批次日期（YYYY-MM-DD）
+fn parse_lot_date(
+    s: &Option<String>,
+    err: &str,
+) -> Result<Option<NaiveDateTime>, Box<dyn std::error::Error>> {
+    match s {
+        Some(s) => Ok(NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|_| err.to_string())?
+            .and_hms_opt(0, 0, 0)),
+        None => Ok(None),
+    }
+}
+
+/// 查询订单明细（按录入顺序）
+async fn find_order_items<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+) -> Result<Vec<order_item::Model>, Box<dyn std::error::Error>> {
+    let items = order_item::Entity::find()
+        .filter(order_item::Column::OrderId.eq(order_id))
+        .order_by_asc(order_item::Column::Id)
+        .all(conn)
+        .await?;
+    Ok(items)
+}
+
+/// 按订单明细汇总累计实收数量
+pub(crate) async fn received_quantities<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+) -> Result<HashMap<i64, Decimal>, Box<dyn std::error::Error>> {
+    let receipt_ids: Vec<i64> = goods_receipt::Entity::find()
+        .filter(goods_receipt::Column::OrderId.eq(order_id))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+
+    let mut received: HashMap<i64, Decimal> = HashMap::new();
+    for item in goods_receipt_item::Entity::find()
+        .filter(goods_receipt_item::Column::ReceiptId.is_in(receipt_ids))
+        .all(conn)
+        .await?
+    {
+        *received.entry(item.order_item_id).or_insert(Decimal::ZERO) += item.quantity;
+    }
+    Ok(received)
+}
+
+/// 采购订单入库明细：已登记收货时按累计实收数量入库（含超收，未到货的明细不含在内），
+/// 未登记收货时按原明细入库（仅调整数量，金额不变）
+pub(crate) async fn inbound_items<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+    items: &[order_item::Model],
+) -> Result<Vec<order_item::Model>, Box<dyn std::error::Error>> {
+    let received = received_quantities(conn, order_id).await?;
+    if received.is_empty() {
+        return Ok(items.to_vec());
+    }
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let quantity = received.get(&item.id).copied()?;
+            Some(order_item::Model {
+                quantity,
+                ..item.clone()
+            })
+        })
+        .collect())
+}
+
+/// 订单是否已登记收货单
+pub(crate) async fn has_goods_receipts<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let count = goods_receipt::Entity::find()
+        .filter(goods_receipt::Column::OrderId.eq(order_id))
+        .count(conn)
+        .await?;
+    Ok(count > 0)
+}
+
+/// 根据订单明细和累计实收数量判断收货状态
+fn receiving_status(
+    items: &[order_item::Model],
+    received: &HashMap<i64, Decimal>,
+) -> ReceivingStatus {
+    if received.values().all(|q| q.is_zero()) {
+        ReceivingStatus::NotReceived
+    } else if items
+        .iter()
+        .all(|i| received.get(&i.id).copied().unwrap_or(Decimal::ZERO) >= i.quantity)
+    {
+        ReceivingStatus::Received
+    } else {
+        ReceivingStatus::PartiallyReceived
+    }
+}
+
+/// 按当前明细和收货单刷新采购订单的收货状态
+async fn refresh_receiving_status<C: ConnectionTrait>(
+    conn: &C,
+    order: OrderModel,
+) -> Result<OrderModel, Box<dyn std::error::Error>> {
+    let items = find_order_items(conn, order.id).await?;
+    let received = received_quantities(conn, order.id).await?;
+    let status = receiving_status(&items, &received);
+    if order.receiving_status.as_ref() == Some(&status) {
+        return Ok(order);
+    }
+
+    let mut order_active: OrderActiveModel = order.into();
+    order_active.receiving_status = Set(Some(status));
+    Ok(order_active.update(conn).await?)
+}
+
+/// 按实收数量折算后的采购订单明细和金额（仅用于记账和入库，不写回订单明细）
+pub(crate) struct ReceivedAdjustment {
+    /// 折算后的明细（未收货的明细不含在内）
+    pub items: Vec<order_item::Model>,
+    /// 折算后的应付总额
+    pub total_amount: Decimal,
+    /// 折算后的实付总额（整单折扣按金额比例折算）
+    pub actual_amount: Decimal,
+}
+
+/// 开启按实收数量结算时，按收货数量折算未结清采购订单的明细和金额
+///
+/// 未开启该设置、非采购订单或订单不是待结账、部分收付款状态时返回 None；尚未收货时报错
+pub(crate) async fn received_adjustment<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+) -> Result<Option<ReceivedAdjustment>, Box<dyn std::error::Error>> {
+    if order.order_type != OrderType::Purchase
+        || !matches!(
+            order.status,
+            OrderStatus::Pending | OrderStatus::PartiallyPaid
+        )
+    {
+        return Ok(None);
+    }
+    if !load_purchase_config(conn).await?.settle_by_received {
+        return Ok(None);
+    }
+
+    let received = received_quantities(conn, order.id).await?;
+    if received.is_empty() {
+        return Err("采购订单尚未收货，无法按实收数量结算".into());
+    }
+
+    let mut items = Vec::new();
+    for item in find_order_items(conn, order.id).await? {
+        let quantity = received.get(&item.id).copied().unwrap_or(Decimal::ZERO);
+        if quantity.is_zero() {
+            continue;
+        }
+        // 折扣率按实收金额重算，固定折扣金额按数量比例折算
+        let gross = quantity * item.unit_price;
+        let discount = match item.discount_rate {
+            Some(rate) => (gross * rate / Decimal::ONE_HUNDRED).round_dp(2),
+            None if item.quantity.is_zero() => Decimal::ZERO,
+            None => (item.discount_amount * quantity / item.quantity)
+                .round_dp(2)
+                .min(gross),
+        };
+        items.push(order_item::Model {
+            quantity,
+            discount_amount: discount,
+            subtotal: gross - discount,
+            ..item
+        });
+    }
+
+    let total_amount: Decimal = items.iter().map(|i| i.subtotal).sum();
+    let actual_amount = if order.actual_amount == order.total_amount || order.total_amount.is_zero()
+    {
+        total_amount
+    } else {
+        (order.actual_amount * total_amount / order.total_amount).round_dp(2)
+    };
+
+    Ok(Some(ReceivedAdjustment {
+        items,
+        total_amount,
+        actual_amount,
+    }))
+}
+
+/// 结账、收付款前确定订单和用于记账、入库的明细
+///
+/// 开启按实收数量结算时，采购订单明细按收货数量折算（订单明细仍保留订购数量，不做修改），
+/// 折算后的应付、实付总额写回订单，有变动时另存为订单版本；否则返回订单原明细
+pub(crate) async fn apply_received_quantities<C: ConnectionTrait>(
+    conn: &C,
+    order: OrderModel,
+) -> Result<(OrderModel, Vec<order_item::Model>), Box<dyn std::error::Error>> {
+    let Some(adjustment) = received_adjustment(conn, &order).await? else {
+        let items = find_order_items(conn, order.id).await?;
+        return Ok((order, items));
+    };
+
+    if order.total_amount == adjustment.total_amount
+        && order.actual_amount == adjustment.actual_amount
+    {
+        return Ok((order, adjustment.items));
+    }
+
+    ensure_original_revision(conn, &order).await?;
+
+    let mut order_active: OrderActiveModel = order.into();
+    order_active.total_amount = Set(adjustment.total_amount);
+    order_active.actual_amount = Set(adjustment.actual_amount);
+    let updated_order = order_active.update(conn).await?;
+    record_revision(conn, &updated_order, OrderRevisionOrigin::Receiving, None).await?;
+
+    Ok((updated_order, adjustment.items))
+}
+
+/// 采购收货服务
+#[derive(Debug)]
+pub struct GoodsReceiptService {
+    db: DatabaseConnection,
+}
+
+impl GoodsReceiptService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 登记收货单（可分多次收货，允许超收，已结账订单须先撤销结账），并更新订单收货状态
+    pub async fn create_goods_receipt(
+        &self,
+        input: CreateGoodsReceiptDto,
+    ) -> Result<GoodsReceiptDetail, Box<dyn std::error::Error>> {
+        if input.items.is_empty() {
+            return Err("收货明细不能为空".into());
+        }
+
+        let txn = self.db.begin().await?;
+
+        let order = order::Entity::find_by_id(input.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("订单不存在")?;
+        if order.order_type != OrderType::Purchase {
+            return Err("仅采购订单可登记收货".into());
+        }
+        if order.status == OrderStatus::Cancelled {
+            return Err("已取消的订单不能收货".into());
+        }
+        // 已结账订单的入库、批次和结算金额已确定，补登收货不会再生效
+        if order.status == OrderStatus::Settled {
+            return Err("已结账的订单不能登记收货，请先撤销结账".into());
+        }
+
+        let order_items: HashMap<i64, order_item::Model> = find_order_items(&txn, order.id)
+            .await?
+            .into_iter()
+            .map(|item| (item.id, item))
+            .collect();
+        let mut lines = Vec::with_capacity(input.items.len());
+        for item in &input.items {
+            let original = order_items
+                .get(&item.order_item_id)
+                .ok_or("收货明细不属于该订单")?;
+            if item.quantity <= Decimal::ZERO {
+                return Err(format!("{} 的收货数量必须大于 0", original.product_name).into());
+            }
+            let production_date =
+                parse_lot_date(&item.production_date, "无效的生产日期格式，应为 YYYY-MM-DD")?;
+            let expiry_date =
+                parse_lot_date(&item.expiry_date, "无效的到期日期格式，应为 YYYY-MM-DD")?;
+            if let (Some(production), Some(expiry)) = (production_date, expiry_date) {
+                if expiry < production {
+                    return Err(
+                        format!("{} 的到期日期不能早于生产日期", original.product_name).into(),
+                    );
+                }
+            }
+            let lot_no = item.lot_no.clone().filter(|no| !no.trim().is_empty());
+            lines.push((
+                original,
+                item.quantity,
+                lot_no,
+                production_date,
+                expiry_date,
+            ));
+        }
+
+        let now = Local::now().naive_local();
+        let receipt_no = next_document_no(&txn, DocumentType::GoodsReceipt, now).await?;
+        let receipt = GoodsReceiptActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            receipt_no: Set(receipt_no),
+            order_id: Set(order.id),
+            remark: Set(input.remark),
+            create_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+
+        let mut items = Vec::with_capacity(lines.len());
+        for (original, quantity, lot_no, production_date, expiry_date) in lines {
+            let item = GoodsReceiptItemActiveModel {
+                id: sea_orm::ActiveValue::NotSet,
+                receipt_id: Set(receipt.id),
+                order_item_id: Set(original.id),
+                product_id: Set(original.product_id),
+                product_name: Set(original.product_name.clone()),
+                quantity: Set(quantity),
+                unit: Set(original.unit.clone()),
+                lot_no: Set(lot_no),
+                production_date: Set(production_date),
+                expiry_date: Set(expiry_date),
+            }
+            .insert(&txn)
+            .await?;
+            items.push(item);
+        }
+
+        refresh_receiving_status(&txn, order).await?;
+        txn.commit().await?;
+
+        Ok(GoodsReceiptDetail { receipt, items })
+    }
+
+    /// 删除收货单（已结账订单不可删除），并更新订单收货状态
+    pub async fn delete_goods_receipt(
+        &self,
+        receipt_id: i64,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        let receipt = goods_receipt::Entity::find_by_id(receipt_id)
+            .one(&txn)
+            .await?
+            .ok_or("收货单不存在")?;
+        let order = order::Entity::find_by_id(receipt.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("订单不存在")?;
+        if order.status == OrderStatus::Settled {
+            return Err("已结账订单的收货单不能删除".into());
+        }
+
+        goods_receipt_item::Entity::delete_many()
+            .filter(goods_receipt_item::Column::ReceiptId.eq(receipt.id))
+            .exec(&txn)
+            .await?;
+        goods_receipt::Entity::delete_by_id(receipt.id)
+            .exec(&txn)
+            .await?;
+
+        let updated_order = refresh_receiving_status(&txn, order).await?;
+        txn.commit().await?;
+
+        Ok(updated_order)
+    }
+
+    /// 查询订单的收货单（按收货时间升序）
+    pub async fn get_goods_receipts(
+        &self,
+        order_id: i64,
+    ) -> Result<Vec<GoodsReceiptDetail>, Box<dyn std::error::Error>> {
+        let receipts = goods_receipt::Entity::find()
+            .filter(goods_receipt::Column::OrderId.eq(order_id))
+            .order_by_asc(goods_receipt::Column::CreateAt)
+            .order_by_asc(goods_receipt::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        let mut details = Vec::with_capacity(receipts.len());
+        for receipt in receipts {
+            let items = goods_receipt_item::Entity::find()
+                .filter(goods_receipt_item::Column::ReceiptId.eq(receipt.id))
+                .order_by_asc(goods_receipt_item::Column::Id)
+                .all(&self.db)
+                .await?;
+            details.push(GoodsReceiptDetail { receipt, items });
+        }
+        Ok(details)
+    }
+
+    /// 查询采购订单收货汇总（各明细订单数量、累计实收、短缺与超收数量）
+    pub async fn get_order_receiving(
+        &self,
+        order_id: i64,
+    ) -> Result<OrderReceivingSummary, Box<dyn std::error::Error>> {
+        let order = order::Entity::find_by_id(order_id)
+            .one(&self.db)
+            .await?
+            .ok_or("订单不存在")?;
+        if order.order_type != OrderType::Purchase {
+            return Err("仅采购订单可查询收货情况".into());
+        }
+
+        let items = find_order_items(&self.db, order.id).await?;
+        let received = received_quantities(&self.db, order.id).await?;
+        let status = receiving_status(&items, &received);
+
+        let lines = items
+            .into_iter()
+            .map(|item| {
+                let received_quantity = received.get(&item.id).copied().unwrap_or(Decimal::ZERO);
+                ReceivingLine {
+                    order_item_id: item.id,
+                    product_id: item.product_id,
+                    product_name: item.product_name,
+                    unit: item.unit,
+                    short_quantity: (item.quantity - received_quantity).max(Decimal::ZERO),
+                    over_quantity: (received_quantity - item.quantity).max(Decimal::ZERO),
+                    ordered_quantity: item.quantity,
+                    received_quantity,
+                }
+            })
+            .collect();
+
+        Ok(OrderReceivingSummary {
+            order_id: order.id,
+            status,
+            lines,
+        })
+    }
+}
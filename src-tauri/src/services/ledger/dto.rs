@@ -0,0 +1,101 @@
+use crate::enums::CustomerCategory;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 客户往来明细查询 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerLedgerDto {
+    /// 客户 ID
+    pub customer_id: i64,
+    /// 开始日期（可选，格式 YYYY-MM-DD，之前的发生额计入期初余额）
+    pub start_date: Option<String>,
+    /// 结束日期（可选，格式 YYYY-MM-DD，含）
+    pub end_date: Option<String>,
+}
+
+/// 往来明细类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerEntryType {
+    /// 销售订单（应收增加）
+    SalesOrder,
+    /// 采购订单（应付增加）
+    PurchaseOrder,
+    /// 收款（应收减少）
+    Receipt,
+    /// 付款（应付减少）
+    Payment,
+    /// 销售退货（应收减少）
+    SalesReturn,
+    /// 销售退款（应收增加）
+    SalesRefund,
+    /// 采购退货（应付减少）
+    PurchaseReturn,
+    /// 采购退款（应付增加）
+    PurchaseRefund,
+}
+
+/// 往来明细
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    /// 发生时间
+    pub date: NaiveDateTime,
+    /// 明细类型
+    pub entry_type: LedgerEntryType,
+    /// 关联订单 ID
+    pub order_id: i64,
+    /// 关联订单编号
+    pub order_no: String,
+    /// 关联收付款 ID（订单明细为 None）
+    pub payment_id: Option<i64>,
+    /// 关联退货单 ID（退货 / 退款明细）
+    pub return_id: Option<i64>,
+    /// 摘要
+    pub description: String,
+    /// 金额变动（正数表示客户欠我方增加，负数表示减少）
+    pub amount: Decimal,
+    /// 变动后余额
+    pub balance: Decimal,
+}
+
+/// 客户往来明细账
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerLedger {
+    /// 客户 ID
+    pub customer_id: i64,
+    /// 客户名称
+    pub customer_name: String,
+    /// 开始日期
+    pub start_date: Option<NaiveDate>,
+    /// 结束日期（含）
+    pub end_date: Option<NaiveDate>,
+    /// 期初余额
+    pub opening_balance: Decimal,
+    /// 明细（按时间升序，含逐笔余额）
+    pub entries: Vec<LedgerEntry>,
+    /// 期末余额
+    pub closing_balance: Decimal,
+}
+
+/// 客户往来余额
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerBalance {
+    /// 客户 ID
+    pub customer_id: i64,
+    /// 客户名称
+    pub customer_name: String,
+    /// 客户分类
+    pub category: CustomerCategory,
+    /// 应收余额（销售订单未收金额）
+    pub receivable: Decimal,
+    /// 应付余额（采购订单未付金额）
+    pub payable: Decimal,
+    /// 净余额（应收 - 应付，正数为客户欠我方，负数为我方欠客户）
+    pub balance: Decimal,
+    /// 截止日期（None 表示当前）
+    pub as_of: Option<NaiveDate>,
+}
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use super::dto::{
+    CustomerBalance, CustomerLedger, CustomerLedgerDto, LedgerEntry, LedgerEntryType,
+};
+use crate::entity::{customer, order, order_payment, order_return};
+use crate::enums::{OrderStatus, OrderType};
+
+/// 解析日期（YYYY-MM-DD）
+fn parse_date(s: &str, err: &str) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| err.to_string().into())
+}
+
+/// 当日结束时间
+fn end_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(23, 59, 59).unwrap()
+}
+
+/// 明细排序权重：同一时间先记订单，再记收付款，最后记退货与退款
+fn entry_rank(entry_type: &LedgerEntryType) -> u8 {
+    match entry_type {
+        LedgerEntryType::SalesOrder | LedgerEntryType::PurchaseOrder => 0,
+        LedgerEntryType::Receipt | LedgerEntryType::Payment => 1,
+        LedgerEntryType::SalesReturn | LedgerEntryType::PurchaseReturn => 2,
+        LedgerEntryType::SalesRefund | LedgerEntryType::PurchaseRefund => 3,
+    }
+}
+
+/// 由订单及其收付款、退货单生成往来明细（未排序，余额未计算）
+///
+/// 符号约定：正数表示客户欠我方增加（销售订单、采购付款、销售退款、采购退货），
+/// 负数表示减少（销售收款、采购订单、销售退货、采购退款）
+fn build_entries(
+    orders: &[order::Model],
+    payments: &HashMap<i64, Vec<order_payment::Model>>,
+    returns: &HashMap<i64, Vec<order_return::Model>>,
+) -> Vec<LedgerEntry> {
+    let mut entries: Vec<LedgerEntry> = Vec::new();
+
+    for o in orders {
+        let (order_type, order_desc, payment_type, payment_desc, sign) = match o.order_type {
+            OrderType::Sales => (
+                LedgerEntryType::SalesOrder,
+                "销售订单",
+                LedgerEntryType::Receipt,
+                "收款",
+                Decimal::ONE,
+            ),
+            OrderType::Purchase => (
+                LedgerEntryType::PurchaseOrder,
+                "采购订单",
+                LedgerEntryType::Payment,
+                "付款",
+                Decimal::NEGATIVE_ONE,
+            ),
+        };
+
+        entries.push(LedgerEntry {
+            date: o.create_at,
+            entry_type: order_type,
+            order_id: o.id,
+            order_no: o.order_no.clone(),
+            payment_id: None,
+            return_id: None,
+            description: format!("{}-{}", order_desc, o.order_no),
+            amount: o.actual_amount * sign,
+            balance: Decimal::ZERO,
+        });
+
+        let order_payments = payments.get(&o.id);
+        match order_payments {
+            Some(list) if !list.is_empty() => {
+                for p in list {
+                    entries.push(LedgerEntry {
+                        date: p.paid_at,
+                        entry_type: payment_type.clone(),
+                        order_id: o.id,
+                        order_no: o.order_no.clone(),
+                        payment_id: Some(p.id),
+                        return_id: None,
+                        description: format!("{}-{}", payment_desc, o.order_no),
+                        amount: -p.amount * sign,
+                        balance: Decimal::ZERO,
+                    });
+                }
+            }
+            _ => {
+                // 早期一次性结账的订单没有收付款记录，按结账时间视为全额收付
+                if o.status == OrderStatus::Settled {
+                    entries.push(LedgerEntry {
+                        date: o.settled_at.unwrap_or(o.create_at),
+                        entry_type: payment_type,
+                        order_id: o.id,
+                        order_no: o.order_no.clone(),
+                        payment_id: None,
+                        return_id: None,
+                        description: format!("{}-{}", payment_desc, o.order_no),
+                        amount: -o.actual_amount * sign,
+                        balance: Decimal::ZERO,
+                    });
+                }
+            }
+        }
+
+        // 退货冲减往来，退款随退货当即发生，两者金额相等
+        let (return_type, refund_type) = match o.order_type {
+            OrderType::Sales => (LedgerEntryType::SalesReturn, LedgerEntryType::SalesRefund),
+            OrderType::Purchase => (
+                LedgerEntryType::PurchaseReturn,
+                LedgerEntryType::PurchaseRefund,
+            ),
+        };
+        for r in returns.get(&o.id).into_iter().flatten() {
+            entries.push(LedgerEntry {
+                date: r.create_at,
+                entry_type: return_type.clone(),
+                order_id: o.id,
+                order_no: o.order_no.clone(),
+                payment_id: None,
+                return_id: Some(r.id),
+                description: format!("退货-{}", r.return_no),
+                amount: -r.refund_amount * sign,
+                balance: Decimal::ZERO,
+            });
+            entries.push(LedgerEntry {
+                date: r.create_at,
+                entry_type: refund_type.clone(),
+                order_id: o.id,
+                order_no: o.order_no.clone(),
+                payment_id: None,
+                return_id: Some(r.id),
+                description: format!("退款-{}", r.return_no),
+                amount: r.refund_amount * sign,
+                balance: Decimal::ZERO,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then_with(|| entry_rank(&a.entry_type).cmp(&entry_rank(&b.entry_type)))
+            .then_with(|| a.order_id.cmp(&b.order_id))
+            .then_with(|| a.payment_id.cmp(&b.payment_id))
+            .then_with(|| a.return_id.cmp(&b.return_id))
+    });
+    entries
+}
+
+/// 客户往来账服务（应收 / 应付）
+#[derive(Debug)]
+pub struct LedgerService {
+    db: DatabaseConnection,
+}
+
+impl LedgerService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取客户往来明细（含期初余额与逐笔余额）
+    pub async fn get_customer_ledger(
+        &self,
+        input: CustomerLedgerDto,
+    ) -> Result<CustomerLedger, Box<dyn std::error::Error>> {
+        let customer = customer::Entity::find_by_id(input.customer_id)
+            .one(&self.db)
+            .await?
+            .ok_or("客户不存在")?;
+
+        let start_date = match &input.start_date {
+            Some(s) => Some(parse_date(s, "无效的开始日期格式，应为 YYYY-MM-DD")?),
+            None => None,
+        };
+        let end_date = match &input.end_date {
+            Some(s) => Some(parse_date(s, "无效的结束日期格式，应为 YYYY-MM-DD")?),
+            None => None,
+        };
+        if let (Some(start), Some(end)) = (start_date, end_date) {
+            if end < start {
+                return Err("结束日期不能早于开始日期".into());
+            }
+        }
+
+        let entries = self
+            .load_entries(Some(customer.id))
+            .await?
+            .remove(&customer.id)
+            .unwrap_or_default();
+
+        let mut opening_balance = Decimal::ZERO;
+        let mut balance = Decimal::ZERO;
+        let mut visible: Vec<LedgerEntry> = Vec::new();
+        for mut entry in entries {
+            if let Some(end) = end_date {
+                if entry.date > end_of_day(end) {
+                    break;
+                }
+            }
+            balance += entry.amount;
+            match start_date {
+                Some(start) if entry.date.date() < start => opening_balance = balance,
+                _ => {
+                    entry.balance = balance;
+                    visible.push(entry);
+                }
+            }
+        }
+
+        Ok(CustomerLedger {
+            customer_id: customer.id,
+            customer_name: customer.name,
+            start_date,
+            end_date,
+            opening_balance,
+            entries: visible,
+            closing_balance: balance,
+        })
+    }
+
+    /// 获取客户往来余额（可指定截止日期，默认当前）
+    pub async fn get_customer_balance(
+        &self,
+        customer_id: i64,
+        as_of: Option<String>,
+    ) -> Result<CustomerBalance, Box<dyn std::error::Error>> {
+        let customer = customer::Entity::find_by_id(customer_id)
+            .one(&self.db)
+            .await?
+            .ok_or("客户不存在")?;
+
+        let as_of = match &as_of {
+            Some(s) => Some(parse_date(s, "无效的截止日期格式，应为 YYYY-MM-DD")?),
+            None => None,
+        };
+
+        let entries = self
+            .load_entries(Some(customer.id))
+            .await?
+            .remove(&customer.id)
+            .unwrap_or_default();
+        Ok(Self::summarize(&customer, &entries, as_of))
+    }
+
+    /// 获取全部客户的当前往来余额（按净余额绝对值降序）
+    pub async fn get_all_customer_balances(
+        &self,
+    ) -> Result<Vec<CustomerBalance>, Box<dyn std::error::Error>> {
+        let customers = customer::Entity::find()
+            .order_by_desc(customer::Column::CreateAt)
+            .all(&self.db)
+            .await?;
+
+        let mut grouped = self.load_entries(None).await?;
+
+        let mut balances: Vec<CustomerBalance> = customers
+            .iter()
+            .map(|c| {
+                let entries = grouped.remove(&c.id).unwrap_or_default();
+                Self::summarize(c, &entries, None)
+            })
+            .collect();
+        balances.sort_by_key(|b| std::cmp::Reverse(b.balance.abs()));
+
+        Ok(balances)
+    }
+
+    /// 加载往来明细并按客户分组（指定客户或全部有客户的订单，已取消订单不计入）
+    async fn load_entries(
+        &self,
+        customer_id: Option<i64>,
+    ) -> Result<HashMap<i64, Vec<LedgerEntry>>, Box<dyn std::error::Error>> {
+        let mut query = order::Entity::find()
+            .filter(order::Column::Status.ne(OrderStatus::Cancelled))
+            .filter(order::Column::CustomerId.is_not_null());
+        if let Some(id) = customer_id {
+            query = query.filter(order::Column::CustomerId.eq(id));
+        }
+        let orders = query.all(&self.db).await?;
+
+        let order_ids: Vec<i64> = orders.iter().map(|o| o.id).collect();
+        let mut payments: HashMap<i64, Vec<order_payment::Model>> = HashMap::new();
+        for p in order_payment::Entity::find()
+            .filter(order_payment::Column::OrderId.is_in(order_ids.clone()))
+            .all(&self.db)
+            .await?
+        {
+            payments.entry(p.order_id).or_default().push(p);
+        }
+        let mut returns: HashMap<i64, Vec<order_return::Model>> = HashMap::new();
+        for r in order_return::Entity::find()
+            .filter(order_return::Column::OrderId.is_in(order_ids))
+            .all(&self.db)
+            .await?
+        {
+            returns.entry(r.order_id).or_default().push(r);
+        }
+
+        let mut orders_by_customer: HashMap<i64, Vec<order::Model>> = HashMap::new();
+        for o in orders {
+            if let Some(id) = o.customer_id {
+                orders_by_customer.entry(id).or_default().push(o);
+            }
+        }
+
+        Ok(orders_by_customer
+            .into_iter()
+            .map(|(id, orders)| (id, build_entries(&orders, &payments, &returns)))
+            .collect())
+    }
+
+    /// 汇总截止日期前的应收 / 应付余额
+    fn summarize(
+        customer: &customer::Model,
+        entries: &[LedgerEntry],
+        as_of: Option<NaiveDate>,
+    ) -> CustomerBalance {
+        let mut receivable = Decimal::ZERO;
+        let mut payable = Decimal::ZERO;
+
+        for entry in entries {
+            if let Some(date) = as_of {
+                if entry.date > end_of_day(date) {
+                    continue;
+                }
+            }
+            match entry.entry_type {
+                LedgerEntryType::SalesOrder
+                | LedgerEntryType::Receipt
+                | LedgerEntryType::SalesReturn
+                | LedgerEntryType::SalesRefund => receivable += entry.amount,
+                LedgerEntryType::PurchaseOrder
+                | LedgerEntryType::Payment
+                | LedgerEntryType::PurchaseReturn
+                | LedgerEntryType::PurchaseRefund => payable -= entry.amount,
+            }
+        }
+
+        CustomerBalance {
+            customer_id: customer.id,
+            customer_name: customer.name.clone(),
+            category: customer.category.clone(),
+            receivable,
+            payable,
+            balance: receivable - payable,
+            as_of,
+        }
+    }
+}
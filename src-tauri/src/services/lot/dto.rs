@@ -0,0 +1,46 @@
+use crate::entity::stock_lot;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 查询临期批次 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryExpiringLotsDto {
+    /// 预警天数（可选，默认 3 天，含已过期批次）
+    pub within_days: Option<i64>,
+    /// 基准日期（格式 YYYY-MM-DD，可选，默认为今天）
+    pub as_of_date: Option<String>,
+    /// 商品 ID 筛选（可选）
+    pub product_id: Option<i64>,
+}
+
+/// 临期批次
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiringLot {
+    #[serde(flatten)]
+    pub lot: stock_lot::Model,
+    /// 距到期天数（已过期为负数）
+    pub days_to_expiry: i64,
+    /// 是否已过期
+    pub expired: bool,
+    /// 单位成本（按成本核算的当前单位成本，无入库成本时为空）
+    pub unit_cost: Option<Decimal>,
+    /// 剩余数量成本金额
+    pub value: Option<Decimal>,
+}
+
+/// 批次核对结果（库存数量与批次数量不一致的商品）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LotMismatch {
+    pub product_id: i64,
+    pub product_name: String,
+    pub unit: String,
+    /// 当前库存数量
+    pub stock_quantity: Decimal,
+    /// 批次剩余数量合计
+    pub lot_quantity: Decimal,
+    /// 批次未覆盖的净出库数量（无批次扣减记录合计）
+    pub untracked_quantity: Decimal,
+}
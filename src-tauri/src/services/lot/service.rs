@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+
+use super::dto::{ExpiringLot, LotMismatch, QueryExpiringLotsDto};
+use crate::entity::order::Model as OrderModel;
+use crate::entity::stock_lot::{self, ActiveModel as StockLotActiveModel};
+use crate::entity::stock_lot_consumption::{self, ActiveModel as LotConsumptionActiveModel};
+use crate::entity::{
+    goods_receipt, goods_receipt_item, order_item, order_return, order_return_item, product_stock,
+    stocktake, stocktake_item,
+};
+use crate::enums::OrderType;
+use crate::services::costing::service::current_unit_costs;
+
+/// 默认临期预警天数
+const DEFAULT_EXPIRY_WARNING_DAYS: i64 = 3;
+
+/// 批次扣减来源
+#[derive(Clone, Copy)]
+struct ConsumptionSource {
+    order_id: Option<i64>,
+    order_item_id: Option<i64>,
+    return_id: Option<i64>,
+    stocktake_id: Option<i64>,
+    create_at: NaiveDateTime,
+}
+
+/// 按到期日期先到先出排序（无到期日期的排在最后，其次按入库顺序）
+fn sort_fefo(lots: &mut [stock_lot::Model]) {
+    lots.sort_by_key(|l| (l.expiry_date.is_none(), l.expiry_date, l.create_at, l.id));
+}
+
+/// 查询商品 + 单位有剩余的批次（按先到期先出排序，可限定采购订单）
+async fn available_lots<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i64,
+    unit: &str,
+    order_id: Option<i64>,
+) -> Result<Vec<stock_lot::Model>, Box<dyn std::error::Error>> {
+    let mut query = stock_lot::Entity::find()
+        .filter(stock_lot::Column::ProductId.eq(product_id))
+        .filter(stock_lot::Column::Unit.eq(unit))
+        .filter(stock_lot::Column::RemainingQuantity.gt(Decimal::ZERO));
+    if let Some(order_id) = order_id {
+        query = query.filter(stock_lot::Column::OrderId.eq(order_id));
+    }
+    let mut lots = query.all(conn).await?;
+    sort_fefo(&mut lots);
+    Ok(lots)
+}
+
+/// 登记批次扣减记录（`lot_id` 为空表示批次未覆盖的数量）
+async fn insert_consumption<C: ConnectionTrait>(
+    conn: &C,
+    lot_id: Option<i64>,
+    product_id: i64,
+    unit: &str,
+    quantity: Decimal,
+    source: &ConsumptionSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    LotConsumptionActiveModel {
+        id: sea_orm::ActiveValue::NotSet,
+        lot_id: Set(lot_id),
+        product_id: Set(product_id),
+        unit: Set(unit.to_string()),
+        order_id: Set(source.order_id),
+        order_item_id: Set(source.order_item_id),
+        return_id: Set(source.return_id),
+        stocktake_id: Set(source.stocktake_id),
+        quantity: Set(quantity),
+        create_at: Set(source.create_at),
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
+
+/// 调整批次剩余数量并登记扣减记录（`quantity` 扣减为正，恢复为负）
+async fn post_consumption<C: ConnectionTrait>(
+    conn: &C,
+    lot: stock_lot::Model,
+    quantity: Decimal,
+    source: &ConsumptionSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (lot_id, product_id, unit) = (lot.id, lot.product_id, lot.unit.clone());
+    let remaining = lot.remaining_quantity - quantity;
+    let mut active: StockLotActiveModel = lot.into();
+    active.remaining_quantity = Set(remaining);
+    active.update(conn).await?;
+    insert_consumption(conn, Some(lot_id), product_id, &unit, quantity, source).await
+}
+
+/// 按先到期先出扣减批次（批次剩余不足的部分登记为无批次扣减，供批次核对报表提示）
+async fn consume_lots<C: ConnectionTrait>(
+    conn: &C,
+    lots: Vec<stock_lot::Model>,
+    product_id: i64,
+    unit: &str,
+    quantity: Decimal,
+    source: &ConsumptionSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut remaining = quantity;
+    for lot in lots {
+        if remaining <= Decimal::ZERO {
+            break;
+        }
+        let taken = remaining.min(lot.remaining_quantity);
+        remaining -= taken;
+        post_consumption(conn, lot, taken, source).await?;
+    }
+    if remaining > Decimal::ZERO {
+        insert_consumption(conn, None, product_id, unit, remaining, source).await?;
+    }
+    Ok(())
+}
+
+/// 按扣减记录恢复批次（按批次 + 订单明细汇总净扣减，按扣减的相反顺序恢复，
+/// 最多恢复 `limit`，为空表示全部恢复；无批次扣减按无批次记录冲回）
+async fn restore_lots<C: ConnectionTrait>(
+    conn: &C,
+    consumptions: Vec<stock_lot_consumption::Model>,
+    limit: Option<Decimal>,
+    source: &ConsumptionSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut keys: Vec<(Option<i64>, Option<i64>)> = Vec::new();
+    let mut products: HashMap<(Option<i64>, Option<i64>), (i64, String)> = HashMap::new();
+    let mut net: HashMap<(Option<i64>, Option<i64>), Decimal> = HashMap::new();
+    for c in consumptions {
+        let key = (c.lot_id, c.order_item_id);
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+        products.insert(key, (c.product_id, c.unit));
+        *net.entry(key).or_insert(Decimal::ZERO) += c.quantity;
+    }
+
+    let mut remaining = limit;
+    for key in keys.into_iter().rev() {
+        let mut quantity = net[&key];
+        if let Some(left) = remaining {
+            quantity = quantity.min(left);
+            remaining = Some(left - quantity.max(Decimal::ZERO));
+        }
+        if quantity <= Decimal::ZERO {
+            continue;
+        }
+        let source = ConsumptionSource {
+            order_item_id: key.1,
+            ..*source
+        };
+        let Some(lot_id) = key.0 else {
+            let (product_id, unit) = &products[&key];
+            insert_consumption(conn, None, *product_id, unit, -quantity, &source).await?;
+            continue;
+        };
+        let lot = stock_lot::Entity::find_by_id(lot_id).one(conn).await?;
+        if let Some(lot) = lot {
+            post_consumption(conn, lot, -quantity, &source).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 登记批次
+async fn insert_lot<C: ConnectionTrait>(
+    conn: &C,
+    lot: StockLotActiveModel,
+) -> Result<(), Box<dyn std::error::Error>> {
+    lot.insert(conn).await?;
+    Ok(())
+}
+
+/// 采购订单结账入库时登记批次
+///
+/// 已登记收货的订单按收货明细逐条登记批次（沿用收货时填写的批次号和日期，含超收数量），
+/// 与按实收数量入库的数量一致；未登记收货的订单按订单编号为各明细登记无到期日期的批次
+async fn create_order_lots<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    items: &[order_item::Model],
+    create_at: NaiveDateTime,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let receipts: HashMap<i64, goods_receipt::Model> = goods_receipt::Entity::find()
+        .filter(goods_receipt::Column::OrderId.eq(order.id))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|r| (r.id, r))
+        .collect();
+    let receipt_items = goods_receipt_item::Entity::find()
+        .filter(goods_receipt_item::Column::ReceiptId.is_in(receipts.keys().copied()))
+        .order_by_asc(goods_receipt_item::Column::Id)
+        .all(conn)
+        .await?;
+
+    if receipt_items.is_empty() {
+        for item in items.iter().filter(|i| i.quantity > Decimal::ZERO) {
+            insert_lot(
+                conn,
+                StockLotActiveModel {
+                    id: sea_orm::ActiveValue::NotSet,
+                    product_id: Set(item.product_id),
+                    product_name: Set(item.product_name.clone()),
+                    unit: Set(item.unit.clone()),
+                    lot_no: Set(order.order_no.clone()),
+                    production_date: Set(None),
+                    expiry_date: Set(None),
+                    received_quantity: Set(item.quantity),
+                    remaining_quantity: Set(item.quantity),
+                    order_id: Set(Some(order.id)),
+                    order_item_id: Set(Some(item.id)),
+                    stocktake_id: Set(None),
+                    receipt_id: Set(None),
+                    receipt_item_id: Set(None),
+                    create_at: Set(create_at),
+                },
+            )
+            .await?;
+        }
+        return Ok(());
+    }
+
+    for received in receipt_items {
+        // 未填写批次号的取收货单编号
+        let lot_no = received.lot_no.clone().unwrap_or_else(|| {
+            receipts
+                .get(&received.receipt_id)
+                .map(|r| r.receipt_no.clone())
+                .unwrap_or_else(|| order.order_no.clone())
+        });
+        insert_lot(
+            conn,
+            StockLotActiveModel {
+                id: sea_orm::ActiveValue::NotSet,
+                product_id: Set(received.product_id),
+                product_name: Set(received.product_name),
+                unit: Set(received.unit),
+                lot_no: Set(lot_no),
+                production_date: Set(received.production_date),
+                expiry_date: Set(received.expiry_date),
+                received_quantity: Set(received.quantity),
+                remaining_quantity: Set(received.quantity),
+                order_id: Set(Some(order.id)),
+                order_item_id: Set(Some(received.order_item_id)),
+                stocktake_id: Set(None),
+                receipt_id: Set(Some(received.receipt_id)),
+                receipt_item_id: Set(Some(received.id)),
+                create_at: Set(create_at),
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// 订单结账入库、出库时调整批次（采购入库登记批次，销售出库按先到期先出扣减批次）
+pub(crate) async fn apply_order_lots<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    items: &[order_item::Model],
+    create_at: NaiveDateTime,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if order.order_type == OrderType::Purchase {
+        return create_order_lots(conn, order, items, create_at).await;
+    }
+    for item in items.iter().filter(|i| i.quantity > Decimal::ZERO) {
+        let lots = available_lots(conn, item.product_id, &item.unit, None).await?;
+        let source = ConsumptionSource {
+            order_id: Some(order.id),
+            order_item_id: Some(item.id),
+            return_id: None,
+            stocktake_id: None,
+            create_at,
+        };
+        consume_lots(
+            conn,
+            lots,
+            item.product_id,
+            &item.unit,
+            item.quantity,
+            &source,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// 撤销结账时冲回批次
+///
+/// 销售订单按净扣减数量恢复出库批次（已恢复的不再重复恢复）；
+/// 采购订单按冲回的入库数量（商品 + 单位）扣减该订单的入库批次，冲回后不再参与出库，
+/// 批次已被销售扣减而不足的部分登记为无批次扣减
+pub(crate) async fn reverse_order_lots<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    reversed: &[(i64, String, Decimal)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = ConsumptionSource {
+        order_id: Some(order.id),
+        order_item_id: None,
+        return_id: None,
+        stocktake_id: None,
+        create_at: Local::now().naive_local(),
+    };
+    if order.order_type == OrderType::Purchase {
+        for (product_id, unit, quantity) in reversed {
+            if *quantity <= Decimal::ZERO {
+                continue;
+            }
+            let lots = available_lots(conn, *product_id, unit, Some(order.id)).await?;
+            consume_lots(conn, lots, *product_id, unit, *quantity, &source).await?;
+        }
+        return Ok(());
+    }
+
+    let consumptions = stock_lot_consumption::Entity::find()
+        .filter(stock_lot_consumption::Column::OrderId.eq(order.id))
+        .order_by_asc(stock_lot_consumption::Column::Id)
+        .all(conn)
+        .await?;
+    restore_lots(conn, consumptions, None, &source).await
+}
+
+/// 退货时调整批次（销售退货恢复原出库批次，采购退货扣减该采购订单的批次）
+pub(crate) async fn apply_return_lots<C: ConnectionTrait>(
+    conn: &C,
+    order_return: &order_return::Model,
+    items: &[order_return_item::Model],
+) -> Result<(), Box<dyn std::error::Error>> {
+    for item in items {
+        let source = ConsumptionSource {
+            order_id: Some(order_return.order_id),
+            order_item_id: Some(item.order_item_id),
+            return_id: Some(order_return.id),
+            stocktake_id: None,
+            create_at: order_return.create_at,
+        };
+        match order_return.order_type {
+            OrderType::Sales => {
+                let consumptions = stock_lot_consumption::Entity::find()
+                    .filter(stock_lot_consumption::Column::OrderItemId.eq(item.order_item_id))
+                    .order_by_asc(stock_lot_consumption::Column::Id)
+                    .all(conn)
+                    .await?;
+                restore_lots(conn, consumptions, Some(item.quantity), &source).await?;
+            }
+            OrderType::Purchase => {
+                let lots = available_lots(
+                    conn,
+                    item.product_id,
+                    &item.unit,
+                    Some(order_return.order_id),
+                )
+                .await?;
+                consume_lots(
+                    conn,
+                    lots,
+                    item.product_id,
+                    &item.unit,
+                    item.quantity,
+                    &source,
+                )
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 取批次核对行（不存在时按商品 + 单位新建，保持首次出现的顺序）
+fn mismatch_entry<'a>(
+    keys: &mut Vec<(i64, String)>,
+    rows: &'a mut HashMap<(i64, String), LotMismatch>,
+    product_id: i64,
+    product_name: &str,
+    unit: &str,
+) -> &'a mut LotMismatch {
+    let key = (product_id, unit.to_string());
+    if !keys.contains(&key) {
+        keys.push(key.clone());
+    }
+    rows.entry(key).or_insert_with(|| LotMismatch {
+        product_id,
+        product_name: product_name.to_string(),
+        unit: unit.to_string(),
+        stock_quantity: Decimal::ZERO,
+        lot_quantity: Decimal::ZERO,
+        untracked_quantity: Decimal::ZERO,
+    })
+}
+
+/// 盘点审核时调整批次（盘亏按先到期先出扣减批次，盘盈按盘点单编号登记无到期日期的调整批次）
+pub(crate) async fn apply_stocktake_lots<C: ConnectionTrait>(
+    conn: &C,
+    stocktake: &stocktake::Model,
+    items: &[stocktake_item::Model],
+    create_at: NaiveDateTime,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = ConsumptionSource {
+        order_id: None,
+        order_item_id: None,
+        return_id: None,
+        stocktake_id: Some(stocktake.id),
+        create_at,
+    };
+    for item in items {
+        if item.variance < Decimal::ZERO {
+            let lots = available_lots(conn, item.product_id, &item.unit, None).await?;
+            consume_lots(
+                conn,
+                lots,
+                item.product_id,
+                &item.unit,
+                -item.variance,
+                &source,
+            )
+            .await?;
+        } else if item.variance > Decimal::ZERO {
+            insert_lot(
+                conn,
+                StockLotActiveModel {
+                    id: sea_orm::ActiveValue::NotSet,
+                    product_id: Set(item.product_id),
+                    product_name: Set(item.product_name.clone()),
+                    unit: Set(item.unit.clone()),
+                    lot_no: Set(stocktake.stocktake_no.clone()),
+                    production_date: Set(None),
+                    expiry_date: Set(None),
+                    received_quantity: Set(item.variance),
+                    remaining_quantity: Set(item.variance),
+                    order_id: Set(None),
+                    order_item_id: Set(None),
+                    stocktake_id: Set(Some(stocktake.id)),
+                    receipt_id: Set(None),
+                    receipt_item_id: Set(None),
+                    create_at: Set(create_at),
+                },
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// 批次服务
+#[derive(Debug)]
+pub struct LotService {
+    db: DatabaseConnection,
+}
+
+impl LotService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查询商品有剩余的批次（按先到期先出排序）
+    pub async fn get_product_lots(
+        &self,
+        product_id: i64,
+    ) -> Result<Vec<stock_lot::Model>, Box<dyn std::error::Error>> {
+        let mut lots = stock_lot::Entity::find()
+            .filter(stock_lot::Column::ProductId.eq(product_id))
+            .filter(stock_lot::Column::RemainingQuantity.gt(Decimal::ZERO))
+            .all(&self.db)
+            .await?;
+        sort_fefo(&mut lots);
+        Ok(lots)
+    }
+
+    /// 查询临期批次（到期日期在基准日期后预警天数内，含已过期，按到期日期升序）
+    pub async fn get_expiring_lots(
+        &self,
+        input: QueryExpiringLotsDto,
+    ) -> Result<Vec<ExpiringLot>, Box<dyn std::error::Error>> {
+        let within_days = input.within_days.unwrap_or(DEFAULT_EXPIRY_WARNING_DAYS);
+        if within_days < 0 {
+            return Err("预警天数不能为负数".into());
+        }
+        let as_of_date = match &input.as_of_date {
+            Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map_err(|_| "无效的基准日期格式，应为 YYYY-MM-DD".to_string())?,
+            None => Local::now().date_naive(),
+        };
+
+        let mut query = stock_lot::Entity::find()
+            .filter(stock_lot::Column::RemainingQuantity.gt(Decimal::ZERO))
+            .filter(
+                stock_lot::Column::ExpiryDate
+                    .lt((as_of_date + Duration::days(within_days + 1)).and_hms_opt(0, 0, 0)),
+            );
+        if let Some(product_id) = input.product_id {
+            query = query.filter(stock_lot::Column::ProductId.eq(product_id));
+        }
+        let mut lots = query.all(&self.db).await?;
+        sort_fefo(&mut lots);
+
+        let costs = current_unit_costs(&self.db).await?;
+        Ok(lots
+            .into_iter()
+            .map(|lot| {
+                let days_to_expiry = lot
+                    .expiry_date
+                    .map(|d| (d.date() - as_of_date).num_days())
+                    .unwrap_or_default();
+                let unit_cost = costs.get(&(lot.product_id, lot.unit.clone())).copied();
+                ExpiringLot {
+                    days_to_expiry,
+                    expired: days_to_expiry < 0,
+                    unit_cost,
+                    value: unit_cost.map(|c| (c * lot.remaining_quantity).round_dp(2)),
+                    lot,
+                }
+            })
+            .collect())
+    }
+
+    /// 批次核对：列出库存数量与批次剩余数量不一致，或存在批次未覆盖出库的商品
+    pub async fn get_lot_mismatches(&self) -> Result<Vec<LotMismatch>, Box<dyn std::error::Error>> {
+        let mut keys: Vec<(i64, String)> = Vec::new();
+        let mut rows: HashMap<(i64, String), LotMismatch> = HashMap::new();
+        let stocks = product_stock::Entity::find()
+            .order_by_asc(product_stock::Column::ProductId)
+            .all(&self.db)
+            .await?;
+        for stock in stocks {
+            mismatch_entry(
+                &mut keys,
+                &mut rows,
+                stock.product_id,
+                &stock.product_name,
+                &stock.unit,
+            )
+            .stock_quantity = stock.quantity;
+        }
+        let lots = stock_lot::Entity::find()
+            .filter(stock_lot::Column::RemainingQuantity.gt(Decimal::ZERO))
+            .all(&self.db)
+            .await?;
+        for lot in lots {
+            mismatch_entry(
+                &mut keys,
+                &mut rows,
+                lot.product_id,
+                &lot.product_name,
+                &lot.unit,
+            )
+            .lot_quantity += lot.remaining_quantity;
+        }
+        let untracked = stock_lot_consumption::Entity::find()
+            .filter(stock_lot_consumption::Column::LotId.is_null())
+            .all(&self.db)
+            .await?;
+        for c in untracked {
+            mismatch_entry(&mut keys, &mut rows, c.product_id, "", &c.unit).untracked_quantity +=
+                c.quantity;
+        }
+
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| rows.remove(&key))
+            .filter(|r| r.stock_quantity != r.lot_quantity || !r.untracked_quantity.is_zero())
+            .collect())
+    }
+}
@@ -1,20 +1,57 @@
 pub mod accounting;
 pub mod accounting_book;
 pub mod attachment;
+pub mod cash_flow;
 pub mod category;
 pub mod chat;
+pub mod costing;
 pub mod customer;
+pub mod dashboard;
+pub mod document;
+pub mod goods_receipt;
+pub mod ledger;
+pub mod lot;
+pub mod numbering;
 pub mod order;
+pub mod order_revision;
+pub mod order_template;
+pub mod printer;
 pub mod product;
+pub mod profit;
+pub mod quotation;
+pub mod receipt;
+pub mod reorder;
+pub mod setting;
+pub mod statement;
+pub mod stock;
+pub mod stocktake;
 
 pub use accounting::AccountingService;
 pub use accounting_book::AccountingBookService;
 pub use attachment::AttachmentService;
+pub use cash_flow::CashFlowService;
 pub use category::CategoryService;
 pub use chat::ChatService;
+pub use costing::CostingService;
 pub use customer::CustomerService;
+pub use dashboard::DashboardService;
+pub use goods_receipt::GoodsReceiptService;
+pub use ledger::LedgerService;
+pub use lot::LotService;
+pub use numbering::NumberingService;
 pub use order::OrderService;
+pub use order_revision::OrderRevisionService;
+pub use order_template::OrderTemplateService;
+pub use printer::PrinterService;
 pub use product::ProductService;
+pub use profit::ProfitService;
+pub use quotation::QuotationService;
+pub use receipt::ReceiptService;
+pub use reorder::ReorderService;
+pub use setting::SettingService;
+pub use statement::StatementService;
+pub use stock::StockService;
+pub use stocktake::StocktakeService;
 use sea_orm::DatabaseConnection;
 use tauri::{App, Manager};
 
@@ -29,11 +66,29 @@ pub fn init_services(
     let accounting_service = AccountingService::new(db.clone());
     let attachment_service = AttachmentService::new(db.clone());
     let accounting_book_service = AccountingBookService::new(db.clone());
+    let cash_flow_service = CashFlowService::new(db.clone());
     let category_service = CategoryService::new(db.clone());
     let chat_service = ChatService::new(db.clone());
+    let costing_service = CostingService::new(db.clone());
     let customer_service = CustomerService::new(db.clone());
+    let dashboard_service = DashboardService::new(db.clone());
+    let goods_receipt_service = GoodsReceiptService::new(db.clone());
+    let ledger_service = LedgerService::new(db.clone());
+    let lot_service = LotService::new(db.clone());
+    let numbering_service = NumberingService::new(db.clone());
     let product_service = ProductService::new(db.clone());
+    let profit_service = ProfitService::new(db.clone());
     let order_service = OrderService::new(db.clone());
+    let order_revision_service = OrderRevisionService::new(db.clone());
+    let order_template_service = OrderTemplateService::new(db.clone());
+    let printer_service = PrinterService::new(db.clone());
+    let quotation_service = QuotationService::new(db.clone());
+    let receipt_service = ReceiptService::new(db.clone());
+    let reorder_service = ReorderService::new(db.clone());
+    let setting_service = SettingService::new(db.clone());
+    let statement_service = StatementService::new(db.clone());
+    let stock_service = StockService::new(db.clone());
+    let stocktake_service = StocktakeService::new(db.clone());
 
     rt.block_on(accounting_book_service.create_default_book())?;
     rt.block_on(category_service.create_default_category())?;
@@ -41,11 +96,29 @@ pub fn init_services(
     app.manage(accounting_service);
     app.manage(attachment_service);
     app.manage(accounting_book_service);
+    app.manage(cash_flow_service);
     app.manage(category_service);
     app.manage(chat_service);
+    app.manage(costing_service);
     app.manage(customer_service);
+    app.manage(dashboard_service);
+    app.manage(goods_receipt_service);
+    app.manage(ledger_service);
+    app.manage(lot_service);
+    app.manage(numbering_service);
     app.manage(product_service);
+    app.manage(profit_service);
     app.manage(order_service);
+    app.manage(order_revision_service);
+    app.manage(order_template_service);
+    app.manage(printer_service);
+    app.manage(quotation_service);
+    app.manage(receipt_service);
+    app.manage(reorder_service);
+    app.manage(setting_service);
+    app.manage(statement_service);
+    app.manage(stock_service);
+    app.manage(stocktake_service);
 
     Ok(())
 }
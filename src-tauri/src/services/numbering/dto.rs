@@ -0,0 +1,31 @@
+use crate::enums::{DocumentType, ResetPolicy};
+use serde::{Deserialize, Serialize};
+
+/// 更新单据编号规则 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNumberingRuleDto {
+    /// 单据类型（SalesOrder / PurchaseOrder / SalesReturn / PurchaseReturn / Statement / Quotation / GoodsReceipt / Stocktake）
+    pub document_type: String,
+    /// 编号模板，支持 {yyyy} {yy} {MM} {dd} 等日期占位符及组合（如 {yyyyMMdd}），
+    /// 必须包含流水号占位符 {seq} 或 {seq:N}（N 为补零位数）
+    pub pattern: String,
+    /// 流水号重置周期（Daily / Monthly / Yearly / Never）
+    pub reset_policy: String,
+}
+
+/// 单据编号规则
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberingRuleInfo {
+    /// 单据类型
+    pub document_type: DocumentType,
+    /// 编号模板
+    pub pattern: String,
+    /// 流水号重置周期
+    pub reset_policy: ResetPolicy,
+    /// 是否为自定义规则（false 表示使用默认规则）
+    pub is_custom: bool,
+    /// 示例编号（按当前时间、流水号 1 生成）
+    pub example: String,
+}
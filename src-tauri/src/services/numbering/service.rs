@@ -0,0 +1,291 @@
+use chrono::{Local, NaiveDateTime};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, Set,
+};
+use strum::IntoEnumIterator;
+
+use super::dto::{NumberingRuleInfo, UpdateNumberingRuleDto};
+use crate::entity::{
+    document_sequence, goods_receipt, numbering_rule, order, order_return, quotation, stocktake,
+};
+use crate::enums::{DocumentType, ResetPolicy};
+
+/// 单个周期内查找未占用编号的最大尝试次数
+const MAX_ATTEMPTS: usize = 1000;
+
+/// 默认编号规则（模板, 重置周期）
+pub fn default_rule(document_type: DocumentType) -> (&'static str, ResetPolicy) {
+    match document_type {
+        DocumentType::SalesOrder => ("XS-{yyyyMMdd}-{seq:4}", ResetPolicy::Daily),
+        DocumentType::PurchaseOrder => ("CG-{yyyyMMdd}-{seq:4}", ResetPolicy::Daily),
+        DocumentType::SalesReturn => ("XT-{yyyyMMdd}-{seq:3}", ResetPolicy::Daily),
+        DocumentType::PurchaseReturn => ("CT-{yyyyMMdd}-{seq:3}", ResetPolicy::Daily),
+        DocumentType::Statement => ("DZ-{yyyyMM}-{seq:3}", ResetPolicy::Monthly),
+        DocumentType::Quotation => ("BJ-{yyyyMMdd}-{seq:3}", ResetPolicy::Daily),
+        DocumentType::GoodsReceipt => ("SH-{yyyyMMdd}-{seq:3}", ResetPolicy::Daily),
+        DocumentType::Stocktake => ("PD-{yyyyMMdd}-{seq:3}", ResetPolicy::Daily),
+    }
+}
+
+/// 流水号所属周期标识
+fn period_key(policy: ResetPolicy, now: NaiveDateTime) -> String {
+    match policy {
+        ResetPolicy::Daily => now.format("%Y%m%d").to_string(),
+        ResetPolicy::Monthly => now.format("%Y%m").to_string(),
+        ResetPolicy::Yearly => now.format("%Y").to_string(),
+        ResetPolicy::Never => "ALL".to_string(),
+    }
+}
+
+/// 渲染单个占位符
+fn render_token(
+    token: &str,
+    now: NaiveDateTime,
+    seq: i32,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if token == "seq" {
+        return Ok(seq.to_string());
+    }
+    if let Some(width) = token.strip_prefix("seq:") {
+        let width: usize = width
+            .parse()
+            .ok()
+            .filter(|w| (1..=10).contains(w))
+            .ok_or("流水号位数必须为 1 ~ 10")?;
+        return Ok(format!("{:0width$}", seq, width = width));
+    }
+
+    // 日期占位符：yyyy / yy / MM / dd 的任意组合
+    let mut format = String::new();
+    let mut rest = token;
+    while !rest.is_empty() {
+        let (spec, len) = if rest.starts_with("yyyy") {
+            ("%Y", 4)
+        } else if rest.starts_with("yy") {
+            ("%y", 2)
+        } else if rest.starts_with("MM") {
+            ("%m", 2)
+        } else if rest.starts_with("dd") {
+            ("%d", 2)
+        } else {
+            return Err(format!("无效的编号占位符: {{{}}}", token).into());
+        };
+        format.push_str(spec);
+        rest = &rest[len..];
+    }
+    Ok(now.format(&format).to_string())
+}
+
+/// 按模板生成编号
+pub fn render_pattern(
+    pattern: &str,
+    now: NaiveDateTime,
+    seq: i32,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut result = String::new();
+    let mut has_seq = false;
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .map(|i| start + i)
+            .ok_or("编号模板中的占位符未闭合")?;
+        let token = &rest[start + 1..end];
+        if token == "seq" || token.starts_with("seq:") {
+            has_seq = true;
+        }
+        result.push_str(&render_token(token, now, seq)?);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+
+    if !has_seq {
+        return Err("编号模板必须包含流水号占位符 {seq}".into());
+    }
+    if result.trim().is_empty() {
+        return Err("编号模板不能为空".into());
+    }
+    Ok(result)
+}
+
+/// 编号是否已被同类单据占用（订单的销售 / 采购共用一张表，编号在两者间同样唯一）
+async fn document_no_exists<C: ConnectionTrait>(
+    conn: &C,
+    document_type: DocumentType,
+    document_no: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let count = match document_type {
+        DocumentType::SalesOrder | DocumentType::PurchaseOrder => {
+            order::Entity::find()
+                .filter(order::Column::OrderNo.eq(document_no))
+                .count(conn)
+                .await?
+        }
+        DocumentType::SalesReturn | DocumentType::PurchaseReturn => {
+            order_return::Entity::find()
+                .filter(order_return::Column::ReturnNo.eq(document_no))
+                .count(conn)
+                .await?
+        }
+        DocumentType::Quotation => {
+            quotation::Entity::find()
+                .filter(quotation::Column::QuotationNo.eq(document_no))
+                .count(conn)
+                .await?
+        }
+        DocumentType::GoodsReceipt => {
+            goods_receipt::Entity::find()
+                .filter(goods_receipt::Column::ReceiptNo.eq(document_no))
+                .count(conn)
+                .await?
+        }
+        DocumentType::Stocktake => {
+            stocktake::Entity::find()
+                .filter(stocktake::Column::StocktakeNo.eq(document_no))
+                .count(conn)
+                .await?
+        }
+        // 对账单不落库，编号仅用于导出文件
+        DocumentType::Statement => 0,
+    };
+    Ok(count > 0)
+}
+
+/// 生成下一个单据编号（可在事务中使用）
+///
+/// 按单据类型的编号规则递增流水号；若生成的编号已被占用（如模板不含日期但按日重置），
+/// 继续递增直到找到未占用的编号，保证编号唯一
+pub(crate) async fn next_document_no<C: ConnectionTrait>(
+    conn: &C,
+    document_type: DocumentType,
+    now: NaiveDateTime,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let (pattern, reset_policy) = match numbering_rule::Entity::find_by_id(document_type)
+        .one(conn)
+        .await?
+    {
+        Some(rule) => (rule.pattern, rule.reset_policy),
+        None => {
+            let (pattern, reset_policy) = default_rule(document_type);
+            (pattern.to_string(), reset_policy)
+        }
+    };
+    let period = period_key(reset_policy, now);
+
+    for _ in 0..MAX_ATTEMPTS {
+        let seq = document_sequence::Model::get_next_sequence(conn, document_type, &period).await?;
+        let document_no = render_pattern(&pattern, now, seq)?;
+        if !document_no_exists(conn, document_type, &document_no).await? {
+            return Ok(document_no);
+        }
+    }
+
+    Err("无法生成唯一的单据编号，请检查编号规则".into())
+}
+
+/// 单据编号服务
+#[derive(Debug)]
+pub struct NumberingService {
+    db: DatabaseConnection,
+}
+
+impl NumberingService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取全部单据类型的编号规则（未配置的返回默认规则）
+    pub async fn get_numbering_rules(
+        &self,
+    ) -> Result<Vec<NumberingRuleInfo>, Box<dyn std::error::Error>> {
+        let rules = numbering_rule::Entity::find().all(&self.db).await?;
+        let now = Local::now().naive_local();
+
+        DocumentType::iter()
+            .map(|document_type| {
+                let custom = rules.iter().find(|r| r.document_type == document_type);
+                let (pattern, reset_policy, is_custom) = match custom {
+                    Some(rule) => (rule.pattern.clone(), rule.reset_policy, true),
+                    None => {
+                        let (pattern, reset_policy) = default_rule(document_type);
+                        (pattern.to_string(), reset_policy, false)
+                    }
+                };
+                Ok(NumberingRuleInfo {
+                    document_type,
+                    example: render_pattern(&pattern, now, 1)?,
+                    pattern,
+                    reset_policy,
+                    is_custom,
+                })
+            })
+            .collect()
+    }
+
+    /// 更新单据编号规则（校验模板后保存，流水号按新规则的周期继续计数）
+    pub async fn update_numbering_rule(
+        &self,
+        input: UpdateNumberingRuleDto,
+    ) -> Result<NumberingRuleInfo, Box<dyn std::error::Error>> {
+        let document_type = input
+            .document_type
+            .parse::<DocumentType>()
+            .map_err(|_| "无效的单据类型".to_string())?;
+        let reset_policy = input
+            .reset_policy
+            .parse::<ResetPolicy>()
+            .map_err(|_| "无效的流水号重置周期".to_string())?;
+
+        let pattern = input.pattern.trim().to_string();
+        let now = Local::now().naive_local();
+        let example = render_pattern(&pattern, now, 1)?;
+
+        let existing = numbering_rule::Entity::find_by_id(document_type)
+            .one(&self.db)
+            .await?;
+        let rule = numbering_rule::ActiveModel {
+            document_type: Set(document_type),
+            pattern: Set(pattern.clone()),
+            reset_policy: Set(reset_policy),
+            update_at: Set(now),
+        };
+        match existing {
+            Some(_) => rule.update(&self.db).await?,
+            None => rule.insert(&self.db).await?,
+        };
+
+        Ok(NumberingRuleInfo {
+            document_type,
+            pattern,
+            reset_policy,
+            is_custom: true,
+            example,
+        })
+    }
+
+    /// 恢复默认编号规则
+    pub async fn reset_numbering_rule(
+        &self,
+        document_type: String,
+    ) -> Result<NumberingRuleInfo, Box<dyn std::error::Error>> {
+        let document_type = document_type
+            .parse::<DocumentType>()
+            .map_err(|_| "无效的单据类型".to_string())?;
+
+        numbering_rule::Entity::delete_by_id(document_type)
+            .exec(&self.db)
+            .await?;
+
+        let (pattern, reset_policy) = default_rule(document_type);
+        Ok(NumberingRuleInfo {
+            document_type,
+            pattern: pattern.to_string(),
+            reset_policy,
+            is_custom: false,
+            example: render_pattern(pattern, Local::now().naive_local(), 1)?,
+        })
+    }
+}
@@ -1,3 +1,5 @@
+use crate::entity::{order, order_return, order_return_item};
+use crate::enums::{AccountingChannel, OrderStatus};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -19,6 +21,8 @@ pub struct CreateOrderDto {
     pub actual_amount: Option<Decimal>,
     /// 订单业务类型（可选，不传则自动填充默认值）
     pub sub_type: Option<String>,
+    /// 预计收付款日期（可选，格式 YYYY-MM-DD）
+    pub due_date: Option<String>,
 }
 
 /// 创建订单明细 DTO
@@ -35,20 +39,150 @@ pub struct CreateOrderItemDto {
     pub unit: String,
     /// 单价
     pub unit_price: Decimal,
+    /// 明细折扣金额（可选，与折扣率二选一）
+    pub discount_amount: Option<Decimal>,
+    /// 明细折扣率（可选，百分比 0 ~ 100，与折扣金额二选一）
+    pub discount_rate: Option<Decimal>,
     /// 备注
     pub remark: Option<String>,
 }
 
+/// 复制订单 DTO（再来一单）
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneOrderDto {
+    /// 原订单 ID
+    pub order_id: i64,
+    /// 是否按商品参考价刷新单价（默认沿用原订单单价）
+    pub refresh_prices: Option<bool>,
+    /// 备注（可选，默认沿用原订单备注）
+    pub remark: Option<String>,
+    /// 预计收付款日期（可选，格式 YYYY-MM-DD）
+    pub due_date: Option<String>,
+}
+
 /// 结账订单 DTO
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SettleOrderDto {
     /// 订单 ID
     pub order_id: i64,
-    /// 支付渠道（必填）
-    pub channel: String,
+    /// 支付渠道（单一渠道结账时必填，与 payments 二选一）
+    pub channel: Option<String>,
     /// 实收金额（可选，不传则使用订单已有的 actual_amount）
     pub actual_amount: Option<Decimal>,
+    /// 分渠道收付款（组合支付时填写，金额合计须等于未结金额）
+    pub payments: Option<Vec<SettlePaymentDto>>,
+}
+
+/// 结账分渠道收付款项
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlePaymentDto {
+    /// 收付款渠道
+    pub channel: String,
+    /// 该渠道收付金额（必须大于 0）
+    pub amount: Decimal,
+}
+
+/// 批量结账 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSettleOrdersDto {
+    /// 订单 ID 列表（均须为待结账状态）
+    pub order_ids: Vec<i64>,
+    /// 支付渠道（必填，所有订单共用）
+    pub channel: String,
+}
+
+/// 撤销结账 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReopenOrderDto {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 撤销原因（写入冲账记录备注）
+    pub reason: Option<String>,
+}
+
+/// 登记收付款 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddOrderPaymentDto {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 本次收付款金额（必须大于 0，且不超过未结金额）
+    pub amount: Decimal,
+    /// 收付款渠道（必填）
+    pub channel: String,
+    /// 收付款时间（可选，默认当前时间）
+    pub paid_at: Option<String>,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+/// 按渠道汇总的收付款金额
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelPayment {
+    /// 收付款渠道
+    pub channel: AccountingChannel,
+    /// 收付款净额
+    pub amount: Decimal,
+}
+
+/// 订单未结余额
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBalance {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 实收/实付总额
+    pub actual_amount: Decimal,
+    /// 已收付款金额
+    pub paid_amount: Decimal,
+    /// 未结金额
+    pub outstanding_amount: Decimal,
+    /// 收付款笔数
+    pub payment_count: usize,
+    /// 按渠道汇总的收付款净额（组合支付时有多项）
+    pub payment_channels: Vec<ChannelPayment>,
+    /// 订单状态
+    pub status: OrderStatus,
+}
+
+/// 退货明细 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderReturnItemDto {
+    /// 原订单明细 ID
+    pub order_item_id: i64,
+    /// 退货数量（必须大于 0，且不超过剩余可退数量）
+    pub quantity: Decimal,
+}
+
+/// 创建退货单 DTO（仅已结账订单可退货）
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderReturnDto {
+    /// 原订单 ID
+    pub order_id: i64,
+    /// 退货明细列表
+    pub items: Vec<CreateOrderReturnItemDto>,
+    /// 退款金额（可选，默认按原订单实收比例折算退货商品金额）
+    pub refund_amount: Option<Decimal>,
+    /// 退款渠道（可选，默认原订单收付款渠道；分多个渠道收付款的订单必填）
+    pub channel: Option<String>,
+    /// 退货原因
+    pub reason: Option<String>,
+}
+
+/// 退货单详情（退货单 + 明细列表）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderReturnDetail {
+    pub order_return: order_return::Model,
+    pub items: Vec<order_return_item::Model>,
 }
 
 /// 编辑订单 DTO（仅允许修改明细和备注，不可修改类型和客户）
@@ -61,6 +195,8 @@ pub struct UpdateOrderDto {
     pub items: Option<Vec<CreateOrderItemDto>>,
     /// 备注（可选，传入则更新备注）
     pub remark: Option<String>,
+    /// 预计收付款日期（可选，传入则更新，格式 YYYY-MM-DD）
+    pub due_date: Option<String>,
 }
 
 /// 结算预览 — 品类分组项
@@ -71,8 +207,10 @@ pub struct SettlePreviewItem {
     pub category_id: i64,
     /// 品类名称
     pub category_name: String,
-    /// 该品类的记账金额（小计之和）
+    /// 该品类的主记录金额（明细折扣前金额之和）
     pub amount: Decimal,
+    /// 该品类的明细折扣之和
+    pub item_discount_amount: Decimal,
     /// 目标账本 ID
     pub book_id: i64,
     /// 目标账本名称
@@ -101,6 +239,12 @@ pub struct SettlePreview {
     pub write_off_preview: Option<Vec<WriteOffPreviewItem>>,
     /// 折扣总额（total_amount - actual_amount）
     pub discount_amount: Option<Decimal>,
+    /// 明细折扣冲账预览（仅在存在明细折扣时有值）
+    pub item_discount_preview: Option<Vec<WriteOffPreviewItem>>,
+    /// 明细折扣总额
+    pub item_discount_amount: Option<Decimal>,
+    /// 毛利测算（仅销售订单）
+    pub margin: Option<OrderMargin>,
 }
 
 /// 分页查询订单 DTO
@@ -125,4 +269,152 @@ pub struct QueryOrdersDto {
     pub channel: Option<String>,
     /// 订单类型筛选
     pub order_type: Option<String>,
+    /// 客户筛选
+    pub customer_id: Option<i64>,
+    /// 订单业务类型筛选
+    pub sub_type: Option<String>,
+    /// 订单编号（模糊匹配）
+    pub order_no: Option<String>,
+    /// 包含指定商品的订单
+    pub product_id: Option<i64>,
+    /// 关键词（模糊匹配客户名称或明细商品名称）
+    pub keyword: Option<String>,
+    /// 排序字段（CreateAt / Amount / SettledAt，默认 CreateAt）
+    pub sort_by: Option<String>,
+    /// 排序方向（Asc / Desc，默认 Desc）
+    pub sort_order: Option<String>,
+}
+
+/// 订单排序字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderSortField {
+    /// 创建时间
+    CreateAt,
+    /// 实收/实付金额
+    Amount,
+    /// 结账时间（未结账订单排在最后）
+    SettledAt,
+}
+
+impl std::str::FromStr for OrderSortField {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "CreateAt" => Ok(OrderSortField::CreateAt),
+            "Amount" => Ok(OrderSortField::Amount),
+            "SettledAt" => Ok(OrderSortField::SettledAt),
+            _ => Err(()),
+        }
+    }
+}
+
+/// 批量结账结果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSettleResult {
+    /// 结账后的订单列表（按传入顺序）
+    pub orders: Vec<order::Model>,
+    /// 订单总额合计
+    pub total_amount: Decimal,
+    /// 实收（实付）金额合计
+    pub actual_amount: Decimal,
+    /// 合并后的结算预览（按品类、账本汇总）
+    pub preview: SettlePreview,
+}
+
+/// 成本来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CostSource {
+    /// 按成本核算方法计算的当前出库单位成本（采购折扣、整单优惠与退货已扣除）
+    StockCost,
+    /// 商品参考采购价
+    DefaultPurchasePrice,
+}
+
+/// 毛利预警类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MarginWarningKind {
+    /// 售价低于成本
+    BelowCost,
+    /// 毛利率低于设置的最低毛利率
+    BelowThreshold,
+    /// 整单毛利为负（如整单优惠过大）
+    OrderBelowCost,
+}
+
+/// 明细毛利
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineMargin {
+    /// 商品 ID
+    pub product_id: i64,
+    /// 商品名称
+    pub product_name: String,
+    /// 数量
+    pub quantity: Decimal,
+    /// 计量单位
+    pub unit: String,
+    /// 成交单价
+    pub unit_price: Decimal,
+    /// 单位成本（无成本数据时为 None）
+    pub unit_cost: Option<Decimal>,
+    /// 成本来源
+    pub cost_source: Option<CostSource>,
+    /// 销售金额（明细折扣后小计）
+    pub revenue: Decimal,
+    /// 成本金额
+    pub cost: Option<Decimal>,
+    /// 毛利
+    pub margin: Option<Decimal>,
+    /// 毛利率（百分比）
+    pub margin_rate: Option<Decimal>,
+}
+
+/// 毛利预警
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginWarning {
+    /// 预警类型
+    pub kind: MarginWarningKind,
+    /// 商品 ID（整单预警为 None）
+    pub product_id: Option<i64>,
+    /// 商品名称（整单预警为 None）
+    pub product_name: Option<String>,
+    /// 毛利
+    pub margin: Decimal,
+    /// 毛利率（百分比）
+    pub margin_rate: Option<Decimal>,
+    /// 提示信息
+    pub message: String,
+}
+
+/// 订单毛利测算结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderMargin {
+    /// 销售收入（实收金额，含整单优惠）
+    pub revenue: Decimal,
+    /// 成本合计（仅含有成本数据的明细）
+    pub cost: Decimal,
+    /// 毛利（销售收入 - 成本合计）
+    pub gross_profit: Decimal,
+    /// 毛利率（百分比）
+    pub gross_margin_rate: Option<Decimal>,
+    /// 是否所有明细都有成本数据（否则毛利偏高）
+    pub cost_complete: bool,
+    /// 各明细毛利
+    pub lines: Vec<LineMargin>,
+    /// 预警列表
+    pub warnings: Vec<MarginWarning>,
+}
+
+/// 订单及毛利测算（序列化时订单字段平铺，附加 margin 字段）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderWithMargin {
+    #[serde(flatten)]
+    pub order: order::Model,
+    /// 毛利测算（仅销售订单）
+    pub margin: Option<OrderMargin>,
 }
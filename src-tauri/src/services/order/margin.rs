@@ -0,0 +1,166 @@
+//! 销售订单毛利测算
+//!
+//! 单位成本优先取成本核算模块按当前核算方法计算的出库单位成本（与销售成本报表一致，
+//! 已扣除采购折扣、整单优惠与采购退货），没有入库记录时取商品参考采购价（仅当明细单位与商品单位一致）。
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+
+use super::dto::{CostSource, LineMargin, MarginWarning, MarginWarningKind, OrderMargin};
+use crate::entity::{order_item, product};
+use crate::enums::OrderType;
+use crate::services::costing::service::current_unit_costs;
+use crate::services::setting::service::load_margin_config;
+
+/// 毛利率（百分比，收入为 0 时为 None）
+pub(crate) fn margin_rate(margin: Decimal, revenue: Decimal) -> Option<Decimal> {
+    (!revenue.is_zero()).then(|| (margin * Decimal::ONE_HUNDRED / revenue).round_dp(2))
+}
+
+/// 查询明细商品的单位成本，按 (商品 ID, 单位) 索引
+pub(crate) async fn unit_costs<C: ConnectionTrait>(
+    conn: &C,
+    items: &[order_item::Model],
+) -> Result<HashMap<(i64, String), (Decimal, CostSource)>, Box<dyn std::error::Error>> {
+    let product_ids: Vec<i64> = items.iter().map(|i| i.product_id).collect();
+
+    // 与销售成本报表相同的核算成本
+    let mut costs: HashMap<(i64, String), (Decimal, CostSource)> = current_unit_costs(conn)
+        .await?
+        .into_iter()
+        .filter(|((product_id, _), _)| product_ids.contains(product_id))
+        .map(|(key, cost)| (key, (cost, CostSource::StockCost)))
+        .collect();
+
+    // 无入库记录时取参考采购价
+    for p in product::Entity::find()
+        .filter(product::Column::Id.is_in(product_ids))
+        .all(conn)
+        .await?
+    {
+        if let Some(price) = p.default_purchase_price {
+            costs
+                .entry((p.id, p.unit))
+                .or_insert((price, CostSource::DefaultPurchasePrice));
+        }
+    }
+
+    Ok(costs)
+}
+
+/// 测算订单毛利（仅销售订单，采购订单返回 None）
+///
+/// `revenue` 为实收金额（含整单优惠），明细毛利按折扣后小计计算
+pub(crate) async fn compute_order_margin<C: ConnectionTrait>(
+    conn: &C,
+    order_type: &OrderType,
+    items: &[order_item::Model],
+    revenue: Decimal,
+) -> Result<Option<OrderMargin>, Box<dyn std::error::Error>> {
+    if *order_type != OrderType::Sales {
+        return Ok(None);
+    }
+
+    let config = load_margin_config(conn).await?;
+    let costs = unit_costs(conn, items).await?;
+
+    let mut lines = Vec::with_capacity(items.len());
+    let mut warnings = Vec::new();
+    for item in items {
+        let unit_cost = costs.get(&(item.product_id, item.unit.clone())).copied();
+        let cost = unit_cost.map(|(c, _)| (c * item.quantity).round_dp(2));
+        let margin = cost.map(|c| item.subtotal - c);
+        let rate = margin.and_then(|m| margin_rate(m, item.subtotal));
+
+        if let Some(margin) = margin {
+            let kind = if margin < Decimal::ZERO {
+                Some(MarginWarningKind::BelowCost)
+            } else {
+                match (rate, config.min_margin_rate) {
+                    (Some(rate), Some(min)) if rate < min => {
+                        Some(MarginWarningKind::BelowThreshold)
+                    }
+                    _ => None,
+                }
+            };
+            if let Some(kind) = kind {
+                let message = match kind {
+                    MarginWarningKind::BelowThreshold => format!(
+                        "{} 毛利率 {}% 低于最低毛利率 {}%",
+                        item.product_name,
+                        rate.unwrap_or_default().normalize(),
+                        config.min_margin_rate.unwrap_or_default().normalize()
+                    ),
+                    _ => format!(
+                        "{} 售价低于成本，亏损 {}",
+                        item.product_name,
+                        (-margin).round_dp(2)
+                    ),
+                };
+                warnings.push(MarginWarning {
+                    kind,
+                    product_id: Some(item.product_id),
+                    product_name: Some(item.product_name.clone()),
+                    margin,
+                    margin_rate: rate,
+                    message,
+                });
+            }
+        }
+
+        lines.push(LineMargin {
+            product_id: item.product_id,
+            product_name: item.product_name.clone(),
+            quantity: item.quantity,
+            unit: item.unit.clone(),
+            unit_price: item.unit_price,
+            unit_cost: unit_cost.map(|(c, _)| c),
+            cost_source: unit_cost.map(|(_, s)| s),
+            revenue: item.subtotal,
+            cost,
+            margin,
+            margin_rate: rate,
+        });
+    }
+
+    let cost: Decimal = lines.iter().filter_map(|l| l.cost).sum();
+    let gross_profit = revenue - cost;
+    if gross_profit < Decimal::ZERO {
+        warnings.push(MarginWarning {
+            kind: MarginWarningKind::OrderBelowCost,
+            product_id: None,
+            product_name: None,
+            margin: gross_profit,
+            margin_rate: margin_rate(gross_profit, revenue),
+            message: format!("整单毛利为负，亏损 {}", (-gross_profit).round_dp(2)),
+        });
+    }
+
+    Ok(Some(OrderMargin {
+        revenue,
+        cost,
+        gross_profit,
+        gross_margin_rate: margin_rate(gross_profit, revenue),
+        cost_complete: lines.iter().all(|l| l.cost.is_some()),
+        lines,
+        warnings,
+    }))
+}
+
+/// 合并多张订单的毛利测算（批量结账预览）
+pub(crate) fn merge_margins(margins: Vec<OrderMargin>) -> Option<OrderMargin> {
+    let mut iter = margins.into_iter();
+    let mut merged = iter.next()?;
+    for margin in iter {
+        merged.revenue += margin.revenue;
+        merged.cost += margin.cost;
+        merged.gross_profit += margin.gross_profit;
+        merged.cost_complete &= margin.cost_complete;
+        merged.lines.extend(margin.lines);
+        merged.warnings.extend(margin.warnings);
+    }
+    merged.gross_margin_rate = margin_rate(merged.gross_profit, merged.revenue);
+    Some(merged)
+}
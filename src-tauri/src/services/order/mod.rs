@@ -1,4 +1,5 @@
 pub mod dto;
+pub mod margin;
 pub mod service;
 
 pub use dto::*;
@@ -1,28 +1,45 @@
-use chrono::Local;
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDateTime};
 use rust_decimal::Decimal;
+use sea_orm::sea_query::Query;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, Set, TransactionTrait,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    Order, PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
 };
 
 use super::dto::{
-    CreateOrderDto, QueryOrdersDto, SettleOrderDto, SettlePreview, SettlePreviewItem,
-    UpdateOrderDto, WriteOffPreviewItem,
+    AddOrderPaymentDto, BatchSettleOrdersDto, BatchSettleResult, ChannelPayment, CloneOrderDto,
+    CreateOrderDto, CreateOrderItemDto, CreateOrderReturnDto, OrderBalance, OrderMargin,
+    OrderReturnDetail, OrderSortField, QueryOrdersDto, ReopenOrderDto, SettleOrderDto,
+    SettlePaymentDto, SettlePreview, SettlePreviewItem, UpdateOrderDto, WriteOffPreviewItem,
 };
+use super::margin::{compute_order_margin, merge_margins};
 use crate::entity::accounting_book;
 use crate::entity::accounting_record::{self, ActiveModel as AccountingActiveModel};
 use crate::entity::category;
 use crate::entity::order::{self, ActiveModel as OrderActiveModel, Model as OrderModel};
 use crate::entity::order_item::{self, ActiveModel as OrderItemActiveModel};
+use crate::entity::order_payment;
+use crate::entity::order_return::{self, ActiveModel as OrderReturnActiveModel};
+use crate::entity::order_return_item::{self, ActiveModel as OrderReturnItemActiveModel};
 use crate::entity::product;
 use crate::enums::{
-    AccountingChannel, AccountingRecordState, AccountingType, OrderStatus, OrderSubType, OrderType,
+    AccountingChannel, AccountingRecordState, AccountingType, DocumentType, OrderRevisionOrigin,
+    OrderStatus, OrderSubType, OrderType, ReceivingStatus,
 };
+use crate::services::accounting::service::find_records_by_order_id;
 use crate::services::accounting_book::DEFAULT_BOOK_ID;
 use crate::services::category::DEFAULT_CATEGORY_NAME;
+use crate::services::goods_receipt::service::{
+    apply_received_quantities, has_goods_receipts, received_adjustment, received_quantities,
+};
+use crate::services::numbering::service::next_document_no;
+use crate::services::order_revision::service::{ensure_original_revision, record_revision};
+use crate::services::stock::service::{apply_order_stock, apply_return_stock, reverse_order_stock};
 
 /// 解析时间字符串，支持多种格式
-fn parse_datetime(
+pub(crate) fn parse_datetime(
     s: &str,
     is_end: bool,
 ) -> Result<chrono::NaiveDateTime, Box<dyn std::error::Error>> {
@@ -45,7 +62,7 @@ fn parse_datetime(
 }
 
 /// 根据 order_type 和 customer_id 自动填充 sub_type 默认值
-fn resolve_default_sub_type(order_type: &OrderType, customer_id: Option<i64>) -> OrderSubType {
+pub(crate) fn resolve_default_sub_type(order_type: &OrderType, customer_id: Option<i64>) -> OrderSubType {
     match order_type {
         OrderType::Sales => {
             if customer_id.is_some() {
@@ -59,7 +76,7 @@ fn resolve_default_sub_type(order_type: &OrderType, customer_id: Option<i64>) ->
 }
 
 /// 验证 sub_type 与 order_type 的匹配关系
-fn validate_sub_type_match(
+pub(crate) fn validate_sub_type_match(
     sub_type: &OrderSubType,
     order_type: &OrderType,
 ) -> Result<(), Box<dyn std::error::Error>> {
@@ -73,6 +90,1005 @@ fn validate_sub_type_match(
     }
 }
 
+/// 计算明细折扣金额（折扣率与折扣金额二选一，均不传时无折扣）
+pub(crate) fn item_discount(
+    item: &CreateOrderItemDto,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let gross = item.quantity * item.unit_price;
+    let discount = match (item.discount_rate, item.discount_amount) {
+        (Some(_), Some(_)) => return Err("明细折扣率与折扣金额不能同时设置".into()),
+        (Some(rate), None) => {
+            if rate < Decimal::ZERO || rate > Decimal::ONE_HUNDRED {
+                return Err("明细折扣率必须在 0 ~ 100 之间".into());
+            }
+            (gross * rate / Decimal::ONE_HUNDRED).round_dp(2)
+        }
+        (None, Some(amount)) => amount,
+        (None, None) => Decimal::ZERO,
+    };
+    if discount < Decimal::ZERO || discount > gross {
+        return Err(format!("{} 的折扣金额不能为负数或超过商品金额", item.product_name).into());
+    }
+    Ok(discount)
+}
+
+/// 按商品参考价刷新明细单价（销售取参考售价，采购取参考采购价；未设置参考价的保留原价）
+pub(crate) async fn refresh_item_prices<C: ConnectionTrait>(
+    conn: &C,
+    order_type: &OrderType,
+    items: &mut [CreateOrderItemDto],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let product_ids: Vec<i64> = items.iter().map(|item| item.product_id).collect();
+    let products: HashMap<i64, product::Model> = product::Entity::find()
+        .filter(product::Column::Id.is_in(product_ids))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+
+    for item in items.iter_mut() {
+        let price = products
+            .get(&item.product_id)
+            .and_then(|p| match order_type {
+                OrderType::Sales => p.default_sell_price,
+                OrderType::Purchase => p.default_purchase_price,
+            });
+        if let Some(price) = price {
+            item.unit_price = price;
+        }
+    }
+
+    Ok(())
+}
+
+/// 订单明细按品类分组后的记账分组
+struct CategoryGroup {
+    /// 品类 ID
+    category_id: i64,
+    /// 品类名称
+    category_name: String,
+    /// 该品类小计之和（明细折扣后）
+    amount: Decimal,
+    /// 该品类明细折扣之和
+    discount: Decimal,
+    /// 目标账本 ID（销售订单取销售账本，采购订单取进货账本）
+    book_id: i64,
+}
+
+/// 替换订单明细（删除旧明细后按新明细重建），返回新的订单总额
+pub(crate) async fn replace_order_items<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+    items: &[CreateOrderItemDto],
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    if items.is_empty() {
+        return Err("订单明细不能为空".into());
+    }
+
+    // 删除旧明细
+    order_item::Entity::delete_many()
+        .filter(order_item::Column::OrderId.eq(order_id))
+        .exec(conn)
+        .await?;
+
+    // 创建新明细并计算总额
+    let mut total_amount = Decimal::ZERO;
+    for item in items {
+        let discount = item_discount(item)?;
+        let subtotal = item.quantity * item.unit_price - discount;
+        let order_item_active = OrderItemActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            order_id: Set(order_id),
+            product_id: Set(item.product_id),
+            product_name: Set(item.product_name.clone()),
+            quantity: Set(item.quantity),
+            unit: Set(item.unit.clone()),
+            unit_price: Set(item.unit_price),
+            discount_amount: Set(discount),
+            discount_rate: Set(item.discount_rate),
+            subtotal: Set(subtotal),
+            remark: Set(item.remark.clone()),
+        };
+        order_item_active.insert(conn).await?;
+        total_amount += subtotal;
+    }
+
+    Ok(total_amount)
+}
+
+/// 订单对应的记账类型和标题前缀
+fn order_accounting_type(order: &OrderModel) -> (AccountingType, String) {
+    match order.order_type {
+        OrderType::Sales => (
+            AccountingType::Income,
+            format!("销售订单-{}", order.order_no),
+        ),
+        OrderType::Purchase => (
+            AccountingType::Expenditure,
+            format!("采购订单-{}", order.order_no),
+        ),
+    }
+}
+
+/// 订单对应的编号单据类型
+fn order_document_type(order_type: &OrderType) -> DocumentType {
+    match order_type {
+        OrderType::Sales => DocumentType::SalesOrder,
+        OrderType::Purchase => DocumentType::PurchaseOrder,
+    }
+}
+
+/// 按权重比例分摊金额（四舍五入到两位小数，最后一项补差保证总和不变）
+fn allocate_proportionally(total: Decimal, weights: &[Decimal]) -> Vec<Decimal> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+
+    let total_weight: Decimal = weights.iter().copied().sum();
+    let mut allocations: Vec<Decimal> = Vec::with_capacity(weights.len());
+    let mut allocated = Decimal::ZERO;
+
+    for (idx, weight) in weights.iter().enumerate() {
+        let amount = if idx == weights.len() - 1 {
+            total - allocated
+        } else if total_weight.is_zero() {
+            Decimal::ZERO
+        } else {
+            (total * (*weight / total_weight)).round_dp(2)
+        };
+        allocated += amount;
+        allocations.push(amount);
+    }
+
+    allocations
+}
+
+/// 按品类分组订单明细（按品类 ID 升序），未设置品类的商品归入"未分类"
+async fn group_items_by_category<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    items: &[order_item::Model],
+) -> Result<Vec<CategoryGroup>, Box<dyn std::error::Error>> {
+    // 获取所有品类
+    let all_categories = category::Entity::find().all(conn).await?;
+    let mut category_map: HashMap<i64, category::Model> = HashMap::new();
+    let mut uncategorized_id: i64 = 0;
+    for cat in all_categories {
+        if cat.name == DEFAULT_CATEGORY_NAME {
+            uncategorized_id = cat.id;
+        }
+        category_map.insert(cat.id, cat);
+    }
+
+    // 查询商品获取 category_id
+    let product_ids: Vec<i64> = items.iter().map(|item| item.product_id).collect();
+    let products = product::Entity::find()
+        .filter(product::Column::Id.is_in(product_ids))
+        .all(conn)
+        .await?;
+    let product_category_map: HashMap<i64, Option<i64>> =
+        products.iter().map(|p| (p.id, p.category_id)).collect();
+
+    // 按 category_id 分组（小计, 明细折扣）
+    let mut grouped: HashMap<i64, (Decimal, Decimal)> = HashMap::new();
+    for item in items {
+        let cat_id = product_category_map
+            .get(&item.product_id)
+            .copied()
+            .flatten()
+            .unwrap_or(uncategorized_id);
+        let entry = grouped
+            .entry(cat_id)
+            .or_insert((Decimal::ZERO, Decimal::ZERO));
+        entry.0 += item.subtotal;
+        entry.1 += item.discount_amount;
+    }
+
+    let mut sorted_keys: Vec<i64> = grouped.keys().copied().collect();
+    sorted_keys.sort();
+
+    Ok(sorted_keys
+        .into_iter()
+        .map(|cat_id| {
+            let cat = category_map.get(&cat_id);
+            CategoryGroup {
+                category_id: cat_id,
+                category_name: cat
+                    .map(|c| c.name.clone())
+                    .unwrap_or_else(|| DEFAULT_CATEGORY_NAME.to_string()),
+                amount: grouped[&cat_id].0,
+                discount: grouped[&cat_id].1,
+                book_id: cat
+                    .map(|c| match order.order_type {
+                        OrderType::Sales => c.sell_book_id,
+                        OrderType::Purchase => c.purchase_book_id,
+                    })
+                    .unwrap_or(DEFAULT_BOOK_ID),
+            }
+        })
+        .collect())
+}
+
+/// 写入订单记账记录并更新所属账本的记录数（盘点损耗记账共用）
+pub(crate) async fn insert_order_record<C: ConnectionTrait>(
+    conn: &C,
+    record: AccountingActiveModel,
+) -> Result<accounting_record::Model, Box<dyn std::error::Error>> {
+    let record = record.insert(conn).await?;
+
+    // 更新账本 record_count +1
+    if let Some(book_id) = record.book_id {
+        let book = accounting_book::Entity::find_by_id(book_id)
+            .one(conn)
+            .await?;
+        if let Some(b) = book {
+            let mut active_book: accounting_book::ActiveModel = b.into();
+            active_book.record_count = Set(active_book.record_count.as_ref() + 1);
+            active_book.update(conn).await?;
+        }
+    }
+
+    Ok(record)
+}
+
+/// 订单已收付款合计
+async fn paid_amount<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let payments = order_payment::Entity::find()
+        .filter(order_payment::Column::OrderId.eq(order_id))
+        .all(conn)
+        .await?;
+    Ok(payments.iter().map(|p| p.amount).sum())
+}
+
+/// 写入收付款记录
+async fn insert_payment_row<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+    amount: Decimal,
+    channel: &AccountingChannel,
+    paid_at: NaiveDateTime,
+    remark: Option<String>,
+) -> Result<order_payment::Model, Box<dyn std::error::Error>> {
+    let payment = order_payment::ActiveModel {
+        order_id: Set(order_id),
+        amount: Set(amount),
+        channel: Set(channel.clone()),
+        paid_at: Set(paid_at),
+        remark: Set(remark),
+        ..Default::default()
+    };
+    Ok(payment.insert(conn).await?)
+}
+
+/// 登记一笔收付款：写入收付款记录，并按品类比例在各品类账本中生成已入账记录
+async fn apply_payment<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    groups: &[CategoryGroup],
+    amount: Decimal,
+    channel: &AccountingChannel,
+    paid_at: NaiveDateTime,
+    remark: Option<String>,
+) -> Result<order_payment::Model, Box<dyn std::error::Error>> {
+    let (accounting_type, title_prefix) = order_accounting_type(order);
+    let title = match order.order_type {
+        OrderType::Sales => format!("{}-收款", title_prefix),
+        OrderType::Purchase => format!("{}-付款", title_prefix),
+    };
+    let now = Local::now().naive_local();
+
+    let weights: Vec<Decimal> = groups.iter().map(|g| g.amount).collect();
+    let allocations = allocate_proportionally(amount, &weights);
+
+    for (group, allocated) in groups.iter().zip(allocations) {
+        if allocated == Decimal::ZERO {
+            continue;
+        }
+        let record_id = accounting_record::Model::generate_id(conn).await?;
+        insert_order_record(
+            conn,
+            AccountingActiveModel {
+                id: Set(record_id),
+                amount: Set(allocated),
+                record_time: Set(paid_at),
+                accounting_type: Set(accounting_type.clone()),
+                title: Set(title.clone()),
+                channel: Set(channel.clone()),
+                remark: Set(remark.clone()),
+                write_off_id: Set(None),
+                create_at: Set(now),
+                state: Set(AccountingRecordState::Posted),
+                book_id: Set(Some(group.book_id)),
+                order_id: Set(Some(order.id)),
+            },
+        )
+        .await?;
+    }
+
+    insert_payment_row(conn, order.id, amount, channel, paid_at, remark).await
+}
+
+/// 分次收付款的订单结清时登记折扣冲账（与一次性结清一致）
+///
+/// 各品类按明细折扣和分摊的整单折扣补记折扣前金额的主记录，再以冲账记录冲减，
+/// 按各渠道已收付金额比例拆分，入账合计不变
+async fn book_discount_write_offs<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    groups: &[CategoryGroup],
+    actual_amount: Decimal,
+    now: NaiveDateTime,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (accounting_type, title_prefix) = order_accounting_type(order);
+
+    let discount_total = order.total_amount - actual_amount;
+    let order_write_offs = if discount_total.is_zero() {
+        vec![Decimal::ZERO; groups.len()]
+    } else {
+        let weights: Vec<Decimal> = groups.iter().map(|g| g.amount).collect();
+        allocate_proportionally(-discount_total, &weights)
+    };
+
+    let breakdown = payments_by_channel(conn, order.id).await?;
+    let channel_weights: Vec<Decimal> = breakdown.iter().map(|b| b.amount).collect();
+    for (group, order_write_off) in groups.iter().zip(order_write_offs) {
+        if group.discount.is_zero() && order_write_off.is_zero() {
+            continue;
+        }
+        let item_discounts = allocate_proportionally(group.discount, &channel_weights);
+        let write_offs = allocate_proportionally(order_write_off, &channel_weights);
+        for (k, payment) in breakdown.iter().enumerate() {
+            let (item_discount, write_off) = (item_discounts[k], write_offs[k]);
+            if item_discount.is_zero() && write_off.is_zero() {
+                continue;
+            }
+
+            let record_id = accounting_record::Model::generate_id(conn).await?;
+            let main = insert_order_record(
+                conn,
+                AccountingActiveModel {
+                    id: Set(record_id),
+                    amount: Set(item_discount - write_off),
+                    record_time: Set(now),
+                    accounting_type: Set(accounting_type.clone()),
+                    title: Set(title_prefix.clone()),
+                    channel: Set(payment.channel.clone()),
+                    remark: Set(None),
+                    write_off_id: Set(None),
+                    create_at: Set(now),
+                    state: Set(AccountingRecordState::Posted),
+                    book_id: Set(Some(group.book_id)),
+                    order_id: Set(Some(order.id)),
+                },
+            )
+            .await?;
+
+            let write_off_entries = [
+                (-item_discount, format!("商品折扣冲账-{}", title_prefix)),
+                (write_off, format!("折扣冲账-{}", title_prefix)),
+            ];
+            for (amount, title) in write_off_entries {
+                if amount.is_zero() {
+                    continue;
+                }
+                let record_id = accounting_record::Model::generate_id(conn).await?;
+                insert_order_record(
+                    conn,
+                    AccountingActiveModel {
+                        id: Set(record_id),
+                        amount: Set(amount),
+                        record_time: Set(now),
+                        accounting_type: Set(AccountingType::WriteOff),
+                        title: Set(title),
+                        channel: Set(payment.channel.clone()),
+                        remark: Set(None),
+                        write_off_id: Set(Some(main.id)),
+                        create_at: Set(now),
+                        state: Set(AccountingRecordState::Posted),
+                        book_id: Set(Some(group.book_id)),
+                        order_id: Set(Some(order.id)),
+                    },
+                )
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 结账收付方式
+enum SettlePayment {
+    /// 单一渠道收付全部未结金额
+    Single(AccountingChannel),
+    /// 组合支付：各渠道金额合计须等于未结金额
+    Split(Vec<(AccountingChannel, Decimal)>),
+}
+
+impl SettlePayment {
+    /// 解析结账 DTO 中的渠道或分渠道收付款（同一渠道的多项合并）
+    fn from_input(
+        channel: Option<&str>,
+        payments: Option<&[SettlePaymentDto]>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        match (channel, payments) {
+            (Some(_), Some(p)) if !p.is_empty() => Err("结账渠道与分渠道收付款只能填写一种".into()),
+            (_, Some(p)) if !p.is_empty() => {
+                let mut split: Vec<(AccountingChannel, Decimal)> = Vec::new();
+                for payment in p {
+                    let channel = payment
+                        .channel
+                        .parse::<AccountingChannel>()
+                        .map_err(|_| format!("无效的收付款渠道: {}", payment.channel))?;
+                    if payment.amount <= Decimal::ZERO {
+                        return Err("分渠道收付款金额必须大于 0".into());
+                    }
+                    match split.iter_mut().find(|(c, _)| *c == channel) {
+                        Some((_, amount)) => *amount += payment.amount,
+                        None => split.push((channel, payment.amount)),
+                    }
+                }
+                Ok(SettlePayment::Split(split))
+            }
+            (Some(c), _) => c
+                .parse::<AccountingChannel>()
+                .map(SettlePayment::Single)
+                .map_err(|_| "结账时必须选择有效的支付渠道".into()),
+            (None, _) => Err("结账时必须选择有效的支付渠道".into()),
+        }
+    }
+
+    /// 按未结金额确定各渠道收付金额
+    fn resolve(
+        self,
+        due: Decimal,
+    ) -> Result<Vec<(AccountingChannel, Decimal)>, Box<dyn std::error::Error>> {
+        match self {
+            SettlePayment::Single(channel) => Ok(vec![(channel, due)]),
+            SettlePayment::Split(split) => {
+                let total: Decimal = split.iter().map(|(_, amount)| *amount).sum();
+                if total != due {
+                    return Err(format!(
+                        "分渠道收付款合计 {} 与未结金额 {} 不一致",
+                        total.round_dp(2),
+                        due.round_dp(2)
+                    )
+                    .into());
+                }
+                Ok(split)
+            }
+        }
+    }
+}
+
+/// 按渠道汇总订单收付款净额（按首次出现顺序，已冲平的渠道不计入）
+async fn payments_by_channel<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+) -> Result<Vec<ChannelPayment>, Box<dyn std::error::Error>> {
+    let payments = order_payment::Entity::find()
+        .filter(order_payment::Column::OrderId.eq(order_id))
+        .order_by_asc(order_payment::Column::Id)
+        .all(conn)
+        .await?;
+
+    let mut breakdown: Vec<ChannelPayment> = Vec::new();
+    for p in payments {
+        match breakdown.iter_mut().find(|b| b.channel == p.channel) {
+            Some(b) => b.amount += p.amount,
+            None => breakdown.push(ChannelPayment {
+                channel: p.channel,
+                amount: p.amount,
+            }),
+        }
+    }
+    breakdown.retain(|b| !b.amount.is_zero());
+    Ok(breakdown)
+}
+
+/// 根据收付款记录确定订单渠道（单一渠道为该渠道，分多个渠道为未知，各渠道金额以收付款记录为准）
+async fn summarize_order_channel<C: ConnectionTrait>(
+    conn: &C,
+    order_id: i64,
+) -> Result<Option<AccountingChannel>, Box<dyn std::error::Error>> {
+    let breakdown = payments_by_channel(conn, order_id).await?;
+    Ok(match breakdown.as_slice() {
+        [] => None,
+        [single] => Some(single.channel.clone()),
+        _ => Some(AccountingChannel::Unknown),
+    })
+}
+
+/// 将各渠道实收金额按品类净额比例分摊（返回 [渠道][品类]，最后一个渠道补差保证各品类合计不变）
+fn allocate_channels(
+    payments: &[(AccountingChannel, Decimal)],
+    nets: &[Decimal],
+) -> Vec<Vec<Decimal>> {
+    let mut shares: Vec<Vec<Decimal>> = Vec::with_capacity(payments.len());
+    for (idx, (_, amount)) in payments.iter().enumerate() {
+        let row = if idx == payments.len() - 1 {
+            nets.iter()
+                .enumerate()
+                .map(|(c, net)| *net - shares.iter().map(|s| s[c]).sum::<Decimal>())
+                .collect()
+        } else {
+            allocate_proportionally(*amount, nets)
+        };
+        shares.push(row);
+    }
+    shares
+}
+
+/// 结账已加载的订单（按品类、渠道分组记账 + 折扣冲账；部分收付款的订单补齐剩余未结金额），调用方负责校验状态
+async fn settle_loaded_order<C: ConnectionTrait>(
+    conn: &C,
+    order: OrderModel,
+    items: &[order_item::Model],
+    payment: SettlePayment,
+    actual_amount: Decimal,
+) -> Result<OrderModel, Box<dyn std::error::Error>> {
+    // 确定记账类型和标题前缀
+    let (accounting_type, title_prefix) = order_accounting_type(&order);
+
+    let now = Local::now().naive_local();
+
+    // 订单明细按品类分组
+    let groups = group_items_by_category(conn, &order, items).await?;
+
+    let payments = if order.status == OrderStatus::PartiallyPaid {
+        // 部分收付款：剩余未结金额按渠道作为最后的收付款入账
+        let paid = paid_amount(conn, order.id).await?;
+        if actual_amount < paid {
+            return Err("实收金额不能小于已收付款金额".into());
+        }
+        let payments = payment.resolve(actual_amount - paid)?;
+        for (channel, amount) in &payments {
+            if *amount > Decimal::ZERO {
+                apply_payment(conn, &order, &groups, *amount, channel, now, None).await?;
+            }
+        }
+        book_discount_write_offs(conn, &order, &groups, actual_amount, now).await?;
+        payments
+    } else {
+        let payments = payment.resolve(actual_amount)?;
+
+        // 整单折扣按品类金额比例分摊（负数）
+        let discount_total = order.total_amount - actual_amount;
+        let order_write_offs = if discount_total.is_zero() {
+            vec![Decimal::ZERO; groups.len()]
+        } else {
+            let weights: Vec<Decimal> = groups.iter().map(|g| g.amount).collect();
+            allocate_proportionally(-discount_total, &weights)
+        };
+
+        // 各品类实收净额按渠道拆分，明细折扣和整单折扣随之按比例拆分
+        let nets: Vec<Decimal> = groups
+            .iter()
+            .zip(&order_write_offs)
+            .map(|(g, w)| g.amount + w)
+            .collect();
+        let shares = allocate_channels(&payments, &nets);
+
+        // (book_id, 渠道, 主记录金额, 明细折扣, 整单折扣冲账)
+        let mut entries: Vec<(i64, AccountingChannel, Decimal, Decimal, Decimal)> = Vec::new();
+        for (c, group) in groups.iter().enumerate() {
+            let weights: Vec<Decimal> = shares.iter().map(|s| s[c]).collect();
+            let item_discounts = allocate_proportionally(group.discount, &weights);
+            let write_offs = allocate_proportionally(order_write_offs[c], &weights);
+            for (k, (channel, _)) in payments.iter().enumerate() {
+                let gross = weights[k] + item_discounts[k] - write_offs[k];
+                // 组合支付时跳过金额为 0 的品类渠道组合
+                if payments.len() > 1
+                    && gross.is_zero()
+                    && item_discounts[k].is_zero()
+                    && write_offs[k].is_zero()
+                {
+                    continue;
+                }
+                entries.push((
+                    group.book_id,
+                    channel.clone(),
+                    gross,
+                    item_discounts[k],
+                    write_offs[k],
+                ));
+            }
+        }
+
+        // 为每个品类、渠道创建主记账记录（明细折扣前金额）
+        let mut main_record_ids: Vec<i64> = Vec::with_capacity(entries.len());
+        for (book_id, channel, gross, _, _) in &entries {
+            let record_id = accounting_record::Model::generate_id(conn).await?;
+            let record = insert_order_record(
+                conn,
+                AccountingActiveModel {
+                    id: Set(record_id),
+                    amount: Set(*gross),
+                    record_time: Set(now),
+                    accounting_type: Set(accounting_type.clone()),
+                    title: Set(title_prefix.clone()),
+                    channel: Set(channel.clone()),
+                    remark: Set(None),
+                    write_off_id: Set(None),
+                    create_at: Set(now),
+                    state: Set(AccountingRecordState::Posted),
+                    book_id: Set(Some(*book_id)),
+                    order_id: Set(Some(order.id)),
+                },
+            )
+            .await?;
+            main_record_ids.push(record.id);
+        }
+
+        // 明细折扣冲减对应品类的主记录
+        for ((book_id, channel, _, item_discount, _), main_record_id) in
+            entries.iter().zip(&main_record_ids)
+        {
+            if item_discount.is_zero() {
+                continue;
+            }
+
+            let record_id = accounting_record::Model::generate_id(conn).await?;
+            insert_order_record(
+                conn,
+                AccountingActiveModel {
+                    id: Set(record_id),
+                    amount: Set(-*item_discount),
+                    record_time: Set(now),
+                    accounting_type: Set(AccountingType::WriteOff),
+                    title: Set(format!("商品折扣冲账-{}", title_prefix)),
+                    channel: Set(channel.clone()),
+                    remark: Set(None),
+                    write_off_id: Set(Some(*main_record_id)),
+                    create_at: Set(now),
+                    state: Set(AccountingRecordState::Posted),
+                    book_id: Set(Some(*book_id)),
+                    order_id: Set(Some(order.id)),
+                },
+            )
+            .await?;
+        }
+
+        // 有整单折扣时创建冲账记录
+        for ((book_id, channel, _, _, write_off_amount), main_record_id) in
+            entries.iter().zip(&main_record_ids)
+        {
+            if write_off_amount.is_zero() {
+                continue;
+            }
+
+            let record_id = accounting_record::Model::generate_id(conn).await?;
+            insert_order_record(
+                conn,
+                AccountingActiveModel {
+                    id: Set(record_id),
+                    amount: Set(*write_off_amount),
+                    record_time: Set(now),
+                    accounting_type: Set(AccountingType::WriteOff),
+                    title: Set(format!("折扣冲账-{}", title_prefix)),
+                    channel: Set(channel.clone()),
+                    remark: Set(None),
+                    write_off_id: Set(Some(*main_record_id)),
+                    create_at: Set(now),
+                    state: Set(AccountingRecordState::Posted),
+                    book_id: Set(Some(*book_id)),
+                    order_id: Set(Some(order.id)),
+                },
+            )
+            .await?;
+        }
+
+        // 一次性结清同样按渠道记录收付款
+        for (channel, amount) in &payments {
+            if *amount > Decimal::ZERO {
+                insert_payment_row(conn, order.id, *amount, channel, now, None).await?;
+            }
+        }
+        payments
+    };
+
+    // 订单渠道按实际收付款汇总，无收付款时沿用所选渠道
+    let channel = match summarize_order_channel(conn, order.id).await? {
+        Some(channel) => channel,
+        None => payments[0].0.clone(),
+    };
+
+    // 更新订单状态
+    let mut order_active: OrderActiveModel = order.into();
+    order_active.status = Set(OrderStatus::Settled);
+    order_active.channel = Set(channel);
+    order_active.actual_amount = Set(actual_amount);
+    order_active.settled_at = Set(Some(now));
+    let updated_order = order_active.update(conn).await?;
+
+    // 结账后登记库存变动（采购入库、销售出库）
+    apply_order_stock(conn, &updated_order, items).await?;
+
+    Ok(updated_order)
+}
+
+/// 按已加载的订单生成结算预览（开启按实收数量结算时按收货数量折算明细和金额）
+async fn build_settle_preview<C: ConnectionTrait>(
+    conn: &C,
+    order: OrderModel,
+    actual_amount: Option<Decimal>,
+) -> Result<SettlePreview, Box<dyn std::error::Error>> {
+    // 查询所有订单明细（开启按实收数量结算时按收货数量折算明细和金额）
+    let adjustment = received_adjustment(conn, &order).await?;
+    let (order, items) = match adjustment {
+        Some(adjustment) => (
+            OrderModel {
+                total_amount: adjustment.total_amount,
+                actual_amount: adjustment.actual_amount,
+                ..order
+            },
+            adjustment.items,
+        ),
+        None => {
+            let items = order_item::Entity::find()
+                .filter(order_item::Column::OrderId.eq(order.id))
+                .all(conn)
+                .await?;
+            (order, items)
+        }
+    };
+
+    // 确定实收金额
+    let actual = actual_amount.unwrap_or(order.actual_amount);
+
+    // 按品类分组
+    let groups = group_items_by_category(conn, &order, &items).await?;
+
+    // 获取账本名称映射
+    let all_books = accounting_book::Entity::find().all(conn).await?;
+    let book_name_map: HashMap<i64, String> =
+        all_books.iter().map(|b| (b.id, b.title.clone())).collect();
+
+    // 构建品类分组预览
+    let category_groups: Vec<SettlePreviewItem> = groups
+        .iter()
+        .map(|group| SettlePreviewItem {
+            category_id: group.category_id,
+            category_name: group.category_name.clone(),
+            amount: group.amount + group.discount,
+            item_discount_amount: group.discount,
+            book_id: group.book_id,
+            book_name: book_name_map
+                .get(&group.book_id)
+                .cloned()
+                .unwrap_or_else(|| "未知账本".to_string()),
+        })
+        .collect();
+
+    // 明细折扣冲账预览
+    let item_discount_total: Decimal = groups.iter().map(|g| g.discount).sum();
+    let (item_discount_amount, item_discount_preview) = if item_discount_total.is_zero() {
+        (None, None)
+    } else {
+        let preview_items: Vec<WriteOffPreviewItem> = groups
+            .iter()
+            .filter(|g| g.discount != Decimal::ZERO)
+            .map(|g| WriteOffPreviewItem {
+                category_name: g.category_name.clone(),
+                write_off_amount: -g.discount,
+                category_id: g.category_id,
+            })
+            .collect();
+        (Some(item_discount_total), Some(preview_items))
+    };
+
+    // 整单折扣冲账预览
+    let has_discount = order.total_amount != actual;
+    let discount_total = order.total_amount - actual;
+    let discount_amount = if has_discount {
+        Some(discount_total)
+    } else {
+        None
+    };
+
+    let write_off_preview = if has_discount && !category_groups.is_empty() {
+        let weights: Vec<Decimal> = groups.iter().map(|g| g.amount).collect();
+        let allocations = allocate_proportionally(-discount_total, &weights);
+
+        let preview_items: Vec<WriteOffPreviewItem> = category_groups
+            .iter()
+            .zip(allocations)
+            .filter(|(_, amount)| *amount != Decimal::ZERO)
+            .map(|(group, amount)| WriteOffPreviewItem {
+                category_name: group.category_name.clone(),
+                write_off_amount: amount,
+                category_id: group.category_id,
+            })
+            .collect();
+
+        Some(preview_items)
+    } else {
+        None
+    };
+
+    // 销售订单毛利测算（按实收金额）
+    let margin = compute_order_margin(conn, &order.order_type, &items, actual).await?;
+
+    Ok(SettlePreview {
+        category_groups,
+        write_off_preview,
+        discount_amount,
+        item_discount_preview,
+        item_discount_amount,
+        margin,
+    })
+}
+
+/// 合并多张订单的结算预览（主记录按品类 + 账本汇总，冲账按品类汇总）
+fn merge_settle_previews(previews: Vec<SettlePreview>) -> SettlePreview {
+    fn merge_write_offs(target: &mut Vec<WriteOffPreviewItem>, items: Vec<WriteOffPreviewItem>) {
+        for item in items {
+            match target
+                .iter_mut()
+                .find(|t| t.category_id == item.category_id)
+            {
+                Some(t) => t.write_off_amount += item.write_off_amount,
+                None => target.push(item),
+            }
+        }
+    }
+
+    let mut category_groups: Vec<SettlePreviewItem> = Vec::new();
+    let mut write_offs: Vec<WriteOffPreviewItem> = Vec::new();
+    let mut item_write_offs: Vec<WriteOffPreviewItem> = Vec::new();
+    let mut discount_amount: Option<Decimal> = None;
+    let mut item_discount_amount: Option<Decimal> = None;
+    let mut margins: Vec<OrderMargin> = Vec::new();
+
+    for preview in previews {
+        for group in preview.category_groups {
+            match category_groups
+                .iter_mut()
+                .find(|g| g.category_id == group.category_id && g.book_id == group.book_id)
+            {
+                Some(g) => {
+                    g.amount += group.amount;
+                    g.item_discount_amount += group.item_discount_amount;
+                }
+                None => category_groups.push(group),
+            }
+        }
+        merge_write_offs(
+            &mut write_offs,
+            preview.write_off_preview.unwrap_or_default(),
+        );
+        merge_write_offs(
+            &mut item_write_offs,
+            preview.item_discount_preview.unwrap_or_default(),
+        );
+        if let Some(amount) = preview.discount_amount {
+            *discount_amount.get_or_insert(Decimal::ZERO) += amount;
+        }
+        if let Some(amount) = preview.item_discount_amount {
+            *item_discount_amount.get_or_insert(Decimal::ZERO) += amount;
+        }
+        margins.extend(preview.margin);
+    }
+
+    SettlePreview {
+        category_groups,
+        write_off_preview: (!write_offs.is_empty()).then_some(write_offs),
+        discount_amount,
+        item_discount_preview: (!item_write_offs.is_empty()).then_some(item_write_offs),
+        item_discount_amount,
+        margin: merge_margins(margins),
+    }
+}
+
+/// 创建订单和明细（生成订单编号，计算总额），调用方负责事务
+pub(crate) async fn insert_order<C: ConnectionTrait>(
+    conn: &C,
+    input: CreateOrderDto,
+) -> Result<OrderModel, Box<dyn std::error::Error>> {
+    // 验证明细不为空
+    if input.items.is_empty() {
+        return Err("订单明细不能为空".into());
+    }
+
+    // 解析枚举
+    let order_type = input
+        .order_type
+        .parse::<OrderType>()
+        .map_err(|_| "无效的订单类型".to_string())?;
+
+    // 确定 sub_type
+    let sub_type = match input.sub_type {
+        Some(st) => {
+            let parsed = st
+                .parse::<OrderSubType>()
+                .map_err(|_| "无效的订单业务类型".to_string())?;
+            validate_sub_type_match(&parsed, &order_type)?;
+            parsed
+        }
+        None => resolve_default_sub_type(&order_type, input.customer_id),
+    };
+
+    // 计算明细折扣与总额（折扣后小计之和）
+    let discounts: Vec<Decimal> = input
+        .items
+        .iter()
+        .map(item_discount)
+        .collect::<Result<_, _>>()?;
+    let total_amount: Decimal = input
+        .items
+        .iter()
+        .zip(&discounts)
+        .map(|(item, discount)| item.quantity * item.unit_price - discount)
+        .sum();
+
+    let actual_amount = input.actual_amount.unwrap_or(total_amount);
+
+    // 解析预计收付款日期
+    let due_date = match &input.due_date {
+        Some(s) => Some(parse_datetime(s, false).map_err(|_| "无效的预计收付款日期".to_string())?),
+        None => None,
+    };
+
+    // 生成订单 ID 和编号（按销售 / 采购订单的编号规则）
+    let id = OrderModel::generate_id(conn).await?;
+    let now = Local::now();
+    let order_no =
+        next_document_no(conn, order_document_type(&order_type), now.naive_local()).await?;
+
+    // 采购订单需跟踪收货，销售订单不涉及
+    let receiving_status = match order_type {
+        OrderType::Purchase => Some(ReceivingStatus::NotReceived),
+        OrderType::Sales => None,
+    };
+
+    // 创建订单
+    let order_active = OrderActiveModel {
+        id: Set(id),
+        order_no: Set(order_no.clone()),
+        order_type: Set(order_type),
+        customer_id: Set(input.customer_id),
+        customer_name: Set(input.customer_name),
+        total_amount: Set(total_amount),
+        actual_amount: Set(actual_amount),
+        sub_type: Set(sub_type),
+        status: Set(OrderStatus::Pending),
+        channel: Set(AccountingChannel::Unknown),
+        remark: Set(input.remark),
+        create_at: Set(now.naive_local()),
+        settled_at: Set(None),
+        due_date: Set(due_date),
+        reopened_at: Set(None),
+        quotation_id: Set(None),
+        receiving_status: Set(receiving_status),
+    };
+
+    let order = order_active.insert(conn).await?;
+
+    // 创建订单明细
+    for (item, discount) in input.items.iter().zip(discounts) {
+        let subtotal = item.quantity * item.unit_price - discount;
+        let order_item_active = OrderItemActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            order_id: Set(order.id),
+            product_id: Set(item.product_id),
+            product_name: Set(item.product_name.clone()),
+            quantity: Set(item.quantity),
+            unit: Set(item.unit.clone()),
+            unit_price: Set(item.unit_price),
+            discount_amount: Set(discount),
+            discount_rate: Set(item.discount_rate),
+            subtotal: Set(subtotal),
+            remark: Set(item.remark.clone()),
+        };
+        order_item_active.insert(conn).await?;
+    }
+
+    Ok(order)
+}
+
 /// 订单服务
 #[derive(Debug)]
 pub struct OrderService {
@@ -84,107 +1100,185 @@ impl OrderService {
         Self { db }
     }
 
-    /// 创建订单（事务中创建订单和明细，生成订单编号，计算总额）
-    pub async fn create_order(
+    /// 创建订单（事务中创建订单和明细，生成订单编号，计算总额）
+    pub async fn create_order(
+        &self,
+        input: CreateOrderDto,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+        let order = insert_order(&txn, input).await?;
+        txn.commit().await?;
+
+        Ok(order)
+    }
+
+    /// 复制订单（按原订单的客户和明细创建新的待结账订单，可选按商品参考价刷新单价）
+    pub async fn clone_order(
+        &self,
+        input: CloneOrderDto,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let order = order::Entity::find_by_id(input.order_id)
+            .one(&self.db)
+            .await?
+            .ok_or("订单不存在")?;
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(order.id))
+            .order_by_asc(order_item::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        let mut new_items: Vec<CreateOrderItemDto> = items
+            .into_iter()
+            .map(|item| CreateOrderItemDto {
+                product_id: item.product_id,
+                product_name: item.product_name,
+                quantity: item.quantity,
+                unit: item.unit,
+                unit_price: item.unit_price,
+                // 折扣率优先保留，按金额折扣的沿用原折扣金额
+                discount_amount: match item.discount_rate {
+                    Some(_) => None,
+                    None if item.discount_amount.is_zero() => None,
+                    None => Some(item.discount_amount),
+                },
+                discount_rate: item.discount_rate,
+                remark: item.remark,
+            })
+            .collect();
+
+        if input.refresh_prices.unwrap_or(false) {
+            refresh_item_prices(&self.db, &order.order_type, &mut new_items).await?;
+        }
+
+        self.create_order(CreateOrderDto {
+            order_type: order.order_type.to_string(),
+            customer_id: order.customer_id,
+            customer_name: order.customer_name,
+            items: new_items,
+            remark: input.remark.or(order.remark),
+            actual_amount: None,
+            sub_type: Some(order.sub_type.to_string()),
+            due_date: input.due_date,
+        })
+        .await
+    }
+
+    /// 结账订单（按品类分组记账 + 折扣冲账；部分收付款的订单补齐剩余未结金额）
+    pub async fn settle_order(
+        &self,
+        input: SettleOrderDto,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        // 查找订单
+        let order = order::Entity::find_by_id(input.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("订单不存在")?;
+
+        // 验证状态
+        if order.status == OrderStatus::Settled {
+            return Err("订单已结账".into());
+        }
+        if order.status == OrderStatus::Cancelled {
+            return Err("订单已取消".into());
+        }
+
+        // 解析并校验支付渠道（单一渠道或分渠道收付款）
+        let payment =
+            SettlePayment::from_input(input.channel.as_deref(), input.payments.as_deref())?;
+
+        // 开启按实收数量结算时，采购订单先按收货数量调整明细和金额
+        let (order, items) = apply_received_quantities(&txn, order).await?;
+
+        // 确定实收金额
+        let actual_amount = input.actual_amount.unwrap_or(order.actual_amount);
+
+        let updated_order =
+            settle_loaded_order(&txn, order, &items, payment, actual_amount).await?;
+
+        txn.commit().await?;
+
+        Ok(updated_order)
+    }
+
+    /// 批量结账（所有订单须为同类型的待结账订单，在同一事务中逐个结账，任一失败则全部回滚）
+    pub async fn batch_settle_orders(
         &self,
-        input: CreateOrderDto,
-    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
-        // 验证明细不为空
-        if input.items.is_empty() {
-            return Err("订单明细不能为空".into());
-        }
-
-        // 解析枚举
-        let order_type = input
-            .order_type
-            .parse::<OrderType>()
-            .map_err(|_| "无效的订单类型".to_string())?;
-
-        // 确定 sub_type
-        let sub_type = match input.sub_type {
-            Some(st) => {
-                let parsed = st
-                    .parse::<OrderSubType>()
-                    .map_err(|_| "无效的订单业务类型".to_string())?;
-                validate_sub_type_match(&parsed, &order_type)?;
-                parsed
+        input: BatchSettleOrdersDto,
+    ) -> Result<BatchSettleResult, Box<dyn std::error::Error>> {
+        let mut order_ids: Vec<i64> = Vec::new();
+        for id in input.order_ids {
+            if !order_ids.contains(&id) {
+                order_ids.push(id);
             }
-            None => resolve_default_sub_type(&order_type, input.customer_id),
-        };
-
-        // 计算总额
-        let total_amount: Decimal = input
-            .items
-            .iter()
-            .map(|item| item.quantity * item.unit_price)
-            .sum();
+        }
+        if order_ids.is_empty() {
+            return Err("请选择需要结账的订单".into());
+        }
 
-        let actual_amount = input.actual_amount.unwrap_or(total_amount);
+        let channel = input
+            .channel
+            .parse::<AccountingChannel>()
+            .map_err(|_| "结账时必须选择有效的支付渠道".to_string())?;
 
         let txn = self.db.begin().await?;
 
-        // 生成订单 ID 和编号
-        let id = OrderModel::generate_id(&txn).await?;
-        let now = Local::now();
-        // 从 ID 中提取序列号部分（后5位），拼接为 #N 格式
-        let seq_part = id % 100000;
-        let order_no = format!("#{}", seq_part);
-
-        // 创建订单
-        let order_active = OrderActiveModel {
-            id: Set(id),
-            order_no: Set(order_no.clone()),
-            order_type: Set(order_type),
-            customer_id: Set(input.customer_id),
-            customer_name: Set(input.customer_name),
-            total_amount: Set(total_amount),
-            actual_amount: Set(actual_amount),
-            sub_type: Set(sub_type),
-            status: Set(OrderStatus::Pending),
-            channel: Set(AccountingChannel::Unknown),
-            remark: Set(input.remark),
-            create_at: Set(now.naive_local()),
-            settled_at: Set(None),
-        };
-
-        let order = order_active.insert(&txn).await?;
+        let mut pending = Vec::with_capacity(order_ids.len());
+        for id in &order_ids {
+            let order = order::Entity::find_by_id(*id)
+                .one(&txn)
+                .await?
+                .ok_or_else(|| format!("订单不存在: {}", id))?;
+            if order.status != OrderStatus::Pending {
+                return Err(format!("订单 {} 不是待结账状态，不能批量结账", order.order_no).into());
+            }
+            pending.push(order);
+        }
+        if pending
+            .iter()
+            .any(|o| o.order_type != pending[0].order_type)
+        {
+            return Err("批量结账的订单类型必须一致".into());
+        }
 
-        // 创建订单明细
-        for item in &input.items {
-            let subtotal = item.quantity * item.unit_price;
-            let order_item_active = OrderItemActiveModel {
-                id: sea_orm::ActiveValue::NotSet,
-                order_id: Set(order.id),
-                product_id: Set(item.product_id),
-                product_name: Set(item.product_name.clone()),
-                quantity: Set(item.quantity),
-                unit: Set(item.unit.clone()),
-                unit_price: Set(item.unit_price),
-                subtotal: Set(subtotal),
-                remark: Set(item.remark.clone()),
-            };
-            order_item_active.insert(&txn).await?;
+        // 结账前在同一事务中按已校验的订单生成结算预览并合并
+        let mut previews = Vec::with_capacity(pending.len());
+        for order in &pending {
+            previews.push(build_settle_preview(&txn, order.clone(), None).await?);
+        }
+        let preview = merge_settle_previews(previews);
+
+        let mut orders = Vec::with_capacity(pending.len());
+        for order in pending {
+            let (order, items) = apply_received_quantities(&txn, order).await?;
+            let actual_amount = order.actual_amount;
+            let payment = SettlePayment::Single(channel.clone());
+            orders.push(settle_loaded_order(&txn, order, &items, payment, actual_amount).await?);
         }
 
         txn.commit().await?;
 
-        Ok(order)
+        Ok(BatchSettleResult {
+            total_amount: orders.iter().map(|o| o.total_amount).sum(),
+            actual_amount: orders.iter().map(|o| o.actual_amount).sum(),
+            orders,
+            preview,
+        })
     }
 
-    /// 结账订单（按品类分组记账 + 折扣冲账）
-    pub async fn settle_order(
+    /// 登记一笔收付款（按品类比例入账，未结清时订单状态变为部分收付款，结清后变为已结账）
+    pub async fn add_payment(
         &self,
-        input: SettleOrderDto,
+        input: AddOrderPaymentDto,
     ) -> Result<OrderModel, Box<dyn std::error::Error>> {
         let txn = self.db.begin().await?;
 
-        // 查找订单
         let order = order::Entity::find_by_id(input.order_id)
             .one(&txn)
             .await?
             .ok_or("订单不存在")?;
 
-        // 验证状态
         if order.status == OrderStatus::Settled {
             return Err("订单已结账".into());
         }
@@ -192,192 +1286,485 @@ impl OrderService {
             return Err("订单已取消".into());
         }
 
-        // 解析并校验支付渠道
+        if input.amount <= Decimal::ZERO {
+            return Err("收付款金额必须大于 0".into());
+        }
+
         let channel = input
             .channel
             .parse::<AccountingChannel>()
-            .map_err(|_| "结账时必须选择有效的支付渠道".to_string())?;
+            .map_err(|_| "必须选择有效的收付款渠道".to_string())?;
 
-        // 确定实收金额
-        let actual_amount = input.actual_amount.unwrap_or(order.actual_amount);
+        let paid_at = match &input.paid_at {
+            Some(s) => parse_datetime(s, false).map_err(|_| "无效的收付款时间".to_string())?,
+            None => Local::now().naive_local(),
+        };
+
+        // 开启按实收数量结算时，采购订单先按收货数量调整明细和金额，按调整后的金额收付款和结清
+        let (order, items) = apply_received_quantities(&txn, order).await?;
+
+        // 校验不超过未结金额
+        let paid = paid_amount(&txn, order.id).await?;
+        if order.actual_amount < paid {
+            return Err("按实收数量折算后的应付金额小于已付款金额".into());
+        }
+        let outstanding = order.actual_amount - paid;
+        if input.amount > outstanding {
+            return Err(format!("收付款金额超过未结金额 {}", outstanding.round_dp(2)).into());
+        }
+
+        let groups = group_items_by_category(&txn, &order, &items).await?;
+
+        apply_payment(
+            &txn,
+            &order,
+            &groups,
+            input.amount,
+            &channel,
+            paid_at,
+            input.remark,
+        )
+        .await?;
+
+        let fully_paid = input.amount == outstanding;
+        if fully_paid {
+            let actual_amount = order.actual_amount;
+            book_discount_write_offs(&txn, &order, &groups, actual_amount, paid_at).await?;
+        }
+        let order_channel = summarize_order_channel(&txn, order.id)
+            .await?
+            .unwrap_or(channel);
+        let mut order_active: OrderActiveModel = order.into();
+        order_active.channel = Set(order_channel);
+        if fully_paid {
+            order_active.status = Set(OrderStatus::Settled);
+            order_active.settled_at = Set(Some(paid_at));
+        } else {
+            order_active.status = Set(OrderStatus::PartiallyPaid);
+        }
+        let updated_order = order_active.update(&txn).await?;
+        if fully_paid {
+            apply_order_stock(&txn, &updated_order, &items).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(updated_order)
+    }
+
+    /// 查询订单的收付款记录（按收付款时间升序）
+    pub async fn get_order_payments(
+        &self,
+        order_id: i64,
+    ) -> Result<Vec<order_payment::Model>, Box<dyn std::error::Error>> {
+        let payments = order_payment::Entity::find()
+            .filter(order_payment::Column::OrderId.eq(order_id))
+            .order_by_asc(order_payment::Column::PaidAt)
+            .order_by_asc(order_payment::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(payments)
+    }
+
+    /// 查询订单未结余额
+    pub async fn get_order_balance(
+        &self,
+        order_id: i64,
+    ) -> Result<OrderBalance, Box<dyn std::error::Error>> {
+        let order = order::Entity::find_by_id(order_id)
+            .one(&self.db)
+            .await?
+            .ok_or("订单不存在")?;
+
+        let payments = self.get_order_payments(order_id).await?;
+        let mut paid: Decimal = payments.iter().map(|p| p.amount).sum();
+
+        // 早期一次性结账的订单没有收付款记录，视为已全额收付
+        if payments.is_empty() && order.status == OrderStatus::Settled {
+            paid = order.actual_amount;
+        }
 
-        // 确定记账类型和标题前缀
-        let (accounting_type, title_prefix) = match order.order_type {
-            OrderType::Sales => (AccountingType::Income, format!("销售订单-{}", order.order_no)),
-            OrderType::Purchase => (
-                AccountingType::Expenditure,
-                format!("采购订单-{}", order.order_no),
-            ),
+        let outstanding_amount = if order.status == OrderStatus::Cancelled {
+            Decimal::ZERO
+        } else {
+            order.actual_amount - paid
         };
 
-        let now = Local::now().naive_local();
+        let payment_channels = payments_by_channel(&self.db, order.id).await?;
 
-        // 查询所有订单明细
-        let items = order_item::Entity::find()
+        Ok(OrderBalance {
+            order_id: order.id,
+            actual_amount: order.actual_amount,
+            paid_amount: paid,
+            outstanding_amount,
+            payment_count: payments.len(),
+            payment_channels,
+            status: order.status,
+        })
+    }
+
+    /// 创建退货单（仅已结账订单）：按品类比例对结账生成的主记录做负数冲账，并登记退货明细
+    pub async fn create_return(
+        &self,
+        input: CreateOrderReturnDto,
+    ) -> Result<OrderReturnDetail, Box<dyn std::error::Error>> {
+        if input.items.is_empty() {
+            return Err("退货明细不能为空".into());
+        }
+
+        let txn = self.db.begin().await?;
+
+        let order = order::Entity::find_by_id(input.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("订单不存在")?;
+        if order.status != OrderStatus::Settled {
+            return Err("仅已结账订单可退货".into());
+        }
+
+        let order_items: HashMap<i64, order_item::Model> = order_item::Entity::find()
             .filter(order_item::Column::OrderId.eq(order.id))
             .all(&txn)
+            .await?
+            .into_iter()
+            .map(|item| (item.id, item))
+            .collect();
+
+        // 已退数量与已退款金额
+        let existing_returns = order_return::Entity::find()
+            .filter(order_return::Column::OrderId.eq(order.id))
+            .all(&txn)
             .await?;
+        let refunded: Decimal = existing_returns.iter().map(|r| r.refund_amount).sum();
+        let return_ids: Vec<i64> = existing_returns.iter().map(|r| r.id).collect();
+        let mut returned: HashMap<i64, Decimal> = HashMap::new();
+        for item in order_return_item::Entity::find()
+            .filter(order_return_item::Column::ReturnId.is_in(return_ids))
+            .all(&txn)
+            .await?
+        {
+            *returned.entry(item.order_item_id).or_insert(Decimal::ZERO) += item.quantity;
+        }
+
+        // 已登记收货的采购订单按实收数量入库，可退数量以实收数量为准
+        let received = match order.order_type {
+            OrderType::Purchase => received_quantities(&txn, order.id).await?,
+            OrderType::Sales => HashMap::new(),
+        };
 
-        // 获取所有品类的 book_id 映射
-        let all_categories = category::Entity::find().all(&txn).await?;
-        let mut category_map: std::collections::HashMap<i64, category::Model> =
-            std::collections::HashMap::new();
-        let mut uncategorized: Option<category::Model> = None;
-        for cat in all_categories {
-            if cat.name == DEFAULT_CATEGORY_NAME {
-                uncategorized = Some(cat.clone());
+        // 校验退货明细，按原明细构造退货小计
+        let mut return_items: Vec<order_item::Model> = Vec::with_capacity(input.items.len());
+        for item in &input.items {
+            let original = order_items
+                .get(&item.order_item_id)
+                .ok_or("退货明细不属于该订单")?;
+            if item.quantity <= Decimal::ZERO {
+                return Err("退货数量必须大于 0".into());
+            }
+            let returnable = if received.is_empty() {
+                original.quantity
+            } else {
+                received.get(&original.id).copied().unwrap_or(Decimal::ZERO)
+            };
+            let already = returned.entry(original.id).or_insert(Decimal::ZERO);
+            if *already + item.quantity > returnable {
+                return Err(format!(
+                    "{} 退货数量超过可退数量 {}",
+                    original.product_name,
+                    (returnable - *already).normalize()
+                )
+                .into());
             }
-            category_map.insert(cat.id, cat);
+            *already += item.quantity;
+
+            // 明细折扣按退货数量比例折算
+            let mut returned_item = original.clone();
+            returned_item.quantity = item.quantity;
+            returned_item.discount_amount =
+                (original.discount_amount * item.quantity / original.quantity).round_dp(2);
+            returned_item.subtotal =
+                item.quantity * original.unit_price - returned_item.discount_amount;
+            return_items.push(returned_item);
         }
 
-        // 查询商品获取 category_id
-        let product_ids: Vec<i64> = items.iter().map(|item| item.product_id).collect();
-        let products = product::Entity::find()
-            .filter(product::Column::Id.is_in(product_ids))
-            .all(&txn)
-            .await?;
-        let product_category_map: std::collections::HashMap<i64, Option<i64>> = products
-            .iter()
-            .map(|p| (p.id, p.category_id))
-            .collect();
+        let total_amount: Decimal = return_items.iter().map(|item| item.subtotal).sum();
 
-        // 获取"未分类"品类（用于未设置 category_id 的商品）
-        let default_category = uncategorized
-            .as_ref()
-            .map(|c| c.id)
-            .unwrap_or(0i64);
-
-        // 按 category_id 分组 order_items
-        let mut grouped: std::collections::HashMap<i64, Decimal> = std::collections::HashMap::new();
-        for item in &items {
-            let cat_id = product_category_map
-                .get(&item.product_id)
-                .copied()
-                .flatten()
-                .unwrap_or(default_category);
-            *grouped.entry(cat_id).or_insert(Decimal::ZERO) += item.subtotal;
-        }
-
-        // 确定每个分组对应的 book_id
-        let get_book_id = |cat_id: i64| -> i64 {
-            category_map
-                .get(&cat_id)
-                .map(|cat| match order.order_type {
-                    OrderType::Sales => cat.sell_book_id,
-                    OrderType::Purchase => cat.purchase_book_id,
-                })
-                .unwrap_or(DEFAULT_BOOK_ID)
+        // 默认退款金额按原订单实收比例折算（含折扣）
+        let refund_amount = match input.refund_amount {
+            Some(amount) => amount,
+            None if order.total_amount.is_zero() => total_amount,
+            None => (total_amount * order.actual_amount / order.total_amount).round_dp(2),
         };
+        if refund_amount < Decimal::ZERO {
+            return Err("退款金额不能为负数".into());
+        }
+        let refundable = order.actual_amount - refunded;
+        if refund_amount > refundable {
+            return Err(format!("退款金额超过可退金额 {}", refundable.round_dp(2)).into());
+        }
 
-        // 是否有折扣
-        let has_discount = order.total_amount != actual_amount;
-        let discount_total = order.total_amount - actual_amount;
-
-        // 保存主记录 ID 用于冲账关联
-        let mut main_record_ids: Vec<(i64, Decimal, i64)> = Vec::new(); // (cat_id, subtotal, record_id)
+        // 未指定退款渠道时按收付款记录确定，分多个渠道收付款的须指定
+        let channel = match &input.channel {
+            Some(s) => s
+                .parse::<AccountingChannel>()
+                .map_err(|_| "无效的退款渠道".to_string())?,
+            None => match payments_by_channel(&txn, order.id).await?.as_slice() {
+                [] => order.channel.clone(),
+                [single] => single.channel.clone(),
+                _ => return Err("分多个渠道收付款的订单退货时必须指定退款渠道".into()),
+            },
+        };
 
-        // 为每个品类分组创建主记账记录
-        let mut sorted_keys: Vec<i64> = grouped.keys().copied().collect();
-        sorted_keys.sort();
+        let (accounting_type, title_prefix) = order_accounting_type(&order);
+        let now = Local::now().naive_local();
 
-        for cat_id in &sorted_keys {
-            let subtotal = grouped[cat_id];
-            let book_id = get_book_id(*cat_id);
+        // 退款按退货商品的品类比例冲减结账时生成的主记录
+        if refund_amount > Decimal::ZERO {
+            let groups = group_items_by_category(&txn, &order, &return_items).await?;
+            let weights: Vec<Decimal> = groups.iter().map(|g| g.amount).collect();
+            let allocations = allocate_proportionally(-refund_amount, &weights);
 
-            let record_id = accounting_record::Model::generate_id(&txn).await?;
+            for (group, write_off_amount) in groups.iter().zip(allocations) {
+                if write_off_amount == Decimal::ZERO {
+                    continue;
+                }
 
-            let new_record = AccountingActiveModel {
-                id: Set(record_id),
-                amount: Set(subtotal),
-                record_time: Set(now),
-                accounting_type: Set(accounting_type.clone()),
-                title: Set(title_prefix.clone()),
-                channel: Set(channel.clone()),
-                remark: Set(None),
-                write_off_id: Set(None),
-                create_at: Set(now),
-                state: Set(AccountingRecordState::Posted),
-                book_id: Set(Some(book_id)),
-                order_id: Set(Some(order.id)),
-            };
+                // 组合支付的订单同一品类有多条主记录，优先冲减退款渠道对应的记录
+                let main_records = accounting_record::Entity::find()
+                    .filter(accounting_record::Column::OrderId.eq(order.id))
+                    .filter(accounting_record::Column::BookId.eq(group.book_id))
+                    .filter(accounting_record::Column::AccountingType.eq(accounting_type.clone()))
+                    .filter(accounting_record::Column::WriteOffId.is_null())
+                    .order_by_asc(accounting_record::Column::Id)
+                    .all(&txn)
+                    .await?;
+                let main_record = main_records
+                    .iter()
+                    .find(|r| r.channel == channel)
+                    .or(main_records.first())
+                    .ok_or_else(|| format!("未找到品类 {} 的原始记账记录", group.category_name))?;
+
+                let record_id = accounting_record::Model::generate_id(&txn).await?;
+                insert_order_record(
+                    &txn,
+                    AccountingActiveModel {
+                        id: Set(record_id),
+                        amount: Set(write_off_amount),
+                        record_time: Set(now),
+                        accounting_type: Set(AccountingType::WriteOff),
+                        title: Set(format!("退货冲账-{}", title_prefix)),
+                        channel: Set(channel.clone()),
+                        remark: Set(input.reason.clone()),
+                        write_off_id: Set(Some(main_record.id)),
+                        create_at: Set(now),
+                        state: Set(AccountingRecordState::Posted),
+                        book_id: Set(Some(group.book_id)),
+                        order_id: Set(Some(order.id)),
+                    },
+                )
+                .await?;
+            }
+        }
 
-            new_record.insert(&txn).await?;
-            main_record_ids.push((*cat_id, subtotal, record_id));
+        // 登记退货单和明细
+        let return_document_type = match order.order_type {
+            OrderType::Sales => DocumentType::SalesReturn,
+            OrderType::Purchase => DocumentType::PurchaseReturn,
+        };
+        let return_no = next_document_no(&txn, return_document_type, now).await?;
+        let order_return = OrderReturnActiveModel {
+            return_no: Set(return_no),
+            order_id: Set(order.id),
+            order_type: Set(order.order_type.clone()),
+            customer_id: Set(order.customer_id),
+            total_amount: Set(total_amount),
+            refund_amount: Set(refund_amount),
+            channel: Set(channel),
+            reason: Set(input.reason),
+            create_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
 
-            // 更新账本 record_count +1
-            let book = accounting_book::Entity::find_by_id(book_id).one(&txn).await?;
-            if let Some(b) = book {
-                let mut active_book: accounting_book::ActiveModel = b.into();
-                active_book.record_count = Set(active_book.record_count.as_ref() + 1);
-                active_book.update(&txn).await?;
+        let mut items: Vec<order_return_item::Model> = Vec::with_capacity(return_items.len());
+        for item in return_items {
+            let return_item = OrderReturnItemActiveModel {
+                id: sea_orm::ActiveValue::NotSet,
+                return_id: Set(order_return.id),
+                order_item_id: Set(item.id),
+                product_id: Set(item.product_id),
+                product_name: Set(item.product_name),
+                quantity: Set(item.quantity),
+                unit: Set(item.unit),
+                unit_price: Set(item.unit_price),
+                subtotal: Set(item.subtotal),
             }
+            .insert(&txn)
+            .await?;
+            items.push(return_item);
         }
+        apply_return_stock(&txn, &order_return, &items).await?;
 
-        // 有折扣时创建冲账记录
-        if has_discount && !main_record_ids.is_empty() {
-            let total_subtotal: Decimal = main_record_ids.iter().map(|(_, s, _)| *s).sum();
+        txn.commit().await?;
 
-            for (idx, (cat_id, subtotal, main_record_id)) in
-                main_record_ids.iter().enumerate()
-            {
-                let write_off_amount = if idx == main_record_ids.len() - 1 {
-                    // 最后一条用补差值
-                    let allocated: Decimal = main_record_ids
-                        .iter()
-                        .take(idx)
-                        .map(|(_, s, _)| {
-                            -discount_total * (*s / total_subtotal)
-                        })
-                        .sum::<Decimal>();
-                    -(discount_total + allocated)
-                } else {
-                    -discount_total * (*subtotal / total_subtotal)
-                };
-
-                // 四舍五入到两位小数
-                let rounded = write_off_amount.round_dp(2);
-
-                if rounded == Decimal::ZERO {
-                    continue;
-                }
+        Ok(OrderReturnDetail {
+            order_return,
+            items,
+        })
+    }
+
+    /// 查询订单的退货单（按退货时间升序）
+    pub async fn get_order_returns(
+        &self,
+        order_id: i64,
+    ) -> Result<Vec<OrderReturnDetail>, Box<dyn std::error::Error>> {
+        let returns = order_return::Entity::find()
+            .filter(order_return::Column::OrderId.eq(order_id))
+            .order_by_asc(order_return::Column::CreateAt)
+            .order_by_asc(order_return::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        let return_ids: Vec<i64> = returns.iter().map(|r| r.id).collect();
+        let mut items_by_return: HashMap<i64, Vec<order_return_item::Model>> = HashMap::new();
+        for item in order_return_item::Entity::find()
+            .filter(order_return_item::Column::ReturnId.is_in(return_ids))
+            .order_by_asc(order_return_item::Column::Id)
+            .all(&self.db)
+            .await?
+        {
+            items_by_return
+                .entry(item.return_id)
+                .or_default()
+                .push(item);
+        }
+
+        Ok(returns
+            .into_iter()
+            .map(|order_return| OrderReturnDetail {
+                items: items_by_return.remove(&order_return.id).unwrap_or_default(),
+                order_return,
+            })
+            .collect())
+    }
+
+    /// 撤销结账：对订单关联的记账记录逐条冲回、登记负数收付款，订单恢复为待结账
+    ///
+    /// 冲账记录通过 write_off_id 关联原记录、order_id 关联订单，原记录和收付款均保留，
+    /// 重新结账后可完整追溯。已有退货的订单不可撤销。
+    pub async fn reopen_order(
+        &self,
+        input: ReopenOrderDto,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
 
-                let book_id = get_book_id(*cat_id);
+        let order = order::Entity::find_by_id(input.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("订单不存在")?;
+        if order.status != OrderStatus::Settled && order.status != OrderStatus::PartiallyPaid {
+            return Err("仅已结账或部分收付款的订单可撤销结账".into());
+        }
+
+        let return_count = order_return::Entity::find()
+            .filter(order_return::Column::OrderId.eq(order.id))
+            .count(&txn)
+            .await?;
+        if return_count > 0 {
+            return Err("已有退货的订单不可撤销结账".into());
+        }
 
-                let wo_record_id = accounting_record::Model::generate_id(&txn).await?;
+        let (_, title_prefix) = order_accounting_type(&order);
+        let now = Local::now().naive_local();
+
+        // 按原记录汇总净额：原记录金额 + 指向它的冲账（折扣、历次撤销），已冲平的记录不再重复冲回
+        let records = find_records_by_order_id(&txn, order.id).await?;
+        let record_ids: Vec<i64> = records.iter().map(|r| r.id).collect();
+        let mut net_by_root: HashMap<i64, Decimal> = HashMap::new();
+        for record in &records {
+            let root_id = match record.write_off_id {
+                Some(parent_id) if record_ids.contains(&parent_id) => parent_id,
+                _ => record.id,
+            };
+            *net_by_root.entry(root_id).or_insert(Decimal::ZERO) += record.amount;
+        }
 
-                let write_off_record = AccountingActiveModel {
-                    id: Set(wo_record_id),
-                    amount: Set(rounded),
+        for root in records.iter().filter(|r| net_by_root.contains_key(&r.id)) {
+            let net = net_by_root[&root.id];
+            if net == Decimal::ZERO {
+                continue;
+            }
+            // 父记录不属于本订单的冲账记录，沿用其原关联
+            let write_off_id = match root.write_off_id {
+                Some(parent_id) => parent_id,
+                None => root.id,
+            };
+
+            let record_id = accounting_record::Model::generate_id(&txn).await?;
+            insert_order_record(
+                &txn,
+                AccountingActiveModel {
+                    id: Set(record_id),
+                    amount: Set(-net),
                     record_time: Set(now),
                     accounting_type: Set(AccountingType::WriteOff),
-                    title: Set(format!("折扣冲账-{}", title_prefix)),
-                    channel: Set(channel.clone()),
-                    remark: Set(None),
-                    write_off_id: Set(Some(*main_record_id)),
+                    title: Set(format!("撤销结账-{}", title_prefix)),
+                    channel: Set(root.channel.clone()),
+                    remark: Set(input.reason.clone()),
+                    write_off_id: Set(Some(write_off_id)),
                     create_at: Set(now),
                     state: Set(AccountingRecordState::Posted),
-                    book_id: Set(Some(book_id)),
+                    book_id: Set(root.book_id),
                     order_id: Set(Some(order.id)),
-                };
-
-                write_off_record.insert(&txn).await?;
+                },
+            )
+            .await?;
+        }
 
-                // 更新账本 record_count +1
-                let book = accounting_book::Entity::find_by_id(book_id).one(&txn).await?;
-                if let Some(b) = book {
-                    let mut active_book: accounting_book::ActiveModel = b.into();
-                    active_book.record_count = Set(active_book.record_count.as_ref() + 1);
-                    active_book.update(&txn).await?;
-                }
+        // 按渠道登记负数收付款，冲平已收付金额
+        let payments = order_payment::Entity::find()
+            .filter(order_payment::Column::OrderId.eq(order.id))
+            .order_by_asc(order_payment::Column::Id)
+            .all(&txn)
+            .await?;
+        let mut channels: Vec<AccountingChannel> = Vec::new();
+        let mut paid_by_channel: HashMap<AccountingChannel, Decimal> = HashMap::new();
+        for p in payments {
+            if !channels.contains(&p.channel) {
+                channels.push(p.channel.clone());
+            }
+            *paid_by_channel.entry(p.channel).or_insert(Decimal::ZERO) += p.amount;
+        }
+        for channel in channels {
+            let paid = paid_by_channel[&channel];
+            if paid == Decimal::ZERO {
+                continue;
             }
+            insert_payment_row(
+                &txn,
+                order.id,
+                -paid,
+                &channel,
+                now,
+                Some(
+                    input
+                        .reason
+                        .clone()
+                        .unwrap_or_else(|| "撤销结账".to_string()),
+                ),
+            )
+            .await?;
         }
 
-        // 更新订单状态
+        // 冲回结账时登记的库存变动
+        reverse_order_stock(&txn, &order).await?;
+
         let mut order_active: OrderActiveModel = order.into();
-        order_active.status = Set(OrderStatus::Settled);
-        order_active.channel = Set(channel);
-        order_active.actual_amount = Set(actual_amount);
-        order_active.settled_at = Set(Some(now));
+        order_active.status = Set(OrderStatus::Pending);
+        order_active.channel = Set(AccountingChannel::Unknown);
+        order_active.settled_at = Set(None);
+        order_active.reopened_at = Set(Some(now));
         let updated_order = order_active.update(&txn).await?;
 
         txn.commit().await?;
@@ -397,134 +1784,25 @@ impl OrderService {
             .await?
             .ok_or("订单不存在")?;
 
-        // 确定实收金额
-        let actual = actual_amount.unwrap_or(order.actual_amount);
+        build_settle_preview(&self.db, order, actual_amount).await
+    }
 
-        // 查询所有订单明细
+    /// 测算订单毛利（仅销售订单，按当前明细和实收金额计算）
+    pub async fn get_order_margin(
+        &self,
+        order_id: i64,
+    ) -> Result<Option<OrderMargin>, Box<dyn std::error::Error>> {
+        let order = order::Entity::find_by_id(order_id)
+            .one(&self.db)
+            .await?
+            .ok_or("订单不存在")?;
         let items = order_item::Entity::find()
             .filter(order_item::Column::OrderId.eq(order.id))
+            .order_by_asc(order_item::Column::Id)
             .all(&self.db)
             .await?;
 
-        // 获取所有品类
-        let all_categories = category::Entity::find().all(&self.db).await?;
-        let mut category_map: std::collections::HashMap<i64, category::Model> =
-            std::collections::HashMap::new();
-        let mut uncategorized_id: i64 = 0;
-        for cat in &all_categories {
-            if cat.name == DEFAULT_CATEGORY_NAME {
-                uncategorized_id = cat.id;
-            }
-            category_map.insert(cat.id, cat.clone());
-        }
-
-        // 查询商品获取 category_id
-        let product_ids: Vec<i64> = items.iter().map(|item| item.product_id).collect();
-        let products = product::Entity::find()
-            .filter(product::Column::Id.is_in(product_ids))
-            .all(&self.db)
-            .await?;
-        let product_category_map: std::collections::HashMap<i64, Option<i64>> = products
-            .iter()
-            .map(|p| (p.id, p.category_id))
-            .collect();
-
-        // 按 category_id 分组
-        let mut grouped: std::collections::HashMap<i64, Decimal> = std::collections::HashMap::new();
-        for item in &items {
-            let cat_id = product_category_map
-                .get(&item.product_id)
-                .copied()
-                .flatten()
-                .unwrap_or(uncategorized_id);
-            *grouped.entry(cat_id).or_insert(Decimal::ZERO) += item.subtotal;
-        }
-
-        // 获取账本名称映射
-        let all_books = accounting_book::Entity::find().all(&self.db).await?;
-        let book_name_map: std::collections::HashMap<i64, String> = all_books
-            .iter()
-            .map(|b| (b.id, b.title.clone()))
-            .collect();
-
-        // 确定每个品类对应的 book_id
-        let get_book_id = |cat_id: i64| -> i64 {
-            category_map
-                .get(&cat_id)
-                .map(|cat| match order.order_type {
-                    OrderType::Sales => cat.sell_book_id,
-                    OrderType::Purchase => cat.purchase_book_id,
-                })
-                .unwrap_or(DEFAULT_BOOK_ID)
-        };
-
-        // 构建品类分组预览
-        let mut sorted_keys: Vec<i64> = grouped.keys().copied().collect();
-        sorted_keys.sort();
-
-        let category_groups: Vec<SettlePreviewItem> = sorted_keys
-            .iter()
-            .map(|cat_id| {
-                let cat = category_map.get(cat_id);
-                let cat_name = cat
-                    .map(|c| c.name.clone())
-                    .unwrap_or_else(|| DEFAULT_CATEGORY_NAME.to_string());
-                let book_id = get_book_id(*cat_id);
-                let book_name = book_name_map
-                    .get(&book_id)
-                    .cloned()
-                    .unwrap_or_else(|| "未知账本".to_string());
-                SettlePreviewItem {
-                    category_id: *cat_id,
-                    category_name: cat_name,
-                    amount: grouped[cat_id],
-                    book_id,
-                    book_name,
-                }
-            })
-            .collect();
-
-        // 折扣冲账预览
-        let has_discount = order.total_amount != actual;
-        let discount_total = order.total_amount - actual;
-        let discount_amount = if has_discount { Some(discount_total) } else { None };
-
-        let write_off_preview = if has_discount && !category_groups.is_empty() {
-            let total_subtotal: Decimal = category_groups.iter().map(|g| g.amount).sum();
-            let mut preview_items: Vec<WriteOffPreviewItem> = Vec::new();
-
-            for (idx, group) in category_groups.iter().enumerate() {
-                let write_off_amount = if idx == category_groups.len() - 1 {
-                    let allocated: Decimal = category_groups
-                        .iter()
-                        .take(idx)
-                        .map(|g| -discount_total * (g.amount / total_subtotal))
-                        .sum::<Decimal>();
-                    -(discount_total + allocated)
-                } else {
-                    -discount_total * (group.amount / total_subtotal)
-                };
-
-                let rounded = write_off_amount.round_dp(2);
-                if rounded != Decimal::ZERO {
-                    preview_items.push(WriteOffPreviewItem {
-                        category_name: group.category_name.clone(),
-                        write_off_amount: rounded,
-                        category_id: group.category_id,
-                    });
-                }
-            }
-
-            Some(preview_items)
-        } else {
-            None
-        };
-
-        Ok(SettlePreview {
-            category_groups,
-            write_off_preview,
-            discount_amount,
-        })
+        compute_order_margin(&self.db, &order.order_type, &items, order.actual_amount).await
     }
 
     /// 取消订单（验证状态为 Pending 后更新为 Cancelled）
@@ -540,6 +1818,9 @@ impl OrderService {
         if order.status == OrderStatus::Settled {
             return Err("已结账订单不可取消".into());
         }
+        if order.status == OrderStatus::PartiallyPaid {
+            return Err("已有收付款的订单不可取消".into());
+        }
         if order.status == OrderStatus::Cancelled {
             return Err("订单已取消".into());
         }
@@ -569,6 +1850,14 @@ impl OrderService {
             return Err("只有待结账订单可编辑".into());
         }
 
+        // 已收货的采购订单明细与收货单关联，不允许替换
+        if input.items.is_some() && has_goods_receipts(&txn, order.id).await? {
+            return Err("已登记收货的采购订单不能修改明细".into());
+        }
+
+        // 首次编辑前保存原始版本
+        ensure_original_revision(&txn, &order).await?;
+
         let mut order_active: OrderActiveModel = order.into();
 
         // 更新备注
@@ -576,36 +1865,16 @@ impl OrderService {
             order_active.remark = Set(Some(remark));
         }
 
+        // 更新预计收付款日期
+        if let Some(due) = &input.due_date {
+            let due_date =
+                parse_datetime(due, false).map_err(|_| "无效的预计收付款日期".to_string())?;
+            order_active.due_date = Set(Some(due_date));
+        }
+
         // 更新明细（替换方式）
         if let Some(items) = input.items {
-            if items.is_empty() {
-                return Err("订单明细不能为空".into());
-            }
-
-            // 删除旧明细
-            order_item::Entity::delete_many()
-                .filter(order_item::Column::OrderId.eq(input.order_id))
-                .exec(&txn)
-                .await?;
-
-            // 创建新明细并计算总额
-            let mut total_amount = Decimal::ZERO;
-            for item in &items {
-                let subtotal = item.quantity * item.unit_price;
-                let order_item_active = OrderItemActiveModel {
-                    id: sea_orm::ActiveValue::NotSet,
-                    order_id: Set(input.order_id),
-                    product_id: Set(item.product_id),
-                    product_name: Set(item.product_name.clone()),
-                    quantity: Set(item.quantity),
-                    unit: Set(item.unit.clone()),
-                    unit_price: Set(item.unit_price),
-                    subtotal: Set(subtotal),
-                    remark: Set(item.remark.clone()),
-                };
-                order_item_active.insert(&txn).await?;
-                total_amount += subtotal;
-            }
+            let total_amount = replace_order_items(&txn, input.order_id, &items).await?;
 
             // 重算金额
             order_active.total_amount = Set(total_amount);
@@ -613,6 +1882,7 @@ impl OrderService {
         }
 
         let updated_order = order_active.update(&txn).await?;
+        record_revision(&txn, &updated_order, OrderRevisionOrigin::Edit, None).await?;
         txn.commit().await?;
 
         Ok(updated_order)
@@ -719,7 +1989,20 @@ impl OrderService {
             let channel = channel_str
                 .parse::<AccountingChannel>()
                 .map_err(|_| "无效的支付渠道".to_string())?;
-            condition = condition.add(order::Column::Channel.eq(Some(channel)));
+            // 组合支付的订单按收付款记录匹配渠道
+            condition = condition.add(
+                Condition::any()
+                    .add(order::Column::Channel.eq(Some(channel.clone())))
+                    .add(
+                        order::Column::Id.in_subquery(
+                            Query::select()
+                                .column(order_payment::Column::OrderId)
+                                .from(order_payment::Entity)
+                                .and_where(order_payment::Column::Channel.eq(channel))
+                                .to_owned(),
+                        ),
+                    ),
+            );
         }
 
         // 订单类型筛选
@@ -730,9 +2013,84 @@ impl OrderService {
             condition = condition.add(order::Column::OrderType.eq(order_type));
         }
 
-        let paginator = order::Entity::find()
-            .filter(condition)
-            .order_by_desc(order::Column::CreateAt)
+        // 客户筛选
+        if let Some(customer_id) = input.customer_id {
+            condition = condition.add(order::Column::CustomerId.eq(customer_id));
+        }
+
+        // 业务类型筛选
+        if let Some(sub_type_str) = &input.sub_type {
+            let sub_type = sub_type_str
+                .parse::<OrderSubType>()
+                .map_err(|_| "无效的订单业务类型".to_string())?;
+            condition = condition.add(order::Column::SubType.eq(sub_type));
+        }
+
+        // 订单编号模糊匹配
+        if let Some(order_no) = input.order_no.as_deref().map(str::trim) {
+            if !order_no.is_empty() {
+                condition = condition.add(order::Column::OrderNo.contains(order_no));
+            }
+        }
+
+        // 包含指定商品
+        if let Some(product_id) = input.product_id {
+            condition = condition.add(
+                order::Column::Id.in_subquery(
+                    Query::select()
+                        .column(order_item::Column::OrderId)
+                        .from(order_item::Entity)
+                        .and_where(order_item::Column::ProductId.eq(product_id))
+                        .to_owned(),
+                ),
+            );
+        }
+
+        // 关键词：客户名称或明细商品名称
+        if let Some(keyword) = input.keyword.as_deref().map(str::trim) {
+            if !keyword.is_empty() {
+                condition = condition.add(
+                    Condition::any()
+                        .add(order::Column::CustomerName.contains(keyword))
+                        .add(
+                            order::Column::Id.in_subquery(
+                                Query::select()
+                                    .column(order_item::Column::OrderId)
+                                    .from(order_item::Entity)
+                                    .and_where(order_item::Column::ProductName.contains(keyword))
+                                    .to_owned(),
+                            ),
+                        ),
+                );
+            }
+        }
+
+        // 排序（默认按创建时间倒序）
+        let sort_field = match &input.sort_by {
+            Some(s) => s
+                .parse::<OrderSortField>()
+                .map_err(|_| "无效的排序字段".to_string())?,
+            None => OrderSortField::CreateAt,
+        };
+        let sort_order = match input.sort_order.as_deref() {
+            None | Some("Desc") => Order::Desc,
+            Some("Asc") => Order::Asc,
+            Some(_) => return Err("无效的排序方向".into()),
+        };
+
+        let mut query = order::Entity::find().filter(condition);
+        query = match sort_field {
+            OrderSortField::CreateAt => query.order_by(order::Column::CreateAt, sort_order.clone()),
+            OrderSortField::Amount => {
+                query.order_by(order::Column::ActualAmount, sort_order.clone())
+            }
+            // 未结账订单（结账时间为空）始终排在最后
+            OrderSortField::SettledAt => query
+                .order_by_asc(order::Column::SettledAt.is_null())
+                .order_by(order::Column::SettledAt, sort_order.clone()),
+        };
+        let paginator = query
+            .order_by(order::Column::Id, sort_order)
             .paginate(&self.db, page_size);
 
         let total = paginator.num_items().await?;
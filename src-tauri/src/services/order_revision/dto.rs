@@ -0,0 +1,86 @@
+use crate::entity::{order_revision, order_revision_item};
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 订单版本详情（版本 + 明细快照）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRevisionDetail {
+    pub revision: order_revision::Model,
+    pub items: Vec<order_revision_item::Model>,
+}
+
+/// 比较订单版本 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffOrderRevisionsDto {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 起始版本号
+    pub from_version: i32,
+    /// 目标版本号
+    pub to_version: i32,
+}
+
+/// 恢复订单版本 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreOrderRevisionDto {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 要恢复的版本号
+    pub version: i32,
+}
+
+/// 明细变动类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RevisionItemChangeType {
+    /// 新增
+    Added,
+    /// 删除
+    Removed,
+    /// 修改（数量、单价或折扣变化）
+    Modified,
+}
+
+/// 明细变动项（按商品和单位匹配）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionItemChange {
+    pub change_type: RevisionItemChangeType,
+    pub product_id: i64,
+    pub product_name: String,
+    pub unit: String,
+    /// 起始版本数量（新增时为 None）
+    pub from_quantity: Option<Decimal>,
+    /// 目标版本数量（删除时为 None）
+    pub to_quantity: Option<Decimal>,
+    pub from_unit_price: Option<Decimal>,
+    pub to_unit_price: Option<Decimal>,
+    pub from_subtotal: Option<Decimal>,
+    pub to_subtotal: Option<Decimal>,
+}
+
+/// 订单版本差异
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRevisionDiff {
+    pub order_id: i64,
+    pub from_version: i32,
+    pub to_version: i32,
+    pub from_total_amount: Decimal,
+    pub to_total_amount: Decimal,
+    pub from_actual_amount: Decimal,
+    pub to_actual_amount: Decimal,
+    /// 备注是否变化
+    pub remark_changed: bool,
+    pub from_remark: Option<String>,
+    pub to_remark: Option<String>,
+    /// 预计收付款日期是否变化
+    pub due_date_changed: bool,
+    pub from_due_date: Option<NaiveDateTime>,
+    pub to_due_date: Option<NaiveDateTime>,
+    /// 明细变动（未变化的明细不列出）
+    pub item_changes: Vec<RevisionItemChange>,
+}
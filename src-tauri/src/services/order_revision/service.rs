@@ -0,0 +1,298 @@
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+use super::dto::{
+    DiffOrderRevisionsDto, OrderRevisionDetail, OrderRevisionDiff, RestoreOrderRevisionDto,
+    RevisionItemChange, RevisionItemChangeType,
+};
+use crate::entity::order::{self, ActiveModel as OrderActiveModel, Model as OrderModel};
+use crate::entity::order_item;
+use crate::entity::order_revision::{self, ActiveModel as OrderRevisionActiveModel};
+use crate::entity::order_revision_item::{self, ActiveModel as OrderRevisionItemActiveModel};
+use crate::enums::{OrderRevisionOrigin, OrderStatus};
+use crate::services::goods_receipt::service::has_goods_receipts;
+use crate::services::order::dto::CreateOrderItemDto;
+use crate::services::order::service::replace_order_items;
+
+/// 将订单当前内容（明细、备注、金额）保存为新版本，版本号在订单内递增
+pub(crate) async fn record_revision<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    origin: OrderRevisionOrigin,
+    restored_from: Option<i32>,
+) -> Result<order_revision::Model, Box<dyn std::error::Error>> {
+    let latest = order_revision::Entity::find()
+        .filter(order_revision::Column::OrderId.eq(order.id))
+        .order_by_desc(order_revision::Column::Version)
+        .one(conn)
+        .await?;
+    let version = latest.map(|r| r.version + 1).unwrap_or(1);
+
+    let revision = OrderRevisionActiveModel {
+        id: sea_orm::ActiveValue::NotSet,
+        order_id: Set(order.id),
+        version: Set(version),
+        origin: Set(origin),
+        total_amount: Set(order.total_amount),
+        actual_amount: Set(order.actual_amount),
+        remark: Set(order.remark.clone()),
+        due_date: Set(order.due_date),
+        restored_from: Set(restored_from),
+        create_at: Set(Local::now().naive_local()),
+    }
+    .insert(conn)
+    .await?;
+
+    let items = order_item::Entity::find()
+        .filter(order_item::Column::OrderId.eq(order.id))
+        .order_by_asc(order_item::Column::Id)
+        .all(conn)
+        .await?;
+    for item in items {
+        OrderRevisionItemActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            revision_id: Set(revision.id),
+            product_id: Set(item.product_id),
+            product_name: Set(item.product_name),
+            quantity: Set(item.quantity),
+            unit: Set(item.unit),
+            unit_price: Set(item.unit_price),
+            discount_amount: Set(item.discount_amount),
+            discount_rate: Set(item.discount_rate),
+            subtotal: Set(item.subtotal),
+            remark: Set(item.remark),
+        }
+        .insert(conn)
+        .await?;
+    }
+
+    Ok(revision)
+}
+
+/// 订单尚无版本记录时，保存编辑前的原始内容作为第 1 版
+pub(crate) async fn ensure_original_revision<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let count = order_revision::Entity::find()
+        .filter(order_revision::Column::OrderId.eq(order.id))
+        .count(conn)
+        .await?;
+    if count == 0 {
+        record_revision(conn, order, OrderRevisionOrigin::Original, None).await?;
+    }
+    Ok(())
+}
+
+/// 比较两个版本的明细（按商品 + 单位匹配，同一商品多行时按录入顺序配对）
+fn diff_items(
+    from: &[order_revision_item::Model],
+    to: &[order_revision_item::Model],
+) -> Vec<RevisionItemChange> {
+    let mut remaining: Vec<&order_revision_item::Model> = from.iter().collect();
+    let mut changes = Vec::new();
+
+    for item in to {
+        let matched = remaining
+            .iter()
+            .position(|f| f.product_id == item.product_id && f.unit == item.unit)
+            .map(|idx| remaining.remove(idx));
+        match matched {
+            Some(old) => {
+                if old.quantity == item.quantity
+                    && old.unit_price == item.unit_price
+                    && old.discount_amount == item.discount_amount
+                    && old.subtotal == item.subtotal
+                {
+                    continue;
+                }
+                changes.push(RevisionItemChange {
+                    change_type: RevisionItemChangeType::Modified,
+                    product_id: item.product_id,
+                    product_name: item.product_name.clone(),
+                    unit: item.unit.clone(),
+                    from_quantity: Some(old.quantity),
+                    to_quantity: Some(item.quantity),
+                    from_unit_price: Some(old.unit_price),
+                    to_unit_price: Some(item.unit_price),
+                    from_subtotal: Some(old.subtotal),
+                    to_subtotal: Some(item.subtotal),
+                });
+            }
+            None => changes.push(RevisionItemChange {
+                change_type: RevisionItemChangeType::Added,
+                product_id: item.product_id,
+                product_name: item.product_name.clone(),
+                unit: item.unit.clone(),
+                from_quantity: None,
+                to_quantity: Some(item.quantity),
+                from_unit_price: None,
+                to_unit_price: Some(item.unit_price),
+                from_subtotal: None,
+                to_subtotal: Some(item.subtotal),
+            }),
+        }
+    }
+
+    for old in remaining {
+        changes.push(RevisionItemChange {
+            change_type: RevisionItemChangeType::Removed,
+            product_id: old.product_id,
+            product_name: old.product_name.clone(),
+            unit: old.unit.clone(),
+            from_quantity: Some(old.quantity),
+            to_quantity: None,
+            from_unit_price: Some(old.unit_price),
+            to_unit_price: None,
+            from_subtotal: Some(old.subtotal),
+            to_subtotal: None,
+        });
+    }
+
+    changes
+}
+
+/// 订单版本服务
+#[derive(Debug)]
+pub struct OrderRevisionService {
+    db: DatabaseConnection,
+}
+
+impl OrderRevisionService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查询版本明细快照（按录入顺序）
+    async fn find_items(
+        &self,
+        revision_id: i64,
+    ) -> Result<Vec<order_revision_item::Model>, Box<dyn std::error::Error>> {
+        let items = order_revision_item::Entity::find()
+            .filter(order_revision_item::Column::RevisionId.eq(revision_id))
+            .order_by_asc(order_revision_item::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(items)
+    }
+
+    /// 查询订单的所有版本（按版本号升序）
+    pub async fn get_revisions(
+        &self,
+        order_id: i64,
+    ) -> Result<Vec<order_revision::Model>, Box<dyn std::error::Error>> {
+        let revisions = order_revision::Entity::find()
+            .filter(order_revision::Column::OrderId.eq(order_id))
+            .order_by_asc(order_revision::Column::Version)
+            .all(&self.db)
+            .await?;
+        Ok(revisions)
+    }
+
+    /// 查询订单指定版本（含明细快照）
+    pub async fn get_revision(
+        &self,
+        order_id: i64,
+        version: i32,
+    ) -> Result<OrderRevisionDetail, Box<dyn std::error::Error>> {
+        let revision = order_revision::Entity::find()
+            .filter(order_revision::Column::OrderId.eq(order_id))
+            .filter(order_revision::Column::Version.eq(version))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| format!("订单版本 {} 不存在", version))?;
+        let items = self.find_items(revision.id).await?;
+        Ok(OrderRevisionDetail { revision, items })
+    }
+
+    /// 比较订单的两个版本（金额、备注、预计收付款日期和明细变动）
+    pub async fn diff_revisions(
+        &self,
+        input: DiffOrderRevisionsDto,
+    ) -> Result<OrderRevisionDiff, Box<dyn std::error::Error>> {
+        let from = self
+            .get_revision(input.order_id, input.from_version)
+            .await?;
+        let to = self.get_revision(input.order_id, input.to_version).await?;
+
+        Ok(OrderRevisionDiff {
+            order_id: input.order_id,
+            from_version: input.from_version,
+            to_version: input.to_version,
+            from_total_amount: from.revision.total_amount,
+            to_total_amount: to.revision.total_amount,
+            from_actual_amount: from.revision.actual_amount,
+            to_actual_amount: to.revision.actual_amount,
+            remark_changed: from.revision.remark != to.revision.remark,
+            from_remark: from.revision.remark,
+            to_remark: to.revision.remark,
+            due_date_changed: from.revision.due_date != to.revision.due_date,
+            from_due_date: from.revision.due_date,
+            to_due_date: to.revision.due_date,
+            item_changes: diff_items(&from.items, &to.items),
+        })
+    }
+
+    /// 将待结账订单恢复为指定版本（恢复结果另存为新版本）
+    pub async fn restore_revision(
+        &self,
+        input: RestoreOrderRevisionDto,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let target = self.get_revision(input.order_id, input.version).await?;
+
+        let txn = self.db.begin().await?;
+
+        let order = order::Entity::find_by_id(input.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("订单不存在")?;
+        if order.status != OrderStatus::Pending {
+            return Err("只有待结账订单可恢复历史版本".into());
+        }
+        if has_goods_receipts(&txn, order.id).await? {
+            return Err("已登记收货的采购订单不能恢复历史版本".into());
+        }
+        ensure_original_revision(&txn, &order).await?;
+
+        let items: Vec<CreateOrderItemDto> = target
+            .items
+            .into_iter()
+            .map(|item| CreateOrderItemDto {
+                product_id: item.product_id,
+                product_name: item.product_name,
+                quantity: item.quantity,
+                unit: item.unit,
+                unit_price: item.unit_price,
+                discount_amount: match item.discount_rate {
+                    Some(_) => None,
+                    None if item.discount_amount.is_zero() => None,
+                    None => Some(item.discount_amount),
+                },
+                discount_rate: item.discount_rate,
+                remark: item.remark,
+            })
+            .collect();
+        let total_amount = replace_order_items(&txn, order.id, &items).await?;
+
+        let mut order_active: OrderActiveModel = order.into();
+        order_active.total_amount = Set(total_amount);
+        order_active.actual_amount = Set(target.revision.actual_amount);
+        order_active.remark = Set(target.revision.remark);
+        order_active.due_date = Set(target.revision.due_date);
+        let updated_order = order_active.update(&txn).await?;
+
+        record_revision(
+            &txn,
+            &updated_order,
+            OrderRevisionOrigin::Restore,
+            Some(input.version),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(updated_order)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::entity::{order_template, order_template_item};
+use crate::services::order::dto::CreateOrderItemDto;
+use serde::{Deserialize, Serialize};
+
+/// 创建订单模板 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderTemplateDto {
+    /// 模板名称（同一客户下唯一）
+    pub name: String,
+    /// 订单类型（Sales / Purchase）
+    pub order_type: String,
+    /// 客户 ID（散客为 None）
+    pub customer_id: Option<i64>,
+    /// 客户名称（冗余快照，与 customer_id 一同传入）
+    pub customer_name: Option<String>,
+    /// 订单业务类型（可选，不传则自动填充默认值）
+    pub sub_type: Option<String>,
+    /// 模板明细列表（与订单明细结构一致）
+    pub items: Vec<CreateOrderItemDto>,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+/// 从已有订单保存模板 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveOrderAsTemplateDto {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 模板名称（同一客户下唯一）
+    pub name: String,
+}
+
+/// 编辑订单模板 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOrderTemplateDto {
+    /// 模板 ID
+    pub template_id: i64,
+    /// 模板名称（可选）
+    pub name: Option<String>,
+    /// 模板明细列表（可选，传入则替换原有明细）
+    pub items: Option<Vec<CreateOrderItemDto>>,
+    /// 备注（可选，传入则更新备注）
+    pub remark: Option<String>,
+}
+
+/// 按模板下单 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderFromTemplateDto {
+    /// 模板 ID
+    pub template_id: i64,
+    /// 是否按商品参考价刷新单价（默认使用模板保存的单价）
+    pub refresh_prices: Option<bool>,
+    /// 订单备注（可选，默认沿用模板备注）
+    pub remark: Option<String>,
+    /// 预计收付款日期（可选，格式 YYYY-MM-DD）
+    pub due_date: Option<String>,
+}
+
+/// 订单模板详情（模板 + 明细列表）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderTemplateDetail {
+    pub template: order_template::Model,
+    pub items: Vec<order_template_item::Model>,
+}
@@ -0,0 +1,342 @@
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+use super::dto::{
+    CreateOrderFromTemplateDto, CreateOrderTemplateDto, OrderTemplateDetail,
+    SaveOrderAsTemplateDto, UpdateOrderTemplateDto,
+};
+use crate::entity::order::{self, Model as OrderModel};
+use crate::entity::order_item;
+use crate::entity::order_template::{self, ActiveModel as OrderTemplateActiveModel};
+use crate::entity::order_template_item::{self, ActiveModel as OrderTemplateItemActiveModel};
+use crate::enums::{OrderSubType, OrderType};
+use crate::services::order::dto::{CreateOrderDto, CreateOrderItemDto};
+use crate::services::order::service::{
+    item_discount, refresh_item_prices, resolve_default_sub_type, validate_sub_type_match,
+};
+use crate::services::order::OrderService;
+
+/// 校验模板名称（非空，且同一客户下不重复）
+async fn ensure_name_available<C: ConnectionTrait>(
+    conn: &C,
+    customer_id: Option<i64>,
+    name: &str,
+    exclude_id: Option<i64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if name.is_empty() {
+        return Err("模板名称不能为空".into());
+    }
+
+    let mut condition = Condition::all().add(order_template::Column::Name.eq(name));
+    condition = match customer_id {
+        Some(id) => condition.add(order_template::Column::CustomerId.eq(id)),
+        None => condition.add(order_template::Column::CustomerId.is_null()),
+    };
+    if let Some(id) = exclude_id {
+        condition = condition.add(order_template::Column::Id.ne(id));
+    }
+
+    let count = order_template::Entity::find()
+        .filter(condition)
+        .count(conn)
+        .await?;
+    if count > 0 {
+        return Err(format!("该客户已存在名为 {} 的订单模板", name).into());
+    }
+    Ok(())
+}
+
+/// 写入模板明细（校验折扣设置）
+async fn insert_items<C: ConnectionTrait>(
+    conn: &C,
+    template_id: i64,
+    items: &[CreateOrderItemDto],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if items.is_empty() {
+        return Err("模板明细不能为空".into());
+    }
+
+    for item in items {
+        item_discount(item)?;
+        let item_active = OrderTemplateItemActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            template_id: Set(template_id),
+            product_id: Set(item.product_id),
+            product_name: Set(item.product_name.clone()),
+            quantity: Set(item.quantity),
+            unit: Set(item.unit.clone()),
+            unit_price: Set(item.unit_price),
+            discount_amount: Set(item.discount_amount),
+            discount_rate: Set(item.discount_rate),
+            remark: Set(item.remark.clone()),
+        };
+        item_active.insert(conn).await?;
+    }
+
+    Ok(())
+}
+
+/// 订单模板服务
+#[derive(Debug)]
+pub struct OrderTemplateService {
+    db: DatabaseConnection,
+}
+
+impl OrderTemplateService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查询模板明细（按录入顺序）
+    async fn find_items(
+        &self,
+        template_id: i64,
+    ) -> Result<Vec<order_template_item::Model>, Box<dyn std::error::Error>> {
+        let items = order_template_item::Entity::find()
+            .filter(order_template_item::Column::TemplateId.eq(template_id))
+            .order_by_asc(order_template_item::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(items)
+    }
+
+    /// 创建订单模板
+    pub async fn create_template(
+        &self,
+        input: CreateOrderTemplateDto,
+    ) -> Result<OrderTemplateDetail, Box<dyn std::error::Error>> {
+        if input.items.is_empty() {
+            return Err("模板明细不能为空".into());
+        }
+
+        let order_type = input
+            .order_type
+            .parse::<OrderType>()
+            .map_err(|_| "无效的订单类型".to_string())?;
+        let sub_type = match input.sub_type {
+            Some(st) => {
+                let parsed = st
+                    .parse::<OrderSubType>()
+                    .map_err(|_| "无效的订单业务类型".to_string())?;
+                validate_sub_type_match(&parsed, &order_type)?;
+                parsed
+            }
+            None => resolve_default_sub_type(&order_type, input.customer_id),
+        };
+        let name = input.name.trim().to_string();
+
+        let txn = self.db.begin().await?;
+        ensure_name_available(&txn, input.customer_id, &name, None).await?;
+
+        let now = Local::now().naive_local();
+        let template = OrderTemplateActiveModel {
+            name: Set(name),
+            order_type: Set(order_type),
+            sub_type: Set(sub_type),
+            customer_id: Set(input.customer_id),
+            customer_name: Set(input.customer_name),
+            remark: Set(input.remark),
+            create_at: Set(now),
+            update_at: Set(now),
+            last_used_at: Set(None),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        insert_items(&txn, template.id, &input.items).await?;
+        txn.commit().await?;
+
+        let items = self.find_items(template.id).await?;
+        Ok(OrderTemplateDetail { template, items })
+    }
+
+    /// 将已有订单保存为模板（沿用订单的客户、类型、明细和备注）
+    pub async fn save_order_as_template(
+        &self,
+        input: SaveOrderAsTemplateDto,
+    ) -> Result<OrderTemplateDetail, Box<dyn std::error::Error>> {
+        let order = order::Entity::find_by_id(input.order_id)
+            .one(&self.db)
+            .await?
+            .ok_or("订单不存在")?;
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(order.id))
+            .order_by_asc(order_item::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        let items = items
+            .into_iter()
+            .map(|item| CreateOrderItemDto {
+                product_id: item.product_id,
+                product_name: item.product_name,
+                quantity: item.quantity,
+                unit: item.unit,
+                unit_price: item.unit_price,
+                discount_amount: match item.discount_rate {
+                    Some(_) => None,
+                    None if item.discount_amount.is_zero() => None,
+                    None => Some(item.discount_amount),
+                },
+                discount_rate: item.discount_rate,
+                remark: item.remark,
+            })
+            .collect();
+
+        self.create_template(CreateOrderTemplateDto {
+            name: input.name,
+            order_type: order.order_type.to_string(),
+            customer_id: order.customer_id,
+            customer_name: order.customer_name,
+            sub_type: Some(order.sub_type.to_string()),
+            items,
+            remark: order.remark,
+        })
+        .await
+    }
+
+    /// 编辑订单模板
+    pub async fn update_template(
+        &self,
+        input: UpdateOrderTemplateDto,
+    ) -> Result<OrderTemplateDetail, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        let template = order_template::Entity::find_by_id(input.template_id)
+            .one(&txn)
+            .await?
+            .ok_or("订单模板不存在")?;
+        let customer_id = template.customer_id;
+        let mut template_active: OrderTemplateActiveModel = template.into();
+
+        if let Some(name) = input.name {
+            let name = name.trim().to_string();
+            ensure_name_available(&txn, customer_id, &name, Some(input.template_id)).await?;
+            template_active.name = Set(name);
+        }
+
+        if let Some(remark) = input.remark {
+            template_active.remark = Set(Some(remark));
+        }
+
+        // 更新明细（替换方式）
+        if let Some(items) = input.items {
+            order_template_item::Entity::delete_many()
+                .filter(order_template_item::Column::TemplateId.eq(input.template_id))
+                .exec(&txn)
+                .await?;
+            insert_items(&txn, input.template_id, &items).await?;
+        }
+
+        template_active.update_at = Set(Local::now().naive_local());
+        let template = template_active.update(&txn).await?;
+        txn.commit().await?;
+
+        let items = self.find_items(template.id).await?;
+        Ok(OrderTemplateDetail { template, items })
+    }
+
+    /// 删除订单模板（同时删除明细）
+    pub async fn delete_template(&self, id: i64) -> Result<(), Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        order_template::Entity::find_by_id(id)
+            .one(&txn)
+            .await?
+            .ok_or("订单模板不存在")?;
+        order_template_item::Entity::delete_many()
+            .filter(order_template_item::Column::TemplateId.eq(id))
+            .exec(&txn)
+            .await?;
+        order_template::Entity::delete_by_id(id).exec(&txn).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// 根据 ID 查询订单模板（含明细）
+    pub async fn get_template_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<OrderTemplateDetail>, Box<dyn std::error::Error>> {
+        let template = order_template::Entity::find_by_id(id).one(&self.db).await?;
+
+        match template {
+            Some(t) => {
+                let items = self.find_items(t.id).await?;
+                Ok(Some(OrderTemplateDetail { template: t, items }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 查询订单模板（可按客户筛选，最近使用的在前）
+    pub async fn get_templates(
+        &self,
+        customer_id: Option<i64>,
+    ) -> Result<Vec<order_template::Model>, Box<dyn std::error::Error>> {
+        let mut query = order_template::Entity::find();
+        if let Some(id) = customer_id {
+            query = query.filter(order_template::Column::CustomerId.eq(id));
+        }
+        let templates = query
+            .order_by_desc(order_template::Column::LastUsedAt)
+            .order_by_desc(order_template::Column::UpdateAt)
+            .all(&self.db)
+            .await?;
+        Ok(templates)
+    }
+
+    /// 按模板创建待结账订单（可选按商品参考价刷新单价）
+    pub async fn create_order_from_template(
+        &self,
+        input: CreateOrderFromTemplateDto,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let template = order_template::Entity::find_by_id(input.template_id)
+            .one(&self.db)
+            .await?
+            .ok_or("订单模板不存在")?;
+        let items = self.find_items(template.id).await?;
+
+        let mut order_items: Vec<CreateOrderItemDto> = items
+            .into_iter()
+            .map(|item| CreateOrderItemDto {
+                product_id: item.product_id,
+                product_name: item.product_name,
+                quantity: item.quantity,
+                unit: item.unit,
+                unit_price: item.unit_price,
+                discount_amount: item.discount_amount,
+                discount_rate: item.discount_rate,
+                remark: item.remark,
+            })
+            .collect();
+
+        if input.refresh_prices.unwrap_or(false) {
+            refresh_item_prices(&self.db, &template.order_type, &mut order_items).await?;
+        }
+
+        let order = OrderService::new(self.db.clone())
+            .create_order(CreateOrderDto {
+                order_type: template.order_type.to_string(),
+                customer_id: template.customer_id,
+                customer_name: template.customer_name.clone(),
+                items: order_items,
+                remark: input.remark.or_else(|| template.remark.clone()),
+                actual_amount: None,
+                sub_type: Some(template.sub_type.to_string()),
+                due_date: input.due_date,
+            })
+            .await?;
+
+        let mut template_active: OrderTemplateActiveModel = template.into();
+        template_active.last_used_at = Set(Some(Local::now().naive_local()));
+        template_active.update(&self.db).await?;
+
+        Ok(order)
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// 打印订单小票 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintReceiptDto {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 票据类型（Receipt / DeliveryNote，默认 Receipt）
+    pub kind: Option<String>,
+    /// 二维码内容（可选，如收款码链接）
+    pub qr_content: Option<String>,
+    /// 打印份数（默认 1，最多 5）
+    pub copies: Option<u8>,
+}
+
+/// 打印结果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintResult {
+    /// 打印机地址
+    pub target: String,
+    /// 已发送字节数
+    pub bytes_sent: usize,
+}
@@ -0,0 +1,380 @@
+//! ESC/POS 指令生成（热敏小票打印机）
+//!
+//! 文本按 GBK 编码输出，适用于支持汉字模式的国产 58mm / 80mm 打印机。
+//! 全角字符按 2 列、半角字符按 1 列计算宽度。
+
+use encoding_rs::GBK;
+
+use crate::enums::OrderType;
+use crate::services::document::html::{money, quantity};
+use crate::services::document::label::order_type_label;
+use crate::services::receipt::dto::{OrderReceipt, ReceiptKind};
+
+const ESC: u8 = 0x1B;
+const GS: u8 = 0x1D;
+const FS: u8 = 0x1C;
+
+/// 二维码内容最大字节数
+pub const MAX_QR_BYTES: usize = 700;
+
+/// 数量列宽
+const QTY_WIDTH: usize = 6;
+/// 单价列宽
+const PRICE_WIDTH: usize = 7;
+/// 金额列宽
+const AMOUNT_WIDTH: usize = 8;
+
+/// 纸宽对应的每行列数（字体 A，58mm 为 32 列，80mm 为 48 列）
+pub fn columns_for_paper(paper_width: u32) -> usize {
+    if paper_width >= 80 {
+        48
+    } else {
+        32
+    }
+}
+
+/// 文本显示宽度（半角 1 列，全角 2 列）
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    if c.is_ascii() {
+        1
+    } else {
+        2
+    }
+}
+
+/// 截断文本至指定宽度
+fn truncate(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = char_width(c);
+        if used + w > width {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    out
+}
+
+/// 左对齐并补齐空格至指定宽度（超出截断）
+fn pad_right(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let padding = width - display_width(&text);
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// 右对齐并补齐空格至指定宽度（超出截断）
+fn pad_left(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let padding = width - display_width(&text);
+    format!("{}{}", " ".repeat(padding), text)
+}
+
+/// 按显示宽度折行
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = char_width(c);
+        if used + w > width && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+            used = 0;
+        }
+        current.push(c);
+        used += w;
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// 左右两端对齐的一行（右侧优先完整显示）
+fn justify(left: &str, right: &str, width: usize) -> String {
+    let right_width = display_width(right).min(width);
+    let left_width = width.saturating_sub(right_width + 1);
+    format!(
+        "{} {}",
+        pad_right(left, left_width),
+        pad_left(right, right_width)
+    )
+}
+
+/// 文本按 GBK 编码，无法编码的字符输出为 ?
+pub fn encode_gbk(text: &str) -> Vec<u8> {
+    let (bytes, _, had_errors) = GBK.encode(text);
+    if !had_errors {
+        return bytes.into_owned();
+    }
+
+    let mut out = Vec::with_capacity(text.len() * 2);
+    let mut buf = [0u8; 4];
+    for c in text.chars() {
+        let (bytes, _, had_errors) = GBK.encode(c.encode_utf8(&mut buf));
+        if had_errors {
+            out.push(b'?');
+        } else {
+            out.extend_from_slice(&bytes);
+        }
+    }
+    out
+}
+
+/// 对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// ESC/POS 字节流构建器
+#[derive(Debug)]
+pub struct EscPosBuilder {
+    buf: Vec<u8>,
+}
+
+impl Default for EscPosBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EscPosBuilder {
+    /// 初始化打印机并进入汉字模式
+    pub fn new() -> Self {
+        Self {
+            buf: vec![ESC, b'@', FS, b'&'],
+        }
+    }
+
+    /// 设置对齐方式
+    pub fn align(&mut self, align: Align) -> &mut Self {
+        let n = match align {
+            Align::Left => 0,
+            Align::Center => 1,
+            Align::Right => 2,
+        };
+        self.buf.extend_from_slice(&[ESC, b'a', n]);
+        self
+    }
+
+    /// 设置加粗
+    pub fn bold(&mut self, on: bool) -> &mut Self {
+        self.buf.extend_from_slice(&[ESC, b'E', on as u8]);
+        self
+    }
+
+    /// 设置倍宽倍高
+    pub fn double_size(&mut self, on: bool) -> &mut Self {
+        let n = if on { 0x11 } else { 0x00 };
+        self.buf.extend_from_slice(&[GS, b'!', n]);
+        self
+    }
+
+    /// 输出一行文本（自动换行符）
+    pub fn line(&mut self, text: &str) -> &mut Self {
+        self.buf.extend_from_slice(&encode_gbk(text));
+        self.buf.push(b'\n');
+        self
+    }
+
+    /// 输出分隔线
+    pub fn separator(&mut self, columns: usize) -> &mut Self {
+        self.line(&"-".repeat(columns))
+    }
+
+    /// 走纸 n 行
+    pub fn feed(&mut self, lines: u8) -> &mut Self {
+        self.buf.extend_from_slice(&[ESC, b'd', lines]);
+        self
+    }
+
+    /// 打印二维码（模型 2，纠错等级 M）
+    pub fn qr_code(&mut self, content: &str, module_size: u8) -> &mut Self {
+        let data = content.as_bytes();
+        let len = data.len() + 3;
+        let (pl, ph) = ((len % 256) as u8, (len / 256) as u8);
+        self.buf
+            .extend_from_slice(&[GS, b'(', b'k', 4, 0, 49, 65, 50, 0]);
+        self.buf
+            .extend_from_slice(&[GS, b'(', b'k', 3, 0, 49, 67, module_size.clamp(1, 16)]);
+        self.buf
+            .extend_from_slice(&[GS, b'(', b'k', 3, 0, 49, 69, 49]);
+        self.buf
+            .extend_from_slice(&[GS, b'(', b'k', pl, ph, 49, 80, 48]);
+        self.buf.extend_from_slice(data);
+        self.buf
+            .extend_from_slice(&[GS, b'(', b'k', 3, 0, 49, 81, 48]);
+        self
+    }
+
+    /// 走纸并半切纸（无切刀的打印机忽略该指令）
+    pub fn cut(&mut self) -> &mut Self {
+        self.buf.extend_from_slice(&[GS, b'V', 66, 0]);
+        self
+    }
+
+    /// 输出字节流
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// 将订单票据渲染为 ESC/POS 字节流
+pub fn render_receipt(receipt: &OrderReceipt, columns: usize, qr_content: Option<&str>) -> Vec<u8> {
+    let shop = &receipt.shop;
+    let name_width = columns - QTY_WIDTH - PRICE_WIDTH - AMOUNT_WIDTH;
+    let mut p = EscPosBuilder::new();
+
+    // 店铺抬头
+    p.align(Align::Center);
+    if !shop.shop_name.is_empty() {
+        p.double_size(true);
+        for line in wrap(&shop.shop_name, columns / 2) {
+            p.line(&line);
+        }
+        p.double_size(false);
+    }
+    for info in [&shop.address, &shop.phone].into_iter().flatten() {
+        for line in wrap(info, columns) {
+            p.line(&line);
+        }
+    }
+    p.bold(true)
+        .line(&format!(
+            "{}{}",
+            order_type_label(&receipt.order_type),
+            receipt.kind.title()
+        ))
+        .bold(false);
+
+    // 单据信息
+    p.align(Align::Left);
+    p.line(&format!("单号：{}", receipt.order_no));
+    p.line(&format!(
+        "日期：{}",
+        receipt.order_date.format("%Y-%m-%d %H:%M")
+    ));
+    let party = match receipt.order_type {
+        OrderType::Sales => "客户",
+        OrderType::Purchase => "供应商",
+    };
+    p.line(&format!(
+        "{}：{}",
+        party,
+        receipt.customer_name.as_deref().unwrap_or("散客")
+    ));
+    if receipt.kind == ReceiptKind::DeliveryNote {
+        if let Some(address) = &receipt.customer_address {
+            for line in wrap(&format!("地址：{}", address), columns) {
+                p.line(&line);
+            }
+        }
+    }
+    p.separator(columns);
+
+    // 商品明细（名称过长时单独占一行）
+    p.line(&format!(
+        "{}{}{}{}",
+        pad_right("商品", name_width),
+        pad_left("数量", QTY_WIDTH),
+        pad_left("单价", PRICE_WIDTH),
+        pad_left("金额", AMOUNT_WIDTH)
+    ));
+    for item in &receipt.items {
+        let name = if display_width(&item.product_name) > name_width {
+            for line in wrap(&item.product_name, columns) {
+                p.line(&line);
+            }
+            String::new()
+        } else {
+            item.product_name.clone()
+        };
+        p.line(&format!(
+            "{}{}{}{}",
+            pad_right(&name, name_width),
+            pad_left(&quantity(item.quantity), QTY_WIDTH),
+            pad_left(&money(item.unit_price), PRICE_WIDTH),
+            pad_left(&money(item.subtotal), AMOUNT_WIDTH)
+        ));
+        if !item.discount_amount.is_zero() {
+            p.line(&pad_left(
+                &format!("优惠 -{}", money(item.discount_amount)),
+                columns,
+            ));
+        }
+    }
+    p.separator(columns);
+
+    // 金额汇总
+    p.line(&justify("合计", &money(receipt.total_amount), columns));
+    if !receipt.discount_amount.is_zero() {
+        p.line(&justify(
+            "整单优惠",
+            &money(receipt.discount_amount),
+            columns,
+        ));
+    }
+    let actual_label = match receipt.order_type {
+        OrderType::Sales => "实收",
+        OrderType::Purchase => "实付",
+    };
+    p.bold(true)
+        .line(&justify(
+            actual_label,
+            &money(receipt.actual_amount),
+            columns,
+        ))
+        .bold(false);
+    for line in wrap(
+        &format!("大写：{}", receipt.actual_amount_uppercase),
+        columns,
+    ) {
+        p.line(&line);
+    }
+    if receipt.kind == ReceiptKind::Receipt {
+        p.line(&justify("已结", &money(receipt.paid_amount), columns));
+        p.line(&justify(
+            "未结",
+            &money(receipt.outstanding_amount),
+            columns,
+        ));
+        if !receipt.paid_amount.is_zero() {
+            for line in wrap(&format!("渠道：{}", receipt.channel_text()), columns) {
+                p.line(&line);
+            }
+        }
+    }
+    if let Some(remark) = &receipt.remark {
+        for line in wrap(&format!("备注：{}", remark), columns) {
+            p.line(&line);
+        }
+    }
+    if receipt.kind == ReceiptKind::DeliveryNote {
+        p.feed(1).line("收货人签字：");
+    }
+    p.separator(columns);
+
+    // 底部附言与二维码
+    p.align(Align::Center);
+    if let Some(footer) = &shop.receipt_footer {
+        for line in wrap(footer, columns) {
+            p.line(&line);
+        }
+    }
+    if let Some(content) = qr_content {
+        p.qr_code(content, if columns >= 48 { 6 } else { 4 });
+        p.line("");
+    }
+    p.line(&receipt.generated_at.format("%Y-%m-%d %H:%M:%S").to_string());
+    p.feed(4).cut();
+
+    p.into_bytes()
+}
@@ -0,0 +1,6 @@
+pub mod dto;
+pub mod escpos;
+pub mod service;
+
+pub use dto::*;
+pub use service::PrinterService;
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use sea_orm::DatabaseConnection;
+use tokio::io::AsyncWriteExt;
+
+use super::dto::{PrintReceiptDto, PrintResult};
+use super::escpos::{self, MAX_QR_BYTES};
+use crate::services::receipt::dto::ReceiptKind;
+use crate::services::receipt::ReceiptService;
+use crate::services::setting::service::load_printer_config;
+
+/// 网络打印机地址前缀
+const TCP_PREFIX: &str = "tcp://";
+/// 网络打印机连接 / 发送超时
+const TCP_TIMEOUT: Duration = Duration::from_secs(5);
+/// 单次最多打印份数
+const MAX_COPIES: u8 = 5;
+
+/// 将字节流发送到打印机（tcp://host:port 为网络打印机，其余视为设备路径）
+pub async fn send_to_printer(target: &str, bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(addr) = target.strip_prefix(TCP_PREFIX) {
+        let mut stream = tokio::time::timeout(TCP_TIMEOUT, tokio::net::TcpStream::connect(addr))
+            .await
+            .map_err(|_| format!("连接打印机超时: {}", addr))?
+            .map_err(|e| format!("无法连接打印机 {}: {}", addr, e))?;
+        tokio::time::timeout(TCP_TIMEOUT, async {
+            stream.write_all(bytes).await?;
+            stream.flush().await?;
+            stream.shutdown().await
+        })
+        .await
+        .map_err(|_| format!("发送打印数据超时: {}", addr))?
+        .map_err(|e| format!("发送打印数据失败: {}", e))?;
+    } else {
+        let mut device = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(target)
+            .await
+            .map_err(|e| format!("无法打开打印机设备 {}: {}", target, e))?;
+        device.write_all(bytes).await?;
+        device.flush().await?;
+    }
+    Ok(())
+}
+
+/// 小票打印服务
+#[derive(Debug)]
+pub struct PrinterService {
+    db: DatabaseConnection,
+}
+
+impl PrinterService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 将订单票据渲染为 ESC/POS 字节流（按配置的纸宽排版，不访问打印机）
+    pub async fn render_receipt(
+        &self,
+        order_id: i64,
+        kind: ReceiptKind,
+        qr_content: Option<&str>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let qr_content = qr_content.map(str::trim).filter(|c| !c.is_empty());
+        if qr_content.is_some_and(|c| c.len() > MAX_QR_BYTES) {
+            return Err(format!("二维码内容不能超过 {} 字节", MAX_QR_BYTES).into());
+        }
+
+        let config = load_printer_config(&self.db).await?;
+        let receipt = ReceiptService::new(self.db.clone())
+            .generate_receipt(order_id, kind)
+            .await?;
+
+        Ok(escpos::render_receipt(
+            &receipt,
+            escpos::columns_for_paper(config.paper_width),
+            qr_content,
+        ))
+    }
+
+    /// 打印订单小票到已配置的打印机
+    pub async fn print_receipt(
+        &self,
+        input: PrintReceiptDto,
+    ) -> Result<PrintResult, Box<dyn std::error::Error>> {
+        let kind = match &input.kind {
+            Some(s) => s
+                .parse::<ReceiptKind>()
+                .map_err(|_| format!("无效的票据类型: {}", s))?,
+            None => ReceiptKind::Receipt,
+        };
+        let copies = input.copies.unwrap_or(1);
+        if copies == 0 || copies > MAX_COPIES {
+            return Err(format!("打印份数必须为 1 ~ {}", MAX_COPIES).into());
+        }
+
+        let target = load_printer_config(&self.db)
+            .await?
+            .target
+            .ok_or("尚未配置小票打印机")?;
+
+        let bytes = self
+            .render_receipt(input.order_id, kind, input.qr_content.as_deref())
+            .await?
+            .repeat(copies as usize);
+        send_to_printer(&target, &bytes).await?;
+
+        Ok(PrintResult {
+            target,
+            bytes_sent: bytes.len(),
+        })
+    }
+}
@@ -17,6 +17,10 @@ pub struct CreateProductDto {
     pub default_sell_price: Option<Decimal>,
     /// 参考采购价（可选）
     pub default_purchase_price: Option<Decimal>,
+    /// 最低库存（可选，不能为负数）
+    pub min_stock: Option<Decimal>,
+    /// 补货数量（可选，必须大于 0）
+    pub reorder_quantity: Option<Decimal>,
     /// 商品编码（可选）
     pub sku: Option<String>,
     /// 检索关键词，多个以分号分隔（可选）
@@ -43,6 +47,10 @@ pub struct UpdateProductDto {
     pub default_sell_price: Option<Option<Decimal>>,
     /// 参考采购价
     pub default_purchase_price: Option<Option<Decimal>>,
+    /// 最低库存
+    pub min_stock: Option<Option<Decimal>>,
+    /// 补货数量
+    pub reorder_quantity: Option<Option<Decimal>>,
     /// 商品编码
     pub sku: Option<Option<String>>,
     /// 检索关键词
@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use sea_orm::{
     ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait,
     QueryFilter, QueryOrder,
@@ -6,6 +7,20 @@ use sea_orm::{
 use super::dto::{CreateProductDto, UpdateProductDto};
 use crate::entity::product::{self, ActiveModel, Model};
 
+/// 校验最低库存和补货数量
+fn validate_reorder(
+    min_stock: Option<Decimal>,
+    reorder_quantity: Option<Decimal>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if min_stock.is_some_and(|q| q < Decimal::ZERO) {
+        return Err("最低库存不能为负数".into());
+    }
+    if reorder_quantity.is_some_and(|q| q <= Decimal::ZERO) {
+        return Err("补货数量必须大于 0".into());
+    }
+    Ok(())
+}
+
 /// 商品管理服务
 #[derive(Debug)]
 pub struct ProductService {
@@ -22,6 +37,7 @@ impl ProductService {
         &self,
         input: CreateProductDto,
     ) -> Result<Model, Box<dyn std::error::Error>> {
+        validate_reorder(input.min_stock, input.reorder_quantity)?;
         let id = Model::generate_id(&self.db).await?;
 
         let new_product = ActiveModel {
@@ -32,6 +48,8 @@ impl ProductService {
             unit: sea_orm::ActiveValue::Set(input.unit),
             default_sell_price: sea_orm::ActiveValue::Set(input.default_sell_price),
             default_purchase_price: sea_orm::ActiveValue::Set(input.default_purchase_price),
+            min_stock: sea_orm::ActiveValue::Set(input.min_stock),
+            reorder_quantity: sea_orm::ActiveValue::Set(input.reorder_quantity),
             sku: sea_orm::ActiveValue::Set(input.sku),
             keywords: sea_orm::ActiveValue::Set(input.keywords),
             remark: sea_orm::ActiveValue::Set(input.remark),
@@ -78,6 +96,16 @@ impl ProductService {
             active_model.default_purchase_price = sea_orm::ActiveValue::Set(default_purchase_price);
         }
 
+        if let Some(min_stock) = input.min_stock {
+            validate_reorder(min_stock, None)?;
+            active_model.min_stock = sea_orm::ActiveValue::Set(min_stock);
+        }
+
+        if let Some(reorder_quantity) = input.reorder_quantity {
+            validate_reorder(None, reorder_quantity)?;
+            active_model.reorder_quantity = sea_orm::ActiveValue::Set(reorder_quantity);
+        }
+
         if let Some(sku) = input.sku {
             active_model.sku = sea_orm::ActiveValue::Set(sku);
         }
@@ -0,0 +1,113 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 利润报表查询 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitReportDto {
+    /// 开始日期（按结账时间筛选，格式 YYYY-MM-DD，含）
+    pub start_date: String,
+    /// 结束日期（格式 YYYY-MM-DD，含）
+    pub end_date: String,
+    /// 客户筛选（可选）
+    pub customer_id: Option<i64>,
+    /// 品类筛选（可选，按明细商品所属品类筛选）
+    pub category_id: Option<i64>,
+}
+
+/// 利润指标
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitMetrics {
+    /// 销售收入（扣除整单优惠和退货）
+    pub revenue: Decimal,
+    /// 销售成本（无成本的明细不计入）
+    pub cost: Decimal,
+    /// 毛利（收入 - 成本）
+    pub gross_profit: Decimal,
+    /// 毛利率（百分比，收入为 0 时为 None）
+    pub margin_rate: Option<Decimal>,
+    /// 无法确定成本的收入（明细商品既无采购记录也无参考采购价）
+    pub uncosted_revenue: Decimal,
+}
+
+/// 按订单汇总的利润
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderProfit {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 订单编号
+    pub order_no: String,
+    /// 客户 ID（散客为 None）
+    pub customer_id: Option<i64>,
+    /// 客户名称
+    pub customer_name: Option<String>,
+    /// 结账时间
+    pub date: NaiveDateTime,
+    #[serde(flatten)]
+    pub metrics: ProfitMetrics,
+}
+
+/// 按商品汇总的利润（同一商品不同单位分别汇总）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductProfit {
+    /// 商品 ID
+    pub product_id: i64,
+    /// 商品名称
+    pub product_name: String,
+    /// 单位
+    pub unit: String,
+    /// 净销售数量（扣除退货）
+    pub quantity: Decimal,
+    #[serde(flatten)]
+    pub metrics: ProfitMetrics,
+}
+
+/// 按品类汇总的利润
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryProfit {
+    /// 品类 ID（商品已删除或品类不存在时为 None）
+    pub category_id: Option<i64>,
+    /// 品类名称
+    pub category_name: String,
+    #[serde(flatten)]
+    pub metrics: ProfitMetrics,
+}
+
+/// 按客户汇总的利润
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerProfit {
+    /// 客户 ID（散客为 None）
+    pub customer_id: Option<i64>,
+    /// 客户名称
+    pub customer_name: String,
+    /// 订单数
+    pub order_count: i64,
+    #[serde(flatten)]
+    pub metrics: ProfitMetrics,
+}
+
+/// 利润报表
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitReport {
+    /// 开始日期
+    pub start_date: NaiveDate,
+    /// 结束日期（含）
+    pub end_date: NaiveDate,
+    /// 期间汇总
+    pub totals: ProfitMetrics,
+    /// 按订单（按结账时间升序）
+    pub orders: Vec<OrderProfit>,
+    /// 按商品（按毛利降序）
+    pub products: Vec<ProductProfit>,
+    /// 按品类（按毛利降序）
+    pub categories: Vec<CategoryProfit>,
+    /// 按客户（按毛利降序）
+    pub customers: Vec<CustomerProfit>,
+}
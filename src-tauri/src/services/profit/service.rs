@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use super::dto::{
+    CategoryProfit, CustomerProfit, OrderProfit, ProductProfit, ProfitMetrics, ProfitReport,
+    ProfitReportDto,
+};
+use crate::entity::{category, order, order_item, order_return, order_return_item, product};
+use crate::enums::{OrderStatus, OrderType};
+use crate::services::category::DEFAULT_CATEGORY_NAME;
+use crate::services::costing::service::sales_item_costs;
+use crate::services::order::margin::{margin_rate, unit_costs};
+
+/// 散客显示名称
+const WALK_IN_CUSTOMER_NAME: &str = "散客";
+
+/// 解析日期（YYYY-MM-DD）
+fn parse_date(s: &str, err: &str) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| err.to_string().into())
+}
+
+impl ProfitMetrics {
+    /// 累加一条明细（成本未知时计入无成本收入）
+    fn add_line(&mut self, revenue: Decimal, cost: Option<Decimal>) {
+        self.revenue += revenue;
+        match cost {
+            Some(cost) => self.cost += cost,
+            None => self.uncosted_revenue += revenue,
+        }
+        self.gross_profit = self.revenue - self.cost;
+        self.margin_rate = margin_rate(self.gross_profit, self.revenue);
+    }
+
+    /// 合并另一组指标
+    fn add(&mut self, other: &ProfitMetrics) {
+        self.revenue += other.revenue;
+        self.cost += other.cost;
+        self.uncosted_revenue += other.uncosted_revenue;
+        self.gross_profit = self.revenue - self.cost;
+        self.margin_rate = margin_rate(self.gross_profit, self.revenue);
+    }
+}
+
+/// 利润报表服务
+#[derive(Debug)]
+pub struct ProfitService {
+    db: DatabaseConnection,
+}
+
+impl ProfitService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 生成利润报表（按订单、商品、品类、客户汇总毛利）
+    ///
+    /// 统计结账时间在期间内的已结账销售订单（部分收付款的订单尚未结账出库，不计入）；
+    /// 明细收入按整单优惠比例分摊并扣除退货，成本与销售成本报表一致，取结账出库的核算成本并扣除退货，
+    /// 出库时成本未知的按当前核算成本估算，无入库记录时取商品参考采购价
+    pub async fn get_profit_report(
+        &self,
+        input: ProfitReportDto,
+    ) -> Result<ProfitReport, Box<dyn std::error::Error>> {
+        let start_date = parse_date(&input.start_date, "无效的开始日期格式，应为 YYYY-MM-DD")?;
+        let end_date = parse_date(&input.end_date, "无效的结束日期格式，应为 YYYY-MM-DD")?;
+        if end_date < start_date {
+            return Err("结束日期不能早于开始日期".into());
+        }
+        if let Some(id) = input.category_id {
+            category::Entity::find_by_id(id)
+                .one(&self.db)
+                .await?
+                .ok_or("品类不存在")?;
+        }
+
+        let mut query = order::Entity::find()
+            .filter(order::Column::OrderType.eq(OrderType::Sales))
+            .filter(order::Column::Status.eq(OrderStatus::Settled))
+            .filter(order::Column::SettledAt.gte(start_date.and_hms_opt(0, 0, 0).unwrap()))
+            .filter(
+                order::Column::SettledAt
+                    .lt((end_date + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap()),
+            );
+        if let Some(customer_id) = input.customer_id {
+            query = query.filter(order::Column::CustomerId.eq(customer_id));
+        }
+        let orders = query
+            .order_by_asc(order::Column::SettledAt)
+            .order_by_asc(order::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        let order_ids: Vec<i64> = orders.iter().map(|o| o.id).collect();
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.is_in(order_ids.clone()))
+            .order_by_asc(order_item::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        // 退货数量按原订单明细汇总
+        let return_ids: Vec<i64> = order_return::Entity::find()
+            .filter(order_return::Column::OrderId.is_in(order_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        let mut returned: HashMap<i64, Decimal> = HashMap::new();
+        for item in order_return_item::Entity::find()
+            .filter(order_return_item::Column::ReturnId.is_in(return_ids))
+            .all(&self.db)
+            .await?
+        {
+            *returned.entry(item.order_item_id).or_default() += item.quantity;
+        }
+
+        // 商品所属品类（未设置品类的商品归入"未分类"）
+        let categories: HashMap<i64, String> = category::Entity::find()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|c| (c.id, c.name))
+            .collect();
+        let default_category_id = categories
+            .iter()
+            .find(|(_, name)| name.as_str() == DEFAULT_CATEGORY_NAME)
+            .map(|(id, _)| *id);
+        let product_categories: HashMap<i64, Option<i64>> = product::Entity::find()
+            .filter(product::Column::Id.is_in(items.iter().map(|i| i.product_id)))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p.category_id.or(default_category_id)))
+            .collect();
+
+        let order_ids: HashSet<i64> = orders.iter().map(|o| o.id).collect();
+        let item_costs = sales_item_costs(&self.db, &order_ids).await?;
+        let costs = unit_costs(&self.db, &items).await?;
+
+        let mut items_by_order: HashMap<i64, Vec<order_item::Model>> = HashMap::new();
+        for item in items {
+            items_by_order.entry(item.order_id).or_default().push(item);
+        }
+
+        let mut totals = ProfitMetrics::default();
+        let mut order_profits = Vec::new();
+        let mut products: HashMap<(i64, String), ProductProfit> = HashMap::new();
+        let mut category_metrics: HashMap<Option<i64>, ProfitMetrics> = HashMap::new();
+        let mut customers: HashMap<Option<i64>, CustomerProfit> = HashMap::new();
+
+        for o in &orders {
+            // 整单优惠按明细小计比例分摊
+            let ratio = if o.total_amount.is_zero() {
+                Decimal::ZERO
+            } else {
+                o.actual_amount / o.total_amount
+            };
+
+            let mut metrics = ProfitMetrics::default();
+            let mut has_lines = false;
+            for item in items_by_order.remove(&o.id).unwrap_or_default() {
+                let category_id = product_categories
+                    .get(&item.product_id)
+                    .copied()
+                    .unwrap_or(default_category_id);
+                if input.category_id.is_some() && category_id != input.category_id {
+                    continue;
+                }
+                has_lines = true;
+
+                let quantity = item.quantity - returned.get(&item.id).copied().unwrap_or_default();
+                if quantity <= Decimal::ZERO || item.quantity.is_zero() {
+                    continue;
+                }
+                let revenue = (item.subtotal * quantity / item.quantity * ratio).round_dp(2);
+                let cost = item_costs.get(&item.id).copied().or_else(|| {
+                    costs
+                        .get(&(item.product_id, item.unit.clone()))
+                        .map(|(unit_cost, _)| (unit_cost * quantity).round_dp(2))
+                });
+
+                metrics.add_line(revenue, cost);
+                category_metrics
+                    .entry(category_id)
+                    .or_default()
+                    .add_line(revenue, cost);
+                let product = products
+                    .entry((item.product_id, item.unit.clone()))
+                    .or_insert_with(|| ProductProfit {
+                        product_id: item.product_id,
+                        product_name: item.product_name.clone(),
+                        unit: item.unit.clone(),
+                        quantity: Decimal::ZERO,
+                        metrics: ProfitMetrics::default(),
+                    });
+                product.quantity += quantity;
+                product.metrics.add_line(revenue, cost);
+            }
+            if !has_lines {
+                continue;
+            }
+
+            totals.add(&metrics);
+            let customer = customers
+                .entry(o.customer_id)
+                .or_insert_with(|| CustomerProfit {
+                    customer_id: o.customer_id,
+                    customer_name: match o.customer_id {
+                        Some(_) => o.customer_name.clone().unwrap_or_default(),
+                        None => WALK_IN_CUSTOMER_NAME.to_string(),
+                    },
+                    order_count: 0,
+                    metrics: ProfitMetrics::default(),
+                });
+            customer.order_count += 1;
+            customer.metrics.add(&metrics);
+
+            order_profits.push(OrderProfit {
+                order_id: o.id,
+                order_no: o.order_no.clone(),
+                customer_id: o.customer_id,
+                customer_name: o.customer_name.clone(),
+                date: o.settled_at.unwrap_or(o.create_at),
+                metrics,
+            });
+        }
+
+        let mut products: Vec<ProductProfit> = products.into_values().collect();
+        products.sort_by(|a, b| {
+            b.metrics
+                .gross_profit
+                .cmp(&a.metrics.gross_profit)
+                .then(a.product_id.cmp(&b.product_id))
+                .then(a.unit.cmp(&b.unit))
+        });
+        let mut categories: Vec<CategoryProfit> = category_metrics
+            .into_iter()
+            .map(|(category_id, metrics)| CategoryProfit {
+                category_id,
+                category_name: category_id
+                    .and_then(|id| categories.get(&id).cloned())
+                    .unwrap_or_else(|| DEFAULT_CATEGORY_NAME.to_string()),
+                metrics,
+            })
+            .collect();
+        categories.sort_by(|a, b| {
+            b.metrics
+                .gross_profit
+                .cmp(&a.metrics.gross_profit)
+                .then(a.category_id.cmp(&b.category_id))
+        });
+        let mut customers: Vec<CustomerProfit> = customers.into_values().collect();
+        customers.sort_by(|a, b| {
+            b.metrics
+                .gross_profit
+                .cmp(&a.metrics.gross_profit)
+                .then(a.customer_id.cmp(&b.customer_id))
+        });
+
+        Ok(ProfitReport {
+            start_date,
+            end_date,
+            totals,
+            orders: order_profits,
+            products,
+            categories,
+            customers,
+        })
+    }
+}
@@ -0,0 +1,56 @@
+use crate::entity::{quotation, quotation_item};
+use crate::services::order::dto::CreateOrderItemDto;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 创建报价单 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateQuotationDto {
+    /// 客户 ID（散客为 None）
+    pub customer_id: Option<i64>,
+    /// 客户名称（冗余快照，与 customer_id 一同传入）
+    pub customer_name: Option<String>,
+    /// 报价明细列表（与订单明细结构一致）
+    pub items: Vec<CreateOrderItemDto>,
+    /// 有效期截止日期（格式 YYYY-MM-DD，当日有效）
+    pub valid_until: String,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+/// 编辑报价单 DTO（仅草稿和已发送的报价单可编辑）
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateQuotationDto {
+    /// 报价单 ID
+    pub quotation_id: i64,
+    /// 报价明细列表（可选，传入则替换原有明细）
+    pub items: Option<Vec<CreateOrderItemDto>>,
+    /// 有效期截止日期（可选，格式 YYYY-MM-DD）
+    pub valid_until: Option<String>,
+    /// 备注（可选，传入则更新备注）
+    pub remark: Option<String>,
+}
+
+/// 报价单转订单 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertQuotationDto {
+    /// 报价单 ID
+    pub quotation_id: i64,
+    /// 实收金额（可选，默认等于报价总额）
+    pub actual_amount: Option<Decimal>,
+    /// 预计收付款日期（可选，格式 YYYY-MM-DD）
+    pub due_date: Option<String>,
+    /// 订单备注（可选，默认沿用报价单备注）
+    pub remark: Option<String>,
+}
+
+/// 报价单详情（报价单 + 明细列表）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotationDetail {
+    pub quotation: quotation::Model,
+    pub items: Vec<quotation_item::Model>,
+}
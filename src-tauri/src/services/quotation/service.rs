@@ -0,0 +1,326 @@
+use chrono::{Local, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+
+use super::dto::{ConvertQuotationDto, CreateQuotationDto, QuotationDetail, UpdateQuotationDto};
+use crate::entity::order::{self, Model as OrderModel};
+use crate::entity::quotation::{self, ActiveModel as QuotationActiveModel};
+use crate::entity::quotation_item::{self, ActiveModel as QuotationItemActiveModel};
+use crate::enums::{DocumentType, QuotationStatus};
+use crate::services::numbering::service::next_document_no;
+use crate::services::order::dto::{CreateOrderDto, CreateOrderItemDto};
+use crate::services::order::service::{item_discount, parse_datetime};
+use crate::services::order::OrderService;
+
+/// 解析有效期截止日期（仅日期时取当日 23:59:59）
+fn parse_valid_until(s: &str) -> Result<NaiveDateTime, Box<dyn std::error::Error>> {
+    parse_datetime(s, true).map_err(|_| "无效的有效期日期".to_string().into())
+}
+
+/// 写入报价明细并返回报价总额（明细折扣后小计之和）
+async fn insert_items<C: ConnectionTrait>(
+    conn: &C,
+    quotation_id: i64,
+    items: &[CreateOrderItemDto],
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    if items.is_empty() {
+        return Err("报价明细不能为空".into());
+    }
+
+    let mut total_amount = Decimal::ZERO;
+    for item in items {
+        let discount = item_discount(item)?;
+        let subtotal = item.quantity * item.unit_price - discount;
+        let item_active = QuotationItemActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            quotation_id: Set(quotation_id),
+            product_id: Set(item.product_id),
+            product_name: Set(item.product_name.clone()),
+            quantity: Set(item.quantity),
+            unit: Set(item.unit.clone()),
+            unit_price: Set(item.unit_price),
+            discount_amount: Set(discount),
+            discount_rate: Set(item.discount_rate),
+            subtotal: Set(subtotal),
+            remark: Set(item.remark.clone()),
+        };
+        item_active.insert(conn).await?;
+        total_amount += subtotal;
+    }
+
+    Ok(total_amount)
+}
+
+/// 报价单服务
+#[derive(Debug)]
+pub struct QuotationService {
+    db: DatabaseConnection,
+}
+
+impl QuotationService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 将已超过有效期的草稿 / 已发送报价单标记为已过期
+    async fn expire_overdue(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let now = Local::now().naive_local();
+        quotation::Entity::update_many()
+            .col_expr(
+                quotation::Column::Status,
+                Expr::value(QuotationStatus::Expired),
+            )
+            .filter(
+                quotation::Column::Status.is_in([QuotationStatus::Draft, QuotationStatus::Sent]),
+            )
+            .filter(quotation::Column::ValidUntil.lt(now))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// 查找报价单（先刷新过期状态）
+    async fn find_quotation(
+        &self,
+        id: i64,
+    ) -> Result<quotation::Model, Box<dyn std::error::Error>> {
+        self.expire_overdue().await?;
+        let quotation = quotation::Entity::find_by_id(id)
+            .one(&self.db)
+            .await?
+            .ok_or("报价单不存在")?;
+        Ok(quotation)
+    }
+
+    /// 创建报价单（状态为草稿）
+    pub async fn create_quotation(
+        &self,
+        input: CreateQuotationDto,
+    ) -> Result<QuotationDetail, Box<dyn std::error::Error>> {
+        if input.items.is_empty() {
+            return Err("报价明细不能为空".into());
+        }
+
+        let now = Local::now().naive_local();
+        let valid_until = parse_valid_until(&input.valid_until)?;
+        if valid_until < now {
+            return Err("有效期不能早于今天".into());
+        }
+
+        let txn = self.db.begin().await?;
+
+        let quotation_no = next_document_no(&txn, DocumentType::Quotation, now).await?;
+        let quotation = QuotationActiveModel {
+            quotation_no: Set(quotation_no),
+            customer_id: Set(input.customer_id),
+            customer_name: Set(input.customer_name),
+            total_amount: Set(Decimal::ZERO),
+            status: Set(QuotationStatus::Draft),
+            valid_until: Set(valid_until),
+            order_id: Set(None),
+            remark: Set(input.remark),
+            create_at: Set(now),
+            sent_at: Set(None),
+            accepted_at: Set(None),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        let total_amount = insert_items(&txn, quotation.id, &input.items).await?;
+        let mut quotation_active: QuotationActiveModel = quotation.into();
+        quotation_active.total_amount = Set(total_amount);
+        let quotation = quotation_active.update(&txn).await?;
+
+        let items = quotation_item::Entity::find()
+            .filter(quotation_item::Column::QuotationId.eq(quotation.id))
+            .all(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(QuotationDetail { quotation, items })
+    }
+
+    /// 编辑报价单（仅草稿和已发送的报价单可编辑）
+    pub async fn update_quotation(
+        &self,
+        input: UpdateQuotationDto,
+    ) -> Result<quotation::Model, Box<dyn std::error::Error>> {
+        let quotation = self.find_quotation(input.quotation_id).await?;
+        match quotation.status {
+            QuotationStatus::Draft | QuotationStatus::Sent => {}
+            QuotationStatus::Accepted => return Err("报价单已转为订单，不可编辑".into()),
+            QuotationStatus::Expired => return Err("报价单已过期，不可编辑".into()),
+        }
+
+        let txn = self.db.begin().await?;
+        let mut quotation_active: QuotationActiveModel = quotation.into();
+
+        if let Some(remark) = input.remark {
+            quotation_active.remark = Set(Some(remark));
+        }
+
+        if let Some(valid_until) = &input.valid_until {
+            let valid_until = parse_valid_until(valid_until)?;
+            if valid_until < Local::now().naive_local() {
+                return Err("有效期不能早于今天".into());
+            }
+            quotation_active.valid_until = Set(valid_until);
+        }
+
+        // 更新明细（替换方式）
+        if let Some(items) = input.items {
+            quotation_item::Entity::delete_many()
+                .filter(quotation_item::Column::QuotationId.eq(input.quotation_id))
+                .exec(&txn)
+                .await?;
+            let total_amount = insert_items(&txn, input.quotation_id, &items).await?;
+            quotation_active.total_amount = Set(total_amount);
+        }
+
+        let updated = quotation_active.update(&txn).await?;
+        txn.commit().await?;
+
+        Ok(updated)
+    }
+
+    /// 标记报价单已发送给客户
+    pub async fn send_quotation(
+        &self,
+        id: i64,
+    ) -> Result<quotation::Model, Box<dyn std::error::Error>> {
+        let quotation = self.find_quotation(id).await?;
+        if quotation.status != QuotationStatus::Draft {
+            return Err("只有草稿状态的报价单可发送".into());
+        }
+
+        let mut quotation_active: QuotationActiveModel = quotation.into();
+        quotation_active.status = Set(QuotationStatus::Sent);
+        quotation_active.sent_at = Set(Some(Local::now().naive_local()));
+        Ok(quotation_active.update(&self.db).await?)
+    }
+
+    /// 报价单转为销售订单（通过 OrderService::create_order 创建待结账订单，并互相关联）
+    pub async fn convert_to_order(
+        &self,
+        input: ConvertQuotationDto,
+    ) -> Result<OrderModel, Box<dyn std::error::Error>> {
+        let quotation = self.find_quotation(input.quotation_id).await?;
+        match quotation.status {
+            QuotationStatus::Draft | QuotationStatus::Sent => {}
+            QuotationStatus::Accepted => return Err("报价单已转为订单".into()),
+            QuotationStatus::Expired => return Err("报价单已过期，不可转为订单".into()),
+        }
+
+        let items = quotation_item::Entity::find()
+            .filter(quotation_item::Column::QuotationId.eq(quotation.id))
+            .order_by_asc(quotation_item::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        // 按报价明细构造订单明细（折扣率优先，保证折扣金额与报价一致）
+        let order_items = items
+            .into_iter()
+            .map(|item| CreateOrderItemDto {
+                product_id: item.product_id,
+                product_name: item.product_name,
+                quantity: item.quantity,
+                unit: item.unit,
+                unit_price: item.unit_price,
+                discount_amount: match item.discount_rate {
+                    Some(_) => None,
+                    None if item.discount_amount.is_zero() => None,
+                    None => Some(item.discount_amount),
+                },
+                discount_rate: item.discount_rate,
+                remark: item.remark,
+            })
+            .collect();
+
+        let order = OrderService::new(self.db.clone())
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: quotation.customer_id,
+                customer_name: quotation.customer_name.clone(),
+                items: order_items,
+                remark: input.remark.or_else(|| quotation.remark.clone()),
+                actual_amount: input.actual_amount,
+                sub_type: None,
+                due_date: input.due_date,
+            })
+            .await?;
+
+        // 回写关联关系
+        let txn = self.db.begin().await?;
+
+        let quotation_id = quotation.id;
+        let mut quotation_active: QuotationActiveModel = quotation.into();
+        quotation_active.status = Set(QuotationStatus::Accepted);
+        quotation_active.order_id = Set(Some(order.id));
+        quotation_active.accepted_at = Set(Some(Local::now().naive_local()));
+        quotation_active.update(&txn).await?;
+
+        let mut order_active: order::ActiveModel = order.into();
+        order_active.quotation_id = Set(Some(quotation_id));
+        let order = order_active.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(order)
+    }
+
+    /// 根据 ID 查询报价单（含明细）
+    pub async fn get_quotation_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<QuotationDetail>, Box<dyn std::error::Error>> {
+        self.expire_overdue().await?;
+        let quotation = quotation::Entity::find_by_id(id).one(&self.db).await?;
+
+        match quotation {
+            Some(q) => {
+                let items = quotation_item::Entity::find()
+                    .filter(quotation_item::Column::QuotationId.eq(q.id))
+                    .order_by_asc(quotation_item::Column::Id)
+                    .all(&self.db)
+                    .await?;
+                Ok(Some(QuotationDetail {
+                    quotation: q,
+                    items,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 查询所有报价单（按创建时间倒序）
+    pub async fn get_all_quotations(
+        &self,
+    ) -> Result<Vec<quotation::Model>, Box<dyn std::error::Error>> {
+        self.expire_overdue().await?;
+        let quotations = quotation::Entity::find()
+            .order_by_desc(quotation::Column::CreateAt)
+            .all(&self.db)
+            .await?;
+        Ok(quotations)
+    }
+
+    /// 按客户查询报价单（按创建时间倒序）
+    pub async fn get_quotations_by_customer_id(
+        &self,
+        customer_id: i64,
+    ) -> Result<Vec<quotation::Model>, Box<dyn std::error::Error>> {
+        self.expire_overdue().await?;
+        let quotations = quotation::Entity::find()
+            .filter(quotation::Column::CustomerId.eq(customer_id))
+            .order_by_desc(quotation::Column::CreateAt)
+            .all(&self.db)
+            .await?;
+        Ok(quotations)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::enums::{AccountingChannel, OrderStatus, OrderType};
+use crate::services::document::html::money;
+use crate::services::document::label::channel_label;
+use crate::services::document::DocumentFormat;
+use crate::services::order::dto::ChannelPayment;
+use crate::services::setting::dto::ShopProfile;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 票据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiptKind {
+    /// 收据（含收款信息）
+    Receipt,
+    /// 送货单（含签收栏）
+    DeliveryNote,
+}
+
+impl std::str::FromStr for ReceiptKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Receipt" => Ok(ReceiptKind::Receipt),
+            "DeliveryNote" => Ok(ReceiptKind::DeliveryNote),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ReceiptKind {
+    /// 票据标题
+    pub fn title(&self) -> &'static str {
+        match self {
+            ReceiptKind::Receipt => "收据",
+            ReceiptKind::DeliveryNote => "送货单",
+        }
+    }
+}
+
+/// 导出订单票据 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportReceiptDto {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 票据类型（Receipt / DeliveryNote，默认 Receipt）
+    pub kind: Option<String>,
+    /// 输出格式（Html / Pdf，默认 Pdf）
+    pub format: Option<String>,
+    /// 是否保存为订单附件（默认否）
+    pub save_as_attachment: Option<bool>,
+}
+
+/// 票据商品明细
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptItem {
+    pub product_name: String,
+    pub quantity: Decimal,
+    pub unit: String,
+    pub unit_price: Decimal,
+    /// 明细折扣金额
+    pub discount_amount: Decimal,
+    /// 小计（明细折扣后）
+    pub subtotal: Decimal,
+}
+
+/// 订单票据
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderReceipt {
+    pub kind: ReceiptKind,
+    /// 店铺抬头
+    pub shop: ShopProfile,
+    pub order_id: i64,
+    pub order_no: String,
+    pub order_type: OrderType,
+    pub order_date: NaiveDateTime,
+    pub status: OrderStatus,
+    pub customer_name: Option<String>,
+    pub customer_phone: Option<String>,
+    pub customer_address: Option<String>,
+    pub items: Vec<ReceiptItem>,
+    /// 应收/应付总额
+    pub total_amount: Decimal,
+    /// 整单优惠（应收 - 实收）
+    pub discount_amount: Decimal,
+    /// 实收/实付总额
+    pub actual_amount: Decimal,
+    /// 实收金额大写
+    pub actual_amount_uppercase: String,
+    /// 已收付款金额
+    pub paid_amount: Decimal,
+    /// 未结金额
+    pub outstanding_amount: Decimal,
+    pub channel: AccountingChannel,
+    /// 按渠道汇总的收付款（组合支付时有多项）
+    pub payment_channels: Vec<ChannelPayment>,
+    pub remark: Option<String>,
+    pub generated_at: NaiveDateTime,
+}
+
+impl OrderReceipt {
+    /// 结算渠道说明（组合支付时列出各渠道金额，如"现金 50.00 + 微信 70.00"）
+    pub fn channel_text(&self) -> String {
+        match self.payment_channels.as_slice() {
+            [] => channel_label(&self.channel).to_string(),
+            [single] => channel_label(&single.channel).to_string(),
+            payments => payments
+                .iter()
+                .map(|p| format!("{} {}", channel_label(&p.channel), money(p.amount)))
+                .collect::<Vec<_>>()
+                .join(" + "),
+        }
+    }
+}
+
+/// 已导出的票据文件
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptFile {
+    pub receipt: OrderReceipt,
+    pub format: DocumentFormat,
+    /// 文件保存路径
+    pub file_path: String,
+    /// 保存为订单附件时的附件 ID
+    pub attachment_id: Option<i64>,
+}
@@ -0,0 +1,436 @@
+use std::path::Path;
+
+use chrono::Local;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+use super::dto::{ExportReceiptDto, OrderReceipt, ReceiptFile, ReceiptItem, ReceiptKind};
+use crate::entity::{attachment, customer, order, order_item};
+use crate::enums::{OrderStatus, OrderType};
+use crate::services::document::html::{self, escape, money, quantity};
+use crate::services::document::label::{order_status_label, order_type_label};
+use crate::services::document::pdf::{PAGE_HEIGHT, PAGE_WIDTH};
+use crate::services::document::{uppercase_amount, write_document, DocumentFormat, PdfWriter};
+use crate::services::order::OrderService;
+use crate::services::setting::service::load_shop_profile;
+
+/// 订单票据服务（收据 / 送货单）
+#[derive(Debug)]
+pub struct ReceiptService {
+    db: DatabaseConnection,
+}
+
+impl ReceiptService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 生成订单票据数据（已取消订单不可出票）
+    pub async fn generate_receipt(
+        &self,
+        order_id: i64,
+        kind: ReceiptKind,
+    ) -> Result<OrderReceipt, Box<dyn std::error::Error>> {
+        let order = order::Entity::find_by_id(order_id)
+            .one(&self.db)
+            .await?
+            .ok_or("订单不存在")?;
+        if order.status == OrderStatus::Cancelled {
+            return Err("已取消订单不可打印票据".into());
+        }
+
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(order.id))
+            .order_by_asc(order_item::Column::Id)
+            .all(&self.db)
+            .await?;
+        let customer = match order.customer_id {
+            Some(id) => customer::Entity::find_by_id(id).one(&self.db).await?,
+            None => None,
+        };
+        let balance = OrderService::new(self.db.clone())
+            .get_order_balance(order.id)
+            .await?;
+        let shop = load_shop_profile(&self.db).await?;
+
+        Ok(OrderReceipt {
+            kind,
+            shop,
+            order_id: order.id,
+            order_no: order.order_no,
+            order_type: order.order_type,
+            order_date: order.create_at,
+            status: order.status,
+            customer_name: customer
+                .as_ref()
+                .map(|c| c.name.clone())
+                .or(order.customer_name),
+            customer_phone: customer.as_ref().map(|c| c.phone.clone()),
+            customer_address: customer.and_then(|c| c.address),
+            items: items
+                .into_iter()
+                .map(|item| ReceiptItem {
+                    product_name: item.product_name,
+                    quantity: item.quantity,
+                    unit: item.unit,
+                    unit_price: item.unit_price,
+                    discount_amount: item.discount_amount,
+                    subtotal: item.subtotal,
+                })
+                .collect(),
+            total_amount: order.total_amount,
+            discount_amount: order.total_amount - order.actual_amount,
+            actual_amount: order.actual_amount,
+            actual_amount_uppercase: uppercase_amount(order.actual_amount),
+            paid_amount: balance.paid_amount,
+            outstanding_amount: balance.outstanding_amount,
+            channel: order.channel,
+            payment_channels: balance.payment_channels,
+            remark: order.remark,
+            generated_at: Local::now().naive_local(),
+        })
+    }
+
+    /// 生成票据并渲染为 HTML / PDF 文件，保存至指定目录（可选登记为订单附件）
+    pub async fn export_receipt(
+        &self,
+        input: ExportReceiptDto,
+        output_dir: &Path,
+    ) -> Result<ReceiptFile, Box<dyn std::error::Error>> {
+        let kind = match &input.kind {
+            Some(s) => s
+                .parse::<ReceiptKind>()
+                .map_err(|_| format!("无效的票据类型: {}", s))?,
+            None => ReceiptKind::Receipt,
+        };
+        let format = match &input.format {
+            Some(s) => s
+                .parse::<DocumentFormat>()
+                .map_err(|_| format!("无效的票据格式: {}", s))?,
+            None => DocumentFormat::Pdf,
+        };
+
+        let receipt = self.generate_receipt(input.order_id, kind).await?;
+        let bytes = match format {
+            DocumentFormat::Html => Self::render_html(&receipt).into_bytes(),
+            DocumentFormat::Pdf => Self::render_pdf(&receipt),
+        };
+        let file_size = bytes.len();
+
+        let file_path = write_document(
+            output_dir,
+            &format!("{}-{}", kind.title(), receipt.order_no),
+            format,
+            receipt.generated_at,
+            bytes,
+        )
+        .await?;
+        let file_path = file_path.to_string_lossy().to_string();
+
+        // 登记为订单附件（master_id 为订单 ID）
+        let attachment_id = if input.save_as_attachment.unwrap_or(false) {
+            let file_name = Path::new(&file_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let attachment = attachment::ActiveModel {
+                id: sea_orm::ActiveValue::NotSet,
+                master_id: Set(receipt.order_id),
+                path: Set(file_path.clone()),
+                file_name: Set(file_name),
+                file_suffix: Set(format.extension().to_string()),
+                file_size: Set(file_size.to_string()),
+                create_at: Set(receipt.generated_at),
+            }
+            .insert(&self.db)
+            .await?;
+            Some(attachment.id)
+        } else {
+            None
+        };
+
+        Ok(ReceiptFile {
+            receipt,
+            format,
+            file_path,
+            attachment_id,
+        })
+    }
+
+    /// 票据标题（如"销售收据"、"采购送货单"）
+    fn title(receipt: &OrderReceipt) -> String {
+        format!(
+            "{}{}",
+            order_type_label(&receipt.order_type),
+            receipt.kind.title()
+        )
+    }
+
+    /// 对方称谓（销售为客户，采购为供应商）
+    fn party_label(receipt: &OrderReceipt) -> &'static str {
+        match receipt.order_type {
+            OrderType::Sales => "客户",
+            OrderType::Purchase => "供应商",
+        }
+    }
+
+    /// 金额汇总行（名称, 金额）
+    fn summary_rows(receipt: &OrderReceipt) -> Vec<(&'static str, Decimal)> {
+        let mut rows = vec![("合计", receipt.total_amount)];
+        if !receipt.discount_amount.is_zero() {
+            rows.push(("整单优惠", receipt.discount_amount));
+        }
+        match receipt.order_type {
+            OrderType::Sales => rows.push(("实收金额", receipt.actual_amount)),
+            OrderType::Purchase => rows.push(("实付金额", receipt.actual_amount)),
+        }
+        if receipt.kind == ReceiptKind::Receipt {
+            rows.push(("已结金额", receipt.paid_amount));
+            rows.push(("未结金额", receipt.outstanding_amount));
+        }
+        rows
+    }
+
+    /// 渲染票据 HTML
+    pub fn render_html(receipt: &OrderReceipt) -> String {
+        let shop = &receipt.shop;
+        let title = Self::title(receipt);
+        let mut body = String::new();
+
+        if !shop.shop_name.is_empty() {
+            body.push_str(&format!("<h1>{}</h1>\n", escape(&shop.shop_name)));
+        }
+        let contact: Vec<String> = [
+            shop.address
+                .as_ref()
+                .map(|a| format!("地址：{}", escape(a))),
+            shop.phone.as_ref().map(|p| format!("电话：{}", escape(p))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !contact.is_empty() {
+            body.push_str(&format!("<p class=\"meta\">{}</p>\n", contact.join("　")));
+        }
+        body.push_str(&format!(
+            "<h2 style=\"text-align: center\">{}</h2>\n",
+            title
+        ));
+        body.push_str(&format!(
+            "<p>单号：{}　日期：{}　状态：{}</p>\n",
+            escape(&receipt.order_no),
+            receipt.order_date.format("%Y-%m-%d %H:%M"),
+            order_status_label(&receipt.status)
+        ));
+        body.push_str(&format!(
+            "<p>{}：{}　电话：{}</p>\n",
+            Self::party_label(receipt),
+            escape(receipt.customer_name.as_deref().unwrap_or("散客")),
+            escape(receipt.customer_phone.as_deref().unwrap_or("-"))
+        ));
+        if receipt.kind == ReceiptKind::DeliveryNote {
+            body.push_str(&format!(
+                "<p>送货地址：{}</p>\n",
+                escape(receipt.customer_address.as_deref().unwrap_or("-"))
+            ));
+        }
+
+        body.push_str("<table>\n<tr><th>序号</th><th>商品</th><th>数量</th><th>单价</th><th>折扣</th><th>金额</th></tr>\n");
+        for (idx, item) in receipt.items.iter().enumerate() {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td class=\"num\">{} {}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+                idx + 1,
+                escape(&item.product_name),
+                quantity(item.quantity),
+                escape(&item.unit),
+                money(item.unit_price),
+                money(item.discount_amount),
+                money(item.subtotal)
+            ));
+        }
+        for (label, amount) in Self::summary_rows(receipt) {
+            body.push_str(&format!(
+                "<tr><th colspan=\"5\" class=\"num\">{}</th><td class=\"num\">{}</td></tr>\n",
+                label,
+                money(amount)
+            ));
+        }
+        body.push_str(&format!(
+            "<tr><th colspan=\"2\">大写金额</th><td colspan=\"4\">{}</td></tr>\n",
+            receipt.actual_amount_uppercase
+        ));
+        body.push_str("</table>\n");
+
+        if receipt.kind == ReceiptKind::Receipt && !receipt.paid_amount.is_zero() {
+            body.push_str(&format!("<p>结算渠道：{}</p>\n", receipt.channel_text()));
+        }
+        if let Some(remark) = &receipt.remark {
+            body.push_str(&format!("<p>备注：{}</p>\n", escape(remark)));
+        }
+        if receipt.kind == ReceiptKind::DeliveryNote {
+            body.push_str("<p>送货人：＿＿＿＿＿＿　　收货人签字：＿＿＿＿＿＿</p>\n");
+        }
+        if let Some(footer) = &shop.receipt_footer {
+            body.push_str(&format!("<p class=\"meta\">{}</p>\n", escape(footer)));
+        }
+        body.push_str(&format!(
+            "<p class=\"meta\">打印时间：{}</p>\n",
+            receipt.generated_at.format("%Y-%m-%d %H:%M:%S")
+        ));
+
+        html::page(&format!("{}-{}", title, receipt.order_no), &body)
+    }
+
+    /// 渲染票据 PDF（A4 纵向，超出一页自动分页）
+    pub fn render_pdf(receipt: &OrderReceipt) -> Vec<u8> {
+        const MARGIN: f32 = 40.0;
+        const LINE_HEIGHT: f32 = 16.0;
+        const FONT_SIZE: f32 = 10.0;
+        let right = PAGE_WIDTH - MARGIN;
+        let shop = &receipt.shop;
+
+        let mut pdf = PdfWriter::new();
+        let mut y = PAGE_HEIGHT - MARGIN;
+
+        // 剩余空间不足时换页
+        let next_line = |pdf: &mut PdfWriter, y: &mut f32| {
+            *y -= LINE_HEIGHT;
+            if *y < MARGIN {
+                pdf.new_page();
+                *y = PAGE_HEIGHT - MARGIN - LINE_HEIGHT;
+            }
+        };
+
+        if !shop.shop_name.is_empty() {
+            pdf.text_center(PAGE_WIDTH / 2.0, y - 18.0, 18.0, &shop.shop_name);
+            y -= 30.0;
+        }
+        let contact: Vec<String> = [
+            shop.address.as_ref().map(|a| format!("地址：{}", a)),
+            shop.phone.as_ref().map(|p| format!("电话：{}", p)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !contact.is_empty() {
+            pdf.text_center(PAGE_WIDTH / 2.0, y - 8.0, 9.0, &contact.join("    "));
+            y -= 16.0;
+        }
+        pdf.text_center(PAGE_WIDTH / 2.0, y - 18.0, 15.0, &Self::title(receipt));
+        y -= 40.0;
+
+        pdf.text(
+            MARGIN,
+            y,
+            FONT_SIZE,
+            &format!(
+                "单号：{}    日期：{}    状态：{}",
+                receipt.order_no,
+                receipt.order_date.format("%Y-%m-%d %H:%M"),
+                order_status_label(&receipt.status)
+            ),
+        );
+        next_line(&mut pdf, &mut y);
+        pdf.text(
+            MARGIN,
+            y,
+            FONT_SIZE,
+            &format!(
+                "{}：{}    电话：{}",
+                Self::party_label(receipt),
+                receipt.customer_name.as_deref().unwrap_or("散客"),
+                receipt.customer_phone.as_deref().unwrap_or("-")
+            ),
+        );
+        next_line(&mut pdf, &mut y);
+        if receipt.kind == ReceiptKind::DeliveryNote {
+            pdf.text(
+                MARGIN,
+                y,
+                FONT_SIZE,
+                &format!(
+                    "送货地址：{}",
+                    receipt.customer_address.as_deref().unwrap_or("-")
+                ),
+            );
+            next_line(&mut pdf, &mut y);
+        }
+        next_line(&mut pdf, &mut y);
+
+        // 明细表头
+        pdf.text(MARGIN, y, FONT_SIZE, "序号");
+        pdf.text(MARGIN + 36.0, y, FONT_SIZE, "商品");
+        pdf.text_right(320.0, y, FONT_SIZE, "数量");
+        pdf.text_right(400.0, y, FONT_SIZE, "单价");
+        pdf.text_right(460.0, y, FONT_SIZE, "折扣");
+        pdf.text_right(right, y, FONT_SIZE, "金额");
+        pdf.line(MARGIN, y - 4.0, right, y - 4.0);
+        next_line(&mut pdf, &mut y);
+        for (idx, item) in receipt.items.iter().enumerate() {
+            pdf.text(MARGIN, y, FONT_SIZE, &(idx + 1).to_string());
+            pdf.text(MARGIN + 36.0, y, FONT_SIZE, &item.product_name);
+            pdf.text_right(
+                320.0,
+                y,
+                FONT_SIZE,
+                &format!("{} {}", quantity(item.quantity), item.unit),
+            );
+            pdf.text_right(400.0, y, FONT_SIZE, &money(item.unit_price));
+            pdf.text_right(460.0, y, FONT_SIZE, &money(item.discount_amount));
+            pdf.text_right(right, y, FONT_SIZE, &money(item.subtotal));
+            next_line(&mut pdf, &mut y);
+        }
+        pdf.line(MARGIN, y + LINE_HEIGHT / 2.0, right, y + LINE_HEIGHT / 2.0);
+
+        for (label, amount) in Self::summary_rows(receipt) {
+            pdf.text_right(460.0, y, FONT_SIZE, label);
+            pdf.text_right(right, y, FONT_SIZE, &money(amount));
+            next_line(&mut pdf, &mut y);
+        }
+        pdf.text(
+            MARGIN,
+            y,
+            FONT_SIZE,
+            &format!("大写金额：{}", receipt.actual_amount_uppercase),
+        );
+        next_line(&mut pdf, &mut y);
+
+        if receipt.kind == ReceiptKind::Receipt && !receipt.paid_amount.is_zero() {
+            pdf.text(
+                MARGIN,
+                y,
+                FONT_SIZE,
+                &format!("结算渠道：{}", receipt.channel_text()),
+            );
+            next_line(&mut pdf, &mut y);
+        }
+        if let Some(remark) = &receipt.remark {
+            pdf.text(MARGIN, y, FONT_SIZE, &format!("备注：{}", remark));
+            next_line(&mut pdf, &mut y);
+        }
+        if receipt.kind == ReceiptKind::DeliveryNote {
+            next_line(&mut pdf, &mut y);
+            pdf.text(MARGIN, y, FONT_SIZE, "送货人：");
+            pdf.text(PAGE_WIDTH / 2.0, y, FONT_SIZE, "收货人签字：");
+            next_line(&mut pdf, &mut y);
+        }
+        next_line(&mut pdf, &mut y);
+        if let Some(footer) = &shop.receipt_footer {
+            pdf.text_center(PAGE_WIDTH / 2.0, y, 9.0, footer);
+            next_line(&mut pdf, &mut y);
+        }
+        pdf.text(
+            MARGIN,
+            y,
+            8.0,
+            &format!(
+                "打印时间：{}",
+                receipt.generated_at.format("%Y-%m-%d %H:%M:%S")
+            ),
+        );
+
+        pdf.to_bytes()
+    }
+}
@@ -0,0 +1,56 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 低于最低库存的商品
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LowStockProduct {
+    /// 商品 ID
+    pub product_id: i64,
+    /// 商品名称
+    pub product_name: String,
+    /// 计量单位（商品单位）
+    pub unit: String,
+    /// 当前库存
+    pub on_hand: Decimal,
+    /// 在途数量（待结账、部分付款的采购订单中商品单位的数量）
+    pub on_order: Decimal,
+    /// 最低库存
+    pub min_stock: Decimal,
+    /// 补货数量（商品设置）
+    pub reorder_quantity: Option<Decimal>,
+    /// 缺口数量（最低库存 - 当前库存 - 在途数量，在途已补足时为 0）
+    pub shortfall: Decimal,
+    /// 建议采购数量（补货数量与缺口数量取大者，无缺口时为 0）
+    pub suggested_quantity: Decimal,
+    /// 建议采购单价（最近一次采购单价，无采购记录时取参考采购价）
+    pub unit_price: Option<Decimal>,
+    /// 常用供应商 ID（历史采购订单中出现次数最多的供应商，无记录时为空）
+    pub supplier_id: Option<i64>,
+    /// 常用供应商名称
+    pub supplier_name: Option<String>,
+}
+
+/// 按供应商分组的补货建议
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderSuggestion {
+    /// 供应商 ID（为空表示无历史供应商）
+    pub supplier_id: Option<i64>,
+    /// 供应商名称
+    pub supplier_name: Option<String>,
+    /// 补货商品列表
+    pub items: Vec<LowStockProduct>,
+    /// 预计采购金额（建议数量 × 建议单价，无单价的不计入）
+    pub amount: Decimal,
+}
+
+/// 按补货建议生成采购订单 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReorderOrdersDto {
+    /// 仅为指定商品生成（可选，不传则包含全部低库存商品）
+    pub product_ids: Option<Vec<i64>>,
+    /// 订单备注（可选，默认为"补货建议生成"）
+    pub remark: Option<String>,
+}
@@ -0,0 +1,317 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::sea_query::{Query, SelectStatement};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+
+use super::dto::{CreateReorderOrdersDto, LowStockProduct, ReorderSuggestion};
+use crate::entity::order::{self, Model as OrderModel};
+use crate::entity::{order_item, product, product_stock};
+use crate::enums::{OrderStatus, OrderType};
+use crate::services::order::dto::{CreateOrderDto, CreateOrderItemDto};
+use crate::services::order::service::insert_order;
+
+/// 供应商采购情况
+struct SupplierUsage {
+    name: Option<String>,
+    /// 采购订单数
+    order_count: usize,
+    /// 最近采购时间
+    last_at: NaiveDateTime,
+}
+
+/// 商品历史采购情况
+#[derive(Default)]
+struct PurchaseHistory {
+    suppliers: HashMap<i64, SupplierUsage>,
+    /// 最近一次采购（时间，单价），仅统计与商品单位一致的明细
+    last_price: Option<(NaiveDateTime, Decimal)>,
+}
+
+impl PurchaseHistory {
+    /// 常用供应商（采购订单数最多，相同时取最近采购的）
+    fn usual_supplier(&self) -> Option<(i64, Option<String>)> {
+        self.suppliers
+            .iter()
+            .max_by(|(_, a), (_, b)| {
+                a.order_count
+                    .cmp(&b.order_count)
+                    .then(a.last_at.cmp(&b.last_at))
+            })
+            .map(|(id, usage)| (*id, usage.name.clone()))
+    }
+}
+
+/// 按订单状态筛选采购订单 ID 的子查询
+fn purchase_order_ids(statuses: [OrderStatus; 2]) -> SelectStatement {
+    Query::select()
+        .column(order::Column::Id)
+        .from(order::Entity)
+        .and_where(order::Column::OrderType.eq(OrderType::Purchase))
+        .and_where(order::Column::Status.is_in(statuses))
+        .to_owned()
+}
+
+/// 汇总商品的在途数量（待结账、部分付款的采购订单中与商品单位一致的明细）
+async fn on_order_quantities<C: ConnectionTrait>(
+    conn: &C,
+    products: &[product::Model],
+) -> Result<HashMap<i64, Decimal>, Box<dyn std::error::Error>> {
+    let units: HashMap<i64, &str> = products.iter().map(|p| (p.id, p.unit.as_str())).collect();
+    let items = order_item::Entity::find()
+        .filter(order_item::Column::ProductId.is_in(units.keys().copied()))
+        .filter(order_item::Column::OrderId.in_subquery(purchase_order_ids([
+            OrderStatus::Pending,
+            OrderStatus::PartiallyPaid,
+        ])))
+        .all(conn)
+        .await?;
+
+    let mut on_order: HashMap<i64, Decimal> = HashMap::new();
+    for item in items {
+        if units.get(&item.product_id) == Some(&item.unit.as_str()) {
+            *on_order.entry(item.product_id).or_insert(Decimal::ZERO) += item.quantity;
+        }
+    }
+    Ok(on_order)
+}
+
+/// 汇总商品的历史采购情况（已结账及部分付款的采购订单）
+async fn purchase_history<C: ConnectionTrait>(
+    conn: &C,
+    products: &[product::Model],
+) -> Result<HashMap<i64, PurchaseHistory>, Box<dyn std::error::Error>> {
+    let units: HashMap<i64, &str> = products.iter().map(|p| (p.id, p.unit.as_str())).collect();
+    let items = order_item::Entity::find()
+        .filter(order_item::Column::ProductId.is_in(units.keys().copied()))
+        .filter(order_item::Column::OrderId.in_subquery(purchase_order_ids([
+            OrderStatus::Settled,
+            OrderStatus::PartiallyPaid,
+        ])))
+        .all(conn)
+        .await?;
+    let order_ids: HashSet<i64> = items.iter().map(|i| i.order_id).collect();
+    let orders: HashMap<i64, OrderModel> = order::Entity::find()
+        .filter(order::Column::Id.is_in(order_ids))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|o| (o.id, o))
+        .collect();
+
+    let mut history: HashMap<i64, PurchaseHistory> = HashMap::new();
+    let mut counted: HashSet<(i64, i64)> = HashSet::new();
+    for item in items {
+        let Some(order) = orders.get(&item.order_id) else {
+            continue;
+        };
+        let entry = history.entry(item.product_id).or_default();
+        if units.get(&item.product_id) == Some(&item.unit.as_str())
+            && entry.last_price.is_none_or(|(at, _)| order.create_at >= at)
+        {
+            entry.last_price = Some((order.create_at, item.unit_price));
+        }
+        let Some(supplier_id) = order.customer_id else {
+            continue;
+        };
+        if !counted.insert((item.product_id, order.id)) {
+            continue;
+        }
+        let usage = entry
+            .suppliers
+            .entry(supplier_id)
+            .or_insert_with(|| SupplierUsage {
+                name: order.customer_name.clone(),
+                order_count: 0,
+                last_at: order.create_at,
+            });
+        usage.order_count += 1;
+        if order.create_at >= usage.last_at {
+            usage.last_at = order.create_at;
+            usage.name = order.customer_name.clone();
+        }
+    }
+    Ok(history)
+}
+
+/// 查询低于最低库存的商品（当前库存取商品单位的结存，由已结账订单等库存变动累计；
+/// 缺口数量扣除在途数量，已下单补足的商品不再建议采购）
+async fn low_stock_products<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<Vec<LowStockProduct>, Box<dyn std::error::Error>> {
+    let products = product::Entity::find()
+        .filter(product::Column::MinStock.is_not_null())
+        .order_by_asc(product::Column::Id)
+        .all(conn)
+        .await?;
+    let stocks: HashMap<(i64, String), Decimal> = product_stock::Entity::find()
+        .filter(product_stock::Column::ProductId.is_in(products.iter().map(|p| p.id)))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|s| ((s.product_id, s.unit), s.quantity))
+        .collect();
+
+    let mut low = Vec::new();
+    let mut candidates = Vec::new();
+    for p in products {
+        let min_stock = p.min_stock.unwrap_or_default();
+        let on_hand = stocks
+            .get(&(p.id, p.unit.clone()))
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+        if on_hand < min_stock {
+            candidates.push((p, on_hand, min_stock));
+        }
+    }
+
+    let models: Vec<product::Model> = candidates.iter().map(|(p, _, _)| p.clone()).collect();
+    let history = purchase_history(conn, &models).await?;
+    let on_order = on_order_quantities(conn, &models).await?;
+    for (p, on_hand, min_stock) in candidates {
+        let on_order = on_order.get(&p.id).copied().unwrap_or(Decimal::ZERO);
+        let shortfall = (min_stock - on_hand - on_order).max(Decimal::ZERO);
+        let suggested_quantity = if shortfall.is_zero() {
+            Decimal::ZERO
+        } else {
+            p.reorder_quantity.map_or(shortfall, |q| q.max(shortfall))
+        };
+        let purchases = history.get(&p.id);
+        let supplier = purchases.and_then(PurchaseHistory::usual_supplier);
+        low.push(LowStockProduct {
+            product_id: p.id,
+            product_name: p.name,
+            unit: p.unit,
+            on_hand,
+            on_order,
+            min_stock,
+            reorder_quantity: p.reorder_quantity,
+            shortfall,
+            suggested_quantity,
+            unit_price: purchases
+                .and_then(|h| h.last_price.map(|(_, price)| price))
+                .or(p.default_purchase_price),
+            supplier_id: supplier.as_ref().map(|(id, _)| *id),
+            supplier_name: supplier.and_then(|(_, name)| name),
+        });
+    }
+    Ok(low)
+}
+
+/// 按常用供应商分组需要补货的商品（无缺口的不计入，无历史供应商的排在最后）
+fn group_by_supplier(products: Vec<LowStockProduct>) -> Vec<ReorderSuggestion> {
+    let mut groups: Vec<ReorderSuggestion> = Vec::new();
+    for item in products.into_iter().filter(|p| !p.shortfall.is_zero()) {
+        let amount = item
+            .unit_price
+            .map(|price| (price * item.suggested_quantity).round_dp(2))
+            .unwrap_or_default();
+        match groups
+            .iter_mut()
+            .find(|g| g.supplier_id == item.supplier_id)
+        {
+            Some(group) => {
+                group.amount += amount;
+                group.items.push(item);
+            }
+            None => groups.push(ReorderSuggestion {
+                supplier_id: item.supplier_id,
+                supplier_name: item.supplier_name.clone(),
+                items: vec![item],
+                amount,
+            }),
+        }
+    }
+    groups.sort_by_key(|g| g.supplier_id.is_none());
+    groups
+}
+
+/// 补货服务
+#[derive(Debug)]
+pub struct ReorderService {
+    db: DatabaseConnection,
+}
+
+impl ReorderService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查询低于最低库存的商品（仅设置了最低库存的商品）
+    pub async fn get_low_stock_products(
+        &self,
+    ) -> Result<Vec<LowStockProduct>, Box<dyn std::error::Error>> {
+        low_stock_products(&self.db).await
+    }
+
+    /// 查询按常用供应商分组的补货建议
+    pub async fn get_reorder_suggestions(
+        &self,
+    ) -> Result<Vec<ReorderSuggestion>, Box<dyn std::error::Error>> {
+        let products = low_stock_products(&self.db).await?;
+        Ok(group_by_supplier(products))
+    }
+
+    /// 按补货建议生成待结账采购订单（每个供应商一张，在同一事务中创建，任一失败则全部回滚）
+    pub async fn create_reorder_orders(
+        &self,
+        input: CreateReorderOrdersDto,
+    ) -> Result<Vec<OrderModel>, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        let mut products = low_stock_products(&txn).await?;
+        if let Some(ids) = &input.product_ids {
+            products.retain(|p| ids.contains(&p.product_id));
+        }
+        let groups = group_by_supplier(products);
+        if groups.is_empty() {
+            return Err("没有需要补货的商品".into());
+        }
+
+        let mut orders = Vec::new();
+        for group in groups {
+            let items = group
+                .items
+                .into_iter()
+                .map(|item| CreateOrderItemDto {
+                    product_id: item.product_id,
+                    product_name: item.product_name,
+                    quantity: item.suggested_quantity,
+                    unit: item.unit,
+                    unit_price: item.unit_price.unwrap_or(Decimal::ZERO),
+                    discount_amount: None,
+                    discount_rate: None,
+                    remark: None,
+                })
+                .collect();
+            let order = insert_order(
+                &txn,
+                CreateOrderDto {
+                    order_type: "Purchase".to_string(),
+                    customer_id: group.supplier_id,
+                    customer_name: group.supplier_name,
+                    items,
+                    remark: Some(
+                        input
+                            .remark
+                            .clone()
+                            .unwrap_or_else(|| "补货建议生成".to_string()),
+                    ),
+                    actual_amount: None,
+                    sub_type: None,
+                    due_date: None,
+                },
+            )
+            .await?;
+            orders.push(order);
+        }
+
+        txn.commit().await?;
+
+        Ok(orders)
+    }
+}
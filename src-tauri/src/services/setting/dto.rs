@@ -0,0 +1,90 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 店铺信息（打印收据、送货单时作为抬头）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopProfile {
+    /// 店铺名称
+    pub shop_name: String,
+    /// 地址
+    pub address: Option<String>,
+    /// 联系电话
+    pub phone: Option<String>,
+    /// 票据底部附言（如"货物当面点清，过后概不负责"）
+    pub receipt_footer: Option<String>,
+}
+
+/// 小票打印机配置
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterConfig {
+    /// 打印机地址：设备路径（如 /dev/usb/lp0、COM3）或网络端口（tcp://192.168.1.100:9100）
+    pub target: Option<String>,
+    /// 纸宽（毫米，支持 58 / 80）
+    pub paper_width: u32,
+}
+
+impl Default for PrinterConfig {
+    fn default() -> Self {
+        Self {
+            target: None,
+            paper_width: 58,
+        }
+    }
+}
+
+/// 采购设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurchaseConfig {
+    /// 结账时是否按实收数量结算（开启后采购订单结账前按收货单汇总的数量调整明细和金额）
+    pub settle_by_received: bool,
+}
+
+/// 毛利预警设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginConfig {
+    /// 最低毛利率（百分比，0 ~ 100；未设置时仅在低于成本时预警）
+    pub min_margin_rate: Option<Decimal>,
+}
+
+/// 成本核算方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CostingMethod {
+    /// 移动加权平均
+    #[default]
+    MovingAverage,
+    /// 先进先出
+    Fifo,
+}
+
+impl std::str::FromStr for CostingMethod {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "MovingAverage" => Ok(CostingMethod::MovingAverage),
+            "Fifo" => Ok(CostingMethod::Fifo),
+            _ => Err(()),
+        }
+    }
+}
+
+impl CostingMethod {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            CostingMethod::MovingAverage => "MovingAverage",
+            CostingMethod::Fifo => "Fifo",
+        }
+    }
+}
+
+/// 成本核算设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostingConfig {
+    /// 成本核算方法（默认移动加权平均）
+    pub method: CostingMethod,
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use chrono::Local;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    Set, TransactionTrait,
+};
+
+use super::dto::{
+    CostingConfig, CostingMethod, MarginConfig, PrinterConfig, PurchaseConfig, ShopProfile,
+};
+use crate::entity::app_setting;
+
+/// 店铺名称设置键
+const SHOP_NAME_KEY: &str = "shop.name";
+/// 店铺地址设置键
+const SHOP_ADDRESS_KEY: &str = "shop.address";
+/// 店铺电话设置键
+const SHOP_PHONE_KEY: &str = "shop.phone";
+/// 票据底部附言设置键
+const RECEIPT_FOOTER_KEY: &str = "shop.receiptFooter";
+/// 打印机地址设置键
+const PRINTER_TARGET_KEY: &str = "printer.target";
+/// 打印机纸宽设置键
+const PRINTER_PAPER_WIDTH_KEY: &str = "printer.paperWidth";
+/// 采购按实收数量结算设置键
+const PURCHASE_SETTLE_BY_RECEIVED_KEY: &str = "purchase.settleByReceived";
+/// 最低毛利率设置键
+const MARGIN_MIN_RATE_KEY: &str = "margin.minRate";
+/// 成本核算方法设置键
+const COSTING_METHOD_KEY: &str = "costing.method";
+/// 支持的小票纸宽（毫米）
+const SUPPORTED_PAPER_WIDTHS: [u32; 2] = [58, 80];
+
+/// 写入单个设置项（值为 None 或空白时删除该项）
+async fn save_setting<C: ConnectionTrait>(
+    conn: &C,
+    key: &str,
+    value: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let value = value.map(str::trim).filter(|v| !v.is_empty());
+    let existing = app_setting::Entity::find_by_id(key.to_string())
+        .one(conn)
+        .await?;
+
+    match (existing, value) {
+        (Some(_), None) => {
+            app_setting::Entity::delete_by_id(key.to_string())
+                .exec(conn)
+                .await?;
+        }
+        (None, None) => {}
+        (existing, Some(value)) => {
+            let setting = app_setting::ActiveModel {
+                key: Set(key.to_string()),
+                value: Set(value.to_string()),
+                update_at: Set(Local::now().naive_local()),
+            };
+            match existing {
+                Some(_) => setting.update(conn).await?,
+                None => setting.insert(conn).await?,
+            };
+        }
+    }
+
+    Ok(())
+}
+
+/// 读取店铺信息（未配置的项为空）
+pub(crate) async fn load_shop_profile<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<ShopProfile, Box<dyn std::error::Error>> {
+    let mut settings: HashMap<String, String> = app_setting::Entity::find()
+        .filter(app_setting::Column::Key.is_in([
+            SHOP_NAME_KEY,
+            SHOP_ADDRESS_KEY,
+            SHOP_PHONE_KEY,
+            RECEIPT_FOOTER_KEY,
+        ]))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|s| (s.key, s.value))
+        .collect();
+
+    Ok(ShopProfile {
+        shop_name: settings.remove(SHOP_NAME_KEY).unwrap_or_default(),
+        address: settings.remove(SHOP_ADDRESS_KEY),
+        phone: settings.remove(SHOP_PHONE_KEY),
+        receipt_footer: settings.remove(RECEIPT_FOOTER_KEY),
+    })
+}
+
+/// 读取小票打印机配置（未配置时纸宽默认 58mm）
+pub(crate) async fn load_printer_config<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<PrinterConfig, Box<dyn std::error::Error>> {
+    let mut settings: HashMap<String, String> = app_setting::Entity::find()
+        .filter(app_setting::Column::Key.is_in([PRINTER_TARGET_KEY, PRINTER_PAPER_WIDTH_KEY]))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|s| (s.key, s.value))
+        .collect();
+
+    let default = PrinterConfig::default();
+    Ok(PrinterConfig {
+        target: settings.remove(PRINTER_TARGET_KEY),
+        paper_width: settings
+            .remove(PRINTER_PAPER_WIDTH_KEY)
+            .and_then(|w| w.parse().ok())
+            .unwrap_or(default.paper_width),
+    })
+}
+
+/// 读取采购设置（未配置时按订单数量结算）
+pub(crate) async fn load_purchase_config<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<PurchaseConfig, Box<dyn std::error::Error>> {
+    let setting = app_setting::Entity::find_by_id(PURCHASE_SETTLE_BY_RECEIVED_KEY.to_string())
+        .one(conn)
+        .await?;
+
+    Ok(PurchaseConfig {
+        settle_by_received: setting.is_some_and(|s| s.value == "true"),
+    })
+}
+
+/// 读取毛利预警设置
+pub(crate) async fn load_margin_config<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<MarginConfig, Box<dyn std::error::Error>> {
+    let setting = app_setting::Entity::find_by_id(MARGIN_MIN_RATE_KEY.to_string())
+        .one(conn)
+        .await?;
+
+    Ok(MarginConfig {
+        min_margin_rate: setting.and_then(|s| s.value.parse().ok()),
+    })
+}
+
+/// 读取成本核算设置（未配置或无法识别时为移动加权平均）
+pub(crate) async fn load_costing_config<C: ConnectionTrait>(
+    conn: &C,
+) -> Result<CostingConfig, Box<dyn std::error::Error>> {
+    let setting = app_setting::Entity::find_by_id(COSTING_METHOD_KEY.to_string())
+        .one(conn)
+        .await?;
+
+    Ok(CostingConfig {
+        method: setting
+            .and_then(|s| s.value.parse::<CostingMethod>().ok())
+            .unwrap_or_default(),
+    })
+}
+
+/// 应用设置服务
+#[derive(Debug)]
+pub struct SettingService {
+    db: DatabaseConnection,
+}
+
+impl SettingService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 获取店铺信息
+    pub async fn get_shop_profile(&self) -> Result<ShopProfile, Box<dyn std::error::Error>> {
+        load_shop_profile(&self.db).await
+    }
+
+    /// 更新店铺信息
+    pub async fn update_shop_profile(
+        &self,
+        input: ShopProfile,
+    ) -> Result<ShopProfile, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+        save_setting(&txn, SHOP_NAME_KEY, Some(&input.shop_name)).await?;
+        save_setting(&txn, SHOP_ADDRESS_KEY, input.address.as_deref()).await?;
+        save_setting(&txn, SHOP_PHONE_KEY, input.phone.as_deref()).await?;
+        save_setting(&txn, RECEIPT_FOOTER_KEY, input.receipt_footer.as_deref()).await?;
+        let profile = load_shop_profile(&txn).await?;
+        txn.commit().await?;
+
+        Ok(profile)
+    }
+
+    /// 获取小票打印机配置
+    pub async fn get_printer_config(&self) -> Result<PrinterConfig, Box<dyn std::error::Error>> {
+        load_printer_config(&self.db).await
+    }
+
+    /// 更新小票打印机配置
+    pub async fn update_printer_config(
+        &self,
+        input: PrinterConfig,
+    ) -> Result<PrinterConfig, Box<dyn std::error::Error>> {
+        if !SUPPORTED_PAPER_WIDTHS.contains(&input.paper_width) {
+            return Err("纸宽仅支持 58mm 或 80mm".into());
+        }
+
+        let txn = self.db.begin().await?;
+        save_setting(&txn, PRINTER_TARGET_KEY, input.target.as_deref()).await?;
+        save_setting(
+            &txn,
+            PRINTER_PAPER_WIDTH_KEY,
+            Some(&input.paper_width.to_string()),
+        )
+        .await?;
+        let config = load_printer_config(&txn).await?;
+        txn.commit().await?;
+
+        Ok(config)
+    }
+
+    /// 获取采购设置
+    pub async fn get_purchase_config(&self) -> Result<PurchaseConfig, Box<dyn std::error::Error>> {
+        load_purchase_config(&self.db).await
+    }
+
+    /// 更新采购设置
+    pub async fn update_purchase_config(
+        &self,
+        input: PurchaseConfig,
+    ) -> Result<PurchaseConfig, Box<dyn std::error::Error>> {
+        let value = input.settle_by_received.then_some("true");
+        save_setting(&self.db, PURCHASE_SETTLE_BY_RECEIVED_KEY, value).await?;
+        load_purchase_config(&self.db).await
+    }
+
+    /// 获取毛利预警设置
+    pub async fn get_margin_config(&self) -> Result<MarginConfig, Box<dyn std::error::Error>> {
+        load_margin_config(&self.db).await
+    }
+
+    /// 更新毛利预警设置
+    pub async fn update_margin_config(
+        &self,
+        input: MarginConfig,
+    ) -> Result<MarginConfig, Box<dyn std::error::Error>> {
+        if input
+            .min_margin_rate
+            .is_some_and(|r| r < Decimal::ZERO || r > Decimal::ONE_HUNDRED)
+        {
+            return Err("最低毛利率必须在 0 ~ 100 之间".into());
+        }
+
+        let value = input.min_margin_rate.map(|r| r.normalize().to_string());
+        save_setting(&self.db, MARGIN_MIN_RATE_KEY, value.as_deref()).await?;
+        load_margin_config(&self.db).await
+    }
+
+    /// 获取成本核算设置
+    pub async fn get_costing_config(&self) -> Result<CostingConfig, Box<dyn std::error::Error>> {
+        load_costing_config(&self.db).await
+    }
+
+    /// 更新成本核算设置（切换方法后，库存估值和销售成本按新方法重新推算）
+    pub async fn update_costing_config(
+        &self,
+        input: CostingConfig,
+    ) -> Result<CostingConfig, Box<dyn std::error::Error>> {
+        save_setting(&self.db, COSTING_METHOD_KEY, Some(input.method.as_str())).await?;
+        load_costing_config(&self.db).await
+    }
+}
@@ -0,0 +1,130 @@
+use crate::enums::{AccountingChannel, OrderStatus, OrderType};
+use crate::services::document::DocumentFormat;
+use crate::services::ledger::dto::LedgerEntryType;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 生成客户对账单 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerStatementDto {
+    /// 客户 ID
+    pub customer_id: i64,
+    /// 开始日期（格式 YYYY-MM-DD，含）
+    pub start_date: String,
+    /// 结束日期（格式 YYYY-MM-DD，含）
+    pub end_date: String,
+    /// 输出格式（Html / Pdf，可选，默认 Pdf；仅导出文件时使用）
+    pub format: Option<String>,
+}
+
+/// 对账单商品明细
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementItem {
+    /// 商品名称
+    pub product_name: String,
+    /// 数量
+    pub quantity: Decimal,
+    /// 单位
+    pub unit: String,
+    /// 单价
+    pub unit_price: Decimal,
+    /// 小计
+    pub subtotal: Decimal,
+}
+
+/// 对账单订单
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementOrder {
+    /// 订单 ID
+    pub order_id: i64,
+    /// 订单编号
+    pub order_no: String,
+    /// 订单类型
+    pub order_type: OrderType,
+    /// 订单状态
+    pub status: OrderStatus,
+    /// 下单时间
+    pub date: NaiveDateTime,
+    /// 商品明细
+    pub items: Vec<StatementItem>,
+    /// 应收/应付总额
+    pub total_amount: Decimal,
+    /// 优惠金额（明细折扣 + 整单优惠）
+    pub discount_amount: Decimal,
+    /// 实收/实付总额
+    pub actual_amount: Decimal,
+}
+
+/// 对账单收付款
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementPayment {
+    /// 收付款 ID（早期一次性结账的订单为 None）
+    pub payment_id: Option<i64>,
+    /// 类型（收款 / 付款）
+    pub entry_type: LedgerEntryType,
+    /// 关联订单 ID
+    pub order_id: i64,
+    /// 关联订单编号
+    pub order_no: String,
+    /// 收付款时间
+    pub date: NaiveDateTime,
+    /// 渠道
+    pub channel: AccountingChannel,
+    /// 金额（撤销结账产生的反向收付款为负数）
+    pub amount: Decimal,
+}
+
+/// 客户对账单
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerStatement {
+    /// 对账单编号（仅导出文件时生成）
+    pub statement_no: Option<String>,
+    /// 客户 ID
+    pub customer_id: i64,
+    /// 客户名称
+    pub customer_name: String,
+    /// 联系电话
+    pub customer_phone: String,
+    /// 开始日期
+    pub start_date: NaiveDate,
+    /// 结束日期（含）
+    pub end_date: NaiveDate,
+    /// 生成时间
+    pub generated_at: NaiveDateTime,
+    /// 期初余额（正数为客户欠我方）
+    pub opening_balance: Decimal,
+    /// 期间订单（按下单时间升序）
+    pub orders: Vec<StatementOrder>,
+    /// 期间收付款（按时间升序）
+    pub payments: Vec<StatementPayment>,
+    /// 期间销售额（实收口径）
+    pub sales_total: Decimal,
+    /// 期间采购额（实付口径）
+    pub purchase_total: Decimal,
+    /// 期间优惠合计
+    pub discount_total: Decimal,
+    /// 期间收款合计
+    pub receipt_total: Decimal,
+    /// 期间付款合计
+    pub payment_total: Decimal,
+    /// 期末余额
+    pub closing_balance: Decimal,
+}
+
+/// 对账单导出结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementFile {
+    /// 对账单数据
+    pub statement: CustomerStatement,
+    /// 输出格式
+    pub format: DocumentFormat,
+    /// 文件保存路径
+    pub file_path: String,
+}
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+
+use super::dto::{
+    CustomerStatement, CustomerStatementDto, StatementFile, StatementItem, StatementOrder,
+    StatementPayment,
+};
+use crate::entity::{customer, order, order_item, order_payment};
+use crate::enums::{AccountingChannel, DocumentType, OrderStatus, OrderType};
+use crate::services::document::html::{self, escape, money, quantity};
+use crate::services::document::label::{channel_label, order_status_label, order_type_label};
+use crate::services::document::pdf::{PAGE_HEIGHT, PAGE_WIDTH};
+use crate::services::document::{write_document, DocumentFormat, PdfWriter};
+use crate::services::ledger::dto::{CustomerLedgerDto, LedgerEntryType};
+use crate::services::ledger::LedgerService;
+use crate::services::numbering::service::next_document_no;
+use crate::services::order::OrderService;
+
+/// 解析日期（YYYY-MM-DD）
+fn parse_date(s: &str, err: &str) -> Result<NaiveDate, Box<dyn std::error::Error>> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| err.to_string().into())
+}
+
+/// 收付款类型显示名称
+fn payment_type_label(entry_type: &LedgerEntryType) -> &'static str {
+    match entry_type {
+        LedgerEntryType::Receipt => "收款",
+        LedgerEntryType::Payment => "付款",
+        LedgerEntryType::SalesOrder => "销售订单",
+        LedgerEntryType::PurchaseOrder => "采购订单",
+        LedgerEntryType::SalesReturn | LedgerEntryType::PurchaseReturn => "退货",
+        LedgerEntryType::SalesRefund | LedgerEntryType::PurchaseRefund => "退款",
+    }
+}
+
+/// 客户对账单服务
+#[derive(Debug)]
+pub struct StatementService {
+    db: DatabaseConnection,
+}
+
+impl StatementService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 生成客户对账单（结构化数据）
+    ///
+    /// 期初 / 期末余额与往来明细口径一致：正数表示客户欠我方，负数表示我方欠客户
+    pub async fn generate_statement(
+        &self,
+        input: &CustomerStatementDto,
+    ) -> Result<CustomerStatement, Box<dyn std::error::Error>> {
+        let customer = customer::Entity::find_by_id(input.customer_id)
+            .one(&self.db)
+            .await?
+            .ok_or("客户不存在")?;
+
+        let start_date = parse_date(&input.start_date, "无效的开始日期格式，应为 YYYY-MM-DD")?;
+        let end_date = parse_date(&input.end_date, "无效的结束日期格式，应为 YYYY-MM-DD")?;
+        if end_date < start_date {
+            return Err("结束日期不能早于开始日期".into());
+        }
+
+        let ledger = LedgerService::new(self.db.clone())
+            .get_customer_ledger(CustomerLedgerDto {
+                customer_id: customer.id,
+                start_date: Some(input.start_date.clone()),
+                end_date: Some(input.end_date.clone()),
+            })
+            .await?;
+
+        // 期间订单（已取消订单不计入）
+        let mut orders: Vec<order::Model> = OrderService::new(self.db.clone())
+            .get_orders_by_customer_id(customer.id)
+            .await?
+            .into_iter()
+            .filter(|o| o.status != OrderStatus::Cancelled)
+            .filter(|o| {
+                let date = o.create_at.date();
+                date >= start_date && date <= end_date
+            })
+            .collect();
+        orders.sort_by(|a, b| a.create_at.cmp(&b.create_at).then(a.id.cmp(&b.id)));
+
+        let order_ids: Vec<i64> = orders.iter().map(|o| o.id).collect();
+        let mut items_by_order: HashMap<i64, Vec<order_item::Model>> = HashMap::new();
+        for item in order_item::Entity::find()
+            .filter(order_item::Column::OrderId.is_in(order_ids))
+            .order_by_asc(order_item::Column::Id)
+            .all(&self.db)
+            .await?
+        {
+            items_by_order.entry(item.order_id).or_default().push(item);
+        }
+
+        let mut sales_total = Decimal::ZERO;
+        let mut purchase_total = Decimal::ZERO;
+        let mut discount_total = Decimal::ZERO;
+        let statement_orders: Vec<StatementOrder> = orders
+            .iter()
+            .map(|o| {
+                let order_items = items_by_order.remove(&o.id).unwrap_or_default();
+                // 优惠 = 明细折扣 + 整单优惠
+                let item_discount: Decimal = order_items.iter().map(|i| i.discount_amount).sum();
+                let discount_amount = item_discount + o.total_amount - o.actual_amount;
+                match o.order_type {
+                    OrderType::Sales => sales_total += o.actual_amount,
+                    OrderType::Purchase => purchase_total += o.actual_amount,
+                }
+                discount_total += discount_amount;
+
+                let items = order_items
+                    .into_iter()
+                    .map(|item| StatementItem {
+                        product_name: item.product_name,
+                        quantity: item.quantity,
+                        unit: item.unit,
+                        unit_price: item.unit_price,
+                        subtotal: item.subtotal,
+                    })
+                    .collect();
+
+                StatementOrder {
+                    order_id: o.id,
+                    order_no: o.order_no.clone(),
+                    order_type: o.order_type.clone(),
+                    status: o.status.clone(),
+                    date: o.create_at,
+                    items,
+                    total_amount: o.total_amount,
+                    discount_amount,
+                    actual_amount: o.actual_amount,
+                }
+            })
+            .collect();
+
+        // 期间收付款取自往来明细（含早期一次性结账订单的推算收付款），渠道从收付款记录或订单补齐
+        let payment_entries: Vec<_> = ledger
+            .entries
+            .into_iter()
+            .filter(|e| {
+                matches!(
+                    e.entry_type,
+                    LedgerEntryType::Receipt | LedgerEntryType::Payment
+                )
+            })
+            .collect();
+
+        let payment_ids: Vec<i64> = payment_entries
+            .iter()
+            .filter_map(|e| e.payment_id)
+            .collect();
+        let payment_channels: HashMap<i64, AccountingChannel> = order_payment::Entity::find()
+            .filter(order_payment::Column::Id.is_in(payment_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p.channel))
+            .collect();
+
+        let legacy_order_ids: Vec<i64> = payment_entries
+            .iter()
+            .filter(|e| e.payment_id.is_none())
+            .map(|e| e.order_id)
+            .collect();
+        let order_channels: HashMap<i64, AccountingChannel> = order::Entity::find()
+            .filter(order::Column::Id.is_in(legacy_order_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|o| (o.id, o.channel))
+            .collect();
+
+        let mut receipt_total = Decimal::ZERO;
+        let mut payment_total = Decimal::ZERO;
+        let payments: Vec<StatementPayment> = payment_entries
+            .into_iter()
+            .map(|e| {
+                // 收款在往来明细中为负数，付款为正数；撤销结账产生的反向收付款保留负号
+                let amount = if e.entry_type == LedgerEntryType::Receipt {
+                    receipt_total -= e.amount;
+                    -e.amount
+                } else {
+                    payment_total += e.amount;
+                    e.amount
+                };
+                let channel = match e.payment_id {
+                    Some(id) => payment_channels.get(&id),
+                    None => order_channels.get(&e.order_id),
+                }
+                .cloned()
+                .unwrap_or(AccountingChannel::Unknown);
+
+                StatementPayment {
+                    payment_id: e.payment_id,
+                    entry_type: e.entry_type,
+                    order_id: e.order_id,
+                    order_no: e.order_no,
+                    date: e.date,
+                    channel,
+                    amount,
+                }
+            })
+            .collect();
+
+        Ok(CustomerStatement {
+            statement_no: None,
+            customer_id: customer.id,
+            customer_name: customer.name,
+            customer_phone: customer.phone,
+            start_date,
+            end_date,
+            generated_at: Local::now().naive_local(),
+            opening_balance: ledger.opening_balance,
+            orders: statement_orders,
+            payments,
+            sales_total,
+            purchase_total,
+            discount_total,
+            receipt_total,
+            payment_total,
+            closing_balance: ledger.closing_balance,
+        })
+    }
+
+    /// 生成对账单并渲染为 HTML / PDF 文件，保存至指定目录
+    pub async fn export_statement(
+        &self,
+        input: CustomerStatementDto,
+        output_dir: &Path,
+    ) -> Result<StatementFile, Box<dyn std::error::Error>> {
+        let format = match &input.format {
+            Some(s) => s
+                .parse::<DocumentFormat>()
+                .map_err(|_| format!("无效的对账单格式: {}", s))?,
+            None => DocumentFormat::Pdf,
+        };
+
+        let mut statement = self.generate_statement(&input).await?;
+        // 对账单不落库，取号在事务中完成，保证并发导出时流水号不重复
+        let txn = self.db.begin().await?;
+        let statement_no =
+            next_document_no(&txn, DocumentType::Statement, statement.generated_at).await?;
+        txn.commit().await?;
+        statement.statement_no = Some(statement_no.clone());
+
+        let bytes = match format {
+            DocumentFormat::Html => Self::render_html(&statement).into_bytes(),
+            DocumentFormat::Pdf => Self::render_pdf(&statement),
+        };
+
+        let file_path = write_document(
+            output_dir,
+            &statement_no,
+            format,
+            statement.generated_at,
+            bytes,
+        )
+        .await?;
+
+        Ok(StatementFile {
+            statement,
+            format,
+            file_path: file_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// 渲染对账单 HTML
+    pub fn render_html(statement: &CustomerStatement) -> String {
+        let mut body = String::new();
+        body.push_str("<h1>客户对账单</h1>\n");
+        if let Some(no) = &statement.statement_no {
+            body.push_str(&format!("<p class=\"meta\">编号：{}</p>\n", escape(no)));
+        }
+        body.push_str(&format!(
+            "<p class=\"meta\">客户：{}　电话：{}　期间：{} 至 {}</p>\n",
+            escape(&statement.customer_name),
+            escape(&statement.customer_phone),
+            statement.start_date.format("%Y-%m-%d"),
+            statement.end_date.format("%Y-%m-%d")
+        ));
+        body.push_str(&format!(
+            "<p>期初余额：{}</p>\n",
+            money(statement.opening_balance)
+        ));
+
+        body.push_str("<h2>订单明细</h2>\n<table>\n<tr><th>日期</th><th>单号</th><th>类型</th><th>商品</th><th>数量</th><th>单价</th><th>小计</th></tr>\n");
+        for o in &statement.orders {
+            let rows = o.items.len().max(1);
+            for (idx, item) in o.items.iter().enumerate() {
+                body.push_str("<tr>");
+                if idx == 0 {
+                    body.push_str(&format!(
+                        "<td rowspan=\"{rows}\">{}</td><td rowspan=\"{rows}\">{}</td><td rowspan=\"{rows}\">{}</td>",
+                        o.date.format("%Y-%m-%d"),
+                        escape(&o.order_no),
+                        order_type_label(&o.order_type),
+                    ));
+                }
+                body.push_str(&format!(
+                    "<td>{}</td><td class=\"num\">{} {}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+                    escape(&item.product_name),
+                    quantity(item.quantity),
+                    escape(&item.unit),
+                    money(item.unit_price),
+                    money(item.subtotal)
+                ));
+            }
+            body.push_str(&format!(
+                "<tr><td colspan=\"7\" class=\"num\">{}　合计 {}　优惠 {}　实际 {}</td></tr>\n",
+                order_status_label(&o.status),
+                money(o.total_amount),
+                money(o.discount_amount),
+                money(o.actual_amount)
+            ));
+        }
+        body.push_str("</table>\n");
+
+        body.push_str("<h2>收付款明细</h2>\n<table>\n<tr><th>日期</th><th>类型</th><th>关联单号</th><th>渠道</th><th>金额</th></tr>\n");
+        for p in &statement.payments {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>\n",
+                p.date.format("%Y-%m-%d"),
+                payment_type_label(&p.entry_type),
+                escape(&p.order_no),
+                channel_label(&p.channel),
+                money(p.amount)
+            ));
+        }
+        body.push_str("</table>\n");
+
+        body.push_str("<h2>汇总</h2>\n<table>\n");
+        for (label, amount) in Self::summary_rows(statement) {
+            body.push_str(&format!(
+                "<tr><th>{}</th><td class=\"num\">{}</td></tr>\n",
+                label,
+                money(amount)
+            ));
+        }
+        body.push_str("</table>\n");
+        body.push_str(&format!(
+            "<p class=\"meta\">生成时间：{}</p>\n",
+            statement.generated_at.format("%Y-%m-%d %H:%M:%S")
+        ));
+
+        html::page(&format!("客户对账单-{}", statement.customer_name), &body)
+    }
+
+    /// 渲染对账单 PDF（A4 纵向，超出一页自动分页）
+    pub fn render_pdf(statement: &CustomerStatement) -> Vec<u8> {
+        const MARGIN: f32 = 40.0;
+        const LINE_HEIGHT: f32 = 16.0;
+        const FONT_SIZE: f32 = 10.0;
+        let right = PAGE_WIDTH - MARGIN;
+
+        let mut pdf = PdfWriter::new();
+        let mut y = PAGE_HEIGHT - MARGIN;
+
+        // 剩余空间不足时换页
+        let next_line = |pdf: &mut PdfWriter, y: &mut f32| {
+            *y -= LINE_HEIGHT;
+            if *y < MARGIN {
+                pdf.new_page();
+                *y = PAGE_HEIGHT - MARGIN - LINE_HEIGHT;
+            }
+        };
+
+        pdf.text_center(PAGE_WIDTH / 2.0, y - 18.0, 18.0, "客户对账单");
+        y -= 40.0;
+        if let Some(no) = &statement.statement_no {
+            pdf.text(MARGIN, y, FONT_SIZE, &format!("编号：{}", no));
+            next_line(&mut pdf, &mut y);
+        }
+        pdf.text(
+            MARGIN,
+            y,
+            FONT_SIZE,
+            &format!(
+                "客户：{}    电话：{}    期间：{} 至 {}",
+                statement.customer_name,
+                statement.customer_phone,
+                statement.start_date.format("%Y-%m-%d"),
+                statement.end_date.format("%Y-%m-%d")
+            ),
+        );
+        next_line(&mut pdf, &mut y);
+        pdf.text(
+            MARGIN,
+            y,
+            FONT_SIZE,
+            &format!("期初余额：{}", money(statement.opening_balance)),
+        );
+        next_line(&mut pdf, &mut y);
+        next_line(&mut pdf, &mut y);
+
+        pdf.text(MARGIN, y, 12.0, "订单明细");
+        next_line(&mut pdf, &mut y);
+        for o in &statement.orders {
+            pdf.text(
+                MARGIN,
+                y,
+                FONT_SIZE,
+                &format!(
+                    "{}  {}  {}订单  {}",
+                    o.date.format("%Y-%m-%d"),
+                    o.order_no,
+                    order_type_label(&o.order_type),
+                    order_status_label(&o.status)
+                ),
+            );
+            pdf.text_right(
+                right,
+                y,
+                FONT_SIZE,
+                &format!(
+                    "合计 {}  优惠 {}  实际 {}",
+                    money(o.total_amount),
+                    money(o.discount_amount),
+                    money(o.actual_amount)
+                ),
+            );
+            next_line(&mut pdf, &mut y);
+            for item in &o.items {
+                pdf.text(MARGIN + 20.0, y, FONT_SIZE, &item.product_name);
+                pdf.text_right(
+                    340.0,
+                    y,
+                    FONT_SIZE,
+                    &format!("{} {}", quantity(item.quantity), item.unit),
+                );
+                pdf.text_right(440.0, y, FONT_SIZE, &money(item.unit_price));
+                pdf.text_right(right, y, FONT_SIZE, &money(item.subtotal));
+                next_line(&mut pdf, &mut y);
+            }
+        }
+        pdf.line(MARGIN, y + LINE_HEIGHT / 2.0, right, y + LINE_HEIGHT / 2.0);
+        next_line(&mut pdf, &mut y);
+
+        pdf.text(MARGIN, y, 12.0, "收付款明细");
+        next_line(&mut pdf, &mut y);
+        for p in &statement.payments {
+            pdf.text(
+                MARGIN,
+                y,
+                FONT_SIZE,
+                &format!(
+                    "{}  {}  {}  {}",
+                    p.date.format("%Y-%m-%d"),
+                    payment_type_label(&p.entry_type),
+                    p.order_no,
+                    channel_label(&p.channel)
+                ),
+            );
+            pdf.text_right(right, y, FONT_SIZE, &money(p.amount));
+            next_line(&mut pdf, &mut y);
+        }
+        pdf.line(MARGIN, y + LINE_HEIGHT / 2.0, right, y + LINE_HEIGHT / 2.0);
+        next_line(&mut pdf, &mut y);
+
+        for (label, amount) in Self::summary_rows(statement) {
+            pdf.text(MARGIN, y, FONT_SIZE, label);
+            pdf.text_right(right, y, FONT_SIZE, &money(amount));
+            next_line(&mut pdf, &mut y);
+        }
+        next_line(&mut pdf, &mut y);
+        pdf.text(
+            MARGIN,
+            y,
+            8.0,
+            &format!(
+                "生成时间：{}",
+                statement.generated_at.format("%Y-%m-%d %H:%M:%S")
+            ),
+        );
+
+        pdf.to_bytes()
+    }
+
+    /// 汇总行（名称, 金额）
+    fn summary_rows(statement: &CustomerStatement) -> Vec<(&'static str, Decimal)> {
+        vec![
+            ("期初余额", statement.opening_balance),
+            ("本期销售", statement.sales_total),
+            ("本期采购", statement.purchase_total),
+            ("本期优惠", statement.discount_total),
+            ("本期收款", statement.receipt_total),
+            ("本期付款", statement.payment_total),
+            ("期末余额", statement.closing_balance),
+        ]
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// 查询库存变动流水 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryStockMovementsDto {
+    /// 商品 ID
+    pub product_id: i64,
+    /// 计量单位筛选（可选，不传则返回该商品所有单位的流水）
+    pub unit: Option<String>,
+    /// 开始日期（格式 YYYY-MM-DD，含，可选）
+    pub start_date: Option<String>,
+    /// 结束日期（格式 YYYY-MM-DD，含，可选）
+    pub end_date: Option<String>,
+}
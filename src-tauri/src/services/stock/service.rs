@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+
+use super::dto::QueryStockMovementsDto;
+use crate::entity::order::Model as OrderModel;
+use crate::entity::product_stock::{self, ActiveModel as ProductStockActiveModel};
+use crate::entity::stock_movement::{self, ActiveModel as StockMovementActiveModel};
+use crate::entity::{order_item, order_return, order_return_item, stocktake, stocktake_item};
+use crate::enums::{OrderType, StockMovementType};
+use crate::services::goods_receipt::service::inbound_items;
+use crate::services::lot::service::{
+    apply_order_lots, apply_return_lots, apply_stocktake_lots, reverse_order_lots,
+};
+
+/// 库存变动来源单据及变动时间
+struct MovementSource<'a> {
+    order_id: Option<i64>,
+    source_id: Option<i64>,
+    source_no: Option<&'a str>,
+    create_at: NaiveDateTime,
+}
+
+/// 库存变动明细
+struct MovementLine<'a> {
+    product_id: i64,
+    product_name: &'a str,
+    unit: &'a str,
+    /// 变动数量（入库为正，出库为负）
+    quantity: Decimal,
+    /// 入库单位成本（采购入库取采购单价，采购冲回沿用原单价）
+    unit_cost: Option<Decimal>,
+    order_item_id: Option<i64>,
+}
+
+/// 登记一条库存变动并同步更新当前库存
+async fn post_movement<C: ConnectionTrait>(
+    conn: &C,
+    movement_type: StockMovementType,
+    line: MovementLine<'_>,
+    source: &MovementSource<'_>,
+) -> Result<stock_movement::Model, Box<dyn std::error::Error>> {
+    let stock = product_stock::Entity::find()
+        .filter(product_stock::Column::ProductId.eq(line.product_id))
+        .filter(product_stock::Column::Unit.eq(line.unit))
+        .one(conn)
+        .await?;
+    let balance = match stock {
+        Some(stock) => {
+            let balance = stock.quantity + line.quantity;
+            let mut active: ProductStockActiveModel = stock.into();
+            active.product_name = Set(line.product_name.to_string());
+            active.quantity = Set(balance);
+            active.update_at = Set(source.create_at);
+            active.update(conn).await?;
+            balance
+        }
+        None => {
+            ProductStockActiveModel {
+                id: sea_orm::ActiveValue::NotSet,
+                product_id: Set(line.product_id),
+                product_name: Set(line.product_name.to_string()),
+                unit: Set(line.unit.to_string()),
+                quantity: Set(line.quantity),
+                update_at: Set(source.create_at),
+            }
+            .insert(conn)
+            .await?;
+            line.quantity
+        }
+    };
+
+    let movement = StockMovementActiveModel {
+        id: sea_orm::ActiveValue::NotSet,
+        product_id: Set(line.product_id),
+        product_name: Set(line.product_name.to_string()),
+        unit: Set(line.unit.to_string()),
+        movement_type: Set(movement_type),
+        quantity: Set(line.quantity),
+        balance: Set(balance),
+        unit_cost: Set(line.unit_cost),
+        order_id: Set(source.order_id),
+        order_item_id: Set(line.order_item_id),
+        source_id: Set(source.source_id),
+        source_no: Set(source.source_no.map(str::to_string)),
+        create_at: Set(source.create_at),
+    }
+    .insert(conn)
+    .await?;
+    Ok(movement)
+}
+
+/// 采购明细的净单位成本（折扣后小计 / 数量，再按实付金额分摊整单优惠），按明细 ID 索引
+fn net_unit_costs(order: &OrderModel, items: &[order_item::Model]) -> HashMap<i64, Decimal> {
+    let ratio = if order.total_amount.is_zero() {
+        Decimal::ONE
+    } else {
+        order.actual_amount / order.total_amount
+    };
+    items
+        .iter()
+        .filter(|i| !i.quantity.is_zero())
+        .map(|i| (i.id, (i.subtotal / i.quantity * ratio).round_dp(4)))
+        .collect()
+}
+
+/// 订单结账时按结账明细登记库存变动（采购入库、销售出库），同时登记或扣减批次
+///
+/// 已登记收货的采购订单按累计实收数量入库，与收货登记的批次一致；
+/// 采购入库成本取结账明细折扣后单价，并按实付金额与订单总额之比分摊整单优惠
+pub(crate) async fn apply_order_stock<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+    items: &[order_item::Model],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (movement_type, sign) = match order.order_type {
+        OrderType::Purchase => (StockMovementType::PurchaseIn, Decimal::ONE),
+        OrderType::Sales => (StockMovementType::SalesOut, Decimal::NEGATIVE_ONE),
+    };
+    let source = MovementSource {
+        order_id: Some(order.id),
+        source_id: Some(order.id),
+        source_no: Some(&order.order_no),
+        create_at: order
+            .settled_at
+            .unwrap_or_else(|| Local::now().naive_local()),
+    };
+    let unit_costs = match order.order_type {
+        OrderType::Purchase => net_unit_costs(order, items),
+        OrderType::Sales => HashMap::new(),
+    };
+    let items = match order.order_type {
+        OrderType::Purchase => inbound_items(conn, order.id, items).await?,
+        OrderType::Sales => items.to_vec(),
+    };
+
+    for item in items.iter().filter(|i| !i.quantity.is_zero()) {
+        post_movement(
+            conn,
+            movement_type.clone(),
+            MovementLine {
+                product_id: item.product_id,
+                product_name: &item.product_name,
+                unit: &item.unit,
+                quantity: item.quantity * sign,
+                unit_cost: unit_costs.get(&item.id).copied(),
+                order_item_id: Some(item.id),
+            },
+            &source,
+        )
+        .await?;
+    }
+    apply_order_lots(conn, order, &items, source.create_at).await
+}
+
+/// 撤销结账时冲回订单已登记的库存变动（按商品 + 单位汇总净额，已冲平的不再重复冲回），
+/// 同时冲回批次
+pub(crate) async fn reverse_order_stock<C: ConnectionTrait>(
+    conn: &C,
+    order: &OrderModel,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let movements = stock_movement::Entity::find()
+        .filter(stock_movement::Column::OrderId.eq(order.id))
+        .filter(stock_movement::Column::MovementType.is_in([
+            StockMovementType::PurchaseIn,
+            StockMovementType::SalesOut,
+            StockMovementType::Reopen,
+        ]))
+        .order_by_asc(stock_movement::Column::Id)
+        .all(conn)
+        .await?;
+
+    let mut keys: Vec<(i64, String)> = Vec::new();
+    let mut names: HashMap<(i64, String), String> = HashMap::new();
+    let mut net: HashMap<(i64, String), Decimal> = HashMap::new();
+    // 采购入库的净成本金额，冲回时按原单价出库
+    let mut net_cost: HashMap<(i64, String), Decimal> = HashMap::new();
+    for m in movements {
+        let key = (m.product_id, m.unit);
+        if !keys.contains(&key) {
+            keys.push(key.clone());
+        }
+        names.insert(key.clone(), m.product_name);
+        if let Some(unit_cost) = m.unit_cost {
+            *net_cost.entry(key.clone()).or_insert(Decimal::ZERO) += unit_cost * m.quantity;
+        }
+        *net.entry(key).or_insert(Decimal::ZERO) += m.quantity;
+    }
+
+    let source = MovementSource {
+        order_id: Some(order.id),
+        source_id: Some(order.id),
+        source_no: Some(&order.order_no),
+        create_at: Local::now().naive_local(),
+    };
+    let mut reversed: Vec<(i64, String, Decimal)> = Vec::new();
+    for key in keys {
+        let quantity = net[&key];
+        if quantity.is_zero() {
+            continue;
+        }
+        post_movement(
+            conn,
+            StockMovementType::Reopen,
+            MovementLine {
+                product_id: key.0,
+                product_name: &names[&key],
+                unit: &key.1,
+                quantity: -quantity,
+                unit_cost: net_cost.get(&key).map(|cost| cost / quantity),
+                order_item_id: None,
+            },
+            &source,
+        )
+        .await?;
+        reversed.push((key.0, key.1, quantity));
+    }
+    reverse_order_lots(conn, order, &reversed).await
+}
+
+/// 退货时登记库存变动（销售退货入库、采购退货出库）并调整批次
+pub(crate) async fn apply_return_stock<C: ConnectionTrait>(
+    conn: &C,
+    order_return: &order_return::Model,
+    items: &[order_return_item::Model],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (movement_type, sign) = match order_return.order_type {
+        OrderType::Sales => (StockMovementType::SalesReturn, Decimal::ONE),
+        OrderType::Purchase => (StockMovementType::PurchaseReturn, Decimal::NEGATIVE_ONE),
+    };
+    let source = MovementSource {
+        order_id: Some(order_return.order_id),
+        source_id: Some(order_return.id),
+        source_no: Some(&order_return.return_no),
+        create_at: order_return.create_at,
+    };
+    for item in items {
+        post_movement(
+            conn,
+            movement_type.clone(),
+            MovementLine {
+                product_id: item.product_id,
+                product_name: &item.product_name,
+                unit: &item.unit,
+                quantity: item.quantity * sign,
+                unit_cost: None,
+                order_item_id: Some(item.order_item_id),
+            },
+            &source,
+        )
+        .await?;
+    }
+    apply_return_lots(conn, order_return, items).await
+}
+
+/// 盘点审核时登记库存调整（按差异数量，盘盈入库、盘亏出库）并调整批次
+pub(crate) async fn apply_stocktake_stock<C: ConnectionTrait>(
+    conn: &C,
+    stocktake: &stocktake::Model,
+    items: &[stocktake_item::Model],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = MovementSource {
+        order_id: None,
+        source_id: Some(stocktake.id),
+        source_no: Some(&stocktake.stocktake_no),
+        create_at: stocktake
+            .approved_at
+            .unwrap_or_else(|| Local::now().naive_local()),
+    };
+    for item in items.iter().filter(|i| !i.variance.is_zero()) {
+        post_movement(
+            conn,
+            StockMovementType::Stocktake,
+            MovementLine {
+                product_id: item.product_id,
+                product_name: &item.product_name,
+                unit: &item.unit,
+                quantity: item.variance,
+                unit_cost: None,
+                order_item_id: None,
+            },
+            &source,
+        )
+        .await?;
+    }
+    apply_stocktake_lots(conn, stocktake, items, source.create_at).await
+}
+
+/// 库存服务
+#[derive(Debug)]
+pub struct StockService {
+    db: DatabaseConnection,
+}
+
+impl StockService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查询所有商品的当前库存（按商品 ID、单位排序）
+    pub async fn get_stock_list(
+        &self,
+    ) -> Result<Vec<product_stock::Model>, Box<dyn std::error::Error>> {
+        let stocks = product_stock::Entity::find()
+            .order_by_asc(product_stock::Column::ProductId)
+            .order_by_asc(product_stock::Column::Unit)
+            .all(&self.db)
+            .await?;
+        Ok(stocks)
+    }
+
+    /// 查询指定商品的当前库存（每个单位一条，无变动记录时返回空列表）
+    pub async fn get_product_stock(
+        &self,
+        product_id: i64,
+    ) -> Result<Vec<product_stock::Model>, Box<dyn std::error::Error>> {
+        let stocks = product_stock::Entity::find()
+            .filter(product_stock::Column::ProductId.eq(product_id))
+            .order_by_asc(product_stock::Column::Unit)
+            .all(&self.db)
+            .await?;
+        Ok(stocks)
+    }
+
+    /// 查询商品的库存变动流水（按变动时间升序）
+    pub async fn get_stock_movements(
+        &self,
+        input: QueryStockMovementsDto,
+    ) -> Result<Vec<stock_movement::Model>, Box<dyn std::error::Error>> {
+        let mut query = stock_movement::Entity::find()
+            .filter(stock_movement::Column::ProductId.eq(input.product_id));
+        if let Some(unit) = &input.unit {
+            query = query.filter(stock_movement::Column::Unit.eq(unit));
+        }
+        if let Some(start) = &input.start_date {
+            let start_date = NaiveDate::parse_from_str(start, "%Y-%m-%d")
+                .map_err(|_| "无效的开始日期格式，应为 YYYY-MM-DD".to_string())?;
+            query =
+                query.filter(stock_movement::Column::CreateAt.gte(start_date.and_hms_opt(0, 0, 0)));
+        }
+        if let Some(end) = &input.end_date {
+            let end_date = NaiveDate::parse_from_str(end, "%Y-%m-%d")
+                .map_err(|_| "无效的结束日期格式，应为 YYYY-MM-DD".to_string())?;
+            query = query.filter(
+                stock_movement::Column::CreateAt
+                    .lt((end_date + Duration::days(1)).and_hms_opt(0, 0, 0)),
+            );
+        }
+
+        let movements = query
+            .order_by_asc(stock_movement::Column::CreateAt)
+            .order_by_asc(stock_movement::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(movements)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::entity::{stocktake, stocktake_item};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 盘点明细 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StocktakeItemDto {
+    /// 商品 ID
+    pub product_id: i64,
+    /// 计量单位（可选，不传则取商品单位）
+    pub unit: Option<String>,
+    /// 实盘数量（不能为负数）
+    pub counted_quantity: Decimal,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+/// 创建盘点单 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateStocktakeDto {
+    /// 盘点明细列表
+    pub items: Vec<StocktakeItemDto>,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+/// 修改盘点单 DTO（仅草稿，整单替换明细）
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStocktakeDto {
+    /// 盘点单 ID
+    pub stocktake_id: i64,
+    /// 盘点明细列表
+    pub items: Vec<StocktakeItemDto>,
+    /// 备注
+    pub remark: Option<String>,
+}
+
+/// 审核盘点单 DTO
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveStocktakeDto {
+    /// 盘点单 ID
+    pub stocktake_id: i64,
+    /// 是否登记盘亏损耗（净差异金额为负时写入一条支出记录）
+    #[serde(default)]
+    pub record_loss: bool,
+    /// 损耗记录所属账本 ID（可选，默认为默认账本）
+    pub book_id: Option<i64>,
+    /// 损耗记录渠道（可选，默认为未知）
+    pub channel: Option<String>,
+}
+
+/// 盘点单详情（盘点单 + 明细列表）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StocktakeDetail {
+    pub stocktake: stocktake::Model,
+    pub items: Vec<stocktake_item::Model>,
+}
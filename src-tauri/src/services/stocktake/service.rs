@@ -0,0 +1,328 @@
+use chrono::Local;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+
+use super::dto::{
+    ApproveStocktakeDto, CreateStocktakeDto, StocktakeDetail, StocktakeItemDto, UpdateStocktakeDto,
+};
+use crate::entity::accounting_book;
+use crate::entity::accounting_record::{self, ActiveModel as AccountingActiveModel};
+use crate::entity::product;
+use crate::entity::product_stock;
+use crate::entity::stocktake::{self, ActiveModel as StocktakeActiveModel};
+use crate::entity::stocktake_item::{self, ActiveModel as StocktakeItemActiveModel};
+use crate::enums::{
+    AccountingChannel, AccountingRecordState, AccountingType, DocumentType, StocktakeStatus,
+};
+use crate::services::accounting_book::DEFAULT_BOOK_ID;
+use crate::services::costing::service::current_unit_costs;
+use crate::services::numbering::service::next_document_no;
+use crate::services::order::service::insert_order_record;
+use crate::services::stock::service::apply_stocktake_stock;
+
+/// 查询商品 + 单位的当前库存（无库存记录时为 0）
+async fn system_quantity<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i64,
+    unit: &str,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let stock = product_stock::Entity::find()
+        .filter(product_stock::Column::ProductId.eq(product_id))
+        .filter(product_stock::Column::Unit.eq(unit))
+        .one(conn)
+        .await?;
+    Ok(stock.map(|s| s.quantity).unwrap_or(Decimal::ZERO))
+}
+
+/// 查询盘点单明细（按录入顺序）
+async fn find_items<C: ConnectionTrait>(
+    conn: &C,
+    stocktake_id: i64,
+) -> Result<Vec<stocktake_item::Model>, Box<dyn std::error::Error>> {
+    let items = stocktake_item::Entity::find()
+        .filter(stocktake_item::Column::StocktakeId.eq(stocktake_id))
+        .order_by_asc(stocktake_item::Column::Id)
+        .all(conn)
+        .await?;
+    Ok(items)
+}
+
+/// 写入盘点明细（账面数量取当前库存）
+async fn insert_items<C: ConnectionTrait>(
+    conn: &C,
+    stocktake_id: i64,
+    items: Vec<StocktakeItemDto>,
+) -> Result<Vec<stocktake_item::Model>, Box<dyn std::error::Error>> {
+    if items.is_empty() {
+        return Err("盘点明细不能为空".into());
+    }
+
+    let mut keys: Vec<(i64, String)> = Vec::with_capacity(items.len());
+    let mut inserted = Vec::with_capacity(items.len());
+    for item in items {
+        let product = product::Entity::find_by_id(item.product_id)
+            .one(conn)
+            .await?
+            .ok_or("商品不存在")?;
+        if item.counted_quantity < Decimal::ZERO {
+            return Err(format!("{} 的实盘数量不能为负数", product.name).into());
+        }
+        let unit = item.unit.unwrap_or_else(|| product.unit.clone());
+        let key = (product.id, unit.clone());
+        if keys.contains(&key) {
+            return Err(format!("{}（{}）重复盘点", product.name, unit).into());
+        }
+        keys.push(key);
+
+        let system_quantity = system_quantity(conn, product.id, &unit).await?;
+        let model = StocktakeItemActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            stocktake_id: Set(stocktake_id),
+            product_id: Set(product.id),
+            product_name: Set(product.name),
+            unit: Set(unit),
+            system_quantity: Set(system_quantity),
+            counted_quantity: Set(item.counted_quantity),
+            variance: Set(item.counted_quantity - system_quantity),
+            unit_cost: Set(None),
+            variance_amount: Set(None),
+            remark: Set(item.remark),
+        }
+        .insert(conn)
+        .await?;
+        inserted.push(model);
+    }
+    Ok(inserted)
+}
+
+/// 查询草稿状态的盘点单
+async fn find_draft<C: ConnectionTrait>(
+    conn: &C,
+    stocktake_id: i64,
+) -> Result<stocktake::Model, Box<dyn std::error::Error>> {
+    let stocktake = stocktake::Entity::find_by_id(stocktake_id)
+        .one(conn)
+        .await?
+        .ok_or("盘点单不存在")?;
+    if stocktake.status != StocktakeStatus::Draft {
+        return Err("盘点单已审核".into());
+    }
+    Ok(stocktake)
+}
+
+/// 盘点服务
+#[derive(Debug)]
+pub struct StocktakeService {
+    db: DatabaseConnection,
+}
+
+impl StocktakeService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 创建盘点单（草稿）
+    pub async fn create_stocktake(
+        &self,
+        input: CreateStocktakeDto,
+    ) -> Result<StocktakeDetail, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        let now = Local::now().naive_local();
+        let stocktake_no = next_document_no(&txn, DocumentType::Stocktake, now).await?;
+        let stocktake = StocktakeActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            stocktake_no: Set(stocktake_no),
+            status: Set(StocktakeStatus::Draft),
+            remark: Set(input.remark),
+            variance_amount: Set(None),
+            loss_record_id: Set(None),
+            create_at: Set(now),
+            approved_at: Set(None),
+        }
+        .insert(&txn)
+        .await?;
+        let items = insert_items(&txn, stocktake.id, input.items).await?;
+
+        txn.commit().await?;
+        Ok(StocktakeDetail { stocktake, items })
+    }
+
+    /// 修改盘点单（仅草稿，整单替换明细并重新取账面数量）
+    pub async fn update_stocktake(
+        &self,
+        input: UpdateStocktakeDto,
+    ) -> Result<StocktakeDetail, Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        let stocktake = find_draft(&txn, input.stocktake_id).await?;
+        stocktake_item::Entity::delete_many()
+            .filter(stocktake_item::Column::StocktakeId.eq(stocktake.id))
+            .exec(&txn)
+            .await?;
+        let items = insert_items(&txn, stocktake.id, input.items).await?;
+
+        let mut active: StocktakeActiveModel = stocktake.into();
+        active.remark = Set(input.remark);
+        let stocktake = active.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(StocktakeDetail { stocktake, items })
+    }
+
+    /// 删除盘点单（仅草稿）
+    pub async fn delete_stocktake(
+        &self,
+        stocktake_id: i64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let txn = self.db.begin().await?;
+
+        let stocktake = stocktake::Entity::find_by_id(stocktake_id)
+            .one(&txn)
+            .await?
+            .ok_or("盘点单不存在")?;
+        if stocktake.status != StocktakeStatus::Draft {
+            return Err("已审核的盘点单不能删除".into());
+        }
+        stocktake_item::Entity::delete_many()
+            .filter(stocktake_item::Column::StocktakeId.eq(stocktake.id))
+            .exec(&txn)
+            .await?;
+        stocktake::Entity::delete_by_id(stocktake.id)
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// 查询盘点单详情
+    pub async fn get_stocktake(
+        &self,
+        stocktake_id: i64,
+    ) -> Result<StocktakeDetail, Box<dyn std::error::Error>> {
+        let stocktake = stocktake::Entity::find_by_id(stocktake_id)
+            .one(&self.db)
+            .await?
+            .ok_or("盘点单不存在")?;
+        let items = find_items(&self.db, stocktake.id).await?;
+        Ok(StocktakeDetail { stocktake, items })
+    }
+
+    /// 查询盘点单列表（按创建时间倒序，可按状态筛选）
+    pub async fn get_stocktakes(
+        &self,
+        status: Option<String>,
+    ) -> Result<Vec<stocktake::Model>, Box<dyn std::error::Error>> {
+        let mut query = stocktake::Entity::find();
+        if let Some(status) = &status {
+            let status = status
+                .parse::<StocktakeStatus>()
+                .map_err(|_| "无效的盘点单状态".to_string())?;
+            query = query.filter(stocktake::Column::Status.eq(status));
+        }
+        let stocktakes = query
+            .order_by_desc(stocktake::Column::CreateAt)
+            .order_by_desc(stocktake::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(stocktakes)
+    }
+
+    /// 审核盘点单
+    ///
+    /// 按当前库存重新计算差异并登记库存调整；差异金额按成本核算的当前单位成本计算，
+    /// 无入库记录时取商品参考采购价（仅当单位与商品单位一致）。选择登记损耗且盘盈抵盘亏后
+    /// 净差异金额为负时，在指定账本写入一条支出记录
+    pub async fn approve_stocktake(
+        &self,
+        input: ApproveStocktakeDto,
+    ) -> Result<StocktakeDetail, Box<dyn std::error::Error>> {
+        let channel = match &input.channel {
+            Some(c) => c
+                .parse::<AccountingChannel>()
+                .map_err(|_| "无效的支付渠道".to_string())?,
+            None => AccountingChannel::Unknown,
+        };
+
+        let txn = self.db.begin().await?;
+
+        let stocktake = find_draft(&txn, input.stocktake_id).await?;
+        let book_id = input.book_id.unwrap_or(DEFAULT_BOOK_ID);
+        if input.record_loss {
+            let book = accounting_book::Entity::find_by_id(book_id)
+                .one(&txn)
+                .await?;
+            if book.is_none() {
+                return Err("账本不存在".into());
+            }
+        }
+
+        let costs = current_unit_costs(&txn).await?;
+        let drafts = find_items(&txn, stocktake.id).await?;
+        let mut items = Vec::with_capacity(drafts.len());
+        for item in drafts {
+            let system_quantity = system_quantity(&txn, item.product_id, &item.unit).await?;
+            let variance = item.counted_quantity - system_quantity;
+            let unit_cost = match costs.get(&(item.product_id, item.unit.clone())) {
+                Some(cost) => Some(*cost),
+                None => {
+                    let product = product::Entity::find_by_id(item.product_id)
+                        .one(&txn)
+                        .await?;
+                    product
+                        .filter(|p| p.unit == item.unit)
+                        .and_then(|p| p.default_purchase_price)
+                }
+            };
+            let mut active: StocktakeItemActiveModel = item.into();
+            active.system_quantity = Set(system_quantity);
+            active.variance = Set(variance);
+            active.unit_cost = Set(unit_cost);
+            active.variance_amount = Set(unit_cost.map(|c| (variance * c).round_dp(2)));
+            items.push(active.update(&txn).await?);
+        }
+        let variance_amount: Decimal = items.iter().filter_map(|i| i.variance_amount).sum();
+
+        let now = Local::now().naive_local();
+        let mut active: StocktakeActiveModel = stocktake.into();
+        active.status = Set(StocktakeStatus::Approved);
+        active.variance_amount = Set(Some(variance_amount));
+        active.approved_at = Set(Some(now));
+        let stocktake = active.update(&txn).await?;
+        apply_stocktake_stock(&txn, &stocktake, &items).await?;
+
+        let stocktake = if input.record_loss && variance_amount < Decimal::ZERO {
+            let record_id = accounting_record::Model::generate_id(&txn).await?;
+            let record = insert_order_record(
+                &txn,
+                AccountingActiveModel {
+                    id: Set(record_id),
+                    amount: Set(-variance_amount),
+                    record_time: Set(now),
+                    accounting_type: Set(AccountingType::Expenditure),
+                    title: Set(format!("盘点损耗-{}", stocktake.stocktake_no)),
+                    channel: Set(channel),
+                    remark: Set(stocktake.remark.clone()),
+                    write_off_id: Set(None),
+                    create_at: Set(now),
+                    state: Set(AccountingRecordState::Posted),
+                    book_id: Set(Some(book_id)),
+                    order_id: Set(None),
+                },
+            )
+            .await?;
+            let mut active: StocktakeActiveModel = stocktake.into();
+            active.loss_record_id = Set(Some(record.id));
+            active.update(&txn).await?
+        } else {
+            stocktake
+        };
+
+        txn.commit().await?;
+        Ok(StocktakeDetail { stocktake, items })
+    }
+}
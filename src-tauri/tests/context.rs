@@ -1,6 +1,6 @@
 use accounting_assistant_lib::entity;
 use accounting_assistant_lib::services::{
-    AccountingBookService, AccountingService, AttachmentService,
+    AccountingBookService, AccountingService,
 };
 use once_cell::sync::Lazy;
 use sea_orm::{Database, DatabaseConnection};
@@ -23,6 +23,7 @@ impl Default for TestOptions {
 }
 
 /// 全局数据库连接单例
+#[allow(dead_code)]
 static DB_CONNECTION: Lazy<DatabaseConnection> = Lazy::new(|| {
     // 注意：这是在非 async 上下文中初始化的，但在测试中使用
     // 实际的数据库初始化在第一个测试运行时发生
@@ -47,6 +48,7 @@ async fn init_db_connection_internal() -> Result<DatabaseConnection, Box<dyn std
 }
 
 /// 异步初始化数据库连接并创建默认账簿
+#[allow(dead_code)]
 async fn init_db_with_default_book() -> Result<DatabaseConnection, Box<dyn std::error::Error>> {
     let db = init_db_connection_internal().await?;
 
@@ -129,13 +131,12 @@ where
     let db = init_db_connection_internal().await?;
 
     // 根据测试选项决定是否创建默认账簿
-    {
-        let options = TEST_OPTIONS.lock().unwrap();
-        if options.create_default_book {
-            let book_service = AccountingBookService::new(db.clone());
-            book_service.create_default_book().await?;
-        }
-    } // 释放 MutexGuard，防止测试 panic 时污染 mutex
+    // 先读取选项并立即释放 MutexGuard，防止跨 await 持锁或测试 panic 时污染 mutex
+    let create_default_book = TEST_OPTIONS.lock().unwrap().create_default_book;
+    if create_default_book {
+        let book_service = AccountingBookService::new(db.clone());
+        book_service.create_default_book().await?;
+    }
 
     test_fn(db).await
 }
@@ -172,6 +173,7 @@ pub fn teardown() {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod context_tests {
     use super::*;
 
@@ -1,4 +1,4 @@
-use accounting_assistant_lib::entity::accounting_book::{self, ActiveModel, Entity, Model};
+use accounting_assistant_lib::entity::accounting_book::Entity;
 use accounting_assistant_lib::entity::accounting_record;
 use accounting_assistant_lib::enums::{AccountingChannel, AccountingRecordState, AccountingType};
 use accounting_assistant_lib::services::accounting_book::dto::{
@@ -8,7 +8,7 @@ use accounting_assistant_lib::services::accounting_book::DEFAULT_BOOK_ID;
 use accounting_assistant_lib::services::AccountingBookService;
 use chrono::Local;
 use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use sea_orm::{ActiveModelTrait, EntityTrait, PaginatorTrait, Set};
 
 use crate::context::run_in_transaction;
 use serial_test::serial;
@@ -833,7 +833,7 @@ async fn test_get_records_by_book_id_paginated_basic() {
         for i in 1..=5 {
             let record = accounting_record::ActiveModel {
                 id: Set(20240103000 + i),
-                amount: Set(Decimal::new(10000 * i as i64, 2)),
+                amount: Set(Decimal::new(10000 * i, 2)),
                 record_time: Set(Local::now().naive_local()),
                 accounting_type: Set(AccountingType::Income),
                 title: Set(format!("记录{}", i)),
@@ -7,7 +7,7 @@ use accounting_assistant_lib::services::accounting::dto::{
 use accounting_assistant_lib::services::accounting_book::DEFAULT_BOOK_ID;
 use accounting_assistant_lib::services::AccountingService;
 use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
 use serial_test::serial;
 
 use crate::context::run_in_transaction;
@@ -1,7 +1,7 @@
 use accounting_assistant_lib::entity::attachment::{self, Entity};
 use accounting_assistant_lib::services::AttachmentService;
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 use serial_test::serial;
 
 use crate::context::run_in_transaction;
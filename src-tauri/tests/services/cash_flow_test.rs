@@ -0,0 +1,428 @@
+use accounting_assistant_lib::enums::AccountingChannel;
+use accounting_assistant_lib::services::accounting::dto::{
+    AddAccountingRecordDto, CreateWriteOffRecordDto,
+};
+use accounting_assistant_lib::services::cash_flow::dto::{CashFlowForecastDto, ForecastSource};
+use accounting_assistant_lib::services::cash_flow::service::detect_interval;
+use accounting_assistant_lib::services::order::dto::{
+    AddOrderPaymentDto, CreateOrderDto, CreateOrderItemDto,
+};
+use accounting_assistant_lib::services::{AccountingService, CashFlowService, OrderService};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造记账记录 DTO
+fn make_record(
+    amount: f64,
+    time: &str,
+    accounting_type: &str,
+    title: &str,
+    channel: &str,
+) -> AddAccountingRecordDto {
+    AddAccountingRecordDto {
+        amount,
+        record_time: time.to_string(),
+        accounting_type: accounting_type.to_string(),
+        title: title.to_string(),
+        channel: channel.to_string(),
+        remark: None,
+        write_off_id: None,
+        book_id: None,
+        order_id: None,
+    }
+}
+
+/// 辅助函数：构造单明细订单 DTO
+fn make_order(order_type: &str, amount: Decimal, due_date: Option<&str>) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: order_type.to_string(),
+        customer_id: None,
+        customer_name: None,
+        items: vec![CreateOrderItemDto {
+            product_id: 1,
+            product_name: "苹果".to_string(),
+            quantity: Decimal::ONE,
+            unit: "斤".to_string(),
+            unit_price: amount,
+            discount_amount: None,
+            discount_rate: None,
+            remark: None,
+        }],
+        remark: None,
+        actual_amount: None,
+        sub_type: None,
+        due_date: due_date.map(|d| d.to_string()),
+    }
+}
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+// ==================== detect_interval 测试 ====================
+
+#[test]
+fn test_detect_interval_regular_monthly() {
+    let dates = vec![date("2024-01-05"), date("2024-02-05"), date("2024-03-05")];
+    // 间隔 31 / 29 天，中位数 31，容差 7
+    assert_eq!(detect_interval(&dates), Some(31));
+}
+
+#[test]
+fn test_detect_interval_irregular_or_too_few() {
+    assert_eq!(
+        detect_interval(&[date("2024-01-01"), date("2024-01-08")]),
+        None
+    );
+
+    let irregular = vec![date("2024-01-01"), date("2024-01-03"), date("2024-02-20")];
+    assert_eq!(detect_interval(&irregular), None);
+}
+
+// ==================== get_cash_flow_forecast 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_forecast_opening_balance_and_pending_posting() {
+    run_in_transaction(|db| async move {
+        let accounting = AccountingService::new(db.clone());
+        let service = CashFlowService::new(db.clone());
+
+        // 已入账收入 500（现金），起始日之前
+        let income = accounting
+            .create_record(make_record(
+                500.0,
+                "2024-05-20 10:00:00",
+                "Income",
+                "货款",
+                "Cash",
+            ))
+            .await?;
+        accounting.post_record(income.id).await?;
+
+        // 待入账支出 800（现金），预测期第 3 天
+        accounting
+            .create_record(make_record(
+                800.0,
+                "2024-06-03 09:00:00",
+                "Expenditure",
+                "房租",
+                "Cash",
+            ))
+            .await?;
+
+        let forecast = service
+            .get_cash_flow_forecast(CashFlowForecastDto {
+                horizon_days: Some(30),
+                start_date: Some("2024-06-01".to_string()),
+            })
+            .await?;
+
+        assert_eq!(forecast.start_date, date("2024-06-01"));
+        assert_eq!(forecast.end_date, date("2024-06-30"));
+        assert_eq!(forecast.days.len(), 30);
+
+        let opening_cash = forecast
+            .opening_balances
+            .iter()
+            .find(|b| b.channel == AccountingChannel::Cash)
+            .expect("应有现金期初余额");
+        assert_eq!(opening_cash.balance, Decimal::new(500, 0));
+
+        let pending = forecast
+            .items
+            .iter()
+            .find(|i| i.source == ForecastSource::PendingPosting)
+            .expect("应包含待入账记录");
+        assert_eq!(pending.date, date("2024-06-03"));
+        assert_eq!(pending.amount, Decimal::new(-800, 0));
+
+        // 第 3 天现金余额为 -300，应被标记
+        assert_eq!(forecast.negative_days.first(), Some(&date("2024-06-03")));
+        let day3 = &forecast.days[2];
+        assert_eq!(day3.outflow, Decimal::new(800, 0));
+        assert_eq!(day3.negative_channels, vec![AccountingChannel::Cash]);
+        assert!(forecast.days[1].negative_channels.is_empty());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_forecast_write_off_direction_across_window() {
+    run_in_transaction(|db| async move {
+        let accounting = AccountingService::new(db.clone());
+        let service = CashFlowService::new(db.clone());
+
+        // 一年多以前的收入 500，超出周期性识别回看范围但仍计入期初余额
+        let income = accounting
+            .create_record(make_record(
+                500.0,
+                "2023-01-10 10:00:00",
+                "Income",
+                "货款",
+                "Cash",
+            ))
+            .await?;
+        accounting.post_record(income.id).await?;
+
+        // 支出 300，起始日之前冲回 100
+        let expense = accounting
+            .create_record(make_record(
+                300.0,
+                "2024-03-01 10:00:00",
+                "Expenditure",
+                "采购",
+                "Cash",
+            ))
+            .await?;
+        accounting.post_record(expense.id).await?;
+        accounting
+            .create_write_off_record(CreateWriteOffRecordDto {
+                original_record_id: expense.id,
+                amount: -100.0,
+                channel: None,
+                remark: None,
+                record_time: Some("2024-03-05 10:00:00".to_string()),
+            })
+            .await?;
+
+        // 预测期内再冲回 50，被冲账记录在预测期之前
+        let late = accounting
+            .create_write_off_record(CreateWriteOffRecordDto {
+                original_record_id: expense.id,
+                amount: -50.0,
+                channel: None,
+                remark: None,
+                record_time: Some("2024-06-02 10:00:00".to_string()),
+            })
+            .await?;
+
+        let forecast = service
+            .get_cash_flow_forecast(CashFlowForecastDto {
+                horizon_days: Some(7),
+                start_date: Some("2024-06-01".to_string()),
+            })
+            .await?;
+
+        let opening_cash = forecast
+            .opening_balances
+            .iter()
+            .find(|b| b.channel == AccountingChannel::Cash)
+            .expect("应有现金期初余额");
+        assert_eq!(opening_cash.balance, Decimal::new(300, 0));
+
+        let posted: Vec<_> = forecast
+            .items
+            .iter()
+            .filter(|i| i.source == ForecastSource::Posted)
+            .collect();
+        assert_eq!(posted.len(), 1);
+        assert_eq!(posted[0].reference_id, Some(late.id));
+        assert_eq!(posted[0].amount, Decimal::new(50, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_forecast_pending_orders_by_due_date() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = CashFlowService::new(db.clone());
+
+        // 销售订单：到期日在预测期内
+        let sales = orders
+            .create_order(make_order(
+                "Sales",
+                Decimal::new(300, 0),
+                Some("2024-06-10"),
+            ))
+            .await?;
+        // 采购订单：无到期日，视为起始日
+        let purchase = orders
+            .create_order(make_order("Purchase", Decimal::new(120, 0), None))
+            .await?;
+        // 销售订单：到期日超出预测期
+        orders
+            .create_order(make_order(
+                "Sales",
+                Decimal::new(999, 0),
+                Some("2024-09-01"),
+            ))
+            .await?;
+
+        let forecast = service
+            .get_cash_flow_forecast(CashFlowForecastDto {
+                horizon_days: Some(30),
+                start_date: Some("2024-06-01".to_string()),
+            })
+            .await?;
+
+        let order_items: Vec<_> = forecast
+            .items
+            .iter()
+            .filter(|i| i.source == ForecastSource::PendingOrder)
+            .collect();
+        assert_eq!(order_items.len(), 2);
+
+        let sales_item = order_items
+            .iter()
+            .find(|i| i.reference_id == Some(sales.id))
+            .expect("应包含销售订单");
+        assert_eq!(sales_item.date, date("2024-06-10"));
+        assert_eq!(sales_item.amount, Decimal::new(300, 0));
+
+        let purchase_item = order_items
+            .iter()
+            .find(|i| i.reference_id == Some(purchase.id))
+            .expect("应包含采购订单");
+        assert_eq!(purchase_item.date, date("2024-06-01"));
+        assert_eq!(purchase_item.amount, Decimal::new(-120, 0));
+
+        // 待结账订单尚未确定渠道，单独累计，不计入渠道余额和负余额判断
+        assert!(order_items.iter().all(|i| i.channel.is_none()));
+        assert_eq!(forecast.days[0].unassigned, Decimal::new(-120, 0));
+        assert_eq!(
+            forecast.days.last().unwrap().unassigned,
+            Decimal::new(180, 0)
+        );
+        assert!(forecast.days.iter().all(|d| d.balances.is_empty()));
+        assert!(forecast.negative_days.is_empty());
+
+        assert_eq!(forecast.total_inflow, Decimal::new(300, 0));
+        assert_eq!(forecast.total_outflow, Decimal::new(120, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_forecast_partially_paid_order_uses_outstanding() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = CashFlowService::new(db.clone());
+
+        let order = orders
+            .create_order(make_order(
+                "Sales",
+                Decimal::new(500, 0),
+                Some("2024-06-20"),
+            ))
+            .await?;
+        orders
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(200, 0),
+                channel: "Cash".to_string(),
+                paid_at: Some("2024-05-10 10:00:00".to_string()),
+                remark: None,
+            })
+            .await?;
+
+        let forecast = service
+            .get_cash_flow_forecast(CashFlowForecastDto {
+                horizon_days: Some(30),
+                start_date: Some("2024-06-01".to_string()),
+            })
+            .await?;
+
+        let item = forecast
+            .items
+            .iter()
+            .find(|i| i.reference_id == Some(order.id))
+            .expect("应包含部分收款订单");
+        assert_eq!(item.date, date("2024-06-20"));
+        assert_eq!(item.amount, Decimal::new(300, 0));
+        // 渠道沿用已收款的渠道
+        assert_eq!(item.channel, Some(AccountingChannel::Cash));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_forecast_projects_recurring_records() {
+    run_in_transaction(|db| async move {
+        let accounting = AccountingService::new(db.clone());
+        let service = CashFlowService::new(db.clone());
+
+        // 每月 5 日的水电费
+        for time in [
+            "2024-03-05 10:00:00",
+            "2024-04-05 10:00:00",
+            "2024-05-05 10:00:00",
+        ] {
+            let record = accounting
+                .create_record(make_record(200.0, time, "Expenditure", "水电费", "Wechat"))
+                .await?;
+            accounting.post_record(record.id).await?;
+        }
+
+        let forecast = service
+            .get_cash_flow_forecast(CashFlowForecastDto {
+                horizon_days: Some(60),
+                start_date: Some("2024-06-01".to_string()),
+            })
+            .await?;
+
+        assert_eq!(forecast.recurring_patterns.len(), 1);
+        let pattern = &forecast.recurring_patterns[0];
+        assert_eq!(pattern.title, "水电费");
+        assert_eq!(pattern.occurrences, 3);
+        assert_eq!(pattern.average_amount, Decimal::new(-200, 0));
+
+        let recurring: Vec<_> = forecast
+            .items
+            .iter()
+            .filter(|i| i.source == ForecastSource::Recurring)
+            .collect();
+        assert_eq!(recurring.len(), 2);
+        assert_eq!(
+            recurring[0].date,
+            pattern.last_date + chrono::Duration::days(pattern.interval_days)
+        );
+        assert!(recurring
+            .iter()
+            .all(|i| i.channel == Some(AccountingChannel::Wechat)));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_forecast_invalid_horizon_error() {
+    run_in_transaction(|db| async move {
+        let service = CashFlowService::new(db.clone());
+
+        let result = service
+            .get_cash_flow_forecast(CashFlowForecastDto {
+                horizon_days: Some(0),
+                start_date: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -3,7 +3,7 @@ use accounting_assistant_lib::services::accounting_book::DEFAULT_BOOK_ID;
 use accounting_assistant_lib::services::category::DEFAULT_CATEGORY_NAME;
 use accounting_assistant_lib::services::category::dto::{CreateCategoryDto, UpdateCategoryDto};
 use accounting_assistant_lib::services::CategoryService;
-use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, EntityTrait};
 use serial_test::serial;
 
 use crate::context::run_in_transaction;
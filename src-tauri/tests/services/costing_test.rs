@@ -0,0 +1,218 @@
+use accounting_assistant_lib::enums::StockMovementType;
+use accounting_assistant_lib::services::costing::dto::{InventoryValuationDto, QueryCogsDto};
+use accounting_assistant_lib::services::order::dto::{
+    CreateOrderDto, CreateOrderItemDto, CreateOrderReturnDto, CreateOrderReturnItemDto,
+    ReopenOrderDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::setting::dto::{CostingConfig, CostingMethod};
+use accounting_assistant_lib::services::{CostingService, OrderService, SettingService};
+use chrono::{Duration, Local};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建并结账单明细订单，返回订单 ID
+async fn settled_order(
+    service: &OrderService,
+    order_type: &str,
+    quantity: i64,
+    unit_price: i64,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let order = service
+        .create_order(CreateOrderDto {
+            order_type: order_type.to_string(),
+            customer_id: None,
+            customer_name: None,
+            items: vec![CreateOrderItemDto {
+                product_id: 1,
+                product_name: "苹果".to_string(),
+                quantity: Decimal::new(quantity, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(unit_price, 2),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            }],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await?;
+    service
+        .settle_order(SettleOrderDto {
+            order_id: order.id,
+            channel: Some("Cash".to_string()),
+            actual_amount: None,
+            payments: None,
+        })
+        .await?;
+    Ok(order.id)
+}
+
+/// 辅助函数：切换成本核算方法
+async fn set_method(
+    service: &SettingService,
+    method: CostingMethod,
+) -> Result<(), Box<dyn std::error::Error>> {
+    service
+        .update_costing_config(CostingConfig { method })
+        .await?;
+    Ok(())
+}
+
+fn all_cogs() -> QueryCogsDto {
+    QueryCogsDto {
+        start_date: None,
+        end_date: None,
+        order_id: None,
+    }
+}
+
+fn today() -> InventoryValuationDto {
+    InventoryValuationDto { as_of_date: None }
+}
+
+// ==================== 成本核算测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_cogs_and_valuation_by_method() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let costing = CostingService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+
+        // 10 × 4.00 + 10 × 6.00 入库，销售 15
+        settled_order(&orders, "Purchase", 10, 400).await?;
+        settled_order(&orders, "Purchase", 10, 600).await?;
+        let sales_id = settled_order(&orders, "Sales", 15, 900).await?;
+
+        // 默认移动加权平均：单位成本 5.00
+        assert_eq!(
+            settings.get_costing_config().await?.method,
+            CostingMethod::MovingAverage
+        );
+        let cogs = costing
+            .get_cogs(QueryCogsDto {
+                order_id: Some(sales_id),
+                ..all_cogs()
+            })
+            .await?;
+        assert_eq!(cogs.lines.len(), 1);
+        assert_eq!(cogs.lines[0].unit_cost, Decimal::new(500, 2));
+        assert_eq!(cogs.total_cost, Decimal::new(7500, 2));
+        let valuation = costing.get_inventory_valuation(today()).await?;
+        assert_eq!(valuation.lines[0].quantity, Decimal::new(5, 0));
+        assert_eq!(valuation.total_value, Decimal::new(2500, 2));
+
+        // 先进先出：10 × 4.00 + 5 × 6.00
+        set_method(&settings, CostingMethod::Fifo).await?;
+        let cogs = costing.get_cogs(all_cogs()).await?;
+        assert_eq!(cogs.method, CostingMethod::Fifo);
+        assert_eq!(cogs.total_cost, Decimal::new(7000, 2));
+        let valuation = costing.get_inventory_valuation(today()).await?;
+        assert_eq!(valuation.lines[0].unit_cost, Some(Decimal::new(600, 2)));
+        assert_eq!(valuation.total_value, Decimal::new(3000, 2));
+
+        // 销售退货按原出库成本入库，冲减销售成本
+        let (_, items) = orders.get_order_by_id(sales_id).await?.expect("订单应存在");
+        orders
+            .create_return(CreateOrderReturnDto {
+                order_id: sales_id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: items[0].id,
+                    quantity: Decimal::new(3, 0),
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+        let cogs = costing.get_cogs(all_cogs()).await?;
+        assert_eq!(cogs.lines.len(), 2);
+        assert_eq!(cogs.lines[1].movement_type, StockMovementType::SalesReturn);
+        assert_eq!(cogs.lines[1].quantity, Decimal::new(-3, 0));
+        assert_eq!(cogs.lines[1].cost, Decimal::new(-1400, 2));
+        assert_eq!(cogs.total_cost, Decimal::new(5600, 2));
+        let valuation = costing.get_inventory_valuation(today()).await?;
+        assert_eq!(valuation.lines[0].quantity, Decimal::new(8, 0));
+        assert_eq!(valuation.total_value, Decimal::new(4400, 2));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_reopen_purchase_removes_its_cost() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let costing = CostingService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+
+        settled_order(&orders, "Purchase", 10, 400).await?;
+        let second = settled_order(&orders, "Purchase", 10, 600).await?;
+        orders
+            .reopen_order(ReopenOrderDto {
+                order_id: second,
+                reason: None,
+            })
+            .await?;
+
+        // 撤销结账按原采购单价出库，两种方法均只剩第一批
+        for method in [CostingMethod::MovingAverage, CostingMethod::Fifo] {
+            set_method(&settings, method).await?;
+            let valuation = costing.get_inventory_valuation(today()).await?;
+            assert_eq!(valuation.lines[0].quantity, Decimal::new(10, 0));
+            assert_eq!(valuation.total_value, Decimal::new(4000, 2));
+        }
+
+        // 估值日期早于任何变动时无库存
+        let yesterday = (Local::now().date_naive() - Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        let valuation = costing
+            .get_inventory_valuation(InventoryValuationDto {
+                as_of_date: Some(yesterday),
+            })
+            .await?;
+        assert!(valuation.lines.is_empty());
+        assert_eq!(valuation.total_value, Decimal::ZERO);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_costing_invalid_dates() {
+    run_in_transaction(|db| async move {
+        let costing = CostingService::new(db.clone());
+
+        let result = costing
+            .get_cogs(QueryCogsDto {
+                start_date: Some("2024-02-01".to_string()),
+                end_date: Some("2024-01-01".to_string()),
+                order_id: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "结束日期不能早于开始日期");
+
+        let result = costing
+            .get_inventory_valuation(InventoryValuationDto {
+                as_of_date: Some("2024/01/01".to_string()),
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
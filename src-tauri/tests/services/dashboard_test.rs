@@ -0,0 +1,327 @@
+use accounting_assistant_lib::services::accounting::dto::{
+    AddAccountingRecordDto, CreateWriteOffRecordDto,
+};
+use accounting_assistant_lib::services::dashboard::dto::DashboardSeriesDto;
+use accounting_assistant_lib::services::order::dto::{
+    CreateOrderDto, CreateOrderItemDto, SettleOrderDto, SettlePaymentDto,
+};
+use accounting_assistant_lib::services::{AccountingService, DashboardService, OrderService};
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建并入账一条记账记录
+async fn create_posted_record(
+    service: &AccountingService,
+    amount: f64,
+    time: &str,
+    accounting_type: &str,
+    channel: &str,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let record = service
+        .create_record(AddAccountingRecordDto {
+            amount,
+            record_time: time.to_string(),
+            accounting_type: accounting_type.to_string(),
+            title: "测试记录".to_string(),
+            channel: channel.to_string(),
+            remark: None,
+            write_off_id: None,
+            book_id: None,
+            order_id: None,
+        })
+        .await?;
+    service.post_record(record.id).await?;
+    Ok(record.id)
+}
+
+/// 辅助函数：构造查询 DTO
+fn make_query(start: &str, end: &str, granularity: &str) -> DashboardSeriesDto {
+    DashboardSeriesDto {
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        granularity: granularity.to_string(),
+        book_id: None,
+        channel: None,
+        category_id: None,
+    }
+}
+
+/// 辅助函数：构造单明细销售订单
+fn make_sales_order(amount: Decimal) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: "Sales".to_string(),
+        customer_id: None,
+        customer_name: None,
+        items: vec![CreateOrderItemDto {
+            product_id: 1,
+            product_name: "苹果".to_string(),
+            quantity: Decimal::ONE,
+            unit: "斤".to_string(),
+            unit_price: amount,
+            discount_amount: None,
+            discount_rate: None,
+            remark: None,
+        }],
+        remark: None,
+        actual_amount: None,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+// ==================== get_dashboard_series 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_dashboard_daily_income_and_expenditure() {
+    run_in_transaction(|db| async move {
+        let accounting = AccountingService::new(db.clone());
+        let service = DashboardService::new(db.clone());
+
+        let income_id =
+            create_posted_record(&accounting, 100.0, "2024-06-01 09:00:00", "Income", "Cash")
+                .await?;
+        create_posted_record(
+            &accounting,
+            40.0,
+            "2024-06-02 18:30:00",
+            "Expenditure",
+            "Wechat",
+        )
+        .await?;
+
+        // 冲账记录按被冲账记录的类型计入收入
+        accounting
+            .create_write_off_record(CreateWriteOffRecordDto {
+                original_record_id: income_id,
+                amount: -10.0,
+                channel: None,
+                remark: None,
+                record_time: Some("2024-06-01 10:00:00".to_string()),
+            })
+            .await?;
+
+        // 待入账记录不计入
+        accounting
+            .create_record(AddAccountingRecordDto {
+                amount: 999.0,
+                record_time: "2024-06-01 12:00:00".to_string(),
+                accounting_type: "Income".to_string(),
+                title: "待入账".to_string(),
+                channel: "Cash".to_string(),
+                remark: None,
+                write_off_id: None,
+                book_id: None,
+                order_id: None,
+            })
+            .await?;
+
+        let series = service
+            .get_dashboard_series(make_query("2024-06-01", "2024-06-03", "Day"))
+            .await?;
+
+        assert_eq!(series.buckets.len(), 3);
+        assert_eq!(series.buckets[0].bucket_start, date("2024-06-01"));
+        assert_eq!(series.buckets[0].metrics.income, Decimal::new(90, 0));
+        assert_eq!(series.buckets[1].metrics.expenditure, Decimal::new(40, 0));
+        assert_eq!(series.buckets[2].metrics.net, Decimal::ZERO);
+
+        assert_eq!(series.totals.income, Decimal::new(90, 0));
+        assert_eq!(series.totals.expenditure, Decimal::new(40, 0));
+        assert_eq!(series.totals.net, Decimal::new(50, 0));
+
+        // 渠道筛选
+        let mut query = make_query("2024-06-01", "2024-06-03", "Day");
+        query.channel = Some("Wechat".to_string());
+        let filtered = service.get_dashboard_series(query).await?;
+        assert_eq!(filtered.totals.income, Decimal::ZERO);
+        assert_eq!(filtered.totals.expenditure, Decimal::new(40, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_dashboard_weekly_buckets_and_previous_period_delta() {
+    run_in_transaction(|db| async move {
+        let accounting = AccountingService::new(db.clone());
+        let service = DashboardService::new(db.clone());
+
+        // 上期（2024-05-20 ~ 2024-06-02）
+        create_posted_record(&accounting, 50.0, "2024-05-22 09:00:00", "Income", "Cash").await?;
+        // 本期（2024-06-03 周一 ~ 2024-06-16 周日）
+        create_posted_record(&accounting, 60.0, "2024-06-03 09:00:00", "Income", "Cash").await?;
+        create_posted_record(&accounting, 40.0, "2024-06-16 21:00:00", "Income", "Cash").await?;
+
+        let series = service
+            .get_dashboard_series(make_query("2024-06-03", "2024-06-16", "Week"))
+            .await?;
+
+        assert_eq!(series.buckets.len(), 2);
+        assert_eq!(series.buckets[0].bucket_start, date("2024-06-03"));
+        assert_eq!(series.buckets[1].bucket_start, date("2024-06-10"));
+        assert_eq!(series.buckets[0].metrics.income, Decimal::new(60, 0));
+        assert_eq!(series.buckets[1].metrics.income, Decimal::new(40, 0));
+
+        assert_eq!(series.previous_start_date, date("2024-05-20"));
+        assert_eq!(series.previous_end_date, date("2024-06-02"));
+        assert_eq!(series.previous_totals.income, Decimal::new(50, 0));
+        assert_eq!(series.deltas.income.change, Decimal::new(50, 0));
+        assert_eq!(series.deltas.income.change_rate, Some(Decimal::new(100, 0)));
+        // 上期支出为 0 时无变化率
+        assert_eq!(series.deltas.expenditure.change_rate, None);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_dashboard_order_metrics() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = DashboardService::new(db.clone());
+
+        let settled = orders
+            .create_order(make_sales_order(Decimal::new(300, 0)))
+            .await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: settled.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+        orders
+            .create_order(make_sales_order(Decimal::new(200, 0)))
+            .await?;
+        let cancelled = orders
+            .create_order(make_sales_order(Decimal::new(500, 0)))
+            .await?;
+        orders.cancel_order(cancelled.id).await?;
+
+        let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let series = service
+            .get_dashboard_series(make_query(&today, &today, "Month"))
+            .await?;
+
+        assert_eq!(series.buckets.len(), 1);
+        assert_eq!(series.totals.order_count, 2);
+        assert_eq!(series.totals.average_order_value, Decimal::new(250, 0));
+        assert_eq!(series.totals.settled_sales, Decimal::new(300, 0));
+        // 结账生成的收入记录同样计入
+        assert_eq!(series.totals.income, Decimal::new(300, 0));
+
+        // 渠道筛选：待结账订单渠道为 Unknown，不计入
+        let mut query = make_query(&today, &today, "Day");
+        query.channel = Some("Cash".to_string());
+        let filtered = service.get_dashboard_series(query).await?;
+        assert_eq!(filtered.totals.order_count, 1);
+        assert_eq!(filtered.totals.average_order_value, Decimal::new(300, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_dashboard_invalid_input_error() {
+    run_in_transaction(|db| async move {
+        let service = DashboardService::new(db.clone());
+
+        let result = service
+            .get_dashboard_series(make_query("2024-06-01", "2024-06-30", "Year"))
+            .await;
+        assert!(result.is_err());
+
+        let result = service
+            .get_dashboard_series(make_query("2024-06-30", "2024-06-01", "Day"))
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_dashboard_mixed_channel_orders_and_exact_totals() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = DashboardService::new(db.clone());
+
+        // 组合支付的订单按收付款渠道筛选时应计入
+        let mixed = orders
+            .create_order(make_sales_order(Decimal::new(1_0005, 4)))
+            .await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: mixed.id,
+                channel: None,
+                actual_amount: None,
+                payments: Some(vec![
+                    SettlePaymentDto {
+                        channel: "Cash".to_string(),
+                        amount: Decimal::new(5, 1),
+                    },
+                    SettlePaymentDto {
+                        channel: "Wechat".to_string(),
+                        amount: Decimal::new(5005, 4),
+                    },
+                ]),
+            })
+            .await?;
+        let cash = orders
+            .create_order(make_sales_order(Decimal::new(2_0001, 4)))
+            .await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: cash.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+
+        let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let series = service
+            .get_dashboard_series(make_query(&today, &today, "Day"))
+            .await?;
+        // 金额按 Decimal 精确累加，不经浮点汇总或截断为两位小数
+        assert_eq!(series.totals.order_count, 2);
+        assert_eq!(series.totals.settled_sales, Decimal::new(3_0006, 4));
+
+        let mut query = make_query(&today, &today, "Day");
+        query.channel = Some("Wechat".to_string());
+        let wechat = service.get_dashboard_series(query).await?;
+        assert_eq!(wechat.totals.order_count, 1);
+        assert_eq!(wechat.totals.settled_sales, Decimal::new(1_0005, 4));
+
+        let mut query = make_query(&today, &today, "Day");
+        query.channel = Some("Cash".to_string());
+        let cash = service.get_dashboard_series(query).await?;
+        assert_eq!(cash.totals.order_count, 2);
+        assert_eq!(cash.totals.settled_sales, Decimal::new(3_0006, 4));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,453 @@
+use accounting_assistant_lib::enums::{OrderRevisionOrigin, OrderStatus, ReceivingStatus};
+use accounting_assistant_lib::services::goods_receipt::dto::{
+    CreateGoodsReceiptDto, CreateGoodsReceiptItemDto,
+};
+use accounting_assistant_lib::services::order::dto::{
+    AddOrderPaymentDto, CreateOrderDto, CreateOrderItemDto, SettleOrderDto, UpdateOrderDto,
+};
+use accounting_assistant_lib::services::setting::dto::PurchaseConfig;
+use accounting_assistant_lib::services::{
+    GoodsReceiptService, OrderRevisionService, OrderService, SettingService, StockService,
+};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造一条商品明细 DTO
+fn make_item(product_id: i64, name: &str, quantity: i64, unit_price: i64) -> CreateOrderItemDto {
+    CreateOrderItemDto {
+        product_id,
+        product_name: name.to_string(),
+        quantity: Decimal::new(quantity, 0),
+        unit: "斤".to_string(),
+        unit_price: Decimal::new(unit_price, 2),
+        discount_amount: None,
+        discount_rate: None,
+        remark: None,
+    }
+}
+
+/// 辅助函数：构造订单（苹果 10 斤 × 8.00 + 香蕉 5 斤 × 5.00，合计 105.00）
+fn make_order(order_type: &str) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: order_type.to_string(),
+        customer_id: None,
+        customer_name: None,
+        items: vec![make_item(1, "苹果", 10, 800), make_item(2, "香蕉", 5, 500)],
+        remark: None,
+        actual_amount: None,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+/// 辅助函数：构造收货单
+fn make_receipt(order_id: i64, items: Vec<(i64, i64)>) -> CreateGoodsReceiptDto {
+    CreateGoodsReceiptDto {
+        order_id,
+        items: items
+            .into_iter()
+            .map(|(order_item_id, quantity)| CreateGoodsReceiptItemDto {
+                order_item_id,
+                quantity: Decimal::new(quantity, 0),
+                lot_no: None,
+                production_date: None,
+                expiry_date: None,
+            })
+            .collect(),
+        remark: None,
+    }
+}
+
+/// 辅助函数：按商品名称查找订单明细 ID
+async fn item_id(orders: &OrderService, order_id: i64, name: &str) -> i64 {
+    let (_, items) = orders.get_order_by_id(order_id).await.unwrap().unwrap();
+    items.iter().find(|i| i.product_name == name).unwrap().id
+}
+
+/// 辅助函数：现金结账
+fn cash_settle(order_id: i64) -> SettleOrderDto {
+    SettleOrderDto {
+        order_id,
+        channel: Some("Cash".to_string()),
+        actual_amount: None,
+        payments: None,
+    }
+}
+
+/// 辅助函数：现金付款
+fn cash_payment(order_id: i64, amount: i64) -> AddOrderPaymentDto {
+    AddOrderPaymentDto {
+        order_id,
+        amount: Decimal::new(amount, 2),
+        channel: "Cash".to_string(),
+        paid_at: None,
+        remark: None,
+    }
+}
+
+/// 辅助函数：开启按实收数量结算
+async fn enable_settle_by_received(
+    settings: &SettingService,
+) -> Result<(), Box<dyn std::error::Error>> {
+    settings
+        .update_purchase_config(PurchaseConfig {
+            settle_by_received: true,
+        })
+        .await?;
+    Ok(())
+}
+
+// ==================== 收货登记测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_goods_receipt_partial_then_full() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = GoodsReceiptService::new(db.clone());
+
+        let order = orders.create_order(make_order("Purchase")).await?;
+        assert_eq!(order.receiving_status, Some(ReceivingStatus::NotReceived));
+        let apple = item_id(&orders, order.id, "苹果").await;
+        let banana = item_id(&orders, order.id, "香蕉").await;
+
+        // 第一次到货：苹果 6 斤
+        let first = service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 6)]))
+            .await?;
+        assert!(first.receipt.receipt_no.starts_with("SH-"));
+        assert_eq!(first.items.len(), 1);
+        assert_eq!(first.items[0].product_name, "苹果");
+
+        let summary = service.get_order_receiving(order.id).await?;
+        assert_eq!(summary.status, ReceivingStatus::PartiallyReceived);
+        assert_eq!(summary.lines[0].received_quantity, Decimal::new(6, 0));
+        assert_eq!(summary.lines[0].short_quantity, Decimal::new(4, 0));
+        assert_eq!(summary.lines[1].short_quantity, Decimal::new(5, 0));
+        let (updated, _) = orders.get_order_by_id(order.id).await?.unwrap();
+        assert_eq!(
+            updated.receiving_status,
+            Some(ReceivingStatus::PartiallyReceived)
+        );
+
+        // 第二次到货：苹果补齐，香蕉超收 1 斤
+        service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 4), (banana, 6)]))
+            .await?;
+        let summary = service.get_order_receiving(order.id).await?;
+        assert_eq!(summary.status, ReceivingStatus::Received);
+        assert_eq!(summary.lines[0].short_quantity, Decimal::ZERO);
+        assert_eq!(summary.lines[1].received_quantity, Decimal::new(6, 0));
+        assert_eq!(summary.lines[1].over_quantity, Decimal::new(1, 0));
+        assert_eq!(service.get_goods_receipts(order.id).await?.len(), 2);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_goods_receipt_validation() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = GoodsReceiptService::new(db.clone());
+
+        // 销售订单不能收货
+        let sales = orders.create_order(make_order("Sales")).await?;
+        assert_eq!(sales.receiving_status, None);
+        let sales_item = item_id(&orders, sales.id, "苹果").await;
+        let err = service
+            .create_goods_receipt(make_receipt(sales.id, vec![(sales_item, 1)]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("仅采购订单"));
+
+        let order = orders.create_order(make_order("Purchase")).await?;
+        let apple = item_id(&orders, order.id, "苹果").await;
+
+        // 明细为空、明细不属于该订单、数量不大于 0
+        assert!(service
+            .create_goods_receipt(make_receipt(order.id, vec![]))
+            .await
+            .is_err());
+        assert!(service
+            .create_goods_receipt(make_receipt(order.id, vec![(sales_item, 1)]))
+            .await
+            .is_err());
+        assert!(service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 0)]))
+            .await
+            .is_err());
+
+        // 已取消的订单不能收货
+        orders.cancel_order(order.id).await?;
+        assert!(service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 1)]))
+            .await
+            .is_err());
+        assert!(service.get_goods_receipts(order.id).await?.is_empty());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_delete_goods_receipt_and_edit_lock() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = GoodsReceiptService::new(db.clone());
+
+        let order = orders.create_order(make_order("Purchase")).await?;
+        let apple = item_id(&orders, order.id, "苹果").await;
+        let receipt = service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 10)]))
+            .await?;
+
+        // 已收货的订单不能替换明细，但可修改备注
+        let err = orders
+            .update_order(UpdateOrderDto {
+                order_id: order.id,
+                items: Some(vec![make_item(1, "苹果", 8, 800)]),
+                remark: None,
+                due_date: None,
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("已登记收货"));
+        orders
+            .update_order(UpdateOrderDto {
+                order_id: order.id,
+                items: None,
+                remark: Some("到货破损 1 箱".to_string()),
+                due_date: None,
+            })
+            .await?;
+
+        // 删除收货单后恢复为未收货
+        let updated = service.delete_goods_receipt(receipt.receipt.id).await?;
+        assert_eq!(updated.receiving_status, Some(ReceivingStatus::NotReceived));
+        assert!(service.get_goods_receipts(order.id).await?.is_empty());
+
+        // 已结账订单的收货单不能删除
+        let receipt = service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 10)]))
+            .await?;
+        orders.settle_order(cash_settle(order.id)).await?;
+        assert!(service
+            .delete_goods_receipt(receipt.receipt.id)
+            .await
+            .is_err());
+
+        // 已结账订单不能补登收货
+        let err = service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 1)]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("已结账"));
+        assert_eq!(service.get_goods_receipts(order.id).await?.len(), 1);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 按实收数量结算测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_settle_by_received_quantities() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+        let revisions = OrderRevisionService::new(db.clone());
+        let service = GoodsReceiptService::new(db.clone());
+
+        assert_eq!(
+            settings.get_purchase_config().await?,
+            PurchaseConfig::default()
+        );
+        settings
+            .update_purchase_config(PurchaseConfig {
+                settle_by_received: true,
+            })
+            .await?;
+        assert!(settings.get_purchase_config().await?.settle_by_received);
+
+        // 尚未收货时不能结账
+        let empty = orders.create_order(make_order("Purchase")).await?;
+        let err = orders
+            .settle_order(cash_settle(empty.id))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("尚未收货"));
+
+        // 整单优惠 5.00（105.00 → 100.00），苹果到货 6 斤、香蕉未到货
+        let mut input = make_order("Purchase");
+        input.actual_amount = Some(Decimal::new(10000, 2));
+        let order = orders.create_order(input).await?;
+        let apple = item_id(&orders, order.id, "苹果").await;
+        service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 6)]))
+            .await?;
+
+        // 结算预览按实收数量折算：6 × 8.00 = 48.00，实付按比例 48 × 100 / 105
+        let preview = orders.get_settle_preview(order.id, None).await?;
+        assert_eq!(preview.category_groups.len(), 1);
+        assert_eq!(preview.category_groups[0].amount, Decimal::new(4800, 2));
+        assert_eq!(preview.discount_amount, Some(Decimal::new(229, 2)));
+
+        let settled = orders.settle_order(cash_settle(order.id)).await?;
+        assert_eq!(settled.status, OrderStatus::Settled);
+        assert_eq!(settled.total_amount, Decimal::new(4800, 2));
+        assert_eq!(settled.actual_amount, Decimal::new(4571, 2));
+
+        // 订单明细保留订购数量，短缺数量仍可从收货汇总查看
+        assert_eq!(
+            settled.receiving_status,
+            Some(ReceivingStatus::PartiallyReceived)
+        );
+        let (_, items) = orders.get_order_by_id(order.id).await?.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].quantity, Decimal::new(10, 0));
+        assert_eq!(items[0].subtotal, Decimal::new(8000, 2));
+        let receiving = service.get_order_receiving(order.id).await?;
+        assert_eq!(receiving.lines[0].received_quantity, Decimal::new(6, 0));
+        assert_eq!(receiving.lines[0].short_quantity, Decimal::new(4, 0));
+        assert_eq!(receiving.lines[1].short_quantity, Decimal::new(5, 0));
+
+        // 调整前的订单内容保留在版本记录中
+        let history = revisions.get_revisions(order.id).await?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].origin, OrderRevisionOrigin::Original);
+        assert_eq!(history[0].total_amount, Decimal::new(10500, 2));
+        assert_eq!(history[1].origin, OrderRevisionOrigin::Receiving);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_first_payment_by_received_quantities() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+        let stock = StockService::new(db.clone());
+        let service = GoodsReceiptService::new(db.clone());
+        enable_settle_by_received(&settings).await?;
+
+        // 苹果到货 6 斤、香蕉未到货：应付按实收数量折算为 48.00
+        let order = orders.create_order(make_order("Purchase")).await?;
+        let apple = item_id(&orders, order.id, "苹果").await;
+        service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 6)]))
+            .await?;
+
+        let err = orders
+            .add_payment(cash_payment(order.id, 10500))
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "收付款金额超过未结金额 48");
+
+        // 一次付清实收金额即结清，按实收数量入库
+        let paid = orders.add_payment(cash_payment(order.id, 4800)).await?;
+        assert_eq!(paid.status, OrderStatus::Settled);
+        assert_eq!(paid.total_amount, Decimal::new(4800, 2));
+        assert_eq!(paid.actual_amount, Decimal::new(4800, 2));
+        let (_, items) = orders.get_order_by_id(order.id).await?.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            stock.get_product_stock(1).await?[0].quantity,
+            Decimal::new(6, 0)
+        );
+        assert!(stock.get_product_stock(2).await?.is_empty());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_final_payment_by_received_quantities() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+        let stock = StockService::new(db.clone());
+        let service = GoodsReceiptService::new(db.clone());
+        enable_settle_by_received(&settings).await?;
+
+        // 首次付款时苹果到货 6 斤、香蕉未到货：应付 48.00
+        let order = orders.create_order(make_order("Purchase")).await?;
+        let apple = item_id(&orders, order.id, "苹果").await;
+        service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 6)]))
+            .await?;
+        let partial = orders.add_payment(cash_payment(order.id, 2000)).await?;
+        assert_eq!(partial.status, OrderStatus::PartiallyPaid);
+        assert_eq!(partial.actual_amount, Decimal::new(4800, 2));
+
+        // 付尾款前苹果补到 4 斤：应付按最新实收数量调整为 80.00
+        service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 4)]))
+            .await?;
+        let err = orders
+            .add_payment(cash_payment(order.id, 8500))
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "收付款金额超过未结金额 60");
+
+        let settled = orders.add_payment(cash_payment(order.id, 6000)).await?;
+        assert_eq!(settled.status, OrderStatus::Settled);
+        assert_eq!(settled.total_amount, Decimal::new(8000, 2));
+        assert_eq!(settled.actual_amount, Decimal::new(8000, 2));
+        assert_eq!(
+            stock.get_product_stock(1).await?[0].quantity,
+            Decimal::new(10, 0)
+        );
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_settle_by_ordered_quantities_by_default() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = GoodsReceiptService::new(db.clone());
+
+        let order = orders.create_order(make_order("Purchase")).await?;
+        let apple = item_id(&orders, order.id, "苹果").await;
+        service
+            .create_goods_receipt(make_receipt(order.id, vec![(apple, 6)]))
+            .await?;
+
+        // 未开启按实收数量结算时按订单数量结账
+        let settled = orders.settle_order(cash_settle(order.id)).await?;
+        assert_eq!(settled.total_amount, Decimal::new(10500, 2));
+        assert_eq!(
+            settled.receiving_status,
+            Some(ReceivingStatus::PartiallyReceived)
+        );
+        let (_, items) = orders.get_order_by_id(order.id).await?.unwrap();
+        assert_eq!(items.len(), 2);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,328 @@
+use accounting_assistant_lib::entity::customer;
+use accounting_assistant_lib::services::customer::dto::CreateCustomerDto;
+use accounting_assistant_lib::services::ledger::dto::{CustomerLedgerDto, LedgerEntryType};
+use accounting_assistant_lib::services::order::dto::{
+    AddOrderPaymentDto, CreateOrderDto, CreateOrderItemDto, CreateOrderReturnDto,
+    CreateOrderReturnItemDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::{CustomerService, LedgerService, OrderService};
+use chrono::{Duration, Local};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建客户
+async fn create_customer(
+    service: &CustomerService,
+    name: &str,
+    category: &str,
+) -> Result<customer::Model, Box<dyn std::error::Error>> {
+    service
+        .create_customer(CreateCustomerDto {
+            name: name.to_string(),
+            category: category.to_string(),
+            phone: "13800000000".to_string(),
+            wechat: None,
+            address: None,
+            bank_account: None,
+            remark: None,
+        })
+        .await
+}
+
+/// 辅助函数：构造单明细订单 DTO
+fn make_order(order_type: &str, customer_id: i64, amount: Decimal) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: order_type.to_string(),
+        customer_id: Some(customer_id),
+        customer_name: None,
+        items: vec![CreateOrderItemDto {
+            product_id: 1,
+            product_name: "苹果".to_string(),
+            quantity: Decimal::ONE,
+            unit: "斤".to_string(),
+            unit_price: amount,
+            discount_amount: None,
+            discount_rate: None,
+            remark: None,
+        }],
+        remark: None,
+        actual_amount: None,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+/// 辅助函数：构造收付款 DTO
+fn make_payment(order_id: i64, amount: Decimal, paid_at: Option<String>) -> AddOrderPaymentDto {
+    AddOrderPaymentDto {
+        order_id,
+        amount,
+        channel: "Cash".to_string(),
+        paid_at,
+        remark: None,
+    }
+}
+
+// ==================== get_customer_ledger 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_customer_ledger_sales_credit_and_receipts() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = LedgerService::new(db.clone());
+
+        let retailer = create_customer(&customers, "张三", "Retailer").await?;
+        let order = orders
+            .create_order(make_order("Sales", retailer.id, Decimal::new(500, 0)))
+            .await?;
+        orders
+            .add_payment(make_payment(order.id, Decimal::new(200, 0), None))
+            .await?;
+
+        // 已取消订单不计入
+        let cancelled = orders
+            .create_order(make_order("Sales", retailer.id, Decimal::new(999, 0)))
+            .await?;
+        orders.cancel_order(cancelled.id).await?;
+
+        let ledger = service
+            .get_customer_ledger(CustomerLedgerDto {
+                customer_id: retailer.id,
+                start_date: None,
+                end_date: None,
+            })
+            .await?;
+
+        assert_eq!(ledger.customer_name, "张三");
+        assert_eq!(ledger.opening_balance, Decimal::ZERO);
+        assert_eq!(ledger.entries.len(), 2);
+        assert_eq!(ledger.entries[0].entry_type, LedgerEntryType::SalesOrder);
+        assert_eq!(ledger.entries[0].balance, Decimal::new(500, 0));
+        assert_eq!(ledger.entries[1].entry_type, LedgerEntryType::Receipt);
+        assert_eq!(ledger.entries[1].amount, Decimal::new(-200, 0));
+        assert_eq!(ledger.entries[1].balance, Decimal::new(300, 0));
+        assert_eq!(ledger.closing_balance, Decimal::new(300, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_customer_ledger_opening_balance_from_start_date() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = LedgerService::new(db.clone());
+
+        let retailer = create_customer(&customers, "李四", "Retailer").await?;
+        let order = orders
+            .create_order(make_order("Sales", retailer.id, Decimal::new(500, 0)))
+            .await?;
+
+        let tomorrow = Local::now().date_naive() + Duration::days(1);
+        orders
+            .add_payment(make_payment(
+                order.id,
+                Decimal::new(150, 0),
+                Some(format!("{} 10:00:00", tomorrow.format("%Y-%m-%d"))),
+            ))
+            .await?;
+
+        let ledger = service
+            .get_customer_ledger(CustomerLedgerDto {
+                customer_id: retailer.id,
+                start_date: Some(tomorrow.format("%Y-%m-%d").to_string()),
+                end_date: None,
+            })
+            .await?;
+
+        assert_eq!(ledger.opening_balance, Decimal::new(500, 0));
+        assert_eq!(ledger.entries.len(), 1);
+        assert_eq!(ledger.entries[0].balance, Decimal::new(350, 0));
+        assert_eq!(ledger.closing_balance, Decimal::new(350, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_customer_ledger_not_found_error() {
+    run_in_transaction(|db| async move {
+        let service = LedgerService::new(db.clone());
+
+        let result = service
+            .get_customer_ledger(CustomerLedgerDto {
+                customer_id: 999999,
+                start_date: None,
+                end_date: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== get_customer_balance 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_customer_balance_payable_and_as_of() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = LedgerService::new(db.clone());
+
+        let supplier = create_customer(&customers, "王五", "Supplier").await?;
+
+        // 一次性结清的采购订单不产生余额
+        let settled = orders
+            .create_order(make_order("Purchase", supplier.id, Decimal::new(800, 0)))
+            .await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: settled.id,
+                channel: Some("BankCard".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+
+        // 赊购 300，明天付 100
+        let credit = orders
+            .create_order(make_order("Purchase", supplier.id, Decimal::new(300, 0)))
+            .await?;
+        let today = Local::now().date_naive();
+        let tomorrow = today + Duration::days(1);
+        orders
+            .add_payment(make_payment(
+                credit.id,
+                Decimal::new(100, 0),
+                Some(format!("{} 09:00:00", tomorrow.format("%Y-%m-%d"))),
+            ))
+            .await?;
+
+        let current = service.get_customer_balance(supplier.id, None).await?;
+        assert_eq!(current.receivable, Decimal::ZERO);
+        assert_eq!(current.payable, Decimal::new(200, 0));
+        assert_eq!(current.balance, Decimal::new(-200, 0));
+
+        let as_of_today = service
+            .get_customer_balance(supplier.id, Some(today.format("%Y-%m-%d").to_string()))
+            .await?;
+        assert_eq!(as_of_today.payable, Decimal::new(300, 0));
+        assert_eq!(as_of_today.as_of, Some(today));
+
+        let before = service
+            .get_customer_balance(
+                supplier.id,
+                Some((today - Duration::days(1)).format("%Y-%m-%d").to_string()),
+            )
+            .await?;
+        assert_eq!(before.payable, Decimal::ZERO);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_get_all_customer_balances_sorted_by_balance() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = LedgerService::new(db.clone());
+
+        let small = create_customer(&customers, "小额客户", "Retailer").await?;
+        let large = create_customer(&customers, "大额客户", "Retailer").await?;
+        let idle = create_customer(&customers, "无往来客户", "Retailer").await?;
+
+        orders
+            .create_order(make_order("Sales", small.id, Decimal::new(100, 0)))
+            .await?;
+        orders
+            .create_order(make_order("Sales", large.id, Decimal::new(1000, 0)))
+            .await?;
+
+        let balances = service.get_all_customer_balances().await?;
+        assert_eq!(balances.len(), 3);
+        assert_eq!(balances[0].customer_id, large.id);
+        assert_eq!(balances[0].receivable, Decimal::new(1000, 0));
+        assert_eq!(balances[1].customer_id, small.id);
+        assert_eq!(balances[2].customer_id, idle.id);
+        assert_eq!(balances[2].balance, Decimal::ZERO);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_customer_ledger_includes_return_and_refund() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = LedgerService::new(db.clone());
+
+        let retailer = create_customer(&customers, "退货客户", "Retailer").await?;
+        let order = orders
+            .create_order(make_order("Sales", retailer.id, Decimal::new(100, 0)))
+            .await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+        let (_, items) = orders.get_order_by_id(order.id).await?.expect("订单应存在");
+        orders
+            .create_return(CreateOrderReturnDto {
+                order_id: order.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: items[0].id,
+                    quantity: Decimal::ONE,
+                }],
+                refund_amount: Some(Decimal::new(30, 0)),
+                channel: None,
+                reason: None,
+            })
+            .await?;
+
+        let ledger = service
+            .get_customer_ledger(CustomerLedgerDto {
+                customer_id: retailer.id,
+                start_date: None,
+                end_date: None,
+            })
+            .await?;
+
+        assert_eq!(ledger.entries.len(), 4);
+        assert_eq!(ledger.entries[2].entry_type, LedgerEntryType::SalesReturn);
+        assert_eq!(ledger.entries[2].amount, Decimal::new(-30, 0));
+        assert_eq!(ledger.entries[3].entry_type, LedgerEntryType::SalesRefund);
+        assert_eq!(ledger.entries[3].amount, Decimal::new(30, 0));
+        assert_eq!(ledger.closing_balance, Decimal::ZERO);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,446 @@
+use accounting_assistant_lib::entity::order;
+use accounting_assistant_lib::services::goods_receipt::dto::{
+    CreateGoodsReceiptDto, CreateGoodsReceiptItemDto,
+};
+use accounting_assistant_lib::services::lot::dto::QueryExpiringLotsDto;
+use accounting_assistant_lib::services::order::dto::{
+    CreateOrderDto, CreateOrderItemDto, CreateOrderReturnDto, CreateOrderReturnItemDto,
+    ReopenOrderDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::{GoodsReceiptService, LotService, OrderService};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建商品 1（苹果，斤）的单明细订单
+async fn create_order(
+    service: &OrderService,
+    order_type: &str,
+    quantity: i64,
+) -> Result<order::Model, Box<dyn std::error::Error>> {
+    service
+        .create_order(CreateOrderDto {
+            order_type: order_type.to_string(),
+            customer_id: None,
+            customer_name: None,
+            items: vec![CreateOrderItemDto {
+                product_id: 1,
+                product_name: "苹果".to_string(),
+                quantity: Decimal::new(quantity, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(500, 2),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            }],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await
+}
+
+/// 辅助函数：以现金结账
+async fn settle(service: &OrderService, order_id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    service
+        .settle_order(SettleOrderDto {
+            order_id,
+            channel: Some("Cash".to_string()),
+            actual_amount: None,
+            payments: None,
+        })
+        .await?;
+    Ok(())
+}
+
+/// 辅助函数：查询订单首条明细 ID
+async fn first_item_id(service: &OrderService, order_id: i64) -> i64 {
+    let (_, items) = service.get_order_by_id(order_id).await.unwrap().unwrap();
+    items[0].id
+}
+
+/// 辅助函数：登记单批次收货单，返回收货单 ID
+async fn receive_lot(
+    service: &GoodsReceiptService,
+    order_id: i64,
+    order_item_id: i64,
+    quantity: i64,
+    lot_no: Option<&str>,
+    expiry_date: Option<&str>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let receipt = service
+        .create_goods_receipt(CreateGoodsReceiptDto {
+            order_id,
+            items: vec![CreateGoodsReceiptItemDto {
+                order_item_id,
+                quantity: Decimal::new(quantity, 0),
+                lot_no: lot_no.map(str::to_string),
+                production_date: None,
+                expiry_date: expiry_date.map(str::to_string),
+            }],
+            remark: None,
+        })
+        .await?;
+    Ok(receipt.receipt.id)
+}
+
+/// 辅助函数：查询商品 1 各批次剩余数量（按先到期先出排序）
+async fn remaining(
+    service: &LotService,
+) -> Result<Vec<(String, Decimal)>, Box<dyn std::error::Error>> {
+    Ok(service
+        .get_product_lots(1)
+        .await?
+        .into_iter()
+        .map(|l| (l.lot_no, l.remaining_quantity))
+        .collect())
+}
+
+// ==================== 批次与先到期先出测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_sales_consume_lots_fefo() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let receipts = GoodsReceiptService::new(db.clone());
+        let lots = LotService::new(db.clone());
+
+        let purchase = create_order(&orders, "Purchase", 10).await?;
+        let item = first_item_id(&orders, purchase.id).await;
+        receive_lot(
+            &receipts,
+            purchase.id,
+            item,
+            6,
+            Some("L1"),
+            Some("2026-11-10"),
+        )
+        .await?;
+        receive_lot(
+            &receipts,
+            purchase.id,
+            item,
+            4,
+            Some("L2"),
+            Some("2026-11-05"),
+        )
+        .await?;
+        settle(&orders, purchase.id).await?;
+
+        // 先到期的 L2 先出库
+        let sales = create_order(&orders, "Sales", 5).await?;
+        settle(&orders, sales.id).await?;
+        assert_eq!(
+            remaining(&lots).await?,
+            vec![("L1".to_string(), Decimal::new(5, 0))]
+        );
+
+        // 撤销结账恢复批次，重新结账后再次扣减
+        orders
+            .reopen_order(ReopenOrderDto {
+                order_id: sales.id,
+                reason: None,
+            })
+            .await?;
+        assert_eq!(
+            remaining(&lots).await?,
+            vec![
+                ("L2".to_string(), Decimal::new(4, 0)),
+                ("L1".to_string(), Decimal::new(6, 0)),
+            ]
+        );
+        settle(&orders, sales.id).await?;
+
+        // 销售退货按出库的相反顺序恢复批次
+        orders
+            .create_return(CreateOrderReturnDto {
+                order_id: sales.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: first_item_id(&orders, sales.id).await,
+                    quantity: Decimal::new(2, 0),
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+        assert_eq!(
+            remaining(&lots).await?,
+            vec![
+                ("L2".to_string(), Decimal::ONE),
+                ("L1".to_string(), Decimal::new(6, 0)),
+            ]
+        );
+
+        // 采购退货扣减该采购订单的批次
+        orders
+            .create_return(CreateOrderReturnDto {
+                order_id: purchase.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: item,
+                    quantity: Decimal::new(2, 0),
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+        assert_eq!(
+            remaining(&lots).await?,
+            vec![("L1".to_string(), Decimal::new(5, 0))]
+        );
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_lots_follow_purchase_settlement() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let receipts = GoodsReceiptService::new(db.clone());
+        let lots = LotService::new(db.clone());
+
+        let purchase = create_order(&orders, "Purchase", 10).await?;
+        let item = first_item_id(&orders, purchase.id).await;
+
+        let result = receipts
+            .create_goods_receipt(CreateGoodsReceiptDto {
+                order_id: purchase.id,
+                items: vec![CreateGoodsReceiptItemDto {
+                    order_item_id: item,
+                    quantity: Decimal::new(10, 0),
+                    lot_no: None,
+                    production_date: Some("2026-11-05".to_string()),
+                    expiry_date: Some("2026-11-01".to_string()),
+                }],
+                remark: None,
+            })
+            .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "苹果 的到期日期不能早于生产日期"
+        );
+        let result = receive_lot(&receipts, purchase.id, item, 10, None, Some("20261101")).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "无效的到期日期格式，应为 YYYY-MM-DD"
+        );
+
+        // 收货时不登记批次，待结账的采购订单不参与出库
+        receive_lot(&receipts, purchase.id, item, 6, None, Some("2026-11-05")).await?;
+        let sales = create_order(&orders, "Sales", 1).await?;
+        settle(&orders, sales.id).await?;
+        assert!(lots.get_product_lots(1).await?.is_empty());
+
+        // 超收 2 斤（订购 10 斤，两次共收 12 斤）
+        receive_lot(
+            &receipts,
+            purchase.id,
+            item,
+            6,
+            Some("L9"),
+            Some("2026-11-20"),
+        )
+        .await?;
+
+        // 结账按实收数量入库并逐条登记收货批次：未填写批次号的取收货单编号，超收数量不截断
+        settle(&orders, purchase.id).await?;
+        let receipt_no = receipts.get_goods_receipts(purchase.id).await?[0]
+            .receipt
+            .receipt_no
+            .clone();
+        assert_eq!(
+            remaining(&lots).await?,
+            vec![
+                (receipt_no, Decimal::new(6, 0)),
+                ("L9".to_string(), Decimal::new(6, 0)),
+            ]
+        );
+
+        // 可退数量以实收数量为准
+        let err = orders
+            .create_return(CreateOrderReturnDto {
+                order_id: purchase.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: item,
+                    quantity: Decimal::new(13, 0),
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "苹果 退货数量超过可退数量 12");
+
+        // 撤销采购结账冲回批次，不再参与出库
+        orders
+            .reopen_order(ReopenOrderDto {
+                order_id: purchase.id,
+                reason: None,
+            })
+            .await?;
+        assert!(lots.get_product_lots(1).await?.is_empty());
+        let sales = create_order(&orders, "Sales", 2).await?;
+        settle(&orders, sales.id).await?;
+        assert!(lots.get_product_lots(1).await?.is_empty());
+
+        // 重新结账后按收货明细重新登记批次
+        settle(&orders, purchase.id).await?;
+        assert_eq!(remaining(&lots).await?.len(), 2);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_lot_mismatches() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let lots = LotService::new(db.clone());
+
+        let purchase = create_order(&orders, "Purchase", 5).await?;
+        settle(&orders, purchase.id).await?;
+        assert!(lots.get_lot_mismatches().await?.is_empty());
+
+        // 批次不足时未覆盖的数量登记为无批次扣减
+        let sales = create_order(&orders, "Sales", 7).await?;
+        settle(&orders, sales.id).await?;
+        let mismatches = lots.get_lot_mismatches().await?;
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].product_id, 1);
+        assert_eq!(mismatches[0].stock_quantity, Decimal::new(-2, 0));
+        assert_eq!(mismatches[0].lot_quantity, Decimal::ZERO);
+        assert_eq!(mismatches[0].untracked_quantity, Decimal::new(2, 0));
+
+        // 销售退货先冲回未覆盖的数量
+        orders
+            .create_return(CreateOrderReturnDto {
+                order_id: sales.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: first_item_id(&orders, sales.id).await,
+                    quantity: Decimal::new(2, 0),
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+        assert!(lots.get_lot_mismatches().await?.is_empty());
+
+        // 采购批次已出库后撤销结账，冲回的入库数量登记为无批次扣减
+        orders
+            .reopen_order(ReopenOrderDto {
+                order_id: purchase.id,
+                reason: None,
+            })
+            .await?;
+        let mismatches = lots.get_lot_mismatches().await?;
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].stock_quantity, Decimal::new(-5, 0));
+        assert_eq!(mismatches[0].lot_quantity, Decimal::ZERO);
+        assert_eq!(mismatches[0].untracked_quantity, Decimal::new(5, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 临期批次测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_expiring_lots() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let receipts = GoodsReceiptService::new(db.clone());
+        let lots = LotService::new(db.clone());
+
+        let purchase = create_order(&orders, "Purchase", 10).await?;
+        let item = first_item_id(&orders, purchase.id).await;
+        receive_lot(
+            &receipts,
+            purchase.id,
+            item,
+            2,
+            Some("过期"),
+            Some("2026-10-30"),
+        )
+        .await?;
+        receive_lot(
+            &receipts,
+            purchase.id,
+            item,
+            3,
+            Some("临期"),
+            Some("2026-11-05"),
+        )
+        .await?;
+        receive_lot(
+            &receipts,
+            purchase.id,
+            item,
+            4,
+            Some("新鲜"),
+            Some("2026-11-10"),
+        )
+        .await?;
+        receive_lot(&receipts, purchase.id, item, 1, Some("无期限"), None).await?;
+        settle(&orders, purchase.id).await?;
+
+        let expiring = lots
+            .get_expiring_lots(QueryExpiringLotsDto {
+                within_days: Some(5),
+                as_of_date: Some("2026-11-01".to_string()),
+                product_id: None,
+            })
+            .await?;
+        assert_eq!(expiring.len(), 2);
+        assert_eq!(expiring[0].lot.lot_no, "过期");
+        assert_eq!(expiring[0].days_to_expiry, -2);
+        assert!(expiring[0].expired);
+        assert_eq!(expiring[1].lot.lot_no, "临期");
+        assert_eq!(expiring[1].days_to_expiry, 4);
+        assert!(!expiring[1].expired);
+        assert_eq!(expiring[1].unit_cost, Some(Decimal::new(5, 0)));
+        assert_eq!(expiring[1].value, Some(Decimal::new(15, 0)));
+
+        // 已出库完的批次不再提示
+        let sales = create_order(&orders, "Sales", 2).await?;
+        settle(&orders, sales.id).await?;
+        let expiring = lots
+            .get_expiring_lots(QueryExpiringLotsDto {
+                within_days: Some(5),
+                as_of_date: Some("2026-11-01".to_string()),
+                product_id: Some(1),
+            })
+            .await?;
+        assert_eq!(expiring.len(), 1);
+        assert_eq!(expiring[0].lot.lot_no, "临期");
+
+        let result = lots
+            .get_expiring_lots(QueryExpiringLotsDto {
+                within_days: Some(-1),
+                as_of_date: None,
+                product_id: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "预警天数不能为负数");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -2,7 +2,24 @@
 pub mod accounting_book_test;
 pub mod accounting_test;
 pub mod attachment_test;
+pub mod cash_flow_test;
 pub mod category_test;
+pub mod costing_test;
 pub mod customer_test;
+pub mod dashboard_test;
+pub mod goods_receipt_test;
+pub mod ledger_test;
+pub mod lot_test;
+pub mod numbering_test;
+pub mod order_revision_test;
+pub mod order_template_test;
 pub mod order_test;
+pub mod printer_test;
 pub mod product_test;
+pub mod profit_test;
+pub mod quotation_test;
+pub mod receipt_test;
+pub mod reorder_test;
+pub mod statement_test;
+pub mod stock_test;
+pub mod stocktake_test;
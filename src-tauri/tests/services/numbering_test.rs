@@ -0,0 +1,267 @@
+use accounting_assistant_lib::entity::{self, document_sequence, order};
+use accounting_assistant_lib::enums::{DocumentType, ResetPolicy};
+use accounting_assistant_lib::services::numbering::dto::UpdateNumberingRuleDto;
+use accounting_assistant_lib::services::numbering::service::render_pattern;
+use accounting_assistant_lib::services::order::dto::{CreateOrderDto, CreateOrderItemDto};
+use accounting_assistant_lib::services::{NumberingService, OrderService};
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, Statement};
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造单明细订单 DTO
+fn make_order(order_type: &str) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: order_type.to_string(),
+        customer_id: None,
+        customer_name: None,
+        items: vec![CreateOrderItemDto {
+            product_id: 1,
+            product_name: "苹果".to_string(),
+            quantity: Decimal::ONE,
+            unit: "斤".to_string(),
+            unit_price: Decimal::new(10, 0),
+            discount_amount: None,
+            discount_rate: None,
+            remark: None,
+        }],
+        remark: None,
+        actual_amount: None,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+/// 辅助函数：构造编号规则 DTO
+fn make_rule(document_type: &str, pattern: &str, reset_policy: &str) -> UpdateNumberingRuleDto {
+    UpdateNumberingRuleDto {
+        document_type: document_type.to_string(),
+        pattern: pattern.to_string(),
+        reset_policy: reset_policy.to_string(),
+    }
+}
+
+// ==================== render_pattern 测试 ====================
+
+#[test]
+fn test_render_pattern_tokens() {
+    let now = NaiveDate::from_ymd_opt(2024, 6, 5)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap();
+
+    assert_eq!(
+        render_pattern("XS-{yyyyMMdd}-{seq:4}", now, 7).unwrap(),
+        "XS-20240605-0007"
+    );
+    assert_eq!(
+        render_pattern("{yy}{MM}/{dd}-{seq}", now, 123).unwrap(),
+        "2406/05-123"
+    );
+    // 流水号超过位数时不截断
+    assert_eq!(render_pattern("A{seq:2}", now, 123).unwrap(), "A123");
+
+    assert!(render_pattern("XS-{yyyyMMdd}", now, 1).is_err());
+    assert!(render_pattern("XS-{foo}-{seq}", now, 1).is_err());
+    assert!(render_pattern("XS-{seq", now, 1).is_err());
+    assert!(render_pattern("XS-{seq:0}", now, 1).is_err());
+}
+
+// ==================== 订单编号测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_order_no_uses_default_rules_per_type() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let today = Local::now().format("%Y%m%d").to_string();
+
+        let first = orders.create_order(make_order("Sales")).await?;
+        let second = orders.create_order(make_order("Sales")).await?;
+        let purchase = orders.create_order(make_order("Purchase")).await?;
+
+        let prefix = format!("XS-{}-", today);
+        assert!(first.order_no.starts_with(&prefix));
+        assert_eq!(first.order_no.len(), prefix.len() + 4);
+        let first_seq: i32 = first.order_no[prefix.len()..].parse()?;
+        let second_seq: i32 = second.order_no[prefix.len()..].parse()?;
+        assert_eq!(second_seq, first_seq + 1);
+
+        // 采购订单独立计数
+        assert!(purchase.order_no.starts_with(&format!("CG-{}-", today)));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_custom_rule_and_uniqueness_after_sequence_reset() {
+    run_in_transaction(|db| async move {
+        let service = NumberingService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+
+        let rule = service
+            .update_numbering_rule(make_rule("SalesOrder", "DUP-{seq}", "Never"))
+            .await?;
+        assert!(rule.is_custom);
+        assert_eq!(rule.reset_policy, ResetPolicy::Never);
+        assert_eq!(rule.example, "DUP-1");
+
+        let first = orders.create_order(make_order("Sales")).await?;
+        assert!(first.order_no.starts_with("DUP-"));
+
+        // 流水号被清空后，已占用的编号会被跳过
+        document_sequence::Entity::delete_many().exec(&db).await?;
+        let second = orders.create_order(make_order("Sales")).await?;
+        assert_ne!(second.order_no, first.order_no);
+
+        let reset = service
+            .reset_numbering_rule("SalesOrder".to_string())
+            .await?;
+        assert!(!reset.is_custom);
+        let third = orders.create_order(make_order("Sales")).await?;
+        assert!(third.order_no.starts_with("XS-"));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_numbering_rules_listing_and_validation() {
+    run_in_transaction(|db| async move {
+        let service = NumberingService::new(db.clone());
+
+        let rules = service.get_numbering_rules().await?;
+        assert_eq!(rules.len(), 8);
+        let statement = rules
+            .iter()
+            .find(|r| r.document_type == DocumentType::Statement)
+            .expect("应有对账单编号规则");
+        assert_eq!(statement.reset_policy, ResetPolicy::Monthly);
+        assert!(!statement.is_custom);
+
+        assert!(service
+            .update_numbering_rule(make_rule("SalesOrder", "XS-{yyyy}", "Daily"))
+            .await
+            .is_err());
+        assert!(service
+            .update_numbering_rule(make_rule("Invoice", "FP-{seq}", "Daily"))
+            .await
+            .is_err());
+        assert!(service
+            .update_numbering_rule(make_rule("SalesOrder", "XS-{seq}", "Weekly"))
+            .await
+            .is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_document_no_unique_index() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+
+        let first = orders.create_order(make_order("Sales")).await?;
+        let second = orders.create_order(make_order("Sales")).await?;
+
+        // 编号唯一索引阻止写入重复编号
+        let result = order::Entity::update_many()
+            .col_expr(order::Column::OrderNo, Expr::value(first.order_no.clone()))
+            .filter(order::Column::Id.eq(second.id))
+            .exec(&db)
+            .await;
+        assert!(result.is_err());
+
+        // 流水号按周期连续递增
+        let period = "TEST";
+        let seqs = [
+            document_sequence::Model::get_next_sequence(&db, DocumentType::Statement, period)
+                .await?,
+            document_sequence::Model::get_next_sequence(&db, DocumentType::Statement, period)
+                .await?,
+        ];
+        assert_eq!(seqs, [1, 2]);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_legacy_duplicate_order_nos_are_kept() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(orders.create_order(make_order("Sales")).await?.id);
+        }
+
+        // 模拟旧版本数据库：订单表没有编号唯一约束，订单编号按日重复
+        let create_sql: String = db
+            .query_one_raw(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'order'",
+            ))
+            .await?
+            .expect("订单表应存在")
+            .try_get("", "sql")?;
+        db.execute_unprepared(
+            &create_sql
+                .replace(r#"CREATE TABLE "order""#, r#"CREATE TABLE "order_legacy""#)
+                .replace(" UNIQUE", ""),
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"INSERT INTO "order_legacy" SELECT * FROM "order";
+            DROP TABLE "order";
+            ALTER TABLE "order_legacy" RENAME TO "order";"#,
+        )
+        .await?;
+        order::Entity::update_many()
+            .col_expr(order::Column::OrderNo, Expr::value("#1"))
+            .filter(order::Column::Id.is_in([ids[0], ids[1]]))
+            .exec(&db)
+            .await?;
+
+        // 升级后多次启动，已有编号保持不变
+        entity::with_install_entities(&db).await?;
+        entity::with_install_entities(&db).await?;
+        let legacy = order::Entity::find()
+            .filter(order::Column::OrderNo.eq("#1"))
+            .count(&db)
+            .await?;
+        assert_eq!(legacy, 2);
+
+        // 最后一条重复编号之后的订单仍受唯一约束
+        let third = order::Entity::find_by_id(ids[2])
+            .one(&db)
+            .await?
+            .expect("订单应存在");
+        let fourth = orders.create_order(make_order("Sales")).await?;
+        let result = order::Entity::update_many()
+            .col_expr(order::Column::OrderNo, Expr::value(third.order_no))
+            .filter(order::Column::Id.eq(fourth.id))
+            .exec(&db)
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
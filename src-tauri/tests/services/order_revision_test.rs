@@ -0,0 +1,240 @@
+use accounting_assistant_lib::enums::OrderRevisionOrigin;
+use accounting_assistant_lib::services::order::dto::{
+    CreateOrderDto, CreateOrderItemDto, SettleOrderDto, UpdateOrderDto,
+};
+use accounting_assistant_lib::services::order_revision::dto::{
+    DiffOrderRevisionsDto, RestoreOrderRevisionDto, RevisionItemChangeType,
+};
+use accounting_assistant_lib::services::{OrderRevisionService, OrderService};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造一条商品明细 DTO
+fn make_item(product_id: i64, name: &str, quantity: i64, unit_price: i64) -> CreateOrderItemDto {
+    CreateOrderItemDto {
+        product_id,
+        product_name: name.to_string(),
+        quantity: Decimal::new(quantity, 0),
+        unit: "斤".to_string(),
+        unit_price: Decimal::new(unit_price, 2),
+        discount_amount: None,
+        discount_rate: None,
+        remark: None,
+    }
+}
+
+/// 辅助函数：构造销售订单（苹果 10 斤 × 8.00 + 香蕉 5 斤 × 5.00，合计 105.00）
+fn make_sales_order() -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: "Sales".to_string(),
+        customer_id: None,
+        customer_name: None,
+        items: vec![make_item(1, "苹果", 10, 800), make_item(2, "香蕉", 5, 500)],
+        remark: Some("原始备注".to_string()),
+        actual_amount: None,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+/// 辅助函数：编辑订单（苹果改为 20 斤，删除香蕉，新增橙子 3 斤 × 6.00）
+fn make_update(order_id: i64) -> UpdateOrderDto {
+    UpdateOrderDto {
+        order_id,
+        items: Some(vec![
+            make_item(1, "苹果", 20, 800),
+            make_item(3, "橙子", 3, 600),
+        ]),
+        remark: Some("修改备注".to_string()),
+        due_date: None,
+    }
+}
+
+// ==================== 版本记录测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_update_order_records_revisions() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = OrderRevisionService::new(db.clone());
+
+        let order = orders.create_order(make_sales_order()).await?;
+        // 未编辑的订单没有版本记录
+        assert!(service.get_revisions(order.id).await?.is_empty());
+
+        orders.update_order(make_update(order.id)).await?;
+        orders
+            .update_order(UpdateOrderDto {
+                order_id: order.id,
+                items: None,
+                remark: Some("再次修改".to_string()),
+                due_date: Some("2026-11-30".to_string()),
+            })
+            .await?;
+
+        let revisions = service.get_revisions(order.id).await?;
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(
+            revisions.iter().map(|r| r.version).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(revisions[0].origin, OrderRevisionOrigin::Original);
+        assert_eq!(revisions[0].total_amount, Decimal::new(10500, 2));
+        assert_eq!(revisions[0].remark, Some("原始备注".to_string()));
+        assert_eq!(revisions[1].origin, OrderRevisionOrigin::Edit);
+        // 20 × 8.00 + 3 × 6.00 = 178.00
+        assert_eq!(revisions[1].total_amount, Decimal::new(17800, 2));
+        assert_eq!(revisions[2].origin, OrderRevisionOrigin::Edit);
+        assert!(revisions[2].due_date.is_some());
+
+        let original = service.get_revision(order.id, 1).await?;
+        assert_eq!(original.items.len(), 2);
+        assert_eq!(original.items[1].product_name, "香蕉");
+        assert!(service.get_revision(order.id, 9).await.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 版本比较测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_diff_order_revisions() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = OrderRevisionService::new(db.clone());
+
+        let order = orders.create_order(make_sales_order()).await?;
+        orders.update_order(make_update(order.id)).await?;
+
+        let diff = service
+            .diff_revisions(DiffOrderRevisionsDto {
+                order_id: order.id,
+                from_version: 1,
+                to_version: 2,
+            })
+            .await?;
+        assert_eq!(diff.from_total_amount, Decimal::new(10500, 2));
+        assert_eq!(diff.to_total_amount, Decimal::new(17800, 2));
+        assert!(diff.remark_changed);
+        assert_eq!(diff.to_remark, Some("修改备注".to_string()));
+        assert!(!diff.due_date_changed);
+
+        assert_eq!(diff.item_changes.len(), 3);
+        let apple = &diff.item_changes[0];
+        assert_eq!(apple.change_type, RevisionItemChangeType::Modified);
+        assert_eq!(apple.from_quantity, Some(Decimal::new(10, 0)));
+        assert_eq!(apple.to_quantity, Some(Decimal::new(20, 0)));
+        let orange = &diff.item_changes[1];
+        assert_eq!(orange.change_type, RevisionItemChangeType::Added);
+        assert_eq!(orange.product_id, 3);
+        assert_eq!(orange.from_quantity, None);
+        let banana = &diff.item_changes[2];
+        assert_eq!(banana.change_type, RevisionItemChangeType::Removed);
+        assert_eq!(banana.product_id, 2);
+        assert_eq!(banana.to_subtotal, None);
+
+        // 相同版本无差异
+        let same = service
+            .diff_revisions(DiffOrderRevisionsDto {
+                order_id: order.id,
+                from_version: 2,
+                to_version: 2,
+            })
+            .await?;
+        assert!(same.item_changes.is_empty());
+        assert!(!same.remark_changed);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 版本恢复测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_restore_order_revision() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = OrderRevisionService::new(db.clone());
+
+        let order = orders.create_order(make_sales_order()).await?;
+        orders.update_order(make_update(order.id)).await?;
+
+        let restored = service
+            .restore_revision(RestoreOrderRevisionDto {
+                order_id: order.id,
+                version: 1,
+            })
+            .await?;
+        assert_eq!(restored.total_amount, Decimal::new(10500, 2));
+        assert_eq!(restored.actual_amount, Decimal::new(10500, 2));
+        assert_eq!(restored.remark, Some("原始备注".to_string()));
+
+        let (_, items) = orders.get_order_by_id(order.id).await?.unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().any(|i| i.product_name == "香蕉"));
+
+        let revisions = service.get_revisions(order.id).await?;
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[2].origin, OrderRevisionOrigin::Restore);
+        assert_eq!(revisions[2].restored_from, Some(1));
+
+        // 恢复后的版本与原始版本内容一致
+        let diff = service
+            .diff_revisions(DiffOrderRevisionsDto {
+                order_id: order.id,
+                from_version: 1,
+                to_version: 3,
+            })
+            .await?;
+        assert!(diff.item_changes.is_empty());
+        assert!(!diff.remark_changed);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_restore_order_revision_settled_error() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = OrderRevisionService::new(db.clone());
+
+        let order = orders.create_order(make_sales_order()).await?;
+        orders.update_order(make_update(order.id)).await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+
+        let err = service
+            .restore_revision(RestoreOrderRevisionDto {
+                order_id: order.id,
+                version: 1,
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("只有待结账订单"));
+        assert_eq!(service.get_revisions(order.id).await?.len(), 2);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
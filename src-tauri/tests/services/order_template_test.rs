@@ -0,0 +1,261 @@
+use accounting_assistant_lib::entity::order_item;
+use accounting_assistant_lib::enums::{OrderStatus, OrderSubType, OrderType};
+use accounting_assistant_lib::services::order::dto::{CreateOrderDto, CreateOrderItemDto};
+use accounting_assistant_lib::services::order_template::dto::{
+    CreateOrderFromTemplateDto, CreateOrderTemplateDto, SaveOrderAsTemplateDto,
+    UpdateOrderTemplateDto,
+};
+use accounting_assistant_lib::services::product::dto::CreateProductDto;
+use accounting_assistant_lib::services::{OrderService, OrderTemplateService, ProductService};
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造一条明细 DTO
+fn make_item(
+    product_id: i64,
+    name: &str,
+    quantity: i64,
+    unit_price: Decimal,
+) -> CreateOrderItemDto {
+    CreateOrderItemDto {
+        product_id,
+        product_name: name.to_string(),
+        quantity: Decimal::new(quantity, 0),
+        unit: "斤".to_string(),
+        unit_price,
+        discount_amount: None,
+        discount_rate: None,
+        remark: None,
+    }
+}
+
+/// 辅助函数：构造模板 DTO
+fn make_template(
+    name: &str,
+    customer_id: Option<i64>,
+    items: Vec<CreateOrderItemDto>,
+) -> CreateOrderTemplateDto {
+    CreateOrderTemplateDto {
+        name: name.to_string(),
+        order_type: "Sales".to_string(),
+        customer_id,
+        customer_name: customer_id.map(|_| "张记水果店".to_string()),
+        sub_type: None,
+        items,
+        remark: Some("周一例单".to_string()),
+    }
+}
+
+// ==================== create_template 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_create_template_and_unique_name() {
+    run_in_transaction(|db| async move {
+        let service = OrderTemplateService::new(db.clone());
+
+        let detail = service
+            .create_template(make_template(
+                "周一例单",
+                Some(1),
+                vec![make_item(1, "苹果", 10, Decimal::new(800, 2))],
+            ))
+            .await?;
+        assert_eq!(detail.template.order_type, OrderType::Sales);
+        assert_eq!(detail.template.sub_type, OrderSubType::Wholesale);
+        assert_eq!(detail.items.len(), 1);
+        assert!(detail.template.last_used_at.is_none());
+
+        // 同一客户下名称重复
+        let duplicate = service
+            .create_template(make_template(
+                "周一例单",
+                Some(1),
+                vec![make_item(1, "苹果", 5, Decimal::new(800, 2))],
+            ))
+            .await;
+        assert!(duplicate.is_err());
+
+        // 不同客户可同名
+        service
+            .create_template(make_template(
+                "周一例单",
+                Some(2),
+                vec![make_item(1, "苹果", 5, Decimal::new(800, 2))],
+            ))
+            .await?;
+
+        // 明细为空 / 名称为空
+        assert!(service
+            .create_template(make_template("空模板", Some(1), vec![]))
+            .await
+            .is_err());
+        assert!(service
+            .create_template(make_template(
+                "  ",
+                Some(1),
+                vec![make_item(1, "苹果", 1, Decimal::ONE)]
+            ))
+            .await
+            .is_err());
+
+        let templates = service.get_templates(Some(1)).await?;
+        assert_eq!(templates.len(), 1);
+
+        // 编辑：替换明细并改名
+        let updated = service
+            .update_template(UpdateOrderTemplateDto {
+                template_id: detail.template.id,
+                name: Some("周一大单".to_string()),
+                items: Some(vec![
+                    make_item(1, "苹果", 20, Decimal::new(800, 2)),
+                    make_item(2, "香蕉", 10, Decimal::new(500, 2)),
+                ]),
+                remark: None,
+            })
+            .await?;
+        assert_eq!(updated.template.name, "周一大单");
+        assert_eq!(updated.items.len(), 2);
+
+        service.delete_template(detail.template.id).await?;
+        assert!(service
+            .get_template_by_id(detail.template.id)
+            .await?
+            .is_none());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== create_order_from_template 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_create_order_from_template() {
+    run_in_transaction(|db| async move {
+        let product_service = ProductService::new(db.clone());
+        let service = OrderTemplateService::new(db.clone());
+
+        let apple = product_service
+            .create_product(CreateProductDto {
+                name: "苹果".to_string(),
+                category_id: None,
+                category: None,
+                unit: "斤".to_string(),
+                default_sell_price: Some(Decimal::new(1000, 2)),
+                default_purchase_price: None,
+                min_stock: None,
+                reorder_quantity: None,
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+
+        let detail = service
+            .create_template(make_template(
+                "周一例单",
+                Some(1),
+                vec![make_item(apple.id, "苹果", 10, Decimal::new(800, 2))],
+            ))
+            .await?;
+
+        // 按模板保存的单价下单
+        let order = service
+            .create_order_from_template(CreateOrderFromTemplateDto {
+                template_id: detail.template.id,
+                refresh_prices: None,
+                remark: None,
+                due_date: None,
+            })
+            .await?;
+        assert_eq!(order.status, OrderStatus::Pending);
+        assert_eq!(order.customer_id, Some(1));
+        assert_eq!(order.remark, Some("周一例单".to_string()));
+        assert_eq!(order.total_amount, Decimal::new(8000, 2));
+
+        // 按参考售价刷新单价下单
+        let refreshed = service
+            .create_order_from_template(CreateOrderFromTemplateDto {
+                template_id: detail.template.id,
+                refresh_prices: Some(true),
+                remark: None,
+                due_date: None,
+            })
+            .await?;
+        assert_eq!(refreshed.total_amount, Decimal::new(10000, 2));
+
+        let template = service
+            .get_template_by_id(detail.template.id)
+            .await?
+            .unwrap()
+            .template;
+        assert!(template.last_used_at.is_some());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_save_order_as_template() {
+    run_in_transaction(|db| async move {
+        let order_service = OrderService::new(db.clone());
+        let service = OrderTemplateService::new(db.clone());
+
+        let mut discounted = make_item(2, "香蕉", 10, Decimal::new(500, 2));
+        discounted.discount_amount = Some(Decimal::new(5, 0));
+        let order = order_service
+            .create_order(CreateOrderDto {
+                order_type: "Purchase".to_string(),
+                customer_id: Some(3),
+                customer_name: Some("果园供应商".to_string()),
+                items: vec![make_item(1, "苹果", 50, Decimal::new(600, 2)), discounted],
+                remark: Some("进货".to_string()),
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        let detail = service
+            .save_order_as_template(SaveOrderAsTemplateDto {
+                order_id: order.id,
+                name: "每周进货".to_string(),
+            })
+            .await?;
+        assert_eq!(detail.template.order_type, OrderType::Purchase);
+        assert_eq!(detail.template.sub_type, OrderSubType::WholesalePurchase);
+        assert_eq!(detail.template.customer_id, Some(3));
+        assert_eq!(detail.items.len(), 2);
+
+        let reorder = service
+            .create_order_from_template(CreateOrderFromTemplateDto {
+                template_id: detail.template.id,
+                refresh_prices: None,
+                remark: None,
+                due_date: None,
+            })
+            .await?;
+        assert_eq!(reorder.order_type, OrderType::Purchase);
+        assert_eq!(reorder.total_amount, order.total_amount);
+
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(reorder.id))
+            .all(&db)
+            .await?;
+        let banana = items.iter().find(|i| i.product_id == 2).unwrap();
+        assert_eq!(banana.discount_amount, Decimal::new(5, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
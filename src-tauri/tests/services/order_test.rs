@@ -1,14 +1,22 @@
 use accounting_assistant_lib::entity::accounting_book;
 use accounting_assistant_lib::entity::accounting_record;
+use accounting_assistant_lib::entity::order;
 use accounting_assistant_lib::entity::order_item;
 use accounting_assistant_lib::enums::{
     AccountingChannel, AccountingRecordState, AccountingType, OrderStatus, OrderSubType, OrderType,
 };
 use accounting_assistant_lib::services::accounting_book::DEFAULT_BOOK_ID;
+use accounting_assistant_lib::services::category::dto::CreateCategoryDto;
 use accounting_assistant_lib::services::order::dto::{
-    CreateOrderDto, CreateOrderItemDto, QueryOrdersDto, SettleOrderDto, UpdateOrderDto,
+    AddOrderPaymentDto, BatchSettleOrdersDto, CloneOrderDto, CostSource, CreateOrderDto,
+    CreateOrderItemDto, CreateOrderReturnDto, CreateOrderReturnItemDto, MarginWarningKind,
+    QueryOrdersDto, ReopenOrderDto, SettleOrderDto, SettlePaymentDto, UpdateOrderDto,
+};
+use accounting_assistant_lib::services::product::dto::CreateProductDto;
+use accounting_assistant_lib::services::setting::dto::MarginConfig;
+use accounting_assistant_lib::services::{
+    CategoryService, OrderService, ProductService, SettingService,
 };
-use accounting_assistant_lib::services::OrderService;
 use rust_decimal::Decimal;
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serial_test::serial;
@@ -29,6 +37,8 @@ fn make_item(
         quantity,
         unit: unit.to_string(),
         unit_price,
+        discount_amount: None,
+        discount_rate: None,
         remark: None,
     }
 }
@@ -44,6 +54,7 @@ async fn test_create_order_success() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![
                 make_item(1, "苹果", Decimal::new(10, 0), "斤", Decimal::new(800, 2)),
                 make_item(2, "香蕉", Decimal::new(5, 0), "斤", Decimal::new(500, 2)),
@@ -51,6 +62,7 @@ async fn test_create_order_success() {
             remark: Some("测试订单".to_string()),
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
 
         let order = service.create_order(dto).await?;
@@ -88,10 +100,12 @@ async fn test_create_order_empty_items_error() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![],
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
 
         let result = service.create_order(dto).await;
@@ -113,6 +127,7 @@ async fn test_create_order_with_custom_actual_amount() {
         let dto = CreateOrderDto {
             order_type: "Purchase".to_string(),
             customer_id: Some(100),
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -123,6 +138,7 @@ async fn test_create_order_with_custom_actual_amount() {
             remark: None,
             actual_amount: Some(Decimal::new(7500, 2)), // 抹零
             sub_type: None,
+            due_date: None,
         };
 
         let order = service.create_order(dto).await?;
@@ -150,6 +166,7 @@ async fn test_update_order_pending_success() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -160,6 +177,7 @@ async fn test_update_order_pending_success() {
             remark: Some("原始备注".to_string()),
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
         let original_total = order.total_amount;
@@ -172,6 +190,7 @@ async fn test_update_order_pending_success() {
                 make_item(2, "香蕉", Decimal::new(10, 0), "斤", Decimal::new(500, 2)),
             ]),
             remark: Some("更新备注".to_string()),
+            due_date: None,
         };
 
         let updated = service.update_order(update_dto).await?;
@@ -204,6 +223,7 @@ async fn test_update_order_settled_error() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -214,13 +234,15 @@ async fn test_update_order_settled_error() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
         let settle_dto = SettleOrderDto {
             order_id: order.id,
-            channel: "BankCard".to_string(),
+            channel: Some("BankCard".to_string()),
             actual_amount: None,
+            payments: None,
         };
         service.settle_order(settle_dto).await?;
 
@@ -235,6 +257,7 @@ async fn test_update_order_settled_error() {
                 Decimal::new(800, 2),
             )]),
             remark: None,
+            due_date: None,
         };
 
         let result = service.update_order(update_dto).await;
@@ -255,6 +278,7 @@ async fn test_update_order_cancelled_error() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -265,6 +289,7 @@ async fn test_update_order_cancelled_error() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
@@ -274,6 +299,7 @@ async fn test_update_order_cancelled_error() {
             order_id: order.id,
             items: None,
             remark: Some("尝试修改".to_string()),
+            due_date: None,
         };
 
         let result = service.update_order(update_dto).await;
@@ -294,6 +320,7 @@ async fn test_update_order_empty_items_error() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -304,6 +331,7 @@ async fn test_update_order_empty_items_error() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
@@ -311,6 +339,7 @@ async fn test_update_order_empty_items_error() {
             order_id: order.id,
             items: Some(vec![]),
             remark: None,
+            due_date: None,
         };
 
         let result = service.update_order(update_dto).await;
@@ -333,6 +362,7 @@ async fn test_settle_order_sales_creates_income_record() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -343,6 +373,7 @@ async fn test_settle_order_sales_creates_income_record() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
@@ -355,8 +386,9 @@ async fn test_settle_order_sales_creates_income_record() {
 
         let settle_dto = SettleOrderDto {
             order_id: order.id,
-            channel: "BankCard".to_string(),
+            channel: Some("BankCard".to_string()),
             actual_amount: None,
+            payments: None,
         };
 
         let settled = service.settle_order(settle_dto).await?;
@@ -403,6 +435,7 @@ async fn test_settle_order_purchase_creates_expenditure_record() {
         let create_dto = CreateOrderDto {
             order_type: "Purchase".to_string(),
             customer_id: Some(100),
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -413,13 +446,15 @@ async fn test_settle_order_purchase_creates_expenditure_record() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
         let settle_dto = SettleOrderDto {
             order_id: order.id,
-            channel: "Wechat".to_string(),
+            channel: Some("Wechat".to_string()),
             actual_amount: None,
+            payments: None,
         };
 
         let settled = service.settle_order(settle_dto).await?;
@@ -449,6 +484,7 @@ async fn test_settle_order_with_custom_actual_amount() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -459,13 +495,15 @@ async fn test_settle_order_with_custom_actual_amount() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
         let settle_dto = SettleOrderDto {
             order_id: order.id,
-            channel: "Cash".to_string(),
+            channel: Some("Cash".to_string()),
             actual_amount: Some(Decimal::new(7500, 2)),
+            payments: None,
         };
 
         let settled = service.settle_order(settle_dto).await?;
@@ -505,6 +543,7 @@ async fn test_settle_order_already_settled_error() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -515,21 +554,24 @@ async fn test_settle_order_already_settled_error() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
         let settle_dto = SettleOrderDto {
             order_id: order.id,
-            channel: "BankCard".to_string(),
+            channel: Some("BankCard".to_string()),
             actual_amount: None,
+            payments: None,
         };
         service.settle_order(settle_dto).await?;
 
         // 重复结账
         let settle_dto2 = SettleOrderDto {
             order_id: order.id,
-            channel: "Cash".to_string(),
+            channel: Some("Cash".to_string()),
             actual_amount: None,
+            payments: None,
         };
         let result = service.settle_order(settle_dto2).await;
         assert!(result.is_err());
@@ -549,6 +591,7 @@ async fn test_settle_order_cancelled_error() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -559,14 +602,16 @@ async fn test_settle_order_cancelled_error() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
         service.cancel_order(order.id).await?;
 
         let settle_dto = SettleOrderDto {
             order_id: order.id,
-            channel: "BankCard".to_string(),
+            channel: Some("BankCard".to_string()),
             actual_amount: None,
+            payments: None,
         };
 
         let result = service.settle_order(settle_dto).await;
@@ -589,6 +634,7 @@ async fn test_cancel_order_success() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -599,6 +645,7 @@ async fn test_cancel_order_success() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
@@ -622,6 +669,7 @@ async fn test_cancel_order_settled_error() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -632,13 +680,15 @@ async fn test_cancel_order_settled_error() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(create_dto).await?;
 
         let settle_dto = SettleOrderDto {
             order_id: order.id,
-            channel: "BankCard".to_string(),
+            channel: Some("BankCard".to_string()),
             actual_amount: None,
+            payments: None,
         };
         service.settle_order(settle_dto).await?;
 
@@ -695,6 +745,7 @@ async fn test_get_all_orders_ordered_by_create_at_desc() {
             let dto = CreateOrderDto {
                 order_type: "Sales".to_string(),
                 customer_id: None,
+                customer_name: None,
                 items: vec![make_item(
                     i,
                     &format!("商品{}", i),
@@ -705,6 +756,7 @@ async fn test_get_all_orders_ordered_by_create_at_desc() {
                 remark: None,
                 actual_amount: None,
                 sub_type: None,
+                due_date: None,
             };
             service.create_order(dto).await?;
         }
@@ -733,6 +785,7 @@ async fn test_get_order_by_id_with_items() {
         let create_dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![
                 make_item(1, "苹果", Decimal::new(10, 0), "斤", Decimal::new(800, 2)),
                 make_item(2, "香蕉", Decimal::new(5, 0), "斤", Decimal::new(500, 2)),
@@ -740,6 +793,7 @@ async fn test_get_order_by_id_with_items() {
             remark: Some("含明细".to_string()),
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let created = service.create_order(create_dto).await?;
 
@@ -795,6 +849,7 @@ async fn test_get_orders_by_customer_id() {
         let dto1 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: Some(100),
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -805,6 +860,7 @@ async fn test_get_orders_by_customer_id() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto1).await?;
 
@@ -812,6 +868,7 @@ async fn test_get_orders_by_customer_id() {
         let dto2 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: Some(200),
+            customer_name: None,
             items: vec![make_item(
                 2,
                 "香蕉",
@@ -822,6 +879,7 @@ async fn test_get_orders_by_customer_id() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto2).await?;
 
@@ -829,6 +887,7 @@ async fn test_get_orders_by_customer_id() {
         let dto3 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 3,
                 "橙子",
@@ -839,6 +898,7 @@ async fn test_get_orders_by_customer_id() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto3).await?;
 
@@ -870,6 +930,7 @@ async fn test_get_orders_by_status() {
         let dto1 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -880,12 +941,14 @@ async fn test_get_orders_by_status() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order1 = service.create_order(dto1).await?;
 
         let dto2 = CreateOrderDto {
             order_type: "Purchase".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 2,
                 "香蕉",
@@ -896,14 +959,16 @@ async fn test_get_orders_by_status() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order2 = service.create_order(dto2).await?;
 
         // 结账 order1
         let settle_dto = SettleOrderDto {
             order_id: order1.id,
-            channel: "BankCard".to_string(),
+            channel: Some("BankCard".to_string()),
             actual_amount: None,
+            payments: None,
         };
         service.settle_order(settle_dto).await?;
 
@@ -942,6 +1007,7 @@ async fn test_query_orders_pagination() {
             let dto = CreateOrderDto {
                 order_type: "Sales".to_string(),
                 customer_id: None,
+                customer_name: None,
                 items: vec![make_item(
                     i,
                     &format!("商品{}", i),
@@ -952,6 +1018,7 @@ async fn test_query_orders_pagination() {
                 remark: None,
                 actual_amount: None,
                 sub_type: None,
+                due_date: None,
             };
             service.create_order(dto).await?;
         }
@@ -967,6 +1034,13 @@ async fn test_query_orders_pagination() {
             max_amount: None,
             channel: None,
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders, total) = service.query_orders(query).await?;
 
@@ -984,6 +1058,13 @@ async fn test_query_orders_pagination() {
             max_amount: None,
             channel: None,
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders_p3, total_p3) = service.query_orders(query_page3).await?;
 
@@ -1005,6 +1086,7 @@ async fn test_query_orders_filter_by_status() {
         let dto1 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1015,13 +1097,15 @@ async fn test_query_orders_filter_by_status() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order1 = service.create_order(dto1).await?;
 
         let settle_dto = SettleOrderDto {
             order_id: order1.id,
-            channel: "BankCard".to_string(),
+            channel: Some("BankCard".to_string()),
             actual_amount: None,
+            payments: None,
         };
         service.settle_order(settle_dto).await?;
 
@@ -1029,6 +1113,7 @@ async fn test_query_orders_filter_by_status() {
         let dto2 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 2,
                 "香蕉",
@@ -1039,6 +1124,7 @@ async fn test_query_orders_filter_by_status() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto2).await?;
 
@@ -1052,6 +1138,13 @@ async fn test_query_orders_filter_by_status() {
             max_amount: None,
             channel: None,
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders, total) = service.query_orders(query).await?;
 
@@ -1074,6 +1167,7 @@ async fn test_query_orders_filter_by_order_type() {
         let dto1 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1084,12 +1178,14 @@ async fn test_query_orders_filter_by_order_type() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto1).await?;
 
         let dto2 = CreateOrderDto {
             order_type: "Purchase".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 2,
                 "香蕉",
@@ -1100,6 +1196,7 @@ async fn test_query_orders_filter_by_order_type() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto2).await?;
 
@@ -1113,6 +1210,13 @@ async fn test_query_orders_filter_by_order_type() {
             max_amount: None,
             channel: None,
             order_type: Some("Sales".to_string()),
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders, total) = service.query_orders(query).await?;
 
@@ -1135,6 +1239,7 @@ async fn test_query_orders_filter_by_amount_range() {
         let dto1 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1145,6 +1250,7 @@ async fn test_query_orders_filter_by_amount_range() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto1).await?;
 
@@ -1152,6 +1258,7 @@ async fn test_query_orders_filter_by_amount_range() {
         let dto2 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 2,
                 "香蕉",
@@ -1162,6 +1269,7 @@ async fn test_query_orders_filter_by_amount_range() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto2).await?;
 
@@ -1175,6 +1283,13 @@ async fn test_query_orders_filter_by_amount_range() {
             max_amount: Some(Decimal::new(10000, 2)), // 100.00
             channel: None,
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders, total) = service.query_orders(query).await?;
 
@@ -1203,6 +1318,13 @@ async fn test_query_orders_no_results() {
             max_amount: None,
             channel: None,
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders, total) = service.query_orders(query).await?;
 
@@ -1226,6 +1348,7 @@ async fn test_get_settle_preview_no_discount() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1236,6 +1359,7 @@ async fn test_get_settle_preview_no_discount() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(dto).await?;
 
@@ -1260,6 +1384,7 @@ async fn test_get_settle_preview_with_discount() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![
                 make_item(1, "苹果", Decimal::new(10, 0), "斤", Decimal::new(800, 2)),
                 make_item(2, "香蕉", Decimal::new(5, 0), "斤", Decimal::new(500, 2)),
@@ -1267,6 +1392,7 @@ async fn test_get_settle_preview_with_discount() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(dto).await?;
 
@@ -1313,6 +1439,7 @@ async fn test_create_order_sales_wholesale_sub_type() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: Some(100),
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1323,6 +1450,7 @@ async fn test_create_order_sales_wholesale_sub_type() {
             remark: None,
             actual_amount: None,
             sub_type: Some("Wholesale".to_string()),
+            due_date: None,
         };
 
         let order = service.create_order(dto).await?;
@@ -1344,6 +1472,7 @@ async fn test_create_order_auto_retail_sub_type() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1354,6 +1483,7 @@ async fn test_create_order_auto_retail_sub_type() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
 
         let order = service.create_order(dto).await?;
@@ -1375,6 +1505,7 @@ async fn test_create_order_auto_wholesale_sub_type() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: Some(100),
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1385,6 +1516,7 @@ async fn test_create_order_auto_wholesale_sub_type() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
 
         let order = service.create_order(dto).await?;
@@ -1405,6 +1537,7 @@ async fn test_create_order_purchase_default_sub_type() {
         let dto = CreateOrderDto {
             order_type: "Purchase".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1415,6 +1548,7 @@ async fn test_create_order_purchase_default_sub_type() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
 
         let order = service.create_order(dto).await?;
@@ -1436,6 +1570,7 @@ async fn test_create_order_sub_type_mismatch_error() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1446,6 +1581,7 @@ async fn test_create_order_sub_type_mismatch_error() {
             remark: None,
             actual_amount: None,
             sub_type: Some("WholesalePurchase".to_string()),
+            due_date: None,
         };
 
         let result = service.create_order(dto).await;
@@ -1468,6 +1604,7 @@ async fn test_cancel_order_already_cancelled_error() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1478,6 +1615,7 @@ async fn test_cancel_order_already_cancelled_error() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order = service.create_order(dto).await?;
         service.cancel_order(order.id).await?;
@@ -1504,6 +1642,7 @@ async fn test_query_orders_filter_by_channel() {
         let dto1 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1514,19 +1653,22 @@ async fn test_query_orders_filter_by_channel() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order1 = service.create_order(dto1).await?;
         service
             .settle_order(SettleOrderDto {
                 order_id: order1.id,
-                channel: "BankCard".to_string(),
+                channel: Some("BankCard".to_string()),
                 actual_amount: None,
+                payments: None,
             })
             .await?;
 
         let dto2 = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 2,
                 "香蕉",
@@ -1537,13 +1679,15 @@ async fn test_query_orders_filter_by_channel() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         let order2 = service.create_order(dto2).await?;
         service
             .settle_order(SettleOrderDto {
                 order_id: order2.id,
-                channel: "Wechat".to_string(),
+                channel: Some("Wechat".to_string()),
                 actual_amount: None,
+                payments: None,
             })
             .await?;
 
@@ -1557,6 +1701,13 @@ async fn test_query_orders_filter_by_channel() {
             max_amount: None,
             channel: Some("BankCard".to_string()),
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders, total) = service.query_orders(query).await?;
 
@@ -1581,6 +1732,7 @@ async fn test_query_orders_filter_by_time_range() {
         let dto = CreateOrderDto {
             order_type: "Sales".to_string(),
             customer_id: None,
+            customer_name: None,
             items: vec![make_item(
                 1,
                 "苹果",
@@ -1591,6 +1743,7 @@ async fn test_query_orders_filter_by_time_range() {
             remark: None,
             actual_amount: None,
             sub_type: None,
+            due_date: None,
         };
         service.create_order(dto).await?;
 
@@ -1605,8 +1758,15 @@ async fn test_query_orders_filter_by_time_range() {
             max_amount: None,
             channel: None,
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
-        let (orders, total) = service.query_orders(query).await?;
+        let (_orders, total) = service.query_orders(query).await?;
         assert_eq!(total, 1);
 
         // 使用过去的时间范围
@@ -1620,6 +1780,13 @@ async fn test_query_orders_filter_by_time_range() {
             max_amount: None,
             channel: None,
             order_type: None,
+            customer_id: None,
+            sub_type: None,
+            order_no: None,
+            product_id: None,
+            keyword: None,
+            sort_by: None,
+            sort_order: None,
         };
         let (orders_past, total_past) = service.query_orders(query_past).await?;
         assert_eq!(total_past, 0);
@@ -1630,3 +1797,2009 @@ async fn test_query_orders_filter_by_time_range() {
     .await
     .unwrap();
 }
+
+/// 辅助函数：构造不带任何筛选条件的查询 DTO
+fn make_query() -> QueryOrdersDto {
+    QueryOrdersDto {
+        page: None,
+        page_size: None,
+        start_time: None,
+        end_time: None,
+        status: None,
+        min_amount: None,
+        max_amount: None,
+        channel: None,
+        order_type: None,
+        customer_id: None,
+        sub_type: None,
+        order_no: None,
+        product_id: None,
+        keyword: None,
+        sort_by: None,
+        sort_order: None,
+    }
+}
+
+#[serial]
+#[tokio::test]
+async fn test_query_orders_search_filters() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        let wholesale = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: Some("张三水果店".to_string()),
+                items: vec![make_item(
+                    1,
+                    "苹果",
+                    Decimal::new(1, 0),
+                    "斤",
+                    Decimal::new(800, 2),
+                )],
+                remark: None,
+                actual_amount: None,
+                sub_type: Some("Wholesale".to_string()),
+                due_date: None,
+            })
+            .await?;
+        let retail = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: Some("李四".to_string()),
+                items: vec![
+                    make_item(2, "香蕉", Decimal::new(1, 0), "斤", Decimal::new(500, 2)),
+                    make_item(
+                        3,
+                        "红富士苹果",
+                        Decimal::new(1, 0),
+                        "斤",
+                        Decimal::new(900, 2),
+                    ),
+                ],
+                remark: None,
+                actual_amount: None,
+                sub_type: Some("Retail".to_string()),
+                due_date: None,
+            })
+            .await?;
+
+        let ids = |orders: Vec<order::Model>| -> Vec<i64> {
+            let mut ids: Vec<i64> = orders.into_iter().map(|o| o.id).collect();
+            ids.sort();
+            ids
+        };
+
+        // 关键词匹配客户名称
+        let (orders, _) = service
+            .query_orders(QueryOrdersDto {
+                keyword: Some("张三".to_string()),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(ids(orders), vec![wholesale.id]);
+
+        // 关键词匹配明细商品名称
+        let (orders, _) = service
+            .query_orders(QueryOrdersDto {
+                keyword: Some("苹果".to_string()),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(ids(orders), vec![wholesale.id, retail.id]);
+
+        // 包含指定商品
+        let (orders, _) = service
+            .query_orders(QueryOrdersDto {
+                product_id: Some(2),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(ids(orders), vec![retail.id]);
+
+        // 业务类型
+        let (orders, _) = service
+            .query_orders(QueryOrdersDto {
+                sub_type: Some("Wholesale".to_string()),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(ids(orders), vec![wholesale.id]);
+
+        // 订单编号
+        let (orders, _) = service
+            .query_orders(QueryOrdersDto {
+                order_no: Some(retail.order_no.clone()),
+                ..make_query()
+            })
+            .await?;
+        assert!(orders.iter().any(|o| o.id == retail.id));
+
+        // 多个条件同时生效
+        let (_, total) = service
+            .query_orders(QueryOrdersDto {
+                keyword: Some("香蕉".to_string()),
+                sub_type: Some("Wholesale".to_string()),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(total, 0);
+
+        let result = service
+            .query_orders(QueryOrdersDto {
+                sub_type: Some("Invalid".to_string()),
+                ..make_query()
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "无效的订单业务类型");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_query_orders_sorting() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        let mut ids = Vec::new();
+        for price in [1000, 3000, 2000] {
+            let order = service
+                .create_order(CreateOrderDto {
+                    order_type: "Sales".to_string(),
+                    customer_id: None,
+                    customer_name: None,
+                    items: vec![make_item(
+                        1,
+                        "苹果",
+                        Decimal::new(1, 0),
+                        "斤",
+                        Decimal::new(price, 2),
+                    )],
+                    remark: None,
+                    actual_amount: None,
+                    sub_type: None,
+                    due_date: None,
+                })
+                .await?;
+            ids.push(order.id);
+        }
+        service
+            .settle_order(SettleOrderDto {
+                order_id: ids[2],
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+
+        // 按金额升序
+        let (orders, _) = service
+            .query_orders(QueryOrdersDto {
+                sort_by: Some("Amount".to_string()),
+                sort_order: Some("Asc".to_string()),
+                ..make_query()
+            })
+            .await?;
+        let amounts: Vec<Decimal> = orders.iter().map(|o| o.actual_amount).collect();
+        assert_eq!(
+            amounts,
+            vec![
+                Decimal::new(1000, 2),
+                Decimal::new(2000, 2),
+                Decimal::new(3000, 2)
+            ]
+        );
+
+        // 按结账时间倒序，未结账订单排在最后
+        let (orders, _) = service
+            .query_orders(QueryOrdersDto {
+                sort_by: Some("SettledAt".to_string()),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(orders[0].id, ids[2]);
+        assert!(orders[1..].iter().all(|o| o.settled_at.is_none()));
+
+        let result = service
+            .query_orders(QueryOrdersDto {
+                sort_by: Some("Name".to_string()),
+                ..make_query()
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "无效的排序字段");
+
+        let result = service
+            .query_orders(QueryOrdersDto {
+                sort_order: Some("Up".to_string()),
+                ..make_query()
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "无效的排序方向");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== add_payment 测试 ====================
+
+/// 辅助函数：创建单明细销售订单
+async fn create_sales_order(
+    service: &OrderService,
+    unit_price: Decimal,
+) -> Result<accounting_assistant_lib::entity::order::Model, Box<dyn std::error::Error>> {
+    service
+        .create_order(CreateOrderDto {
+            order_type: "Sales".to_string(),
+            customer_id: None,
+            customer_name: None,
+            items: vec![make_item(1, "苹果", Decimal::ONE, "斤", unit_price)],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await
+}
+
+#[serial]
+#[tokio::test]
+async fn test_add_payment_partial_then_settled() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let order = create_sales_order(&service, Decimal::new(100, 0)).await?;
+
+        let updated = service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(40, 0),
+                channel: "Cash".to_string(),
+                paid_at: Some("2024-06-01 10:00:00".to_string()),
+                remark: Some("首付".to_string()),
+            })
+            .await?;
+        assert_eq!(updated.status, OrderStatus::PartiallyPaid);
+        assert!(updated.settled_at.is_none());
+
+        let balance = service.get_order_balance(order.id).await?;
+        assert_eq!(balance.paid_amount, Decimal::new(40, 0));
+        assert_eq!(balance.outstanding_amount, Decimal::new(60, 0));
+        assert_eq!(balance.payment_count, 1);
+
+        // 每笔收款在品类账本中生成已入账记录
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].amount, Decimal::new(40, 0));
+        assert_eq!(records[0].accounting_type, AccountingType::Income);
+        assert_eq!(records[0].state, AccountingRecordState::Posted);
+        assert_eq!(
+            records[0].title,
+            format!("销售订单-{}-收款", order.order_no)
+        );
+
+        let settled = service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(60, 0),
+                channel: "Wechat".to_string(),
+                paid_at: Some("2024-06-15 10:00:00".to_string()),
+                remark: None,
+            })
+            .await?;
+        assert_eq!(settled.status, OrderStatus::Settled);
+        // 现金 + 微信分笔收款，订单渠道为未知，各渠道金额见收付款记录
+        assert_eq!(settled.channel, AccountingChannel::Unknown);
+
+        let payments = service.get_order_payments(order.id).await?;
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].amount, Decimal::new(40, 0));
+        assert_eq!(payments[1].channel, AccountingChannel::Wechat);
+
+        let balance = service.get_order_balance(order.id).await?;
+        assert_eq!(balance.outstanding_amount, Decimal::ZERO);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_add_payment_exceeds_outstanding_error() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let order = create_sales_order(&service, Decimal::new(100, 0)).await?;
+
+        let result = service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(120, 0),
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        let result = service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::ZERO,
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_add_payment_splits_across_categories() {
+    run_in_transaction(|db| async move {
+        let category_service = CategoryService::new(db.clone());
+        let product_service = ProductService::new(db.clone());
+        let service = OrderService::new(db.clone());
+
+        let fruit = category_service
+            .create_category(CreateCategoryDto {
+                name: "水果".to_string(),
+                sell_book_id: DEFAULT_BOOK_ID,
+                purchase_book_id: DEFAULT_BOOK_ID,
+                remark: None,
+            })
+            .await?;
+        let apple = product_service
+            .create_product(CreateProductDto {
+                name: "苹果".to_string(),
+                category_id: Some(fruit.id),
+                category: None,
+                unit: "斤".to_string(),
+                default_sell_price: None,
+                default_purchase_price: None,
+                min_stock: None,
+                reorder_quantity: None,
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+
+        // 苹果 60（水果）+ 未分类商品 40
+        let order = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![
+                    make_item(apple.id, "苹果", Decimal::ONE, "斤", Decimal::new(60, 0)),
+                    make_item(999999, "杂项", Decimal::ONE, "件", Decimal::new(40, 0)),
+                ],
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(50, 0),
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await?;
+
+        let mut amounts: Vec<Decimal> = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?
+            .into_iter()
+            .map(|r| r.amount)
+            .collect();
+        amounts.sort();
+        assert_eq!(amounts, vec![Decimal::new(20, 0), Decimal::new(30, 0)]);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_settle_partially_paid_order_pays_remainder() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let order = create_sales_order(&service, Decimal::new(100, 0)).await?;
+
+        service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(30, 0),
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await?;
+
+        // 结账时抹零至 90，剩余 60 作为最后一笔收款
+        let settled = service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("BankCard".to_string()),
+                actual_amount: Some(Decimal::new(90, 0)),
+                payments: None,
+            })
+            .await?;
+        assert_eq!(settled.status, OrderStatus::Settled);
+        assert_eq!(settled.actual_amount, Decimal::new(90, 0));
+
+        let payments = service.get_order_payments(order.id).await?;
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[1].amount, Decimal::new(60, 0));
+
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        let total: Decimal = records.iter().map(|r| r.amount).sum();
+        assert_eq!(total, Decimal::new(90, 0));
+        // 抹零的 10 与一次性结清一致，按折扣前金额入账后冲账
+        let write_off: Decimal = records
+            .iter()
+            .filter(|r| r.accounting_type == AccountingType::WriteOff)
+            .map(|r| r.amount)
+            .sum();
+        assert_eq!(write_off, Decimal::new(-10, 0));
+
+        // 实收金额小于已收款时报错
+        let other = create_sales_order(&service, Decimal::new(100, 0)).await?;
+        service
+            .add_payment(AddOrderPaymentDto {
+                order_id: other.id,
+                amount: Decimal::new(50, 0),
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await?;
+        let result = service
+            .settle_order(SettleOrderDto {
+                order_id: other.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: Some(Decimal::new(40, 0)),
+                payments: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_settle_order_records_full_payment() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let order = create_sales_order(&service, Decimal::new(100, 0)).await?;
+
+        service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: Some(Decimal::new(95, 0)),
+                payments: None,
+            })
+            .await?;
+
+        let balance = service.get_order_balance(order.id).await?;
+        assert_eq!(balance.paid_amount, Decimal::new(95, 0));
+        assert_eq!(balance.outstanding_amount, Decimal::ZERO);
+        assert_eq!(balance.payment_count, 1);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_cancel_partially_paid_order_error() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let order = create_sales_order(&service, Decimal::new(100, 0)).await?;
+
+        service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(10, 0),
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await?;
+
+        let result = service.cancel_order(order.id).await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== create_return 测试 ====================
+
+/// 辅助函数：创建并结账一笔 10 斤 × 8.00 的销售订单（实收 72.00）
+async fn create_settled_order(
+    service: &OrderService,
+) -> Result<(accounting_assistant_lib::entity::order::Model, i64), Box<dyn std::error::Error>> {
+    let order = service
+        .create_order(CreateOrderDto {
+            order_type: "Sales".to_string(),
+            customer_id: None,
+            customer_name: None,
+            items: vec![make_item(
+                1,
+                "苹果",
+                Decimal::new(10, 0),
+                "斤",
+                Decimal::new(800, 2),
+            )],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await?;
+    let settled = service
+        .settle_order(SettleOrderDto {
+            order_id: order.id,
+            channel: Some("Cash".to_string()),
+            actual_amount: Some(Decimal::new(72, 0)),
+            payments: None,
+        })
+        .await?;
+    let (_, items) = service
+        .get_order_by_id(order.id)
+        .await?
+        .expect("订单应存在");
+    Ok((settled, items[0].id))
+}
+
+#[serial]
+#[tokio::test]
+async fn test_create_return_writes_off_main_record() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let (order, item_id) = create_settled_order(&service).await?;
+
+        let detail = service
+            .create_return(CreateOrderReturnDto {
+                order_id: order.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: item_id,
+                    quantity: Decimal::new(5, 0),
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: Some("品质问题".to_string()),
+            })
+            .await?;
+
+        // 默认退款按实收比例折算：40.00 × 72 / 80 = 36.00
+        assert!(detail.order_return.return_no.starts_with("XT-"));
+        assert_eq!(detail.order_return.total_amount, Decimal::new(40, 0));
+        assert_eq!(detail.order_return.refund_amount, Decimal::new(36, 0));
+        assert_eq!(detail.order_return.channel, AccountingChannel::Cash);
+        assert_eq!(detail.items.len(), 1);
+        assert_eq!(detail.items[0].quantity, Decimal::new(5, 0));
+
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        let main_record = records
+            .iter()
+            .find(|r| r.accounting_type == AccountingType::Income)
+            .expect("应有 Income 主记录");
+        let refund = records
+            .iter()
+            .find(|r| r.title.starts_with("退货冲账"))
+            .expect("应有退货冲账记录");
+        assert_eq!(refund.accounting_type, AccountingType::WriteOff);
+        assert_eq!(refund.amount, Decimal::new(-36, 0));
+        assert_eq!(refund.write_off_id, Some(main_record.id));
+        assert_eq!(refund.state, AccountingRecordState::Posted);
+
+        let returns = service.get_order_returns(order.id).await?;
+        assert_eq!(returns.len(), 1);
+        assert_eq!(returns[0].items.len(), 1);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_create_return_exceeds_returnable_quantity_error() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let (order, item_id) = create_settled_order(&service).await?;
+
+        let make_return = |quantity: Decimal| CreateOrderReturnDto {
+            order_id: order.id,
+            items: vec![CreateOrderReturnItemDto {
+                order_item_id: item_id,
+                quantity,
+            }],
+            refund_amount: None,
+            channel: None,
+            reason: None,
+        };
+
+        assert!(service
+            .create_return(make_return(Decimal::new(11, 0)))
+            .await
+            .is_err());
+
+        let first = service
+            .create_return(make_return(Decimal::new(6, 0)))
+            .await?;
+        // 剩余可退 4 斤
+        assert!(service
+            .create_return(make_return(Decimal::new(5, 0)))
+            .await
+            .is_err());
+        let second = service
+            .create_return(make_return(Decimal::new(4, 0)))
+            .await?;
+        assert_ne!(second.order_return.return_no, first.order_return.return_no);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_create_return_unsettled_order_error() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let order = create_sales_order(&service, Decimal::new(100, 0)).await?;
+        let (_, items) = service
+            .get_order_by_id(order.id)
+            .await?
+            .expect("订单应存在");
+
+        let result = service
+            .create_return(CreateOrderReturnDto {
+                order_id: order.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: items[0].id,
+                    quantity: Decimal::ONE,
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== reopen_order 测试 ====================
+
+/// 辅助函数：订单关联记账记录金额合计
+async fn order_record_total(
+    db: &sea_orm::DatabaseConnection,
+    order_id: i64,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let records = accounting_record::Entity::find()
+        .filter(accounting_record::Column::OrderId.eq(order_id))
+        .all(db)
+        .await?;
+    Ok(records.iter().map(|r| r.amount).sum())
+}
+
+#[serial]
+#[tokio::test]
+async fn test_reopen_order_reverses_records_and_payments() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let (order, _) = create_settled_order(&service).await?;
+        assert_eq!(
+            order_record_total(&db, order.id).await?,
+            Decimal::new(72, 0)
+        );
+
+        let reopened = service
+            .reopen_order(ReopenOrderDto {
+                order_id: order.id,
+                reason: Some("渠道选错".to_string()),
+            })
+            .await?;
+        assert_eq!(reopened.status, OrderStatus::Pending);
+        assert_eq!(reopened.channel, AccountingChannel::Unknown);
+        assert!(reopened.settled_at.is_none());
+        assert!(reopened.reopened_at.is_some());
+
+        // 原记录保留，冲回记录关联原主记录
+        assert_eq!(order_record_total(&db, order.id).await?, Decimal::ZERO);
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        let main_record = records
+            .iter()
+            .find(|r| r.accounting_type == AccountingType::Income)
+            .expect("应有 Income 主记录");
+        let reversal = records
+            .iter()
+            .find(|r| r.title.starts_with("撤销结账"))
+            .expect("应有撤销结账冲账记录");
+        assert_eq!(reversal.amount, Decimal::new(-72, 0));
+        assert_eq!(reversal.write_off_id, Some(main_record.id));
+        assert_eq!(reversal.remark, Some("渠道选错".to_string()));
+
+        let balance = service.get_order_balance(order.id).await?;
+        assert_eq!(balance.paid_amount, Decimal::ZERO);
+        assert_eq!(balance.outstanding_amount, Decimal::new(72, 0));
+
+        // 重新结账后再次撤销，已冲平的记录不重复冲回
+        service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Wechat".to_string()),
+                actual_amount: Some(Decimal::new(80, 0)),
+                payments: None,
+            })
+            .await?;
+        assert_eq!(
+            order_record_total(&db, order.id).await?,
+            Decimal::new(80, 0)
+        );
+
+        service
+            .reopen_order(ReopenOrderDto {
+                order_id: order.id,
+                reason: None,
+            })
+            .await?;
+        assert_eq!(order_record_total(&db, order.id).await?, Decimal::ZERO);
+        let first_main_reversals = accounting_record::Entity::find()
+            .filter(accounting_record::Column::WriteOffId.eq(main_record.id))
+            .all(&db)
+            .await?;
+        // 折扣冲账 + 首次撤销
+        assert_eq!(first_main_reversals.len(), 2);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_reopen_order_invalid_status_error() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        // 待结账订单不可撤销
+        let pending = create_sales_order(&service, Decimal::new(100, 0)).await?;
+        let result = service
+            .reopen_order(ReopenOrderDto {
+                order_id: pending.id,
+                reason: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        // 已有退货的订单不可撤销
+        let (settled, item_id) = create_settled_order(&service).await?;
+        service
+            .create_return(CreateOrderReturnDto {
+                order_id: settled.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: item_id,
+                    quantity: Decimal::ONE,
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+        let result = service
+            .reopen_order(ReopenOrderDto {
+                order_id: settled.id,
+                reason: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 明细折扣测试 ====================
+
+/// 辅助函数：构造带明细折扣的商品明细 DTO
+fn make_discounted_item(
+    quantity: Decimal,
+    unit_price: Decimal,
+    discount_amount: Option<Decimal>,
+    discount_rate: Option<Decimal>,
+) -> CreateOrderItemDto {
+    let mut item = make_item(1, "苹果", quantity, "斤", unit_price);
+    item.discount_amount = discount_amount;
+    item.discount_rate = discount_rate;
+    item
+}
+
+/// 辅助函数：构造销售订单 DTO
+fn make_sales_dto(items: Vec<CreateOrderItemDto>) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: "Sales".to_string(),
+        customer_id: None,
+        customer_name: None,
+        items,
+        remark: None,
+        actual_amount: None,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+#[serial]
+#[tokio::test]
+async fn test_create_order_with_item_discounts() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        let order = service
+            .create_order(make_sales_dto(vec![
+                // 80.00 按 10% 折扣 → 72.00
+                make_discounted_item(
+                    Decimal::new(10, 0),
+                    Decimal::new(8, 0),
+                    None,
+                    Some(Decimal::new(10, 0)),
+                ),
+                // 100.00 减 5.00 → 95.00
+                make_discounted_item(
+                    Decimal::new(2, 0),
+                    Decimal::new(50, 0),
+                    Some(Decimal::new(5, 0)),
+                    None,
+                ),
+            ]))
+            .await?;
+
+        assert_eq!(order.total_amount, Decimal::new(167, 0));
+        assert_eq!(order.actual_amount, Decimal::new(167, 0));
+
+        let (_, items) = service
+            .get_order_by_id(order.id)
+            .await?
+            .expect("订单应存在");
+        assert_eq!(items[0].discount_amount, Decimal::new(8, 0));
+        assert_eq!(items[0].discount_rate, Some(Decimal::new(10, 0)));
+        assert_eq!(items[0].subtotal, Decimal::new(72, 0));
+        assert_eq!(items[1].discount_amount, Decimal::new(5, 0));
+        assert_eq!(items[1].subtotal, Decimal::new(95, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_create_order_invalid_item_discount_error() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        // 折扣率与折扣金额同时设置
+        let result = service
+            .create_order(make_sales_dto(vec![make_discounted_item(
+                Decimal::ONE,
+                Decimal::new(10, 0),
+                Some(Decimal::ONE),
+                Some(Decimal::new(10, 0)),
+            )]))
+            .await;
+        assert!(result.is_err());
+
+        // 折扣率超过 100
+        let result = service
+            .create_order(make_sales_dto(vec![make_discounted_item(
+                Decimal::ONE,
+                Decimal::new(10, 0),
+                None,
+                Some(Decimal::new(150, 0)),
+            )]))
+            .await;
+        assert!(result.is_err());
+
+        // 折扣金额超过商品金额
+        let result = service
+            .create_order(make_sales_dto(vec![make_discounted_item(
+                Decimal::ONE,
+                Decimal::new(10, 0),
+                Some(Decimal::new(11, 0)),
+                None,
+            )]))
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_settle_order_books_item_and_order_discounts() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        let order = service
+            .create_order(make_sales_dto(vec![make_discounted_item(
+                Decimal::new(10, 0),
+                Decimal::new(8, 0),
+                None,
+                Some(Decimal::new(10, 0)),
+            )]))
+            .await?;
+
+        // 明细折扣 8.00 + 整单折扣 2.00
+        let preview = service
+            .get_settle_preview(order.id, Some(Decimal::new(70, 0)))
+            .await?;
+        assert_eq!(preview.category_groups[0].amount, Decimal::new(80, 0));
+        assert_eq!(
+            preview.category_groups[0].item_discount_amount,
+            Decimal::new(8, 0)
+        );
+        assert_eq!(preview.item_discount_amount, Some(Decimal::new(8, 0)));
+        let item_preview = preview.item_discount_preview.expect("应有明细折扣预览");
+        assert_eq!(item_preview[0].write_off_amount, Decimal::new(-8, 0));
+        assert_eq!(preview.discount_amount, Some(Decimal::new(2, 0)));
+        let order_preview = preview.write_off_preview.expect("应有整单折扣预览");
+        assert_eq!(order_preview[0].write_off_amount, Decimal::new(-2, 0));
+
+        service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: Some(Decimal::new(70, 0)),
+                payments: None,
+            })
+            .await?;
+
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        let main_record = records
+            .iter()
+            .find(|r| r.accounting_type == AccountingType::Income)
+            .expect("应有 Income 主记录");
+        assert_eq!(main_record.amount, Decimal::new(80, 0));
+
+        let item_write_off = records
+            .iter()
+            .find(|r| r.title.starts_with("商品折扣冲账"))
+            .expect("应有明细折扣冲账记录");
+        assert_eq!(item_write_off.amount, Decimal::new(-8, 0));
+        assert_eq!(item_write_off.write_off_id, Some(main_record.id));
+
+        let order_write_off = records
+            .iter()
+            .find(|r| r.title.starts_with("折扣冲账"))
+            .expect("应有整单折扣冲账记录");
+        assert_eq!(order_write_off.amount, Decimal::new(-2, 0));
+
+        let total: Decimal = records.iter().map(|r| r.amount).sum();
+        assert_eq!(total, Decimal::new(70, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_add_payment_books_discounts_when_paid_off() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        // 明细折扣 8.00（80.00 按 10%）+ 整单折扣 2.00，实收 70.00
+        let mut input = make_sales_dto(vec![make_discounted_item(
+            Decimal::new(10, 0),
+            Decimal::new(8, 0),
+            None,
+            Some(Decimal::new(10, 0)),
+        )]);
+        input.actual_amount = Some(Decimal::new(70, 0));
+        let order = service.create_order(input).await?;
+
+        let pay = |amount: i64, channel: &str| AddOrderPaymentDto {
+            order_id: order.id,
+            amount: Decimal::new(amount, 0),
+            channel: channel.to_string(),
+            paid_at: None,
+            remark: None,
+        };
+        service.add_payment(pay(30, "Cash")).await?;
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        assert!(records
+            .iter()
+            .all(|r| r.accounting_type == AccountingType::Income));
+
+        // 结清时补记折扣冲账，入账合计仍为实收金额
+        service.add_payment(pay(40, "Wechat")).await?;
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        let sum_by_title = |prefix: &str| -> Decimal {
+            records
+                .iter()
+                .filter(|r| r.title.starts_with(prefix))
+                .map(|r| r.amount)
+                .sum()
+        };
+        assert_eq!(sum_by_title("商品折扣冲账"), Decimal::new(-8, 0));
+        assert_eq!(sum_by_title("折扣冲账"), Decimal::new(-2, 0));
+        for write_off in records
+            .iter()
+            .filter(|r| r.accounting_type == AccountingType::WriteOff)
+        {
+            let main = records
+                .iter()
+                .find(|r| Some(r.id) == write_off.write_off_id)
+                .expect("冲账记录应关联主记录");
+            assert_eq!(main.accounting_type, AccountingType::Income);
+            assert_eq!(main.channel, write_off.channel);
+        }
+        let total: Decimal = records.iter().map(|r| r.amount).sum();
+        assert_eq!(total, Decimal::new(70, 0));
+
+        // 撤销结账后全部冲平
+        service
+            .reopen_order(ReopenOrderDto {
+                order_id: order.id,
+                reason: None,
+            })
+            .await?;
+        let total: Decimal = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?
+            .iter()
+            .map(|r| r.amount)
+            .sum();
+        assert_eq!(total, Decimal::ZERO);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== clone_order 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_clone_order_keeps_items_and_prices() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+
+        let mut discounted = make_item(2, "香蕉", Decimal::new(5, 0), "斤", Decimal::new(500, 2));
+        discounted.discount_rate = Some(Decimal::new(10, 0));
+        let original = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: Some(7),
+                customer_name: Some("老主顾".to_string()),
+                items: vec![
+                    make_item(1, "苹果", Decimal::new(10, 0), "斤", Decimal::new(800, 2)),
+                    discounted,
+                ],
+                remark: Some("每周例单".to_string()),
+                actual_amount: None,
+                sub_type: Some("Retail".to_string()),
+                due_date: None,
+            })
+            .await?;
+        service
+            .settle_order(SettleOrderDto {
+                order_id: original.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+
+        let cloned = service
+            .clone_order(CloneOrderDto {
+                order_id: original.id,
+                refresh_prices: None,
+                remark: None,
+                due_date: None,
+            })
+            .await?;
+
+        assert_ne!(cloned.id, original.id);
+        assert_ne!(cloned.order_no, original.order_no);
+        assert_eq!(cloned.status, OrderStatus::Pending);
+        assert_eq!(cloned.customer_id, Some(7));
+        assert_eq!(cloned.customer_name, Some("老主顾".to_string()));
+        assert_eq!(cloned.sub_type, OrderSubType::Retail);
+        assert_eq!(cloned.remark, Some("每周例单".to_string()));
+        // 80 + (25 - 2.5) = 102.5
+        assert_eq!(cloned.total_amount, Decimal::new(10250, 2));
+
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(cloned.id))
+            .all(&db)
+            .await?;
+        assert_eq!(items.len(), 2);
+        let banana = items.iter().find(|i| i.product_id == 2).unwrap();
+        assert_eq!(banana.discount_rate, Some(Decimal::new(10, 0)));
+        assert_eq!(banana.discount_amount, Decimal::new(250, 2));
+
+        // 原订单不存在
+        let missing = service
+            .clone_order(CloneOrderDto {
+                order_id: 999999,
+                refresh_prices: None,
+                remark: None,
+                due_date: None,
+            })
+            .await;
+        assert!(missing.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_clone_order_refresh_prices() {
+    run_in_transaction(|db| async move {
+        let product_service = ProductService::new(db.clone());
+        let service = OrderService::new(db.clone());
+
+        let apple = product_service
+            .create_product(CreateProductDto {
+                name: "苹果".to_string(),
+                category_id: None,
+                category: None,
+                unit: "斤".to_string(),
+                default_sell_price: Some(Decimal::new(900, 2)),
+                default_purchase_price: Some(Decimal::new(600, 2)),
+                min_stock: None,
+                reorder_quantity: None,
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+        let pear = product_service
+            .create_product(CreateProductDto {
+                name: "梨".to_string(),
+                category_id: None,
+                category: None,
+                unit: "斤".to_string(),
+                default_sell_price: None,
+                default_purchase_price: None,
+                min_stock: None,
+                reorder_quantity: None,
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+
+        let original = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![
+                    make_item(
+                        apple.id,
+                        "苹果",
+                        Decimal::new(10, 0),
+                        "斤",
+                        Decimal::new(800, 2),
+                    ),
+                    make_item(
+                        pear.id,
+                        "梨",
+                        Decimal::new(2, 0),
+                        "斤",
+                        Decimal::new(400, 2),
+                    ),
+                ],
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        let cloned = service
+            .clone_order(CloneOrderDto {
+                order_id: original.id,
+                refresh_prices: Some(true),
+                remark: Some("调价后再来一单".to_string()),
+                due_date: None,
+            })
+            .await?;
+
+        // 苹果按参考售价 9.00，梨未设置参考价保留 4.00：90 + 8 = 98
+        assert_eq!(cloned.total_amount, Decimal::new(9800, 2));
+        assert_eq!(cloned.remark, Some("调价后再来一单".to_string()));
+
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(cloned.id))
+            .all(&db)
+            .await?;
+        let apple_item = items.iter().find(|i| i.product_id == apple.id).unwrap();
+        assert_eq!(apple_item.unit_price, Decimal::new(900, 2));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== batch_settle_orders 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_batch_settle_orders_combines_preview() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let first = create_sales_order(&service, Decimal::new(100, 0)).await?;
+        let second = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![make_item(
+                    2,
+                    "香蕉",
+                    Decimal::ONE,
+                    "斤",
+                    Decimal::new(50, 0),
+                )],
+                remark: None,
+                actual_amount: Some(Decimal::new(45, 0)),
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        // 重复的订单 ID 只结账一次
+        let result = service
+            .batch_settle_orders(BatchSettleOrdersDto {
+                order_ids: vec![first.id, second.id, first.id],
+                channel: "Wechat".to_string(),
+            })
+            .await?;
+
+        assert_eq!(result.orders.len(), 2);
+        assert_eq!(result.orders[0].id, first.id);
+        assert!(result
+            .orders
+            .iter()
+            .all(|o| o.status == OrderStatus::Settled && o.channel == AccountingChannel::Wechat));
+        assert_eq!(result.total_amount, Decimal::new(150, 0));
+        assert_eq!(result.actual_amount, Decimal::new(145, 0));
+
+        // 同一品类、同一账本的主记录合并
+        assert_eq!(result.preview.category_groups.len(), 1);
+        assert_eq!(
+            result.preview.category_groups[0].amount,
+            Decimal::new(150, 0)
+        );
+        assert_eq!(result.preview.discount_amount, Some(Decimal::new(5, 0)));
+        let write_offs = result.preview.write_off_preview.unwrap();
+        assert_eq!(write_offs.len(), 1);
+        assert_eq!(write_offs[0].write_off_amount, Decimal::new(-5, 0));
+
+        let balance = service.get_order_balance(second.id).await?;
+        assert_eq!(balance.paid_amount, Decimal::new(45, 0));
+        assert_eq!(balance.outstanding_amount, Decimal::ZERO);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_batch_settle_orders_requires_pending() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let pending = create_sales_order(&service, Decimal::new(100, 0)).await?;
+        let partial = create_sales_order(&service, Decimal::new(80, 0)).await?;
+        service
+            .add_payment(AddOrderPaymentDto {
+                order_id: partial.id,
+                amount: Decimal::new(30, 0),
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await?;
+
+        // 部分收付款订单不能批量结账，整批回滚
+        let result = service
+            .batch_settle_orders(BatchSettleOrdersDto {
+                order_ids: vec![pending.id, partial.id],
+                channel: "Cash".to_string(),
+            })
+            .await;
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            format!("订单 {} 不是待结账状态，不能批量结账", partial.order_no)
+        );
+        let (unchanged, _) = service.get_order_by_id(pending.id).await?.unwrap();
+        assert_eq!(unchanged.status, OrderStatus::Pending);
+
+        // 不存在的订单返回批量校验错误
+        let result = service
+            .batch_settle_orders(BatchSettleOrdersDto {
+                order_ids: vec![pending.id, -1],
+                channel: "Cash".to_string(),
+            })
+            .await;
+        assert_eq!(result.err().unwrap().to_string(), "订单不存在: -1");
+
+        // 空列表、无效渠道
+        assert!(service
+            .batch_settle_orders(BatchSettleOrdersDto {
+                order_ids: vec![],
+                channel: "Cash".to_string(),
+            })
+            .await
+            .is_err());
+        assert!(service
+            .batch_settle_orders(BatchSettleOrdersDto {
+                order_ids: vec![pending.id],
+                channel: "Crypto".to_string(),
+            })
+            .await
+            .is_err());
+
+        // 订单类型不一致
+        let purchase = service
+            .create_order(CreateOrderDto {
+                order_type: "Purchase".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![make_item(
+                    1,
+                    "苹果",
+                    Decimal::ONE,
+                    "斤",
+                    Decimal::new(60, 0),
+                )],
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+        assert!(service
+            .batch_settle_orders(BatchSettleOrdersDto {
+                order_ids: vec![pending.id, purchase.id],
+                channel: "Cash".to_string(),
+            })
+            .await
+            .is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 组合支付结账测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_settle_order_split_payments_across_channels() {
+    run_in_transaction(|db| async move {
+        let category_service = CategoryService::new(db.clone());
+        let product_service = ProductService::new(db.clone());
+        let service = OrderService::new(db.clone());
+
+        let fruit = category_service
+            .create_category(CreateCategoryDto {
+                name: "水果".to_string(),
+                sell_book_id: DEFAULT_BOOK_ID,
+                purchase_book_id: DEFAULT_BOOK_ID,
+                remark: None,
+            })
+            .await?;
+        let apple = product_service
+            .create_product(CreateProductDto {
+                name: "苹果".to_string(),
+                category_id: Some(fruit.id),
+                category: None,
+                unit: "斤".to_string(),
+                default_sell_price: None,
+                default_purchase_price: None,
+                min_stock: None,
+                reorder_quantity: None,
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+
+        // 苹果 60（水果）+ 未分类商品 40，实收 90：现金 30 + 微信 60
+        let order = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![
+                    make_item(apple.id, "苹果", Decimal::ONE, "斤", Decimal::new(60, 0)),
+                    make_item(999999, "杂项", Decimal::ONE, "件", Decimal::new(40, 0)),
+                ],
+                remark: None,
+                actual_amount: Some(Decimal::new(90, 0)),
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        let settled = service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: None,
+                actual_amount: None,
+                payments: Some(vec![
+                    SettlePaymentDto {
+                        channel: "Cash".to_string(),
+                        amount: Decimal::new(30, 0),
+                    },
+                    SettlePaymentDto {
+                        channel: "Wechat".to_string(),
+                        amount: Decimal::new(60, 0),
+                    },
+                ]),
+            })
+            .await?;
+        assert_eq!(settled.status, OrderStatus::Settled);
+        assert_eq!(settled.channel, AccountingChannel::Unknown);
+
+        // 每个品类按渠道各有一条主记录，各渠道记账净额等于该渠道收款
+        let records = accounting_record::Entity::find()
+            .filter(accounting_record::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        let main_count = records
+            .iter()
+            .filter(|r| r.accounting_type == AccountingType::Income)
+            .count();
+        assert_eq!(main_count, 4);
+        let channel_total = |channel: AccountingChannel| -> Decimal {
+            records
+                .iter()
+                .filter(|r| r.channel == channel)
+                .map(|r| r.amount)
+                .sum()
+        };
+        assert_eq!(channel_total(AccountingChannel::Cash), Decimal::new(30, 0));
+        assert_eq!(
+            channel_total(AccountingChannel::Wechat),
+            Decimal::new(60, 0)
+        );
+        let main_total: Decimal = records
+            .iter()
+            .filter(|r| r.accounting_type == AccountingType::Income)
+            .map(|r| r.amount)
+            .sum();
+        assert_eq!(main_total, Decimal::new(100, 0));
+        // 冲账记录与主记录渠道一致
+        for write_off in records.iter().filter(|r| r.write_off_id.is_some()) {
+            let main = records
+                .iter()
+                .find(|r| Some(r.id) == write_off.write_off_id)
+                .expect("冲账记录应关联主记录");
+            assert_eq!(main.channel, write_off.channel);
+        }
+
+        let balance = service.get_order_balance(order.id).await?;
+        assert_eq!(balance.payment_count, 2);
+        assert_eq!(balance.payment_channels.len(), 2);
+        assert_eq!(balance.payment_channels[0].channel, AccountingChannel::Cash);
+        assert_eq!(balance.payment_channels[0].amount, Decimal::new(30, 0));
+        assert_eq!(
+            balance.payment_channels[1].channel,
+            AccountingChannel::Wechat
+        );
+        assert_eq!(balance.payment_channels[1].amount, Decimal::new(60, 0));
+
+        // 按渠道筛选时组合支付订单按收付款记录匹配
+        let (orders, total) = service
+            .query_orders(QueryOrdersDto {
+                page: None,
+                page_size: None,
+                start_time: None,
+                end_time: None,
+                status: None,
+                min_amount: None,
+                max_amount: None,
+                channel: Some("Wechat".to_string()),
+                order_type: None,
+                customer_id: None,
+                sub_type: None,
+                order_no: None,
+                product_id: None,
+                keyword: None,
+                sort_by: None,
+                sort_order: None,
+            })
+            .await?;
+        assert_eq!(total, 1);
+        assert_eq!(orders[0].id, order.id);
+
+        // 组合支付订单退货须指定退款渠道
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(order.id))
+            .all(&db)
+            .await?;
+        let return_item = CreateOrderReturnItemDto {
+            order_item_id: items[0].id,
+            quantity: Decimal::ONE,
+        };
+        assert!(service
+            .create_return(CreateOrderReturnDto {
+                order_id: order.id,
+                items: vec![return_item],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await
+            .is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_settle_order_split_payments_validation() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let order = create_sales_order(&service, Decimal::new(100, 0)).await?;
+        let split = |cash: i64, wechat: i64| {
+            Some(vec![
+                SettlePaymentDto {
+                    channel: "Cash".to_string(),
+                    amount: Decimal::new(cash, 0),
+                },
+                SettlePaymentDto {
+                    channel: "Wechat".to_string(),
+                    amount: Decimal::new(wechat, 0),
+                },
+            ])
+        };
+
+        // 合计与实收金额不一致
+        assert!(service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: None,
+                actual_amount: None,
+                payments: split(30, 60),
+            })
+            .await
+            .is_err());
+        // 渠道与分渠道收付款同时填写
+        assert!(service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: split(40, 60),
+            })
+            .await
+            .is_err());
+        // 无效的渠道不能作为收付款渠道
+        assert!(service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Mixed".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await
+            .is_err());
+        // 金额必须大于 0
+        assert!(service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: None,
+                actual_amount: None,
+                payments: split(0, 100),
+            })
+            .await
+            .is_err());
+
+        // 部分收付款后，剩余金额按渠道拆分结清
+        service
+            .add_payment(AddOrderPaymentDto {
+                order_id: order.id,
+                amount: Decimal::new(20, 0),
+                channel: "Cash".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await?;
+        let settled = service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: None,
+                actual_amount: None,
+                payments: split(30, 50),
+            })
+            .await?;
+        assert_eq!(settled.status, OrderStatus::Settled);
+        assert_eq!(settled.channel, AccountingChannel::Unknown);
+
+        let balance = service.get_order_balance(order.id).await?;
+        assert_eq!(balance.outstanding_amount, Decimal::ZERO);
+        assert_eq!(balance.payment_channels[0].amount, Decimal::new(50, 0));
+        assert_eq!(balance.payment_channels[1].amount, Decimal::new(50, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 毛利测算测试 ====================
+
+/// 辅助函数：创建带参考采购价的商品
+async fn create_costed_product(
+    service: &ProductService,
+    name: &str,
+    purchase_price: Decimal,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let product = service
+        .create_product(CreateProductDto {
+            name: name.to_string(),
+            category_id: None,
+            category: None,
+            unit: "斤".to_string(),
+            default_sell_price: None,
+            default_purchase_price: Some(purchase_price),
+            min_stock: None,
+            reorder_quantity: None,
+            sku: None,
+            keywords: None,
+            remark: None,
+        })
+        .await?;
+    Ok(product.id)
+}
+
+#[serial]
+#[tokio::test]
+async fn test_order_margin_warnings() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let products = ProductService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+
+        let apple = create_costed_product(&products, "苹果", Decimal::new(600, 2)).await?;
+        let pear = create_costed_product(&products, "梨", Decimal::new(500, 2)).await?;
+        assert!(settings
+            .update_margin_config(MarginConfig {
+                min_margin_rate: Some(Decimal::new(101, 0)),
+            })
+            .await
+            .is_err());
+        settings
+            .update_margin_config(MarginConfig {
+                min_margin_rate: Some(Decimal::new(20, 0)),
+            })
+            .await?;
+
+        // 苹果 10 × 7.00（成本 60，毛利率 14.29%），梨 4 × 4.00（成本 20），香蕉无成本
+        let order = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![
+                    make_item(
+                        apple,
+                        "苹果",
+                        Decimal::new(10, 0),
+                        "斤",
+                        Decimal::new(700, 2),
+                    ),
+                    make_item(pear, "梨", Decimal::new(4, 0), "斤", Decimal::new(400, 2)),
+                    make_item(999, "香蕉", Decimal::new(2, 0), "斤", Decimal::new(500, 2)),
+                ],
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        let margin = service.get_order_margin(order.id).await?.unwrap();
+        assert_eq!(margin.revenue, Decimal::new(9600, 2));
+        assert_eq!(margin.cost, Decimal::new(8000, 2));
+        assert_eq!(margin.gross_profit, Decimal::new(1600, 2));
+        assert!(!margin.cost_complete);
+        assert_eq!(
+            margin.lines[0].cost_source,
+            Some(CostSource::DefaultPurchasePrice)
+        );
+        assert_eq!(margin.lines[0].margin_rate, Some(Decimal::new(1429, 2)));
+        assert_eq!(margin.lines[2].cost, None);
+        assert_eq!(margin.warnings.len(), 2);
+        assert_eq!(margin.warnings[0].kind, MarginWarningKind::BelowThreshold);
+        assert_eq!(margin.warnings[0].product_id, Some(apple));
+        assert_eq!(margin.warnings[1].kind, MarginWarningKind::BelowCost);
+        assert_eq!(margin.warnings[1].margin, Decimal::new(-400, 2));
+
+        // 整单优惠后实收 75.00，低于成本合计
+        let preview = service
+            .get_settle_preview(order.id, Some(Decimal::new(7500, 2)))
+            .await?;
+        let margin = preview.margin.unwrap();
+        assert_eq!(margin.gross_profit, Decimal::new(-500, 2));
+        assert_eq!(
+            margin.warnings.last().map(|w| w.kind),
+            Some(MarginWarningKind::OrderBelowCost)
+        );
+
+        // 采购订单不测算毛利
+        let purchase = service
+            .create_order(CreateOrderDto {
+                order_type: "Purchase".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![make_item(
+                    apple,
+                    "苹果",
+                    Decimal::new(1, 0),
+                    "斤",
+                    Decimal::new(600, 2),
+                )],
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+        assert!(service.get_order_margin(purchase.id).await?.is_none());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_order_margin_uses_stock_cost() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let products = ProductService::new(db.clone());
+        let apple = create_costed_product(&products, "苹果", Decimal::new(600, 2)).await?;
+
+        // 已结账采购：10 × 4.00 + 30 × 6.00，移动加权成本 5.50；待结账采购未入库，不计入
+        for (quantity, price, settle) in [(10, 400, true), (30, 600, true), (10, 100, false)] {
+            let purchase = service
+                .create_order(CreateOrderDto {
+                    order_type: "Purchase".to_string(),
+                    customer_id: None,
+                    customer_name: None,
+                    items: vec![make_item(
+                        apple,
+                        "苹果",
+                        Decimal::new(quantity, 0),
+                        "斤",
+                        Decimal::new(price, 2),
+                    )],
+                    remark: None,
+                    actual_amount: None,
+                    sub_type: None,
+                    due_date: None,
+                })
+                .await?;
+            if settle {
+                service
+                    .settle_order(SettleOrderDto {
+                        order_id: purchase.id,
+                        channel: Some("Cash".to_string()),
+                        actual_amount: None,
+                        payments: None,
+                    })
+                    .await?;
+            }
+        }
+
+        let order = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![make_item(
+                    apple,
+                    "苹果",
+                    Decimal::new(2, 0),
+                    "斤",
+                    Decimal::new(500, 2),
+                )],
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        let margin = service.get_order_margin(order.id).await?.unwrap();
+        let line = &margin.lines[0];
+        assert_eq!(line.unit_cost, Some(Decimal::new(550, 2)));
+        assert_eq!(line.cost_source, Some(CostSource::StockCost));
+        assert_eq!(line.margin, Some(Decimal::new(-100, 2)));
+        assert!(margin.cost_complete);
+        assert_eq!(margin.warnings[0].kind, MarginWarningKind::BelowCost);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_order_margin_nets_purchase_discounts_and_returns() {
+    run_in_transaction(|db| async move {
+        let service = OrderService::new(db.clone());
+        let products = ProductService::new(db.clone());
+        let apple = create_costed_product(&products, "苹果", Decimal::new(600, 2)).await?;
+
+        // 10 × 6.00 整单优惠后实付 50.00，净成本 5.00；10 × 9.00 全部退货，不计入成本
+        let mut purchases = Vec::new();
+        for (price, actual_amount) in [(600, Some(Decimal::new(50, 0))), (900, None)] {
+            let purchase = service
+                .create_order(CreateOrderDto {
+                    order_type: "Purchase".to_string(),
+                    customer_id: None,
+                    customer_name: None,
+                    items: vec![make_item(
+                        apple,
+                        "苹果",
+                        Decimal::new(10, 0),
+                        "斤",
+                        Decimal::new(price, 2),
+                    )],
+                    remark: None,
+                    actual_amount: None,
+                    sub_type: None,
+                    due_date: None,
+                })
+                .await?;
+            service
+                .settle_order(SettleOrderDto {
+                    order_id: purchase.id,
+                    channel: Some("Cash".to_string()),
+                    actual_amount,
+                    payments: None,
+                })
+                .await?;
+            purchases.push(purchase.id);
+        }
+        let (_, items) = service
+            .get_order_by_id(purchases[1])
+            .await?
+            .expect("订单应存在");
+        service
+            .create_return(CreateOrderReturnDto {
+                order_id: purchases[1],
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: items[0].id,
+                    quantity: Decimal::new(10, 0),
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+
+        let order = service
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id: None,
+                customer_name: None,
+                items: vec![make_item(
+                    apple,
+                    "苹果",
+                    Decimal::new(2, 0),
+                    "斤",
+                    Decimal::new(550, 2),
+                )],
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+
+        let margin = service.get_order_margin(order.id).await?.unwrap();
+        let line = &margin.lines[0];
+        assert_eq!(line.unit_cost, Some(Decimal::new(500, 2)));
+        assert_eq!(line.cost_source, Some(CostSource::StockCost));
+        assert_eq!(line.margin, Some(Decimal::new(100, 2)));
+        assert!(margin
+            .warnings
+            .iter()
+            .all(|w| w.kind != MarginWarningKind::BelowCost));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,307 @@
+use accounting_assistant_lib::services::order::dto::{CreateOrderDto, CreateOrderItemDto};
+use accounting_assistant_lib::services::printer::dto::PrintReceiptDto;
+use accounting_assistant_lib::services::printer::escpos::{
+    columns_for_paper, display_width, encode_gbk, wrap, EscPosBuilder,
+};
+use accounting_assistant_lib::services::receipt::dto::ReceiptKind;
+use accounting_assistant_lib::services::setting::dto::{PrinterConfig, ShopProfile};
+use accounting_assistant_lib::services::{OrderService, PrinterService, SettingService};
+use rust_decimal::Decimal;
+use serial_test::serial;
+use tokio::io::AsyncReadExt;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造销售订单（合计 80.00）
+fn make_sales_order() -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: "Sales".to_string(),
+        customer_id: None,
+        customer_name: None,
+        items: vec![
+            CreateOrderItemDto {
+                product_id: 1,
+                product_name: "苹果".to_string(),
+                quantity: Decimal::new(5, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(800, 2),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            },
+            CreateOrderItemDto {
+                product_id: 2,
+                product_name: "新疆阿克苏冰糖心红富士苹果特级".to_string(),
+                quantity: Decimal::new(4, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(1000, 2),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            },
+        ],
+        remark: None,
+        actual_amount: None,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+/// 辅助函数：判断字节流中是否包含指定片段
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// 辅助函数：按换行拆分字节流后查找包含指定文本的行
+fn find_line<'a>(bytes: &'a [u8], text: &str) -> Option<&'a [u8]> {
+    let needle = encode_gbk(text);
+    bytes.split(|b| *b == b'\n').find(|l| contains(l, &needle))
+}
+
+// ==================== ESC/POS 编码测试 ====================
+
+#[test]
+fn test_encode_gbk_and_width() {
+    // “苹果” 的 GBK 编码
+    assert_eq!(encode_gbk("苹果"), vec![0xC6, 0xBB, 0xB9, 0xFB]);
+    assert_eq!(encode_gbk("A1"), b"A1".to_vec());
+    // 无法编码的字符输出为 ?
+    assert_eq!(encode_gbk("苹😀"), vec![0xC6, 0xBB, b'?']);
+
+    assert_eq!(display_width("苹果x2"), 6);
+    assert_eq!(wrap("一二三四五", 4), vec!["一二", "三四", "五"]);
+    assert_eq!(wrap("", 4), vec![""]);
+
+    assert_eq!(columns_for_paper(58), 32);
+    assert_eq!(columns_for_paper(80), 48);
+}
+
+#[test]
+fn test_builder_qr_code_bytes() {
+    let mut builder = EscPosBuilder::new();
+    builder.qr_code("https://pay.example/abc", 4).cut();
+    let bytes = builder.into_bytes();
+
+    // 初始化 + 汉字模式
+    assert_eq!(&bytes[..4], &[0x1B, b'@', 0x1C, b'&']);
+    // 存储数据：长度 = 内容 23 字节 + 3
+    assert!(contains(
+        &bytes,
+        &[0x1D, b'(', b'k', 26, 0, 49, 80, 48, b'h', b't']
+    ));
+    // 打印二维码
+    assert!(contains(&bytes, &[0x1D, b'(', b'k', 3, 0, 49, 81, 48]));
+    assert!(bytes.ends_with(&[0x1D, b'V', 66, 0]));
+}
+
+// ==================== render_receipt 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_render_receipt_layout() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+        let service = PrinterService::new(db.clone());
+
+        settings
+            .update_shop_profile(ShopProfile {
+                shop_name: "鲜果铺".to_string(),
+                address: None,
+                phone: Some("0571-88888888".to_string()),
+                receipt_footer: Some("谢谢惠顾".to_string()),
+            })
+            .await?;
+        let order = orders.create_order(make_sales_order()).await?;
+
+        // 默认 58mm，每行 32 列
+        let bytes = service
+            .render_receipt(order.id, ReceiptKind::Receipt, None)
+            .await?;
+        assert_eq!(&bytes[..4], &[0x1B, b'@', 0x1C, b'&']);
+        assert!(contains(&bytes, &encode_gbk("鲜果铺")));
+        assert!(contains(&bytes, &encode_gbk("销售收据")));
+        assert!(contains(&bytes, &encode_gbk("谢谢惠顾")));
+        assert!(contains(&bytes, order.order_no.as_bytes()));
+        assert!(contains(&bytes, &encode_gbk("大写：捌拾元整")));
+        // 无二维码
+        assert!(!contains(&bytes, &[0x1D, b'(', b'k']));
+
+        // 名称 11 列、数量 6 列、单价 7 列、金额 8 列
+        let item_line = find_line(&bytes, "苹果").expect("缺少明细行");
+        assert_eq!(
+            item_line,
+            encode_gbk("苹果            5   8.00   40.00").as_slice()
+        );
+        let separator = "-".repeat(32);
+        assert!(find_line(&bytes, &separator).is_some_and(|l| l.len() == 32));
+        // 名称过长时单独占一行，数量金额另起一行
+        assert!(contains(
+            &bytes,
+            &encode_gbk("新疆阿克苏冰糖心红富士苹果特级")
+        ));
+        assert!(find_line(&bytes, "     4  10.00   40.00").is_some());
+
+        // 80mm 纸宽，每行 48 列，并附二维码
+        settings
+            .update_printer_config(PrinterConfig {
+                target: None,
+                paper_width: 80,
+            })
+            .await?;
+        let wide = service
+            .render_receipt(
+                order.id,
+                ReceiptKind::DeliveryNote,
+                Some("https://pay.example/abc"),
+            )
+            .await?;
+        assert!(contains(&wide, &encode_gbk("销售送货单")));
+        assert!(contains(&wide, &encode_gbk("收货人签字")));
+        assert!(find_line(&wide, &"-".repeat(48)).is_some_and(|l| l.len() == 48));
+        assert!(contains(
+            &wide,
+            &[0x1D, b'(', b'k', 26, 0, 49, 80, 48, b'h', b't']
+        ));
+
+        // 二维码内容过长
+        let long_qr = "x".repeat(701);
+        assert!(service
+            .render_receipt(order.id, ReceiptKind::Receipt, Some(&long_qr))
+            .await
+            .is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== print_receipt 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_print_receipt_to_device_and_tcp() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+        let service = PrinterService::new(db.clone());
+        let order = orders.create_order(make_sales_order()).await?;
+
+        // 未配置打印机
+        let err = service
+            .print_receipt(PrintReceiptDto {
+                order_id: order.id,
+                kind: None,
+                qr_content: None,
+                copies: None,
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("尚未配置"));
+
+        // 设备路径（以临时文件模拟）
+        let dir = tempfile::tempdir()?;
+        let device = dir.path().join("usb-lp0");
+        std::fs::write(&device, b"")?;
+        settings
+            .update_printer_config(PrinterConfig {
+                target: Some(device.to_string_lossy().to_string()),
+                paper_width: 58,
+            })
+            .await?;
+        let single = service
+            .render_receipt(order.id, ReceiptKind::Receipt, None)
+            .await?;
+        let result = service
+            .print_receipt(PrintReceiptDto {
+                order_id: order.id,
+                kind: Some("Receipt".to_string()),
+                qr_content: None,
+                copies: Some(2),
+            })
+            .await?;
+        assert_eq!(result.bytes_sent, single.len() * 2);
+        assert_eq!(std::fs::read(&device)?.len(), single.len() * 2);
+
+        // 份数超出范围
+        assert!(service
+            .print_receipt(PrintReceiptDto {
+                order_id: order.id,
+                kind: None,
+                qr_content: None,
+                copies: Some(6),
+            })
+            .await
+            .is_err());
+
+        // 网络打印机
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            socket.read_to_end(&mut received).await.unwrap();
+            received
+        });
+        settings
+            .update_printer_config(PrinterConfig {
+                target: Some(format!("tcp://{}", addr)),
+                paper_width: 58,
+            })
+            .await?;
+        let result = service
+            .print_receipt(PrintReceiptDto {
+                order_id: order.id,
+                kind: None,
+                qr_content: None,
+                copies: None,
+            })
+            .await?;
+        let received = server.await?;
+        assert_eq!(result.bytes_sent, received.len());
+        // 生成时间可能跨秒，仅比较长度与开头的初始化指令
+        assert_eq!(received.len(), single.len());
+        assert_eq!(&received[..4], &single[..4]);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== 打印机配置测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_update_printer_config() {
+    run_in_transaction(|db| async move {
+        let settings = SettingService::new(db.clone());
+
+        let default = settings.get_printer_config().await?;
+        assert_eq!(default, PrinterConfig::default());
+        assert_eq!(default.paper_width, 58);
+
+        assert!(settings
+            .update_printer_config(PrinterConfig {
+                target: None,
+                paper_width: 76,
+            })
+            .await
+            .is_err());
+
+        let saved = settings
+            .update_printer_config(PrinterConfig {
+                target: Some(" /dev/usb/lp0 ".to_string()),
+                paper_width: 80,
+            })
+            .await?;
+        assert_eq!(saved.target, Some("/dev/usb/lp0".to_string()));
+        assert_eq!(saved.paper_width, 80);
+        assert_eq!(settings.get_printer_config().await?, saved);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
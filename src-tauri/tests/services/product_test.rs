@@ -22,6 +22,8 @@ async fn test_create_product_success() {
             unit: "斤".to_string(),
             default_sell_price: Some(Decimal::new(800, 2)),
             default_purchase_price: Some(Decimal::new(500, 2)),
+            min_stock: None,
+            reorder_quantity: None,
             sku: Some("APL001".to_string()),
             keywords: Some("红富士;冰糖心".to_string()),
             remark: Some("当季水果".to_string()),
@@ -58,6 +60,8 @@ async fn test_create_product_minimal_fields() {
             unit: "个".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -91,6 +95,8 @@ async fn test_update_product_success() {
             unit: "个".to_string(),
             default_sell_price: Some(Decimal::new(1000, 2)),
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -105,6 +111,8 @@ async fn test_update_product_success() {
             unit: None,
             default_sell_price: Some(Some(Decimal::new(1200, 2))),
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: Some(Some("SKU001".to_string())),
             keywords: None,
             remark: None,
@@ -138,6 +146,8 @@ async fn test_update_product_not_found() {
             unit: None,
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -168,6 +178,8 @@ async fn test_delete_product_success() {
             unit: "个".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -233,6 +245,8 @@ async fn test_get_all_products_ordered_by_create_at_desc() {
                 unit: "个".to_string(),
                 default_sell_price: None,
                 default_purchase_price: None,
+                min_stock: None,
+                reorder_quantity: None,
                 sku: None,
                 keywords: None,
                 remark: None,
@@ -268,6 +282,8 @@ async fn test_get_product_by_id_found() {
             unit: "箱".to_string(),
             default_sell_price: Some(Decimal::new(2500, 2)),
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -317,6 +333,8 @@ async fn test_search_products_by_name() {
             unit: "斤".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -330,6 +348,8 @@ async fn test_search_products_by_name() {
             unit: "斤".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -360,6 +380,8 @@ async fn test_search_products_by_category() {
             unit: "斤".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -373,6 +395,8 @@ async fn test_search_products_by_category() {
             unit: "斤".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -403,6 +427,8 @@ async fn test_search_products_by_keywords() {
             unit: "个".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: Some("六头;六粒;35#".to_string()),
             remark: None,
@@ -433,6 +459,8 @@ async fn test_search_products_no_match() {
             unit: "斤".to_string(),
             default_sell_price: None,
             default_purchase_price: None,
+            min_stock: None,
+            reorder_quantity: None,
             sku: None,
             keywords: None,
             remark: None,
@@ -0,0 +1,478 @@
+use accounting_assistant_lib::services::accounting_book::DEFAULT_BOOK_ID;
+use accounting_assistant_lib::services::category::dto::CreateCategoryDto;
+use accounting_assistant_lib::services::costing::dto::QueryCogsDto;
+use accounting_assistant_lib::services::customer::dto::CreateCustomerDto;
+use accounting_assistant_lib::services::order::dto::{
+    AddOrderPaymentDto, CreateOrderDto, CreateOrderItemDto, CreateOrderReturnDto,
+    CreateOrderReturnItemDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::product::dto::CreateProductDto;
+use accounting_assistant_lib::services::profit::dto::ProfitReportDto;
+use accounting_assistant_lib::services::{
+    CategoryService, CostingService, CustomerService, OrderService, ProductService, ProfitService,
+};
+use chrono::{Duration, Local};
+use rust_decimal::Decimal;
+use sea_orm::DatabaseConnection;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造一条商品明细 DTO
+fn make_item(product_id: i64, name: &str, quantity: i64, unit_price: i64) -> CreateOrderItemDto {
+    CreateOrderItemDto {
+        product_id,
+        product_name: name.to_string(),
+        quantity: Decimal::new(quantity, 0),
+        unit: "斤".to_string(),
+        unit_price: Decimal::new(unit_price, 2),
+        discount_amount: None,
+        discount_rate: None,
+        remark: None,
+    }
+}
+
+/// 辅助函数：构造查询 DTO（今天）
+fn make_query() -> ProfitReportDto {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    ProfitReportDto {
+        start_date: today.clone(),
+        end_date: today,
+        customer_id: None,
+        category_id: None,
+    }
+}
+
+/// 测试数据 ID
+struct Fixture {
+    fruit_category_id: i64,
+    customer_id: i64,
+    apple_id: i64,
+    pear_id: i64,
+}
+
+/// 辅助函数：准备测试数据
+///
+/// - 订单 1（张三）：苹果 10 × 8.00 + 梨 10 × 5.00，实收 117.00（九折），退货梨 2 斤
+/// - 订单 2（散客）：苹果 5 × 7.00 + 无成本商品 1 × 10.00
+/// - 订单 3：待结账销售订单，不计入
+async fn setup(db: &DatabaseConnection) -> Result<Fixture, Box<dyn std::error::Error>> {
+    let orders = OrderService::new(db.clone());
+    let products = ProductService::new(db.clone());
+
+    let fruit = CategoryService::new(db.clone())
+        .create_category(CreateCategoryDto {
+            name: "水果".to_string(),
+            sell_book_id: DEFAULT_BOOK_ID,
+            purchase_book_id: DEFAULT_BOOK_ID,
+            remark: None,
+        })
+        .await?;
+    let customer = CustomerService::new(db.clone())
+        .create_customer(CreateCustomerDto {
+            name: "张三".to_string(),
+            category: "Retailer".to_string(),
+            phone: "13800000000".to_string(),
+            wechat: None,
+            address: None,
+            bank_account: None,
+            remark: None,
+        })
+        .await?;
+
+    let mut product_ids = Vec::new();
+    for (name, category_id, purchase_price) in [("苹果", Some(fruit.id), 600), ("梨", None, 300)]
+    {
+        let product = products
+            .create_product(CreateProductDto {
+                name: name.to_string(),
+                category_id,
+                category: None,
+                unit: "斤".to_string(),
+                default_sell_price: None,
+                default_purchase_price: Some(Decimal::new(purchase_price, 2)),
+                min_stock: None,
+                reorder_quantity: None,
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+        product_ids.push(product.id);
+    }
+    let (apple_id, pear_id) = (product_ids[0], product_ids[1]);
+
+    let order_inputs = [
+        (
+            Some(customer.id),
+            vec![
+                make_item(apple_id, "苹果", 10, 800),
+                make_item(pear_id, "梨", 10, 500),
+            ],
+            Some(Decimal::new(11700, 2)),
+        ),
+        (
+            None,
+            vec![
+                make_item(apple_id, "苹果", 5, 700),
+                make_item(999, "香蕉", 1, 1000),
+            ],
+            None,
+        ),
+    ];
+    let mut settled = Vec::new();
+    for (customer_id, items, actual_amount) in order_inputs {
+        let order = orders
+            .create_order(CreateOrderDto {
+                order_type: "Sales".to_string(),
+                customer_id,
+                customer_name: None,
+                items,
+                remark: None,
+                actual_amount: None,
+                sub_type: None,
+                due_date: None,
+            })
+            .await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Cash".to_string()),
+                actual_amount,
+                payments: None,
+            })
+            .await?;
+        settled.push(order.id);
+    }
+
+    let (_, items) = orders
+        .get_order_by_id(settled[0])
+        .await?
+        .expect("订单应存在");
+    orders
+        .create_return(CreateOrderReturnDto {
+            order_id: settled[0],
+            items: vec![CreateOrderReturnItemDto {
+                order_item_id: items[1].id,
+                quantity: Decimal::new(2, 0),
+            }],
+            refund_amount: None,
+            channel: None,
+            reason: None,
+        })
+        .await?;
+
+    orders
+        .create_order(CreateOrderDto {
+            order_type: "Sales".to_string(),
+            customer_id: None,
+            customer_name: None,
+            items: vec![make_item(apple_id, "苹果", 100, 100)],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await?;
+
+    Ok(Fixture {
+        fruit_category_id: fruit.id,
+        customer_id: customer.id,
+        apple_id,
+        pear_id,
+    })
+}
+
+// ==================== get_profit_report 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_profit_report_breakdowns() {
+    run_in_transaction(|db| async move {
+        let fixture = setup(&db).await?;
+        let report = ProfitService::new(db.clone())
+            .get_profit_report(make_query())
+            .await?;
+
+        // 苹果 72 + 35，梨 (50 × 8/10 × 0.9) = 36，香蕉 10（无成本）
+        assert_eq!(report.totals.revenue, Decimal::new(15300, 2));
+        assert_eq!(report.totals.cost, Decimal::new(11400, 2));
+        assert_eq!(report.totals.gross_profit, Decimal::new(3900, 2));
+        assert_eq!(report.totals.uncosted_revenue, Decimal::new(1000, 2));
+        assert_eq!(report.totals.margin_rate, Some(Decimal::new(2549, 2)));
+
+        assert_eq!(report.orders.len(), 2);
+        assert_eq!(report.orders[0].metrics.revenue, Decimal::new(10800, 2));
+        assert_eq!(report.orders[0].metrics.gross_profit, Decimal::new(2400, 2));
+
+        assert_eq!(report.products.len(), 3);
+        assert_eq!(report.products[0].product_id, fixture.apple_id);
+        assert_eq!(report.products[0].quantity, Decimal::new(15, 0));
+        assert_eq!(
+            report.products[0].metrics.gross_profit,
+            Decimal::new(1700, 2)
+        );
+        assert_eq!(report.products[1].product_id, fixture.pear_id);
+        assert_eq!(report.products[1].quantity, Decimal::new(8, 0));
+        assert_eq!(report.products[1].metrics.cost, Decimal::new(2400, 2));
+
+        // 梨未设置品类、香蕉商品不存在，均归入"未分类"
+        assert_eq!(report.categories.len(), 2);
+        assert_eq!(report.categories[0].category_name, "未分类");
+        assert_eq!(report.categories[0].metrics.revenue, Decimal::new(4600, 2));
+        assert_eq!(
+            report.categories[1].category_id,
+            Some(fixture.fruit_category_id)
+        );
+        assert_eq!(
+            report.categories[1].metrics.gross_profit,
+            Decimal::new(1700, 2)
+        );
+
+        assert_eq!(report.customers.len(), 2);
+        assert_eq!(report.customers[0].customer_id, Some(fixture.customer_id));
+        assert_eq!(report.customers[0].order_count, 1);
+        assert_eq!(report.customers[1].customer_name, "散客");
+        assert_eq!(
+            report.customers[1].metrics.gross_profit,
+            Decimal::new(1500, 2)
+        );
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_profit_report_filters() {
+    run_in_transaction(|db| async move {
+        let fixture = setup(&db).await?;
+        let service = ProfitService::new(db.clone());
+
+        let report = service
+            .get_profit_report(ProfitReportDto {
+                category_id: Some(fixture.fruit_category_id),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(report.orders.len(), 2);
+        assert_eq!(report.products.len(), 1);
+        assert_eq!(report.totals.revenue, Decimal::new(10700, 2));
+
+        let report = service
+            .get_profit_report(ProfitReportDto {
+                customer_id: Some(fixture.customer_id),
+                ..make_query()
+            })
+            .await?;
+        assert_eq!(report.orders.len(), 1);
+        assert_eq!(report.totals.gross_profit, Decimal::new(2400, 2));
+
+        let yesterday = (Local::now().date_naive() - Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        let report = service
+            .get_profit_report(ProfitReportDto {
+                start_date: yesterday.clone(),
+                end_date: yesterday,
+                customer_id: None,
+                category_id: None,
+            })
+            .await?;
+        assert!(report.orders.is_empty());
+        assert_eq!(report.totals.revenue, Decimal::ZERO);
+        assert_eq!(report.totals.margin_rate, None);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_profit_report_filters_by_settled_date() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let service = ProfitService::new(db.clone());
+        let yesterday = Local::now().date_naive() - Duration::days(1);
+
+        // 今天下单、昨天收清款项结账；另一张仅部分收款，尚未结账
+        let mut order_ids = Vec::new();
+        for (amount, paid_at) in [
+            (
+                Decimal::new(80, 0),
+                yesterday.and_hms_opt(10, 0, 0).unwrap(),
+            ),
+            (Decimal::new(30, 0), Local::now().naive_local()),
+        ] {
+            let order = orders
+                .create_order(CreateOrderDto {
+                    order_type: "Sales".to_string(),
+                    customer_id: None,
+                    customer_name: None,
+                    items: vec![make_item(999, "香蕉", 10, 800)],
+                    remark: None,
+                    actual_amount: None,
+                    sub_type: None,
+                    due_date: None,
+                })
+                .await?;
+            orders
+                .add_payment(AddOrderPaymentDto {
+                    order_id: order.id,
+                    amount,
+                    channel: "Cash".to_string(),
+                    paid_at: Some(paid_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    remark: None,
+                })
+                .await?;
+            order_ids.push(order.id);
+        }
+
+        let report = service.get_profit_report(make_query()).await?;
+        assert!(report.orders.is_empty());
+
+        let date = yesterday.format("%Y-%m-%d").to_string();
+        let report = service
+            .get_profit_report(ProfitReportDto {
+                start_date: date.clone(),
+                end_date: date,
+                customer_id: None,
+                category_id: None,
+            })
+            .await?;
+        assert_eq!(report.orders.len(), 1);
+        assert_eq!(report.orders[0].order_id, order_ids[0]);
+        assert_eq!(
+            report.orders[0].date,
+            yesterday.and_hms_opt(10, 0, 0).unwrap()
+        );
+        assert_eq!(report.totals.revenue, Decimal::new(8000, 2));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_profit_report_cost_matches_cogs() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let orange = ProductService::new(db.clone())
+            .create_product(CreateProductDto {
+                name: "橙子".to_string(),
+                category_id: None,
+                category: None,
+                unit: "斤".to_string(),
+                default_sell_price: None,
+                default_purchase_price: Some(Decimal::new(100, 2)),
+                min_stock: None,
+                reorder_quantity: None,
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+
+        // 采购 10 × 4.00 + 10 × 6.00，销售 4 斤按移动加权成本 5.00 出库，之后再按 9.00 采购
+        let mut sales_id = 0;
+        for (order_type, quantity, unit_price) in [
+            ("Purchase", 10, 400),
+            ("Purchase", 10, 600),
+            ("Sales", 4, 800),
+            ("Purchase", 10, 900),
+        ] {
+            let order = orders
+                .create_order(CreateOrderDto {
+                    order_type: order_type.to_string(),
+                    customer_id: None,
+                    customer_name: None,
+                    items: vec![make_item(orange.id, "橙子", quantity, unit_price)],
+                    remark: None,
+                    actual_amount: None,
+                    sub_type: None,
+                    due_date: None,
+                })
+                .await?;
+            orders
+                .settle_order(SettleOrderDto {
+                    order_id: order.id,
+                    channel: Some("Cash".to_string()),
+                    actual_amount: None,
+                    payments: None,
+                })
+                .await?;
+            if order_type == "Sales" {
+                sales_id = order.id;
+            }
+        }
+        let (_, items) = orders.get_order_by_id(sales_id).await?.expect("订单应存在");
+        orders
+            .create_return(CreateOrderReturnDto {
+                order_id: sales_id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: items[0].id,
+                    quantity: Decimal::ONE,
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+
+        let report = ProfitService::new(db.clone())
+            .get_profit_report(make_query())
+            .await?;
+        let cogs = CostingService::new(db.clone())
+            .get_cogs(QueryCogsDto {
+                start_date: Some(make_query().start_date),
+                end_date: Some(make_query().end_date),
+                order_id: Some(sales_id),
+            })
+            .await?;
+
+        // 净销售 3 斤 × 5.00，不受之后采购成本变化影响
+        assert_eq!(report.totals.cost, Decimal::new(1500, 2));
+        assert_eq!(report.totals.cost, cogs.total_cost);
+        assert_eq!(report.totals.gross_profit, Decimal::new(900, 2));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_profit_report_invalid_input() {
+    run_in_transaction(|db| async move {
+        let service = ProfitService::new(db.clone());
+
+        let result = service
+            .get_profit_report(ProfitReportDto {
+                start_date: "2024-02-01".to_string(),
+                end_date: "2024-01-01".to_string(),
+                customer_id: None,
+                category_id: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "结束日期不能早于开始日期");
+
+        let result = service
+            .get_profit_report(ProfitReportDto {
+                category_id: Some(99999),
+                ..make_query()
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "品类不存在");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
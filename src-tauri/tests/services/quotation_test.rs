@@ -0,0 +1,235 @@
+use accounting_assistant_lib::entity::{order_item, quotation};
+use accounting_assistant_lib::enums::{OrderStatus, OrderSubType, OrderType, QuotationStatus};
+use accounting_assistant_lib::services::order::dto::CreateOrderItemDto;
+use accounting_assistant_lib::services::quotation::dto::{
+    ConvertQuotationDto, CreateQuotationDto, UpdateQuotationDto,
+};
+use accounting_assistant_lib::services::QuotationService;
+use chrono::{Duration, Local};
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造一条报价明细 DTO
+fn make_item(
+    product_id: i64,
+    name: &str,
+    quantity: i64,
+    unit_price: Decimal,
+) -> CreateOrderItemDto {
+    CreateOrderItemDto {
+        product_id,
+        product_name: name.to_string(),
+        quantity: Decimal::new(quantity, 0),
+        unit: "箱".to_string(),
+        unit_price,
+        discount_amount: None,
+        discount_rate: None,
+        remark: None,
+    }
+}
+
+/// 辅助函数：构造报价单 DTO（有效期默认 7 天后）
+fn make_quotation(items: Vec<CreateOrderItemDto>) -> CreateQuotationDto {
+    CreateQuotationDto {
+        customer_id: Some(1),
+        customer_name: Some("批发客户甲".to_string()),
+        items,
+        valid_until: (Local::now() + Duration::days(7))
+            .format("%Y-%m-%d")
+            .to_string(),
+        remark: Some("春季报价".to_string()),
+    }
+}
+
+// ==================== create_quotation 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_create_quotation_success() {
+    run_in_transaction(|db| async move {
+        let service = QuotationService::new(db.clone());
+
+        let mut discounted = make_item(2, "香蕉", 5, Decimal::new(40, 0));
+        discounted.discount_rate = Some(Decimal::new(10, 0));
+        let detail = service
+            .create_quotation(make_quotation(vec![
+                make_item(1, "苹果", 10, Decimal::new(50, 0)),
+                discounted,
+            ]))
+            .await?;
+
+        assert!(detail.quotation.quotation_no.starts_with("BJ-"));
+        assert_eq!(detail.quotation.status, QuotationStatus::Draft);
+        assert_eq!(detail.quotation.order_id, None);
+        // total = 10*50 + (5*40 - 20) = 500 + 180 = 680
+        assert_eq!(detail.quotation.total_amount, Decimal::new(680, 0));
+        assert_eq!(detail.items.len(), 2);
+        assert_eq!(detail.items[1].discount_amount, Decimal::new(20, 0));
+
+        let sent = service.send_quotation(detail.quotation.id).await?;
+        assert_eq!(sent.status, QuotationStatus::Sent);
+        assert!(sent.sent_at.is_some());
+        assert!(service.send_quotation(detail.quotation.id).await.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_create_quotation_validation() {
+    run_in_transaction(|db| async move {
+        let service = QuotationService::new(db.clone());
+
+        // 明细为空
+        assert!(service
+            .create_quotation(make_quotation(vec![]))
+            .await
+            .is_err());
+
+        // 有效期早于今天
+        let mut dto = make_quotation(vec![make_item(1, "苹果", 1, Decimal::new(50, 0))]);
+        dto.valid_until = (Local::now() - Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        assert!(service.create_quotation(dto).await.is_err());
+
+        // 有效期格式无效
+        let mut dto = make_quotation(vec![make_item(1, "苹果", 1, Decimal::new(50, 0))]);
+        dto.valid_until = "下周五".to_string();
+        assert!(service.create_quotation(dto).await.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== convert_to_order 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_convert_quotation_to_order() {
+    run_in_transaction(|db| async move {
+        let service = QuotationService::new(db.clone());
+
+        let mut discounted = make_item(2, "香蕉", 5, Decimal::new(40, 0));
+        discounted.discount_amount = Some(Decimal::new(15, 0));
+        let detail = service
+            .create_quotation(make_quotation(vec![
+                make_item(1, "苹果", 10, Decimal::new(50, 0)),
+                discounted,
+            ]))
+            .await?;
+        service.send_quotation(detail.quotation.id).await?;
+
+        let order = service
+            .convert_to_order(ConvertQuotationDto {
+                quotation_id: detail.quotation.id,
+                actual_amount: None,
+                due_date: None,
+                remark: None,
+            })
+            .await?;
+
+        assert_eq!(order.order_type, OrderType::Sales);
+        assert_eq!(order.sub_type, OrderSubType::Wholesale);
+        assert_eq!(order.status, OrderStatus::Pending);
+        assert_eq!(order.customer_id, Some(1));
+        assert_eq!(order.customer_name, Some("批发客户甲".to_string()));
+        assert_eq!(order.remark, Some("春季报价".to_string()));
+        assert_eq!(order.quotation_id, Some(detail.quotation.id));
+        assert_eq!(order.total_amount, detail.quotation.total_amount);
+
+        let items = order_item::Entity::find()
+            .filter(order_item::Column::OrderId.eq(order.id))
+            .order_by_asc(order_item::Column::Id)
+            .all(&db)
+            .await?;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].discount_amount, Decimal::new(15, 0));
+        assert_eq!(items[1].subtotal, Decimal::new(185, 0));
+
+        // 报价单回写订单关联并标记为已接受
+        let accepted = service
+            .get_quotation_by_id(detail.quotation.id)
+            .await?
+            .unwrap()
+            .quotation;
+        assert_eq!(accepted.status, QuotationStatus::Accepted);
+        assert_eq!(accepted.order_id, Some(order.id));
+        assert!(accepted.accepted_at.is_some());
+
+        // 不可重复转换，也不可再编辑
+        let again = service
+            .convert_to_order(ConvertQuotationDto {
+                quotation_id: detail.quotation.id,
+                actual_amount: None,
+                due_date: None,
+                remark: None,
+            })
+            .await;
+        assert!(again.is_err());
+        let update = service
+            .update_quotation(UpdateQuotationDto {
+                quotation_id: detail.quotation.id,
+                items: None,
+                valid_until: None,
+                remark: Some("改价".to_string()),
+            })
+            .await;
+        assert!(update.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_expired_quotation_cannot_convert() {
+    run_in_transaction(|db| async move {
+        let service = QuotationService::new(db.clone());
+
+        let detail = service
+            .create_quotation(make_quotation(vec![make_item(
+                1,
+                "苹果",
+                10,
+                Decimal::new(50, 0),
+            )]))
+            .await?;
+
+        // 将有效期调整到昨天
+        let mut active: quotation::ActiveModel = detail.quotation.clone().into();
+        active.valid_until = Set(Local::now().naive_local() - Duration::days(1));
+        active.update(&db).await?;
+
+        let quotations = service.get_quotations_by_customer_id(1).await?;
+        let expired = quotations
+            .iter()
+            .find(|q| q.id == detail.quotation.id)
+            .unwrap();
+        assert_eq!(expired.status, QuotationStatus::Expired);
+
+        let result = service
+            .convert_to_order(ConvertQuotationDto {
+                quotation_id: detail.quotation.id,
+                actual_amount: None,
+                due_date: None,
+                remark: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,270 @@
+use accounting_assistant_lib::entity::attachment;
+use accounting_assistant_lib::services::customer::dto::CreateCustomerDto;
+use accounting_assistant_lib::services::document::{uppercase_amount, DocumentFormat};
+use accounting_assistant_lib::services::order::dto::{
+    CreateOrderDto, CreateOrderItemDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::receipt::dto::{ExportReceiptDto, ReceiptKind};
+use accounting_assistant_lib::services::setting::dto::ShopProfile;
+use accounting_assistant_lib::services::{
+    CustomerService, OrderService, ReceiptService, SettingService,
+};
+use rust_decimal::Decimal;
+use sea_orm::EntityTrait;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：构造带整单优惠的销售订单（合计 123.40，实收 120.00）
+fn make_sales_order(customer_id: Option<i64>) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: "Sales".to_string(),
+        customer_id,
+        customer_name: None,
+        items: vec![
+            CreateOrderItemDto {
+                product_id: 1,
+                product_name: "苹果<红富士>".to_string(),
+                quantity: Decimal::new(10, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(800, 2),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            },
+            CreateOrderItemDto {
+                product_id: 2,
+                product_name: "香蕉".to_string(),
+                quantity: Decimal::new(12, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(370, 2),
+                discount_amount: Some(Decimal::ONE),
+                discount_rate: None,
+                remark: None,
+            },
+        ],
+        remark: Some("上午送达".to_string()),
+        actual_amount: Some(Decimal::new(120, 0)),
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+// ==================== 大写金额测试 ====================
+
+#[test]
+fn test_uppercase_amount() {
+    assert_eq!(uppercase_amount(Decimal::ZERO), "零元整");
+    assert_eq!(uppercase_amount(Decimal::new(10, 0)), "壹拾元整");
+    assert_eq!(
+        uppercase_amount(Decimal::new(123450, 2)),
+        "壹仟贰佰叁拾肆元伍角整"
+    );
+    assert_eq!(uppercase_amount(Decimal::new(10005, 2)), "壹佰元零伍分");
+    assert_eq!(uppercase_amount(Decimal::new(105000, 0)), "壹拾万伍仟元整");
+    assert_eq!(uppercase_amount(Decimal::new(10500, 0)), "壹万零伍佰元整");
+    assert_eq!(uppercase_amount(Decimal::new(100000001, 0)), "壹亿零壹元整");
+    assert_eq!(uppercase_amount(Decimal::new(35, 2)), "叁角伍分");
+    assert_eq!(
+        uppercase_amount(Decimal::new(-1201, 0)),
+        "负壹仟贰佰零壹元整"
+    );
+    // 按分四舍五入
+    assert_eq!(uppercase_amount(Decimal::new(19999, 3)), "贰拾元整");
+}
+
+// ==================== 店铺信息测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_update_shop_profile() {
+    run_in_transaction(|db| async move {
+        let service = SettingService::new(db.clone());
+
+        let empty = service.get_shop_profile().await?;
+        assert_eq!(empty, ShopProfile::default());
+
+        let saved = service
+            .update_shop_profile(ShopProfile {
+                shop_name: " 鲜果铺 ".to_string(),
+                address: Some("幸福路 8 号".to_string()),
+                phone: Some("0571-88888888".to_string()),
+                receipt_footer: None,
+            })
+            .await?;
+        assert_eq!(saved.shop_name, "鲜果铺");
+        assert_eq!(saved.address, Some("幸福路 8 号".to_string()));
+        assert_eq!(saved.receipt_footer, None);
+
+        // 清空地址
+        let cleared = service
+            .update_shop_profile(ShopProfile {
+                address: Some("  ".to_string()),
+                ..saved
+            })
+            .await?;
+        assert_eq!(cleared.address, None);
+        assert_eq!(service.get_shop_profile().await?, cleared);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== generate_receipt 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_generate_receipt() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = ReceiptService::new(db.clone());
+
+        let customer = customers
+            .create_customer(CreateCustomerDto {
+                name: "张记水果店".to_string(),
+                category: "Retailer".to_string(),
+                phone: "13900000000".to_string(),
+                wechat: None,
+                address: Some("解放路 1 号".to_string()),
+                bank_account: None,
+                remark: None,
+            })
+            .await?;
+        let order = orders
+            .create_order(make_sales_order(Some(customer.id)))
+            .await?;
+
+        let receipt = service
+            .generate_receipt(order.id, ReceiptKind::DeliveryNote)
+            .await?;
+        assert_eq!(receipt.order_no, order.order_no);
+        assert_eq!(receipt.customer_name, Some("张记水果店".to_string()));
+        assert_eq!(receipt.customer_address, Some("解放路 1 号".to_string()));
+        assert_eq!(receipt.items.len(), 2);
+        // 80 + (44.4 - 1) = 123.4
+        assert_eq!(receipt.total_amount, Decimal::new(12340, 2));
+        assert_eq!(receipt.discount_amount, Decimal::new(340, 2));
+        assert_eq!(receipt.actual_amount_uppercase, "壹佰贰拾元整");
+        assert_eq!(receipt.outstanding_amount, Decimal::new(120, 0));
+
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Wechat".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+        let settled = service
+            .generate_receipt(order.id, ReceiptKind::Receipt)
+            .await?;
+        assert_eq!(settled.paid_amount, Decimal::new(120, 0));
+        assert_eq!(settled.outstanding_amount, Decimal::ZERO);
+
+        // 已取消订单不可出票
+        let cancelled = orders.create_order(make_sales_order(None)).await?;
+        orders.cancel_order(cancelled.id).await?;
+        assert!(service
+            .generate_receipt(cancelled.id, ReceiptKind::Receipt)
+            .await
+            .is_err());
+        assert!(service
+            .generate_receipt(999999, ReceiptKind::Receipt)
+            .await
+            .is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== export_receipt 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_export_receipt_html_pdf_and_attachment() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let settings = SettingService::new(db.clone());
+        let service = ReceiptService::new(db.clone());
+
+        settings
+            .update_shop_profile(ShopProfile {
+                shop_name: "鲜果铺".to_string(),
+                address: None,
+                phone: Some("0571-88888888".to_string()),
+                receipt_footer: Some("货物当面点清".to_string()),
+            })
+            .await?;
+        let order = orders.create_order(make_sales_order(None)).await?;
+        let dir = tempfile::tempdir()?;
+
+        let html = service
+            .export_receipt(
+                ExportReceiptDto {
+                    order_id: order.id,
+                    kind: Some("DeliveryNote".to_string()),
+                    format: Some("Html".to_string()),
+                    save_as_attachment: None,
+                },
+                dir.path(),
+            )
+            .await?;
+        assert_eq!(html.format, DocumentFormat::Html);
+        assert!(html.attachment_id.is_none());
+        assert!(html.file_path.ends_with(".html"));
+        let content = std::fs::read_to_string(&html.file_path)?;
+        assert!(content.contains("鲜果铺"));
+        assert!(content.contains("销售送货单"));
+        assert!(content.contains("壹佰贰拾元整"));
+        assert!(content.contains("货物当面点清"));
+        assert!(content.contains("收货人签字"));
+        assert!(content.contains("苹果&lt;红富士&gt;"));
+
+        let pdf = service
+            .export_receipt(
+                ExportReceiptDto {
+                    order_id: order.id,
+                    kind: None,
+                    format: None,
+                    save_as_attachment: Some(true),
+                },
+                dir.path(),
+            )
+            .await?;
+        assert_eq!(pdf.format, DocumentFormat::Pdf);
+        let bytes = std::fs::read(&pdf.file_path)?;
+        assert!(bytes.starts_with(b"%PDF-"));
+
+        let attachment_id = pdf.attachment_id.expect("应登记为订单附件");
+        let attachment = attachment::Entity::find_by_id(attachment_id)
+            .one(&db)
+            .await?
+            .unwrap();
+        assert_eq!(attachment.master_id, order.id);
+        assert_eq!(attachment.path, pdf.file_path);
+        assert_eq!(attachment.file_suffix, "pdf");
+        assert_eq!(attachment.file_size, bytes.len().to_string());
+
+        let invalid = service
+            .export_receipt(
+                ExportReceiptDto {
+                    order_id: order.id,
+                    kind: Some("Invoice".to_string()),
+                    format: None,
+                    save_as_attachment: None,
+                },
+                dir.path(),
+            )
+            .await;
+        assert!(invalid.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
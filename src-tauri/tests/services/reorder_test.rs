@@ -0,0 +1,312 @@
+use accounting_assistant_lib::enums::{OrderStatus, OrderType};
+use accounting_assistant_lib::services::order::dto::{
+    CreateOrderDto, CreateOrderItemDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::product::dto::{CreateProductDto, UpdateProductDto};
+use accounting_assistant_lib::services::reorder::dto::CreateReorderOrdersDto;
+use accounting_assistant_lib::services::{OrderService, ProductService, ReorderService};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建设置了最低库存的商品（单位为斤）
+async fn create_product(
+    service: &ProductService,
+    name: &str,
+    min_stock: i64,
+    reorder_quantity: Option<i64>,
+    purchase_price: Option<Decimal>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let product = service
+        .create_product(CreateProductDto {
+            name: name.to_string(),
+            category_id: None,
+            category: None,
+            unit: "斤".to_string(),
+            default_sell_price: None,
+            default_purchase_price: purchase_price,
+            min_stock: Some(Decimal::new(min_stock, 0)),
+            reorder_quantity: reorder_quantity.map(|q| Decimal::new(q, 0)),
+            sku: None,
+            keywords: None,
+            remark: None,
+        })
+        .await?;
+    Ok(product.id)
+}
+
+/// 辅助函数：创建单明细订单，`settle` 为 true 时以现金结账
+async fn create_order(
+    service: &OrderService,
+    order_type: &str,
+    supplier: Option<(i64, &str)>,
+    product_id: i64,
+    quantity: i64,
+    unit_price: Decimal,
+    settle: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let order = service
+        .create_order(CreateOrderDto {
+            order_type: order_type.to_string(),
+            customer_id: supplier.map(|(id, _)| id),
+            customer_name: supplier.map(|(_, name)| name.to_string()),
+            items: vec![CreateOrderItemDto {
+                product_id,
+                product_name: "商品".to_string(),
+                quantity: Decimal::new(quantity, 0),
+                unit: "斤".to_string(),
+                unit_price,
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            }],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await?;
+    if settle {
+        service
+            .settle_order(SettleOrderDto {
+                order_id: order.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// 辅助函数：准备低库存数据，返回（苹果 ID，梨 ID）
+///
+/// 苹果：甲供应商采购两次、乙供应商采购一次（最近单价 6），售出 1，结存 8，最低库存 10，补货数量 20；
+/// 梨：无已结账采购，在途 2，最低库存 5，参考采购价 3；香蕉：结存 3，不低于最低库存 1
+async fn setup_low_stock(
+    db: &sea_orm::DatabaseConnection,
+) -> Result<(i64, i64), Box<dyn std::error::Error>> {
+    let orders = OrderService::new(db.clone());
+    let products = ProductService::new(db.clone());
+
+    let apple = create_product(&products, "苹果", 10, Some(20), None).await?;
+    let pear = create_product(&products, "梨", 5, None, Some(Decimal::new(3, 0))).await?;
+    let banana = create_product(&products, "香蕉", 1, None, None).await?;
+
+    let first = Some((9001, "甲供应商"));
+    let second = Some((9002, "乙供应商"));
+    create_order(
+        &orders,
+        "Purchase",
+        first,
+        apple,
+        4,
+        Decimal::new(5, 0),
+        true,
+    )
+    .await?;
+    create_order(
+        &orders,
+        "Purchase",
+        first,
+        apple,
+        3,
+        Decimal::new(5, 0),
+        true,
+    )
+    .await?;
+    create_order(
+        &orders,
+        "Purchase",
+        second,
+        apple,
+        2,
+        Decimal::new(6, 0),
+        true,
+    )
+    .await?;
+    create_order(&orders, "Sales", None, apple, 1, Decimal::new(8, 0), true).await?;
+    create_order(
+        &orders,
+        "Purchase",
+        second,
+        banana,
+        3,
+        Decimal::new(2, 0),
+        true,
+    )
+    .await?;
+    // 待结账的采购订单不计入库存，计入在途数量
+    create_order(
+        &orders,
+        "Purchase",
+        None,
+        pear,
+        2,
+        Decimal::new(3, 0),
+        false,
+    )
+    .await?;
+
+    Ok((apple, pear))
+}
+
+// ==================== 低库存与补货建议测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_low_stock_products() {
+    run_in_transaction(|db| async move {
+        let (apple, pear) = setup_low_stock(&db).await?;
+        let service = ReorderService::new(db.clone());
+
+        let low = service.get_low_stock_products().await?;
+        assert_eq!(low.len(), 2);
+
+        assert_eq!(low[0].product_id, apple);
+        assert_eq!(low[0].on_hand, Decimal::new(8, 0));
+        assert_eq!(low[0].on_order, Decimal::ZERO);
+        assert_eq!(low[0].shortfall, Decimal::new(2, 0));
+        assert_eq!(low[0].suggested_quantity, Decimal::new(20, 0));
+        assert_eq!(low[0].unit_price, Some(Decimal::new(6, 0)));
+        assert_eq!(low[0].supplier_id, Some(9001));
+        assert_eq!(low[0].supplier_name.as_deref(), Some("甲供应商"));
+
+        assert_eq!(low[1].product_id, pear);
+        assert_eq!(low[1].on_hand, Decimal::ZERO);
+        assert_eq!(low[1].on_order, Decimal::new(2, 0));
+        assert_eq!(low[1].shortfall, Decimal::new(3, 0));
+        assert_eq!(low[1].suggested_quantity, Decimal::new(3, 0));
+        assert_eq!(low[1].unit_price, Some(Decimal::new(3, 0)));
+        assert_eq!(low[1].supplier_id, None);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_create_reorder_orders() {
+    run_in_transaction(|db| async move {
+        let (apple, pear) = setup_low_stock(&db).await?;
+        let service = ReorderService::new(db.clone());
+
+        let suggestions = service.get_reorder_suggestions().await?;
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].supplier_id, Some(9001));
+        assert_eq!(suggestions[0].amount, Decimal::new(120, 0));
+        assert_eq!(suggestions[1].supplier_id, None);
+        assert_eq!(suggestions[1].items[0].product_id, pear);
+
+        let orders = service
+            .create_reorder_orders(CreateReorderOrdersDto {
+                product_ids: Some(vec![apple]),
+                remark: None,
+            })
+            .await?;
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_type, OrderType::Purchase);
+        assert_eq!(orders[0].status, OrderStatus::Pending);
+        assert_eq!(orders[0].customer_id, Some(9001));
+        assert_eq!(orders[0].customer_name.as_deref(), Some("甲供应商"));
+        assert_eq!(orders[0].total_amount, Decimal::new(120, 0));
+        assert_eq!(orders[0].remark.as_deref(), Some("补货建议生成"));
+
+        // 已生成的补货订单计入在途数量，再次生成时不重复补货
+        let low = service.get_low_stock_products().await?;
+        assert_eq!(low[0].on_order, Decimal::new(20, 0));
+        assert_eq!(low[0].shortfall, Decimal::ZERO);
+        assert_eq!(low[0].suggested_quantity, Decimal::ZERO);
+        let suggestions = service.get_reorder_suggestions().await?;
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].supplier_id, None);
+
+        let orders = service
+            .create_reorder_orders(CreateReorderOrdersDto {
+                product_ids: None,
+                remark: Some("周一补货".to_string()),
+            })
+            .await?;
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].customer_id, None);
+        assert_eq!(orders[0].total_amount, Decimal::new(9, 0));
+        assert_eq!(orders[0].remark.as_deref(), Some("周一补货"));
+
+        let result = service
+            .create_reorder_orders(CreateReorderOrdersDto {
+                product_ids: None,
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "没有需要补货的商品");
+
+        let result = service
+            .create_reorder_orders(CreateReorderOrdersDto {
+                product_ids: Some(vec![-1]),
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "没有需要补货的商品");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_reorder_settings_validation() {
+    run_in_transaction(|db| async move {
+        let products = ProductService::new(db.clone());
+
+        let result = create_product(&products, "苹果", -1, None, None).await;
+        assert_eq!(result.unwrap_err().to_string(), "最低库存不能为负数");
+
+        let apple = create_product(&products, "苹果", 0, None, None).await?;
+        let result = products
+            .update_product(UpdateProductDto {
+                id: apple,
+                category_id: None,
+                category: None,
+                name: None,
+                unit: None,
+                default_sell_price: None,
+                default_purchase_price: None,
+                min_stock: None,
+                reorder_quantity: Some(Some(Decimal::ZERO)),
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "补货数量必须大于 0");
+
+        // 清除最低库存后不再参与低库存提示
+        let updated = products
+            .update_product(UpdateProductDto {
+                id: apple,
+                category_id: None,
+                category: None,
+                name: None,
+                unit: None,
+                default_sell_price: None,
+                default_purchase_price: None,
+                min_stock: Some(None),
+                reorder_quantity: Some(Some(Decimal::new(5, 0))),
+                sku: None,
+                keywords: None,
+                remark: None,
+            })
+            .await?;
+        assert_eq!(updated.min_stock, None);
+        assert_eq!(updated.reorder_quantity, Some(Decimal::new(5, 0)));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,299 @@
+use accounting_assistant_lib::entity::customer;
+use accounting_assistant_lib::enums::AccountingChannel;
+use accounting_assistant_lib::services::customer::dto::CreateCustomerDto;
+use accounting_assistant_lib::services::document::DocumentFormat;
+use accounting_assistant_lib::services::ledger::dto::LedgerEntryType;
+use accounting_assistant_lib::services::order::dto::{
+    AddOrderPaymentDto, CreateOrderDto, CreateOrderItemDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::statement::dto::CustomerStatementDto;
+use accounting_assistant_lib::services::{CustomerService, OrderService, StatementService};
+use chrono::{Duration, Local};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建客户
+async fn create_customer(
+    service: &CustomerService,
+    name: &str,
+) -> Result<customer::Model, Box<dyn std::error::Error>> {
+    service
+        .create_customer(CreateCustomerDto {
+            name: name.to_string(),
+            category: "Retailer".to_string(),
+            phone: "13800000000".to_string(),
+            wechat: None,
+            address: None,
+            bank_account: None,
+            remark: None,
+        })
+        .await
+}
+
+/// 辅助函数：构造两条明细的销售订单
+fn make_sales_order(customer_id: i64, actual_amount: Option<Decimal>) -> CreateOrderDto {
+    CreateOrderDto {
+        order_type: "Sales".to_string(),
+        customer_id: Some(customer_id),
+        customer_name: None,
+        items: vec![
+            CreateOrderItemDto {
+                product_id: 1,
+                product_name: "苹果<红富士>".to_string(),
+                quantity: Decimal::new(25, 1),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(40, 0),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            },
+            CreateOrderItemDto {
+                product_id: 2,
+                product_name: "香蕉".to_string(),
+                quantity: Decimal::new(2, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(50, 0),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            },
+        ],
+        remark: None,
+        actual_amount,
+        sub_type: None,
+        due_date: None,
+    }
+}
+
+/// 辅助函数：构造对账单 DTO（今天为止的区间）
+fn make_query(customer_id: i64, days_back: i64, format: Option<&str>) -> CustomerStatementDto {
+    let today = Local::now().date_naive();
+    CustomerStatementDto {
+        customer_id,
+        start_date: (today - Duration::days(days_back))
+            .format("%Y-%m-%d")
+            .to_string(),
+        end_date: today.format("%Y-%m-%d").to_string(),
+        format: format.map(|s| s.to_string()),
+    }
+}
+
+// ==================== generate_statement 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_statement_orders_items_payments_and_balances() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = StatementService::new(db.clone());
+
+        let retailer = create_customer(&customers, "张三").await?;
+
+        // 总额 200，优惠 10，部分收款 90
+        let credit = orders
+            .create_order(make_sales_order(retailer.id, Some(Decimal::new(190, 0))))
+            .await?;
+        orders
+            .add_payment(AddOrderPaymentDto {
+                order_id: credit.id,
+                amount: Decimal::new(90, 0),
+                channel: "Wechat".to_string(),
+                paid_at: None,
+                remark: None,
+            })
+            .await?;
+
+        // 一次性结清
+        let settled = orders
+            .create_order(make_sales_order(retailer.id, None))
+            .await?;
+        orders
+            .settle_order(SettleOrderDto {
+                order_id: settled.id,
+                channel: Some("Cash".to_string()),
+                actual_amount: None,
+                payments: None,
+            })
+            .await?;
+
+        let statement = service
+            .generate_statement(&make_query(retailer.id, 7, None))
+            .await?;
+
+        assert_eq!(statement.customer_name, "张三");
+        assert_eq!(statement.opening_balance, Decimal::ZERO);
+        assert_eq!(statement.orders.len(), 2);
+        assert_eq!(statement.orders[0].order_id, credit.id);
+        assert_eq!(statement.orders[0].items.len(), 2);
+        assert_eq!(statement.orders[0].items[0].subtotal, Decimal::new(100, 0));
+        assert_eq!(statement.orders[0].discount_amount, Decimal::new(10, 0));
+
+        assert_eq!(statement.payments.len(), 2);
+        assert!(statement
+            .payments
+            .iter()
+            .all(|p| p.entry_type == LedgerEntryType::Receipt));
+        assert!(statement
+            .payments
+            .iter()
+            .any(|p| p.channel == AccountingChannel::Wechat && p.amount == Decimal::new(90, 0)));
+
+        assert_eq!(statement.sales_total, Decimal::new(390, 0));
+        assert_eq!(statement.discount_total, Decimal::new(10, 0));
+        assert_eq!(statement.receipt_total, Decimal::new(290, 0));
+        assert_eq!(statement.closing_balance, Decimal::new(100, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_statement_discount_includes_item_discounts() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = StatementService::new(db.clone());
+
+        let retailer = create_customer(&customers, "张三").await?;
+
+        // 明细折扣 20 + 整单优惠 10
+        let mut input = make_sales_order(retailer.id, Some(Decimal::new(170, 0)));
+        input.items[0].discount_amount = Some(Decimal::new(20, 0));
+        orders.create_order(input).await?;
+
+        let statement = service
+            .generate_statement(&make_query(retailer.id, 7, None))
+            .await?;
+        assert_eq!(statement.orders[0].total_amount, Decimal::new(180, 0));
+        assert_eq!(statement.orders[0].discount_amount, Decimal::new(30, 0));
+        assert_eq!(statement.discount_total, Decimal::new(30, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_statement_opening_balance_excludes_earlier_orders() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = StatementService::new(db.clone());
+
+        let retailer = create_customer(&customers, "李四").await?;
+        orders
+            .create_order(make_sales_order(retailer.id, None))
+            .await?;
+
+        // 区间从明天开始：今天的订单计入期初余额
+        let tomorrow = Local::now().date_naive() + Duration::days(1);
+        let statement = service
+            .generate_statement(&CustomerStatementDto {
+                customer_id: retailer.id,
+                start_date: tomorrow.format("%Y-%m-%d").to_string(),
+                end_date: tomorrow.format("%Y-%m-%d").to_string(),
+                format: None,
+            })
+            .await?;
+
+        assert!(statement.orders.is_empty());
+        assert!(statement.payments.is_empty());
+        assert_eq!(statement.opening_balance, Decimal::new(200, 0));
+        assert_eq!(statement.closing_balance, Decimal::new(200, 0));
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_statement_invalid_input_error() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let service = StatementService::new(db.clone());
+
+        let result = service
+            .generate_statement(&make_query(999999, 7, None))
+            .await;
+        assert!(result.is_err());
+
+        let retailer = create_customer(&customers, "王五").await?;
+        let result = service
+            .generate_statement(&CustomerStatementDto {
+                customer_id: retailer.id,
+                start_date: "2024-06-30".to_string(),
+                end_date: "2024-06-01".to_string(),
+                format: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+// ==================== export_statement 测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_export_statement_html_and_pdf() {
+    run_in_transaction(|db| async move {
+        let customers = CustomerService::new(db.clone());
+        let orders = OrderService::new(db.clone());
+        let service = StatementService::new(db.clone());
+
+        let retailer = create_customer(&customers, "赵六").await?;
+        orders
+            .create_order(make_sales_order(retailer.id, None))
+            .await?;
+
+        let dir = tempfile::tempdir()?;
+
+        let html = service
+            .export_statement(make_query(retailer.id, 7, Some("Html")), dir.path())
+            .await?;
+        assert_eq!(html.format, DocumentFormat::Html);
+        assert!(html.file_path.ends_with(".html"));
+        let statement_no = html
+            .statement
+            .statement_no
+            .clone()
+            .expect("导出时应生成编号");
+        assert!(statement_no.starts_with("DZ-"));
+        let content = std::fs::read_to_string(&html.file_path)?;
+        assert!(content.contains("客户对账单"));
+        assert!(content.contains("赵六"));
+        assert!(content.contains(&statement_no));
+        // 商品名称需转义
+        assert!(content.contains("苹果&lt;红富士&gt;"));
+
+        let pdf = service
+            .export_statement(make_query(retailer.id, 7, None), dir.path())
+            .await?;
+        assert_eq!(pdf.format, DocumentFormat::Pdf);
+        let bytes = std::fs::read(&pdf.file_path)?;
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+
+        let result = service
+            .export_statement(make_query(retailer.id, 7, Some("Docx")), dir.path())
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,224 @@
+use accounting_assistant_lib::entity::order;
+use accounting_assistant_lib::enums::StockMovementType;
+use accounting_assistant_lib::services::order::dto::{
+    AddOrderPaymentDto, CreateOrderDto, CreateOrderItemDto, CreateOrderReturnDto,
+    CreateOrderReturnItemDto, ReopenOrderDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::stock::dto::QueryStockMovementsDto;
+use accounting_assistant_lib::services::{OrderService, StockService};
+use rust_decimal::Decimal;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建单明细订单
+async fn create_order(
+    service: &OrderService,
+    order_type: &str,
+    quantity: i64,
+) -> Result<order::Model, Box<dyn std::error::Error>> {
+    service
+        .create_order(CreateOrderDto {
+            order_type: order_type.to_string(),
+            customer_id: None,
+            customer_name: None,
+            items: vec![CreateOrderItemDto {
+                product_id: 1,
+                product_name: "苹果".to_string(),
+                quantity: Decimal::new(quantity, 0),
+                unit: "斤".to_string(),
+                unit_price: Decimal::new(500, 2),
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            }],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await
+}
+
+/// 辅助函数：以现金结账
+async fn settle(
+    service: &OrderService,
+    order_id: i64,
+) -> Result<order::Model, Box<dyn std::error::Error>> {
+    service
+        .settle_order(SettleOrderDto {
+            order_id,
+            channel: Some("Cash".to_string()),
+            actual_amount: None,
+            payments: None,
+        })
+        .await
+}
+
+/// 辅助函数：查询商品 1 的当前库存（斤）
+async fn current_stock(service: &StockService) -> Result<Decimal, Box<dyn std::error::Error>> {
+    Ok(service
+        .get_product_stock(1)
+        .await?
+        .into_iter()
+        .find(|s| s.unit == "斤")
+        .map(|s| s.quantity)
+        .unwrap_or_default())
+}
+
+/// 辅助函数：查询商品 1 的全部库存流水
+fn all_movements() -> QueryStockMovementsDto {
+    QueryStockMovementsDto {
+        product_id: 1,
+        unit: None,
+        start_date: None,
+        end_date: None,
+    }
+}
+
+// ==================== 订单结账库存变动测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_settle_orders_update_stock() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let stock = StockService::new(db.clone());
+
+        let purchase = create_order(&orders, "Purchase", 10).await?;
+        settle(&orders, purchase.id).await?;
+        assert_eq!(current_stock(&stock).await?, Decimal::new(10, 0));
+
+        let sales = create_order(&orders, "Sales", 3).await?;
+        settle(&orders, sales.id).await?;
+        assert_eq!(current_stock(&stock).await?, Decimal::new(7, 0));
+
+        // 待结账订单不影响库存
+        create_order(&orders, "Sales", 5).await?;
+        assert_eq!(current_stock(&stock).await?, Decimal::new(7, 0));
+
+        let movements = stock.get_stock_movements(all_movements()).await?;
+        assert_eq!(movements.len(), 2);
+        assert_eq!(movements[0].movement_type, StockMovementType::PurchaseIn);
+        assert_eq!(movements[0].quantity, Decimal::new(10, 0));
+        assert_eq!(
+            movements[0].source_no.as_deref(),
+            Some(purchase.order_no.as_str())
+        );
+        assert_eq!(movements[1].movement_type, StockMovementType::SalesOut);
+        assert_eq!(movements[1].quantity, Decimal::new(-3, 0));
+        assert_eq!(movements[1].balance, Decimal::new(7, 0));
+        assert_eq!(movements[1].order_id, Some(sales.id));
+
+        let list = stock.get_stock_list().await?;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].product_name, "苹果");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_add_payment_updates_stock_when_fully_paid() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let stock = StockService::new(db.clone());
+
+        let sales = create_order(&orders, "Sales", 4).await?;
+        for amount in [5, 15] {
+            orders
+                .add_payment(AddOrderPaymentDto {
+                    order_id: sales.id,
+                    amount: Decimal::new(amount, 0),
+                    channel: "Cash".to_string(),
+                    paid_at: None,
+                    remark: None,
+                })
+                .await?;
+            // 部分收款时不出库，结清后出库
+            let expected = if amount == 5 { 0 } else { -4 };
+            assert_eq!(current_stock(&stock).await?, Decimal::new(expected, 0));
+        }
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_returns_and_reopen_reverse_stock() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let stock = StockService::new(db.clone());
+
+        let purchase = create_order(&orders, "Purchase", 10).await?;
+        settle(&orders, purchase.id).await?;
+        let sales = create_order(&orders, "Sales", 4).await?;
+        settle(&orders, sales.id).await?;
+
+        // 销售退货入库
+        let (_, items) = orders.get_order_by_id(sales.id).await?.expect("订单应存在");
+        let detail = orders
+            .create_return(CreateOrderReturnDto {
+                order_id: sales.id,
+                items: vec![CreateOrderReturnItemDto {
+                    order_item_id: items[0].id,
+                    quantity: Decimal::ONE,
+                }],
+                refund_amount: None,
+                channel: None,
+                reason: None,
+            })
+            .await?;
+        assert_eq!(current_stock(&stock).await?, Decimal::new(7, 0));
+
+        // 撤销采购订单结账冲回入库，重新结账后再次入库
+        orders
+            .reopen_order(ReopenOrderDto {
+                order_id: purchase.id,
+                reason: None,
+            })
+            .await?;
+        assert_eq!(current_stock(&stock).await?, Decimal::new(-3, 0));
+        settle(&orders, purchase.id).await?;
+        assert_eq!(current_stock(&stock).await?, Decimal::new(7, 0));
+
+        let movements = stock.get_stock_movements(all_movements()).await?;
+        let types: Vec<StockMovementType> =
+            movements.iter().map(|m| m.movement_type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                StockMovementType::PurchaseIn,
+                StockMovementType::SalesOut,
+                StockMovementType::SalesReturn,
+                StockMovementType::Reopen,
+                StockMovementType::PurchaseIn,
+            ]
+        );
+        assert_eq!(movements[2].source_id, Some(detail.order_return.id));
+        assert_eq!(
+            movements[2].source_no.as_deref(),
+            Some(detail.order_return.return_no.as_str())
+        );
+        assert_eq!(movements[3].quantity, Decimal::new(-10, 0));
+
+        // 单位筛选
+        let movements = stock
+            .get_stock_movements(QueryStockMovementsDto {
+                unit: Some("箱".to_string()),
+                ..all_movements()
+            })
+            .await?;
+        assert!(movements.is_empty());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
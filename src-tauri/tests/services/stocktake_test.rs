@@ -0,0 +1,412 @@
+use accounting_assistant_lib::entity::accounting_record;
+use accounting_assistant_lib::enums::{AccountingType, StockMovementType, StocktakeStatus};
+use accounting_assistant_lib::services::accounting_book::DEFAULT_BOOK_ID;
+use accounting_assistant_lib::services::order::dto::{
+    CreateOrderDto, CreateOrderItemDto, SettleOrderDto,
+};
+use accounting_assistant_lib::services::product::dto::CreateProductDto;
+use accounting_assistant_lib::services::stock::dto::QueryStockMovementsDto;
+use accounting_assistant_lib::services::stocktake::dto::{
+    ApproveStocktakeDto, CreateStocktakeDto, StocktakeItemDto, UpdateStocktakeDto,
+};
+use accounting_assistant_lib::services::{
+    LotService, OrderService, ProductService, StockService, StocktakeService,
+};
+use rust_decimal::Decimal;
+use sea_orm::EntityTrait;
+use serial_test::serial;
+
+use crate::context::run_in_transaction;
+
+/// 辅助函数：创建商品（单位为斤）
+async fn create_product(
+    service: &ProductService,
+    name: &str,
+    purchase_price: Option<Decimal>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let product = service
+        .create_product(CreateProductDto {
+            name: name.to_string(),
+            category_id: None,
+            category: None,
+            unit: "斤".to_string(),
+            default_sell_price: None,
+            default_purchase_price: purchase_price,
+            min_stock: None,
+            reorder_quantity: None,
+            sku: None,
+            keywords: None,
+            remark: None,
+        })
+        .await?;
+    Ok(product.id)
+}
+
+/// 辅助函数：创建并以现金结账单明细订单
+async fn settle_order(
+    service: &OrderService,
+    order_type: &str,
+    product_id: i64,
+    quantity: i64,
+    unit_price: Decimal,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let order = service
+        .create_order(CreateOrderDto {
+            order_type: order_type.to_string(),
+            customer_id: None,
+            customer_name: None,
+            items: vec![CreateOrderItemDto {
+                product_id,
+                product_name: "商品".to_string(),
+                quantity: Decimal::new(quantity, 0),
+                unit: "斤".to_string(),
+                unit_price,
+                discount_amount: None,
+                discount_rate: None,
+                remark: None,
+            }],
+            remark: None,
+            actual_amount: None,
+            sub_type: None,
+            due_date: None,
+        })
+        .await?;
+    service
+        .settle_order(SettleOrderDto {
+            order_id: order.id,
+            channel: Some("Cash".to_string()),
+            actual_amount: None,
+            payments: None,
+        })
+        .await?;
+    Ok(())
+}
+
+/// 辅助函数：盘点明细
+fn count(product_id: i64, quantity: i64) -> StocktakeItemDto {
+    StocktakeItemDto {
+        product_id,
+        unit: None,
+        counted_quantity: Decimal::new(quantity, 0),
+        remark: None,
+    }
+}
+
+/// 辅助函数：审核盘点单
+fn approve(stocktake_id: i64, record_loss: bool) -> ApproveStocktakeDto {
+    ApproveStocktakeDto {
+        stocktake_id,
+        record_loss,
+        book_id: None,
+        channel: None,
+    }
+}
+
+// ==================== 盘点测试 ====================
+
+#[serial]
+#[tokio::test]
+async fn test_stocktake_approve_adjusts_stock() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let products = ProductService::new(db.clone());
+        let stock = StockService::new(db.clone());
+        let service = StocktakeService::new(db.clone());
+
+        let apple = create_product(&products, "苹果", None).await?;
+        settle_order(&orders, "Purchase", apple, 10, Decimal::new(500, 2)).await?;
+
+        let draft = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(apple, 8)],
+                remark: None,
+            })
+            .await?;
+        assert!(draft.stocktake.stocktake_no.starts_with("PD-"));
+        assert_eq!(draft.stocktake.status, StocktakeStatus::Draft);
+        assert_eq!(draft.items[0].unit, "斤");
+        assert_eq!(draft.items[0].system_quantity, Decimal::new(10, 0));
+        assert_eq!(draft.items[0].variance, Decimal::new(-2, 0));
+
+        let draft = service
+            .update_stocktake(UpdateStocktakeDto {
+                stocktake_id: draft.stocktake.id,
+                items: vec![count(apple, 7)],
+                remark: Some("复盘".to_string()),
+            })
+            .await?;
+        assert_eq!(draft.items.len(), 1);
+        assert_eq!(draft.stocktake.remark.as_deref(), Some("复盘"));
+
+        // 草稿期间发生的销售在审核时按当前库存重新计算差异
+        settle_order(&orders, "Sales", apple, 1, Decimal::new(800, 2)).await?;
+
+        let approved = service
+            .approve_stocktake(approve(draft.stocktake.id, false))
+            .await?;
+        assert_eq!(approved.stocktake.status, StocktakeStatus::Approved);
+        assert!(approved.stocktake.approved_at.is_some());
+        assert_eq!(approved.items[0].system_quantity, Decimal::new(9, 0));
+        assert_eq!(approved.items[0].variance, Decimal::new(-2, 0));
+        assert_eq!(approved.items[0].unit_cost, Some(Decimal::new(5, 0)));
+        assert_eq!(
+            approved.items[0].variance_amount,
+            Some(Decimal::new(-10, 0))
+        );
+        assert_eq!(
+            approved.stocktake.variance_amount,
+            Some(Decimal::new(-10, 0))
+        );
+        assert_eq!(approved.stocktake.loss_record_id, None);
+
+        let current = stock.get_product_stock(apple).await?;
+        assert_eq!(current[0].quantity, Decimal::new(7, 0));
+        let movements = stock
+            .get_stock_movements(QueryStockMovementsDto {
+                product_id: apple,
+                unit: None,
+                start_date: None,
+                end_date: None,
+            })
+            .await?;
+        let adjustment = movements.last().unwrap();
+        assert_eq!(adjustment.movement_type, StockMovementType::Stocktake);
+        assert_eq!(adjustment.quantity, Decimal::new(-2, 0));
+        assert_eq!(adjustment.source_id, Some(approved.stocktake.id));
+        assert_eq!(adjustment.order_id, None);
+
+        // 已审核的盘点单不能再修改、审核或删除
+        let result = service
+            .approve_stocktake(approve(approved.stocktake.id, false))
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "盘点单已审核");
+        let result = service
+            .update_stocktake(UpdateStocktakeDto {
+                stocktake_id: approved.stocktake.id,
+                items: vec![count(apple, 5)],
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "盘点单已审核");
+        let result = service.delete_stocktake(approved.stocktake.id).await;
+        assert_eq!(result.unwrap_err().to_string(), "已审核的盘点单不能删除");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_stocktake_approve_adjusts_lots() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let products = ProductService::new(db.clone());
+        let lots = LotService::new(db.clone());
+        let service = StocktakeService::new(db.clone());
+
+        let apple = create_product(&products, "苹果", None).await?;
+        settle_order(&orders, "Purchase", apple, 10, Decimal::new(500, 2)).await?;
+
+        // 盘亏按先到期先出扣减批次
+        let loss = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(apple, 7)],
+                remark: None,
+            })
+            .await?;
+        service
+            .approve_stocktake(approve(loss.stocktake.id, false))
+            .await?;
+        let current = lots.get_product_lots(apple).await?;
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].remaining_quantity, Decimal::new(7, 0));
+
+        // 盘盈按盘点单编号登记调整批次
+        let gain = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(apple, 9)],
+                remark: None,
+            })
+            .await?;
+        service
+            .approve_stocktake(approve(gain.stocktake.id, false))
+            .await?;
+        let current = lots.get_product_lots(apple).await?;
+        assert_eq!(current.len(), 2);
+        assert_eq!(current[1].lot_no, gain.stocktake.stocktake_no);
+        assert_eq!(current[1].remaining_quantity, Decimal::new(2, 0));
+        assert_eq!(current[1].stocktake_id, Some(gain.stocktake.id));
+        assert_eq!(current[1].order_id, None);
+        assert!(lots.get_lot_mismatches().await?.is_empty());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_stocktake_records_net_loss() {
+    run_in_transaction(|db| async move {
+        let orders = OrderService::new(db.clone());
+        let products = ProductService::new(db.clone());
+        let stock = StockService::new(db.clone());
+        let service = StocktakeService::new(db.clone());
+
+        let apple = create_product(&products, "苹果", None).await?;
+        // 无入库记录的商品按参考采购价计算盘盈金额
+        let pear = create_product(&products, "梨", Some(Decimal::new(200, 2))).await?;
+        settle_order(&orders, "Purchase", apple, 10, Decimal::new(500, 2)).await?;
+
+        let draft = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(apple, 6), count(pear, 3)],
+                remark: Some("月末盘点".to_string()),
+            })
+            .await?;
+        let approved = service
+            .approve_stocktake(ApproveStocktakeDto {
+                stocktake_id: draft.stocktake.id,
+                record_loss: true,
+                book_id: Some(DEFAULT_BOOK_ID),
+                channel: Some("Cash".to_string()),
+            })
+            .await?;
+        assert_eq!(
+            approved.items[0].variance_amount,
+            Some(Decimal::new(-20, 0))
+        );
+        assert_eq!(approved.items[1].variance_amount, Some(Decimal::new(6, 0)));
+        assert_eq!(
+            approved.stocktake.variance_amount,
+            Some(Decimal::new(-14, 0))
+        );
+
+        let record_id = approved.stocktake.loss_record_id.unwrap();
+        let record = accounting_record::Entity::find_by_id(record_id)
+            .one(&db)
+            .await?
+            .unwrap();
+        assert_eq!(record.accounting_type, AccountingType::Expenditure);
+        assert_eq!(record.amount, Decimal::new(14, 0));
+        assert_eq!(record.book_id, Some(DEFAULT_BOOK_ID));
+        assert_eq!(
+            record.title,
+            format!("盘点损耗-{}", approved.stocktake.stocktake_no)
+        );
+
+        assert_eq!(
+            stock.get_product_stock(pear).await?[0].quantity,
+            Decimal::new(3, 0)
+        );
+
+        // 净盘盈时不登记损耗
+        let draft = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(pear, 5)],
+                remark: None,
+            })
+            .await?;
+        let approved = service
+            .approve_stocktake(approve(draft.stocktake.id, true))
+            .await?;
+        assert_eq!(approved.stocktake.loss_record_id, None);
+
+        // 账本不存在
+        let draft = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(pear, 1)],
+                remark: None,
+            })
+            .await?;
+        let result = service
+            .approve_stocktake(ApproveStocktakeDto {
+                stocktake_id: draft.stocktake.id,
+                record_loss: true,
+                book_id: Some(-1),
+                channel: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "账本不存在");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[serial]
+#[tokio::test]
+async fn test_stocktake_validation_and_listing() {
+    run_in_transaction(|db| async move {
+        let products = ProductService::new(db.clone());
+        let service = StocktakeService::new(db.clone());
+
+        let apple = create_product(&products, "苹果", None).await?;
+
+        let result = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![],
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "盘点明细不能为空");
+
+        let result = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(apple, -1)],
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "苹果 的实盘数量不能为负数");
+
+        let result = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(apple, 1), count(apple, 2)],
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "苹果（斤）重复盘点");
+
+        let result = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(-1, 1)],
+                remark: None,
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "商品不存在");
+
+        // 无库存记录时账面数量为 0
+        let draft = service
+            .create_stocktake(CreateStocktakeDto {
+                items: vec![count(apple, 4)],
+                remark: None,
+            })
+            .await?;
+        assert_eq!(draft.items[0].system_quantity, Decimal::ZERO);
+        assert_eq!(draft.items[0].variance, Decimal::new(4, 0));
+
+        let drafts = service.get_stocktakes(Some("Draft".to_string())).await?;
+        assert_eq!(drafts.len(), 1);
+        assert!(service
+            .get_stocktakes(Some("Approved".to_string()))
+            .await?
+            .is_empty());
+        let result = service.get_stocktakes(Some("Unknown".to_string())).await;
+        assert_eq!(result.unwrap_err().to_string(), "无效的盘点单状态");
+
+        let detail = service.get_stocktake(draft.stocktake.id).await?;
+        assert_eq!(detail.items.len(), 1);
+
+        service.delete_stocktake(draft.stocktake.id).await?;
+        let result = service.get_stocktake(draft.stocktake.id).await;
+        assert_eq!(result.unwrap_err().to_string(), "盘点单不存在");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -7,6 +7,7 @@ import { tryCMD } from '@/lib'
 
 import type {
   Order,
+  OrderBalance,
   OrderDetail,
   CreateOrderDto,
   SettleOrderDto,
@@ -61,6 +62,12 @@ export const getAllOrders = () => tryCMD<Order[]>('get_all_orders')
 export const getOrderById = (id: number) =>
   tryCMD<OrderDetail>('get_order_by_id', { id })
 
+/**
+ * 查询订单未结余额（含按渠道汇总的收付款）
+ */
+export const getOrderBalance = (orderId: number) =>
+  tryCMD<OrderBalance>('get_order_balance', { orderId })
+
 /**
  * 按客户查询订单
  */
@@ -84,6 +91,7 @@ export const orderApi = {
   cancel: cancelOrder,
   create: createOrder,
   getAll: getAllOrders,
+  getBalance: getOrderBalance,
   getById: getOrderById,
   getByCustomerId: getOrdersByCustomerId,
   getByStatus: getOrdersByStatus,
@@ -147,8 +147,42 @@ export type CreateOrderDto = {
  */
 export type SettleOrderDto = {
   orderId: number
-  channel: string
+  /** 单一渠道结账时填写，与 payments 二选一 */
+  channel?: string
   actualAmount?: number
+  /** 组合支付：各渠道金额合计须等于未结金额 */
+  payments?: SettlePaymentDto[]
+}
+
+/**
+ * 结账分渠道收付款项
+ */
+export type SettlePaymentDto = {
+  channel: string
+  amount: number
+}
+
+/**
+ * 按渠道汇总的收付款
+ */
+export type ChannelPayment = {
+  channel: string
+  amount: number
+}
+
+/**
+ * 订单未结余额
+ * 与 Rust 后端 OrderBalance 对齐
+ */
+export type OrderBalance = {
+  orderId: number
+  actualAmount: number
+  paidAmount: number
+  outstandingAmount: number
+  paymentCount: number
+  /** 按渠道汇总的收付款净额（组合支付时有多项） */
+  paymentChannels: ChannelPayment[]
+  status: OrderStatus
 }
 
 /**
@@ -16,7 +16,10 @@ import {
 } from '@/api/commands/accounting/enums'
 import type { AccountingRecord } from '@/api/commands/accounting/type'
 import { orderApi } from '@/api/commands/order'
-import type { OrderDetail as OrderDetailType } from '@/api/commands/order/type'
+import type {
+  ChannelPayment,
+  OrderDetail as OrderDetailType,
+} from '@/api/commands/order/type'
 import {
   ORDER_STATUS_DISPLAY_TEXT,
   ORDER_TYPE_DISPLAY_TEXT,
@@ -70,6 +73,7 @@ export const OrderDetailDialog: React.FC<OrderDetailDialogProps> = ({
   const [accountingRecords, setAccountingRecords] = useState<
     AccountingRecord[]
   >([])
+  const [paymentChannels, setPaymentChannels] = useState<ChannelPayment[]>([])
 
   // 弹窗状态
   const [settleDialogOpen, setSettleDialogOpen] = useState(false)
@@ -86,6 +90,15 @@ export const OrderDetailDialog: React.FC<OrderDetailDialogProps> = ({
     )
   }
 
+  // 加载按渠道汇总的收付款
+  const loadPaymentChannels = async (oid: number) => {
+    const result = await orderApi.getBalance(oid)
+    result.match(
+      (balance) => setPaymentChannels(balance.paymentChannels),
+      () => setPaymentChannels([])
+    )
+  }
+
   // 加载订单详情
   const loadDetail = useCallback(async () => {
     if (!orderId) {
@@ -97,6 +110,7 @@ export const OrderDetailDialog: React.FC<OrderDetailDialogProps> = ({
       (data) => {
         setDetail(data)
         setLoading(false)
+        void loadPaymentChannels(orderId)
         // 已结账订单加载关联记账记录
         if (data.order.status === 'Settled') {
           void loadAccountingRecords(orderId)
@@ -221,13 +235,24 @@ export const OrderDetailDialog: React.FC<OrderDetailDialogProps> = ({
                 {formatDate(order.createAt, 'datetime')}
               </p>
             </div>
-            {order.channel !== 'Unknown' && (
+            {(order.channel !== 'Unknown' || paymentChannels.length > 1) && (
               <div>
                 <span className="text-muted-foreground">支付渠道</span>
                 <p className="font-medium mt-1">
-                  {ACCOUNTING_CHANNEL_DISPLAY_TEXT[
-                    order.channel as keyof typeof ACCOUNTING_CHANNEL_DISPLAY_TEXT
-                  ] ?? order.channel}
+                  {paymentChannels.length > 1
+                    ? paymentChannels
+                        .map(
+                          (p) =>
+                            `${
+                              ACCOUNTING_CHANNEL_DISPLAY_TEXT[
+                                p.channel as keyof typeof ACCOUNTING_CHANNEL_DISPLAY_TEXT
+                              ] ?? p.channel
+                            } ${formatCurrency(p.amount)}`
+                        )
+                        .join(' + ')
+                    : (ACCOUNTING_CHANNEL_DISPLAY_TEXT[
+                        order.channel as keyof typeof ACCOUNTING_CHANNEL_DISPLAY_TEXT
+                      ] ?? order.channel)}
                 </p>
               </div>
             )}